@@ -1352,9 +1352,11 @@ impl Clone for DockerClient {
     }
 }
 
+#[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use super::*;
+    #[cfg(target_os = "macos")]
     use std::env;
 
     // For MacOS
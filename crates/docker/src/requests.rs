@@ -159,11 +159,11 @@ impl TeusRequestBuilder {
             return "Error: Failed to read response".to_string();
         }
 
-        let response = self.parse_buffer_to_string(response_buffer);
-        response
+        self.parse_buffer_to_string(response_buffer)
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -0,0 +1,99 @@
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use std::collections::{HashMap, HashSet};
+use teus_database::storage::Storage;
+use teus_monitor::{query, schema::CgroupInfo};
+use teus_types::api_models::{CgroupResponse, CgroupTreeResponse};
+
+fn cgroupinfo_to_response(c: CgroupInfo) -> CgroupResponse {
+    CgroupResponse {
+        name: c
+            .path
+            .rsplit_once('/')
+            .map_or(c.path.as_str(), |(_, name)| name)
+            .to_string(),
+        path: c.path,
+        cpu_usage_usec: c.cpu_usage_usec,
+        cpu_user_usec: c.cpu_user_usec,
+        cpu_system_usec: c.cpu_system_usec,
+        cpu_usage_percentage: c.cpu_usage_percentage,
+        memory_current: c.memory_current,
+        memory_max: c.memory_max,
+        io_read_bytes: c.io_read_bytes,
+        io_write_bytes: c.io_write_bytes,
+        io_read_bytes_per_sec: c.io_read_bytes_per_sec,
+        io_write_bytes_per_sec: c.io_write_bytes_per_sec,
+        pids_current: c.pids_current,
+        children: Vec::new(),
+    }
+}
+
+/* removes and returns the children of `parent`, each with its own subtree attached */
+fn attach_children(
+    parent: Option<&str>,
+    by_parent: &mut HashMap<Option<String>, Vec<CgroupInfo>>,
+) -> Vec<CgroupResponse> {
+    let children = by_parent
+        .remove(&parent.map(str::to_string))
+        .unwrap_or_default();
+    children
+        .into_iter()
+        .map(|c| {
+            let path = c.path.clone();
+            let mut response = cgroupinfo_to_response(c);
+            response.children = attach_children(Some(&path), by_parent);
+            response
+        })
+        .collect()
+}
+
+/// Nests the flat list of recorded cgroups by path.
+///
+/// A cgroup is placed under its closest recorded ancestor, so services of
+/// an intermediate cgroup that is not recorded (like `docker/`) still end
+/// up in the tree.
+fn build_tree(cgroups: Vec<CgroupInfo>) -> Vec<CgroupResponse> {
+    let paths: HashSet<String> = cgroups.iter().map(|c| c.path.clone()).collect();
+
+    let mut by_parent: HashMap<Option<String>, Vec<CgroupInfo>> = HashMap::new();
+    for cgroup in cgroups {
+        let mut parent = None;
+        let mut ancestor = cgroup.path.as_str();
+        while let Some((prefix, _)) = ancestor.rsplit_once('/') {
+            if paths.contains(prefix) {
+                parent = Some(prefix.to_string());
+                break;
+            }
+            ancestor = prefix;
+        }
+        by_parent.entry(parent).or_default().push(cgroup);
+    }
+
+    attach_children(None, &mut by_parent)
+}
+
+/// Returns the cgroup v2 accounting of the latest collection as a tree of
+/// slices, services and scopes.
+#[get("/cgroups")]
+async fn get_cgroups(storage: web::Data<Storage>) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting cgroups"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let latest = query::get_latest_cgroupinfo(&mut conn).map_err(|e| {
+        eprintln!("Database error getting cgroups: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get cgroups")
+    })?;
+
+    match latest {
+        Some((timestamp, cgroups)) => Ok(HttpResponse::Ok().json(CgroupTreeResponse {
+            timestamp,
+            cgroups: build_tree(cgroups),
+        })),
+        None => Ok(HttpResponse::NotFound().json("No cgroup accounting found")),
+    }
+}
@@ -0,0 +1,94 @@
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use teus_database::storage::Storage;
+use teus_monitor::collectors::kmsg::CATEGORIES;
+use teus_monitor::{query, schema::KernelEvent};
+use teus_types::api_models::KernelEventResponse;
+
+/* default and maximum number of events returned */
+const DEFAULT_EVENT_LIMIT: i64 = 100;
+const MAX_EVENT_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct KernelEventQuery {
+    /* `oom_kill`, `io_error`, `segfault`, `fs_readonly` or `hardware_error` */
+    category: Option<String>,
+    /* RFC3339 */
+    since: Option<String>,
+    limit: Option<i64>,
+}
+
+fn kernelevent_to_response(e: KernelEvent) -> KernelEventResponse {
+    KernelEventResponse {
+        timestamp: e.timestamp,
+        boot_id: e.boot_id,
+        seq: e.seq,
+        level: e.level,
+        category: e.category,
+        message: e.message,
+        pid: e.pid,
+        process: e.process,
+        device: e.device,
+        cgroup: e.cgroup,
+    }
+}
+
+/// Returns the stored kernel events (OOM kills, I/O errors, segfaults,
+/// filesystems remounted read-only and hardware errors), newest first.
+///
+/// Events can be filtered by `category` and `since` (RFC3339). The number
+/// of events is bounded by `limit`.
+#[get("/kernel/events")]
+async fn get_kernel_events(
+    params: web::Query<KernelEventQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    if let Some(category) = params.category.as_deref()
+        && !CATEGORIES.contains(&category)
+    {
+        return Err(ErrorBadRequest(format!(
+            "Unknown category `{}`, expected one of: {}",
+            category,
+            CATEGORIES.join(", ")
+        )));
+    }
+    // Stored timestamps are UTC RFC3339, normalize so they compare as strings
+    let since = params
+        .since
+        .as_deref()
+        .map(|since| {
+            DateTime::parse_from_rfc3339(since)
+                .map(|since| since.with_timezone(&Utc).to_rfc3339())
+                .map_err(|_| ErrorBadRequest("Invalid `since` timestamp, expected RFC3339"))
+        })
+        .transpose()?;
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_EVENT_LIMIT)
+        .clamp(1, MAX_EVENT_LIMIT);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting kernel events"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let events = query::get_kernelevents(
+        &mut conn,
+        params.category.as_deref(),
+        since.as_deref(),
+        limit,
+    )
+    .map_err(|e| {
+        eprintln!("Database error getting kernel events: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get kernel events")
+    })?;
+
+    let response: Vec<KernelEventResponse> =
+        events.into_iter().map(kernelevent_to_response).collect();
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,146 @@
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use serde::Deserialize;
+use teus_database::storage::Storage;
+use teus_monitor::query;
+use teus_types::api_models::{
+    LogEventResponse, LogMatchResponse, LogMatchSampleResponse, LogMatchesResponse,
+};
+
+/* default and maximum number of points returned by the history endpoint */
+const DEFAULT_HISTORY_LIMIT: i64 = 360;
+const MAX_HISTORY_LIMIT: i64 = 10_000;
+
+/* default and maximum number of lines returned */
+const DEFAULT_EVENT_LIMIT: i64 = 100;
+const MAX_EVENT_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogEventQuery {
+    log: Option<String>,
+    pattern: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Returns the pattern counts of every log in `[[monitor.logs]]` from the
+/// latest run of the `logs` collector.
+#[get("/logs")]
+async fn get_log_matches(storage: web::Data<Storage>) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting log matches"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let latest = query::get_latest_logmatches(&mut conn).map_err(|e| {
+        eprintln!("Database error getting log matches: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get log matches")
+    })?;
+
+    match latest {
+        Some((timestamp, matches)) => Ok(HttpResponse::Ok().json(LogMatchesResponse {
+            timestamp,
+            matches: matches
+                .into_iter()
+                .map(|m| LogMatchResponse {
+                    log: m.log,
+                    pattern: m.pattern,
+                    count: m.count,
+                    per_minute: m.per_minute,
+                })
+                .collect(),
+        })),
+        None => Ok(HttpResponse::NotFound().json("No log matches found")),
+    }
+}
+
+/// Returns the stored counts of `pattern` in the log `log`, one point per
+/// run, oldest first. The number of points is bounded by the `limit` query
+/// parameter.
+#[get("/logs/{log}/patterns/{pattern}/history")]
+async fn get_log_match_history(
+    path: web::Path<(String, String)>,
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let (log, pattern) = path.into_inner();
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting log match history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history = query::get_logmatch_history(&mut conn, &log, &pattern, limit).map_err(|e| {
+        eprintln!("Database error getting log match history: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get log match history")
+    })?;
+
+    let response: Vec<LogMatchSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, m)| LogMatchSampleResponse {
+            timestamp,
+            count: m.count,
+            per_minute: m.per_minute,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored matching lines, newest first.
+///
+/// Lines can be filtered by `log` and `pattern`. The number of lines is
+/// bounded by `limit`.
+#[get("/logs/events")]
+async fn get_log_events(
+    params: web::Query<LogEventQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_EVENT_LIMIT)
+        .clamp(1, MAX_EVENT_LIMIT);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting log events"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let events = query::get_logevents(
+        &mut conn,
+        params.log.as_deref(),
+        params.pattern.as_deref(),
+        limit,
+    )
+    .map_err(|e| {
+        eprintln!("Database error getting log events: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get log events")
+    })?;
+
+    let response: Vec<LogEventResponse> = events
+        .into_iter()
+        .map(|(timestamp, e)| LogEventResponse {
+            timestamp,
+            log: e.log,
+            pattern: e.pattern,
+            line: e.line,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,373 @@
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::http::header::ContentType;
+use actix_web::web::Bytes;
+use actix_web::{Error, HttpResponse, get, rt, web};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use teus_database::storage::Storage;
+use teus_monitor::rollup::{
+    self, Aggregation, DISK_METRICS, SYSINFO_METRICS, Segment, SeriesPoint, StepAggregator,
+};
+use teus_types::api_models::MetricPointResponse;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/* range returned when `from` is absent */
+const DEFAULT_RANGE_HOURS: i64 = 1;
+
+/* default and maximum number of steps of a series */
+const DEFAULT_STEPS: i64 = 360;
+const MAX_STEPS: i64 = 10_000;
+
+/* rows read per lock of the connection */
+const PAGE_ROWS: i64 = 5_000;
+
+/* the response is sent in chunks of about this size, with at most
+ * STREAM_CHUNKS of them waiting for a slow client */
+const CHUNK_BYTES: usize = 32 * 1024;
+const STREAM_CHUNKS: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    /* RFC3339 bounds, the latest hour when absent */
+    from: Option<String>,
+    to: Option<String>,
+    /* seconds per point */
+    step: Option<i64>,
+    /* comma-separated metric names, every metric when absent */
+    metrics: Option<String>,
+    /* comma-separated aggregations, `avg,min,max` when absent */
+    agg: Option<String>,
+}
+
+fn parse_time(value: Option<&str>, name: &str) -> Result<Option<DateTime<Utc>>, Error> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|_| ErrorBadRequest(format!("`{}` must be an RFC3339 time", name)))
+        })
+        .transpose()
+}
+
+fn parse_metrics(metrics: Option<&str>) -> Result<Vec<&'static str>, Error> {
+    let Some(metrics) = metrics else {
+        return Ok(SYSINFO_METRICS
+            .iter()
+            .chain(&DISK_METRICS)
+            .copied()
+            .collect());
+    };
+    metrics
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            SYSINFO_METRICS
+                .iter()
+                .chain(&DISK_METRICS)
+                .find(|known| **known == name)
+                .copied()
+                .ok_or_else(|| ErrorBadRequest(format!("Unknown metric `{}`", name)))
+        })
+        .collect()
+}
+
+fn parse_aggregations(agg: Option<&str>) -> Result<Vec<(String, Aggregation)>, Error> {
+    agg.unwrap_or("avg,min,max")
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            Aggregation::parse(name)
+                .map(|aggregation| (name.to_string(), aggregation))
+                .ok_or_else(|| ErrorBadRequest(format!("Unknown aggregation `{}`", name)))
+        })
+        .collect()
+}
+
+/* what the streaming task writes, resolved before the response starts */
+struct HistoryPlan {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step: i64,
+    /* metric and mount point, empty for host metrics */
+    series: Vec<(&'static str, String)>,
+    segments: Vec<Segment>,
+    aggregations: Vec<(String, Aggregation)>,
+}
+
+/* JSON of the response, sent to the client in chunks */
+struct ChunkWriter {
+    buffer: String,
+    tx: mpsc::Sender<io::Result<Bytes>>,
+    /* set once the client is gone */
+    closed: bool,
+}
+
+impl ChunkWriter {
+    fn write(&mut self, json: &str) {
+        self.buffer.push_str(json);
+        if self.buffer.len() >= CHUNK_BYTES {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.closed || self.buffer.is_empty() {
+            return;
+        }
+        let chunk = Bytes::from(std::mem::take(&mut self.buffer));
+        self.closed = self.tx.blocking_send(Ok(chunk)).is_err();
+    }
+
+    /* aborts the response, its status has been sent already */
+    fn fail(&mut self, message: String) {
+        self.closed = true;
+        let _ = self.tx.blocking_send(Err(io::Error::other(message)));
+    }
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn write_point(
+    out: &mut ChunkWriter,
+    mut point: SeriesPoint,
+    aggregations: &[(String, Aggregation)],
+    first: &mut bool,
+) {
+    let mut values = BTreeMap::new();
+    for (name, aggregation) in aggregations {
+        if let Some(value) = aggregation.value(&mut point) {
+            values.insert(name.clone(), value);
+        }
+    }
+    let response = MetricPointResponse {
+        timestamp: point.timestamp.to_rfc3339(),
+        count: point.aggregate.count,
+        values,
+    };
+    if let Ok(json) = serde_json::to_string(&response) {
+        if !std::mem::take(first) {
+            out.write(",");
+        }
+        out.write(&json);
+    }
+}
+
+fn write_history(
+    storage: &Storage,
+    plan: &HistoryPlan,
+    out: &mut ChunkWriter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let keep_values = plan
+        .aggregations
+        .iter()
+        .any(|(_, a)| matches!(a, Aggregation::Percentile(_)));
+
+    out.write(&format!(
+        r#"{{"from":{},"to":{},"step":{},"series":["#,
+        json_string(&plan.from.to_rfc3339()),
+        json_string(&plan.to.to_rfc3339()),
+        plan.step
+    ));
+    for (index, (metric, mount)) in plan.series.iter().enumerate() {
+        if index > 0 {
+            out.write(",");
+        }
+        let mount_json = if mount.is_empty() {
+            "null".to_string()
+        } else {
+            json_string(mount)
+        };
+        out.write(&format!(
+            r#"{{"metric":{},"mount":{},"points":["#,
+            json_string(metric),
+            mount_json
+        ));
+
+        let mut steps = StepAggregator::new(plan.step, keep_values);
+        let mut first = true;
+        for segment in &plan.segments {
+            let mut after: Option<String> = None;
+            loop {
+                if out.closed {
+                    return Ok(());
+                }
+                /* this thread may wait for a reader, it's released between pages */
+                let page = {
+                    let mut conn = storage
+                        .read_blocking()
+                        .map_err(|_| "Mutex poisoned while streaming sysinfo history")?;
+                    rollup::read_segment(
+                        &mut conn,
+                        metric,
+                        mount,
+                        segment,
+                        after.as_deref(),
+                        PAGE_ROWS,
+                    )?
+                };
+                for point in &page {
+                    if let Some(step) = steps.push(point) {
+                        write_point(out, step, &plan.aggregations, &mut first);
+                    }
+                }
+                if page.len() < PAGE_ROWS as usize {
+                    break;
+                }
+                after = page.last().map(|point| point.timestamp.clone());
+            }
+        }
+        if let Some(step) = steps.finish() {
+            write_point(out, step, &plan.aggregations, &mut first);
+        }
+        out.write("]}");
+    }
+    out.write("]}");
+    out.flush();
+    Ok(())
+}
+
+/// Returns the history of host and disk metrics between `from` and `to`
+/// (RFC3339, the latest hour by default), as one point per `step` seconds
+/// for each metric of `metrics` and each mount point of the disk metrics.
+///
+/// Points hold the aggregations of `agg`: `avg`, `min`, `max`, `last` or
+/// percentiles like `p95`. Long ranges are read from the rollups, see
+/// `rollup::plan_segments`; percentiles over rollups are those of the
+/// bucket averages.
+///
+/// The response is streamed while the history is read, a page at a time,
+/// so ranges of any size are served in bounded memory.
+#[get("/sysinfo/history")]
+async fn get_sysinfo_history(
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let to = parse_time(params.to.as_deref(), "to")?.unwrap_or_else(Utc::now);
+    let from = parse_time(params.from.as_deref(), "from")?
+        .unwrap_or(to - TimeDelta::hours(DEFAULT_RANGE_HOURS));
+    if from >= to {
+        return Err(ErrorBadRequest("`from` must be before `to`"));
+    }
+    /* long ranges get longer steps rather than too many points, and no
+     * step is longer than the range */
+    let range = (to - from).num_seconds();
+    let min_step = (range + MAX_STEPS - 1) / MAX_STEPS;
+    let step = params
+        .step
+        .unwrap_or((range + DEFAULT_STEPS - 1) / DEFAULT_STEPS)
+        .max(min_step)
+        .min(range)
+        .max(1);
+    let metrics = parse_metrics(params.metrics.as_deref())?;
+    let aggregations = parse_aggregations(params.agg.as_deref())?;
+
+    let (segments, mounts) = {
+        let mut conn = storage.read().map_err(|e| {
+            if e.is_busy() {
+                return ErrorServiceUnavailable("All database connections are busy, try again");
+            }
+            eprintln!("Mutex poisoned while getting sysinfo history"); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to acquire database lock")
+        })?;
+        let segments = rollup::plan_segments(&mut conn, from, to, step);
+        let mounts = if metrics.iter().any(|m| DISK_METRICS.contains(m)) {
+            rollup::disk_series(&mut conn, from, to)
+        } else {
+            Ok(Vec::new())
+        };
+        segments.and_then(|s| Ok((s, mounts?))).map_err(|e| {
+            eprintln!("Database error getting sysinfo history: {:?}", e); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to get sysinfo history")
+        })?
+    };
+
+    let mut series = Vec::new();
+    for metric in metrics {
+        if DISK_METRICS.contains(&metric) {
+            series.extend(mounts.iter().map(|mount| (metric, mount.clone())));
+        } else {
+            series.push((metric, String::new()));
+        }
+    }
+    let plan = HistoryPlan {
+        from,
+        to,
+        step,
+        series,
+        segments,
+        aggregations,
+    };
+
+    /* reads are blocking, the points are sent through a bounded channel */
+    let (tx, rx) = mpsc::channel(STREAM_CHUNKS);
+    let storage = storage.get_ref().clone();
+    rt::task::spawn_blocking(move || {
+        let mut out = ChunkWriter {
+            buffer: String::with_capacity(CHUNK_BYTES),
+            tx,
+            closed: false,
+        };
+        if let Err(e) = write_history(&storage, &plan, &mut out) {
+            eprintln!("Database error streaming sysinfo history: {}", e); // TODO: Use log::error!
+            out.fail(e.to_string());
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .streaming(ReceiverStream::new(rx)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test};
+    use teus_database::migrations;
+    use teus_monitor::sys::SysInfo;
+
+    #[actix_web::test]
+    async fn test_history_with_oversized_step() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("teus.db");
+        let storage = Storage::new(path.to_str().unwrap()).unwrap();
+        {
+            let mut conn = storage.write().unwrap();
+            migrations::apply_pending(&mut conn, path.to_str().unwrap()).unwrap();
+            for minute in [10, 20] {
+                SysInfo {
+                    timestamp: format!("2024-01-15T10:{}:00+00:00", minute),
+                    ..SysInfo::default()
+                }
+                .store(&mut conn)
+                .unwrap();
+            }
+        }
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(storage))
+                .service(get_sysinfo_history),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri(&format!(
+                "/sysinfo/history?from=2024-01-15T10:00:00Z&to=2024-01-15T11:00:00Z&metrics=cpu_usage&step={}",
+                i64::MAX
+            ))
+            .to_request();
+        let history: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+
+        // The step is cut to the range, both samples fall in one point
+        assert_eq!(history["step"], 3600);
+        let points = history["series"][0]["points"].as_array().unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0]["count"], 2);
+    }
+}
@@ -1 +1,10 @@
+pub mod cgroups;
+pub mod kernel;
+pub mod logs;
+pub mod metrics;
+pub mod packages;
+pub mod paths;
+pub mod processes;
+pub mod scripts;
+pub mod security;
 pub mod systeminfo;
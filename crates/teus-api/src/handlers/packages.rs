@@ -0,0 +1,94 @@
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use serde::Deserialize;
+use teus_database::storage::Storage;
+use teus_monitor::collectors::packages::decode_names;
+use teus_monitor::query;
+use teus_types::api_models::{PackageDayResponse, PackageStatusResponse, PackageUpdateResponse};
+
+/* default and maximum number of days returned by the history endpoint */
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+const MAX_HISTORY_DAYS: i64 = 3650;
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    days: Option<i64>,
+}
+
+/// Returns the pending package updates of the latest check, with their
+/// counts and the reboot-required flag.
+#[get("/packages")]
+async fn get_packages(storage: web::Data<Storage>) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting packages"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let latest = query::get_latest_packagestatus(&mut conn).map_err(|e| {
+        eprintln!("Database error getting packages: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get packages")
+    })?;
+
+    match latest {
+        Some((status, updates)) => Ok(HttpResponse::Ok().json(PackageStatusResponse {
+            day: status.day,
+            timestamp: status.timestamp,
+            manager: status.manager,
+            pending_updates: status.pending_updates,
+            security_updates: status.security_updates,
+            reboot_required: status.reboot_required,
+            reboot_packages: decode_names(&status.reboot_packages),
+            lists_updated_at: status.lists_updated_at,
+            updates: updates
+                .into_iter()
+                .map(|u| PackageUpdateResponse {
+                    name: u.name,
+                    installed_version: u.installed_version,
+                    available_version: u.available_version,
+                    security: u.security,
+                })
+                .collect(),
+        })),
+        None => Ok(HttpResponse::NotFound().json("No package status found")),
+    }
+}
+
+/// Returns the pending update counts of the latest `days` days, oldest
+/// first, one point per day.
+#[get("/packages/history")]
+async fn get_package_history(
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let days = params
+        .days
+        .unwrap_or(DEFAULT_HISTORY_DAYS)
+        .clamp(1, MAX_HISTORY_DAYS);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting package history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history = query::get_packagestatus_history(&mut conn, days).map_err(|e| {
+        eprintln!("Database error getting package history: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get package history")
+    })?;
+
+    let response: Vec<PackageDayResponse> = history
+        .into_iter()
+        .map(|status| PackageDayResponse {
+            day: status.day,
+            pending_updates: status.pending_updates,
+            security_updates: status.security_updates,
+            reboot_required: status.reboot_required,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
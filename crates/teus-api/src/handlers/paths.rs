@@ -0,0 +1,130 @@
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use chrono::{TimeDelta, Utc};
+use serde::Deserialize;
+use teus_database::storage::Storage;
+use teus_monitor::collectors::dirsize::{decode_largest_files, growth_per_hour};
+use teus_monitor::query;
+use teus_monitor::schema::PathInfo;
+use teus_types::api_models::{
+    LargeFileResponse, PathStatusResponse, PathUsageResponse, PathUsageSampleResponse,
+};
+
+/* default and maximum window of the growth rate, in hours */
+const DEFAULT_GROWTH_HOURS: i64 = 24;
+const MAX_GROWTH_HOURS: i64 = 24 * 365;
+
+/* default and maximum number of points returned by the history endpoint */
+const DEFAULT_HISTORY_LIMIT: i64 = 360;
+const MAX_HISTORY_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct LatestQuery {
+    /* window the growth rate is computed over */
+    hours: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    path: String,
+    limit: Option<i64>,
+}
+
+fn pathinfo_to_response(p: PathInfo) -> PathUsageResponse {
+    PathUsageResponse {
+        largest_files: decode_largest_files(&p.largest_files)
+            .into_iter()
+            .map(|f| LargeFileResponse {
+                path: f.path,
+                bytes: f.bytes,
+            })
+            .collect(),
+        path: p.path,
+        total_bytes: p.total_bytes,
+        file_count: p.file_count,
+        dir_count: p.dir_count,
+        complete: p.complete,
+    }
+}
+
+/// Returns the latest measurement of every directory in
+/// `[[monitor.paths]]`, with its growth rate over the latest `hours`.
+#[get("/paths")]
+async fn get_paths(
+    params: web::Query<LatestQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let hours = params
+        .hours
+        .unwrap_or(DEFAULT_GROWTH_HOURS)
+        .clamp(1, MAX_GROWTH_HOURS);
+    let since = (Utc::now() - TimeDelta::hours(hours)).to_rfc3339();
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting paths"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let latest = query::get_latest_pathinfo(&mut conn).map_err(|e| {
+        eprintln!("Database error getting paths: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get paths")
+    })?;
+
+    let mut response = Vec::with_capacity(latest.len());
+    for (timestamp, p) in latest {
+        let first = query::get_first_pathinfo_since(&mut conn, &p.path, &since).map_err(|e| {
+            eprintln!("Database error getting path growth: {:?}", e); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to get paths")
+        })?;
+        let growth_bytes_per_hour = first.and_then(|(first_timestamp, first)| {
+            growth_per_hour(
+                (&first_timestamp, first.total_bytes as u64),
+                (&timestamp, p.total_bytes as u64),
+            )
+        });
+        response.push(PathStatusResponse {
+            timestamp,
+            usage: pathinfo_to_response(p),
+            growth_bytes_per_hour,
+        });
+    }
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored measurements of the watched directory `path`, oldest
+/// first. The number of points is bounded by the `limit` query parameter.
+#[get("/paths/history")]
+async fn get_path_history(
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting path history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history = query::get_pathinfo_history(&mut conn, &params.path, limit).map_err(|e| {
+        eprintln!("Database error getting path history: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get path history")
+    })?;
+
+    let response: Vec<PathUsageSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, p)| PathUsageSampleResponse {
+            timestamp,
+            usage: pathinfo_to_response(p),
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,212 @@
+use actix_web::error::{
+    BlockingError, ErrorBadRequest, ErrorInternalServerError, ErrorServiceUnavailable,
+};
+use actix_web::{Error, HttpResponse, get, web};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sysinfo::{
+    MINIMUM_CPU_UPDATE_INTERVAL, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users,
+};
+use teus_database::storage::Storage;
+use teus_monitor::collectors::processes::{ProcessSample, collect_processes};
+use teus_monitor::{query, schema::ProcessInfo};
+use teus_types::api_models::{ProcessListResponse, ProcessResponse};
+
+/* default and maximum number of processes returned */
+const DEFAULT_PROCESS_LIMIT: usize = 50;
+const MAX_PROCESS_LIMIT: usize = 10_000;
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+enum ProcessSort {
+    #[default]
+    Cpu,
+    Memory,
+    Pid,
+    Name,
+    StartTime,
+}
+
+impl ProcessSort {
+    /* numeric rankings read best from the highest value, names and pids from the lowest */
+    fn default_order(self) -> SortOrder {
+        match self {
+            ProcessSort::Cpu | ProcessSort::Memory | ProcessSort::StartTime => SortOrder::Desc,
+            ProcessSort::Pid | ProcessSort::Name => SortOrder::Asc,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProcessQuery {
+    /* RFC3339 time of a stored snapshot; live view when absent */
+    at: Option<String>,
+    #[serde(default)]
+    sort: ProcessSort,
+    order: Option<SortOrder>,
+    /* case-insensitive substring of the name or command line */
+    name: Option<String>,
+    user: Option<String>,
+    limit: Option<usize>,
+}
+
+fn sample_to_response(p: ProcessSample) -> ProcessResponse {
+    ProcessResponse {
+        pid: p.pid as i32,
+        name: p.name,
+        cmd: p.cmd,
+        user: p.username,
+        cpu_usage: p.cpu_usage,
+        memory: p.memory as i64,
+        start_time: p.start_time as i64,
+    }
+}
+
+fn processinfo_to_response(p: ProcessInfo) -> ProcessResponse {
+    ProcessResponse {
+        pid: p.pid,
+        name: p.name,
+        cmd: p.cmd,
+        user: p.username,
+        cpu_usage: p.cpu_usage,
+        memory: p.memory,
+        start_time: p.start_time,
+    }
+}
+
+/* TODO: Migrate those services into teus-services crate */
+async fn collect_live_processes() -> Result<Vec<ProcessSample>, BlockingError> {
+    /* reading every process is blocking, keep it off the async workers */
+    let sys = web::block(|| {
+        let mut sys = System::new();
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+        sys
+    })
+    .await?;
+    /* cpu usage is computed between two refreshes */
+    actix_web::rt::time::sleep(MINIMUM_CPU_UPDATE_INTERVAL).await;
+    web::block(move || {
+        let mut sys = sys;
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        let users = Users::new_with_refreshed_list();
+        collect_processes(&sys, &users)
+    })
+    .await
+}
+
+fn filter_and_sort(
+    mut processes: Vec<ProcessResponse>,
+    params: &ProcessQuery,
+) -> Vec<ProcessResponse> {
+    if let Some(name) = &params.name {
+        let needle = name.to_lowercase();
+        processes.retain(|p| {
+            p.name.to_lowercase().contains(&needle) || p.cmd.to_lowercase().contains(&needle)
+        });
+    }
+    if let Some(user) = &params.user {
+        processes.retain(|p| p.user.as_deref() == Some(user.as_str()));
+    }
+
+    processes.sort_by(|a, b| match params.sort {
+        ProcessSort::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+        ProcessSort::Memory => a.memory.cmp(&b.memory),
+        ProcessSort::Pid => a.pid.cmp(&b.pid),
+        ProcessSort::Name => a.name.cmp(&b.name),
+        ProcessSort::StartTime => a.start_time.cmp(&b.start_time),
+    });
+    if params.order.unwrap_or(params.sort.default_order()) == SortOrder::Desc {
+        processes.reverse();
+    }
+
+    processes.truncate(
+        params
+            .limit
+            .unwrap_or(DEFAULT_PROCESS_LIMIT)
+            .clamp(1, MAX_PROCESS_LIMIT),
+    );
+    processes
+}
+
+/// Returns the process table, live or as stored at a point in time.
+///
+/// Without parameters every running process is read at request time. With
+/// `at` (RFC3339) the top processes by CPU and by memory of the closest
+/// monitoring snapshot at or before that time are returned instead.
+///
+/// Both views accept `sort` (`cpu`, `memory`, `pid`, `name`, `start_time`),
+/// `order` (`asc`, `desc`), `name` and `user` filters, and `limit`.
+#[get("/processes")]
+async fn get_processes(
+    params: web::Query<ProcessQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let (timestamp, live, processes) = match &params.at {
+        None => {
+            let processes = collect_live_processes()
+                .await
+                .map_err(|e| {
+                    eprintln!("Failed to read the live processes: {}", e); // TODO: Use log::error!
+                    ErrorInternalServerError("Failed to read processes")
+                })?
+                .into_iter()
+                .map(sample_to_response)
+                .collect();
+            (Utc::now().to_rfc3339(), true, processes)
+        }
+        Some(at) => {
+            /* stored timestamps are UTC RFC3339, normalize so they compare as strings */
+            let at = DateTime::parse_from_rfc3339(at)
+                .map_err(|_| ErrorBadRequest("Invalid `at` timestamp, expected RFC3339"))?
+                .with_timezone(&Utc)
+                .to_rfc3339();
+
+            let mut conn = storage.read().map_err(|e| {
+                if e.is_busy() {
+                    return ErrorServiceUnavailable("All database connections are busy, try again");
+                }
+                eprintln!("Mutex poisoned while getting processes"); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to acquire database lock")
+            })?;
+
+            let snapshot = query::get_processinfo_at(&mut conn, &at).map_err(|e| {
+                eprintln!("Database error getting processes: {:?}", e); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to get processes")
+            })?;
+
+            match snapshot {
+                Some((timestamp, processes)) => (
+                    timestamp,
+                    false,
+                    processes.into_iter().map(processinfo_to_response).collect(),
+                ),
+                None => {
+                    return Ok(HttpResponse::NotFound().json("No process snapshot found"));
+                }
+            }
+        }
+    };
+
+    let response = ProcessListResponse {
+        timestamp,
+        live,
+        processes: filter_and_sort(processes, &params),
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
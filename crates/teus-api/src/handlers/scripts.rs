@@ -0,0 +1,103 @@
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use serde::Deserialize;
+use teus_database::storage::Storage;
+use teus_monitor::collectors::scripts::decode_labels;
+use teus_monitor::query;
+use teus_types::api_models::{
+    ScriptMetricResponse, ScriptMetricSampleResponse, ScriptMetricValueResponse,
+};
+
+/* default and maximum number of points returned by the history endpoint */
+const DEFAULT_HISTORY_LIMIT: i64 = 360;
+const MAX_HISTORY_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct LatestQuery {
+    /* only the values of this script */
+    script: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    limit: Option<i64>,
+}
+
+/// Returns the values printed by the latest run of every script in
+/// `[[monitor.scripts]]`, or of a single `script`.
+#[get("/scripts/metrics")]
+async fn get_script_metrics(
+    params: web::Query<LatestQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting script metrics"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let latest = query::get_latest_scriptmetrics(&mut conn).map_err(|e| {
+        eprintln!("Database error getting script metrics: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get script metrics")
+    })?;
+
+    let response: Vec<ScriptMetricResponse> = latest
+        .into_iter()
+        .filter(|(_, m)| params.script.as_deref().is_none_or(|s| m.script == s))
+        .map(|(timestamp, m)| ScriptMetricResponse {
+            timestamp,
+            labels: decode_labels(&m.labels),
+            script: m.script,
+            name: m.name,
+            value: m.value,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored values of metric `name` printed by `script`, one
+/// point per run, oldest first. The number of points is bounded by the
+/// `limit` query parameter.
+#[get("/scripts/{script}/metrics/{name}/history")]
+async fn get_script_metric_history(
+    path: web::Path<(String, String)>,
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let (script, name) = path.into_inner();
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting script metric history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history =
+        query::get_scriptmetric_history(&mut conn, &script, &name, limit).map_err(|e| {
+            eprintln!("Database error getting script metric history: {:?}", e); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to get script metric history")
+        })?;
+
+    let response: Vec<ScriptMetricSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, values)| ScriptMetricSampleResponse {
+            timestamp,
+            values: values
+                .into_iter()
+                .map(|m| ScriptMetricValueResponse {
+                    labels: decode_labels(&m.labels),
+                    value: m.value,
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
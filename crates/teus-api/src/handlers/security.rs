@@ -0,0 +1,191 @@
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, get, web};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use teus_database::storage::Storage;
+use teus_monitor::collectors::security::{LoginRecord, parse_utmp_records};
+use teus_monitor::{query, schema::SecurityEvent};
+use teus_types::api_models::{
+    AuthFailureListResponse, AuthFailuresResponse, LoginSessionResponse, SecurityEventResponse,
+};
+use teus_types::config::Config;
+
+/* default and maximum number of events returned */
+const DEFAULT_EVENT_LIMIT: i64 = 100;
+const MAX_EVENT_LIMIT: i64 = 10_000;
+
+/* period of the failure counts when `since` is absent */
+const DEFAULT_FAILURE_PERIOD_HOURS: i64 = 24;
+
+#[derive(Debug, Deserialize)]
+struct EventQuery {
+    /* `login`, `accepted`, `failed` or `invalid_user` */
+    kind: Option<String>,
+    ip: Option<String>,
+    user: Option<String>,
+    /* RFC3339 */
+    since: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FailureQuery {
+    /* RFC3339; the last 24 hours when absent */
+    since: Option<String>,
+}
+
+/* stored timestamps are UTC RFC3339, normalize so they compare as strings */
+fn parse_since(since: &str) -> Result<String, Error> {
+    Ok(DateTime::parse_from_rfc3339(since)
+        .map_err(|_| ErrorBadRequest("Invalid `since` timestamp, expected RFC3339"))?
+        .with_timezone(&Utc)
+        .to_rfc3339())
+}
+
+fn login_to_response(l: LoginRecord) -> LoginSessionResponse {
+    LoginSessionResponse {
+        username: l.username,
+        tty: l.tty,
+        host: l.host,
+        source_ip: l.source_ip.map(|ip| ip.to_string()),
+        pid: l.pid,
+        login_time: l.login_time.to_rfc3339(),
+    }
+}
+
+fn securityevent_to_response(e: SecurityEvent) -> SecurityEventResponse {
+    SecurityEventResponse {
+        timestamp: e.timestamp,
+        source: e.source,
+        kind: e.kind,
+        username: e.username,
+        source_ip: e.source_ip,
+        auth_method: e.auth_method,
+        tty: e.tty,
+    }
+}
+
+/// Counts the attempts of each address, keeping the addresses with at
+/// least one failure, most failures first.
+fn count_failures(attempts: Vec<(String, String, String)>) -> Vec<AuthFailuresResponse> {
+    let mut by_ip: BTreeMap<String, AuthFailuresResponse> = BTreeMap::new();
+    // Attempts are ordered by time, the first and last ones bound the period
+    for (ip, kind, timestamp) in attempts {
+        let counts = by_ip
+            .entry(ip.clone())
+            .or_insert_with(|| AuthFailuresResponse {
+                source_ip: ip,
+                failed: 0,
+                invalid_user: 0,
+                accepted: 0,
+                first_seen: timestamp.clone(),
+                last_seen: timestamp.clone(),
+            });
+        match kind.as_str() {
+            "failed" => counts.failed += 1,
+            "invalid_user" => counts.invalid_user += 1,
+            "accepted" => counts.accepted += 1,
+            _ => {}
+        }
+        counts.last_seen = timestamp;
+    }
+
+    let mut sources: Vec<AuthFailuresResponse> = by_ip
+        .into_values()
+        .filter(|s| s.failed + s.invalid_user > 0)
+        .collect();
+    sources.sort_by_key(|s| std::cmp::Reverse(s.failed + s.invalid_user));
+    sources
+}
+
+/// Returns the login sessions currently open, read from `utmp` at request
+/// time.
+#[get("/security/sessions")]
+async fn get_login_sessions(config: web::Data<Config>) -> Result<HttpResponse, Error> {
+    let sessions = match std::fs::read(&config.monitor.security.utmp_path) {
+        Ok(bytes) => parse_utmp_records(&bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            eprintln!("Failed to read utmp: {:?}", e); // TODO: Use log::error!
+            return Err(ErrorInternalServerError("Failed to read login sessions"));
+        }
+    };
+
+    let response: Vec<LoginSessionResponse> = sessions.into_iter().map(login_to_response).collect();
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored logins and SSH authentication attempts, newest first.
+///
+/// Events can be filtered by `kind` (`login`, `accepted`, `failed`,
+/// `invalid_user`), source `ip`, `user` and `since` (RFC3339). The number
+/// of events is bounded by `limit`.
+#[get("/security/events")]
+async fn get_security_events(
+    params: web::Query<EventQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let since = params.since.as_deref().map(parse_since).transpose()?;
+    let filter = query::SecurityEventFilter {
+        kind: params.kind.as_deref(),
+        source_ip: params.ip.as_deref(),
+        username: params.user.as_deref(),
+        since: since.as_deref(),
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_EVENT_LIMIT)
+        .clamp(1, MAX_EVENT_LIMIT);
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting security events"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let events = query::get_securityevents(&mut conn, &filter, limit).map_err(|e| {
+        eprintln!("Database error getting security events: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get security events")
+    })?;
+
+    let response: Vec<SecurityEventResponse> =
+        events.into_iter().map(securityevent_to_response).collect();
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the failed SSH attempts per source address.
+///
+/// Counts cover the period starting at `since` (RFC3339), the last 24
+/// hours by default. Addresses are ordered by number of failures, so
+/// brute-force sources come first.
+#[get("/security/failures")]
+async fn get_auth_failures(
+    params: web::Query<FailureQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let since = match params.since.as_deref() {
+        Some(since) => parse_since(since)?,
+        None => (Utc::now() - TimeDelta::hours(DEFAULT_FAILURE_PERIOD_HOURS)).to_rfc3339(),
+    };
+
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting authentication failures"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let attempts = query::get_auth_attempts_since(&mut conn, &since).map_err(|e| {
+        eprintln!("Database error getting authentication failures: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get authentication failures")
+    })?;
+
+    Ok(HttpResponse::Ok().json(AuthFailureListResponse {
+        since,
+        sources: count_failures(attempts),
+    }))
+}
@@ -1,6 +1,132 @@
-use actix_web::{HttpResponse, Responder, get};
+use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{Error, HttpResponse, Responder, get, web};
+use chrono::Utc;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
 use sysinfo::{Networks, System};
-use teus_types::api_models::{GenericSysInfoResponse, IpInfo, MACInfo};
+use teus_database::storage::Storage;
+use teus_monitor::collectors::sockets::{self, SocketEntry};
+use teus_monitor::collectors::timesync::{self, ClockSync};
+use teus_monitor::{
+    query,
+    schema::{DiskIo, NetInfo, PsiInfo, SensorInfo, SocketInfo, TimeSyncInfo},
+};
+use teus_types::api_models::{
+    ClockSyncResponse, ClockSyncSampleResponse, DiskIoResponse, DiskIoSampleResponse,
+    GenericSysInfoResponse, IpInfo, MACInfo, NetworkInterfaceResponse,
+    NetworkInterfaceSampleResponse, PressureResponse, PressureSampleResponse, SensorResponse,
+    SensorSampleResponse, SocketListResponse, SocketResponse, SocketStateCountResponse,
+    SocketStatesSampleResponse,
+};
+use teus_types::config::Config;
+
+/* default and maximum number of points returned by history endpoints */
+const DEFAULT_HISTORY_LIMIT: i64 = 360;
+const MAX_HISTORY_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    limit: Option<i64>,
+}
+
+impl HistoryQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SocketQuery {
+    /* a single state, or `all`; listening and established sockets when absent */
+    state: Option<String>,
+    /* `tcp` or `udp` */
+    protocol: Option<String>,
+    /* local port */
+    port: Option<u16>,
+}
+
+impl SocketQuery {
+    fn matches(&self, socket: &SocketEntry) -> bool {
+        let state = match self.state.as_deref() {
+            None => socket.state == "listen" || socket.state == "established",
+            Some("all") => true,
+            Some(state) => socket.state == state,
+        };
+        state
+            && self
+                .protocol
+                .as_deref()
+                .is_none_or(|p| socket.protocol == p)
+            && self.port.is_none_or(|port| socket.local_port == port)
+    }
+}
+
+fn socketinfo_to_response(s: SocketInfo) -> SocketStateCountResponse {
+    SocketStateCountResponse {
+        protocol: s.protocol,
+        state: s.state,
+        count: s.socket_count,
+    }
+}
+
+pub(crate) fn netinfo_to_response(n: NetInfo) -> NetworkInterfaceResponse {
+    NetworkInterfaceResponse {
+        interface: n.interface,
+        rx_bytes: n.rx_bytes,
+        tx_bytes: n.tx_bytes,
+        rx_packets: n.rx_packets,
+        tx_packets: n.tx_packets,
+        rx_errors: n.rx_errors,
+        tx_errors: n.tx_errors,
+        rx_dropped: n.rx_dropped,
+        tx_dropped: n.tx_dropped,
+        rx_bytes_per_sec: n.rx_bytes_per_sec,
+        tx_bytes_per_sec: n.tx_bytes_per_sec,
+    }
+}
+
+pub(crate) fn diskio_to_response(d: DiskIo) -> DiskIoResponse {
+    DiskIoResponse {
+        device: d.device,
+        reads_completed: d.reads_completed,
+        sectors_read: d.sectors_read,
+        writes_completed: d.writes_completed,
+        sectors_written: d.sectors_written,
+        io_time_ms: d.io_time_ms,
+        read_bytes_per_sec: d.read_bytes_per_sec,
+        write_bytes_per_sec: d.write_bytes_per_sec,
+        read_iops: d.read_iops,
+        write_iops: d.write_iops,
+        avg_read_latency_ms: d.avg_read_latency_ms,
+        avg_write_latency_ms: d.avg_write_latency_ms,
+        busy_percentage: d.busy_percentage,
+    }
+}
+
+pub(crate) fn sensorinfo_to_response(s: SensorInfo) -> SensorResponse {
+    SensorResponse {
+        chip: s.chip,
+        label: s.label,
+        temperature: s.temperature,
+        high: s.high,
+        critical: s.critical,
+        fan_rpm: s.fan_rpm,
+    }
+}
+
+pub(crate) fn psiinfo_to_response(p: PsiInfo) -> PressureResponse {
+    PressureResponse {
+        resource: p.resource,
+        some_avg10: p.some_avg10,
+        some_avg60: p.some_avg60,
+        some_avg300: p.some_avg300,
+        full_avg10: p.full_avg10,
+        full_avg60: p.full_avg60,
+        full_avg300: p.full_avg300,
+    }
+}
 
 /* TODO: Migrate those services into teus-services crate */
 fn collect_network_info() -> Vec<IpInfo> {
@@ -53,14 +179,35 @@ fn get_os_name_version() -> String {
     }
 }
 
-/// Returns system information such as hostname, OS details, and network configurations.
+fn clock_sync_to_response(c: ClockSync) -> ClockSyncResponse {
+    ClockSyncResponse {
+        synchronized: c.synchronized,
+        offset_us: c.offset_us,
+        max_error_us: c.max_error_us,
+        est_error_us: c.est_error_us,
+        ntp_client: c.ntp_client,
+    }
+}
+
+fn timesyncinfo_to_response(t: TimeSyncInfo) -> ClockSyncResponse {
+    ClockSyncResponse {
+        synchronized: t.synchronized,
+        offset_us: t.offset_us,
+        max_error_us: t.max_error_us,
+        est_error_us: t.est_error_us,
+        ntp_client: t.ntp_client,
+    }
+}
+
+/// Returns system information such as hostname, OS details, network
+/// configurations and clock synchronization state.
 ///
 /// This endpoint provides real-time system information that doesn't need to be stored
 /// in the database, as it can be queried directly from the operating system whenever needed.
 /// The information returned is transient and reflects the current state of the system
 /// rather than persistent data that needs database storage.
 #[get("/generic/sysinfo")]
-async fn get_sysinfo() -> impl Responder {
+async fn get_sysinfo(config: web::Data<Config>) -> impl Responder {
     let hostname = System::host_name().unwrap_or_else(|| "No Info".to_string());
     let networks = collect_network_info();
     let mac_addresses = collect_mac_address();
@@ -69,15 +216,333 @@ async fn get_sysinfo() -> impl Responder {
     let uptime_ms = System::uptime();
     let uptime = convert_seconds_to_date_time(uptime_ms);
 
-    let mut response = GenericSysInfoResponse::default();
+    let clock = timesync::read_clock_sync(Path::new(&config.monitor.procfs_root))
+        .inspect_err(|e| eprintln!("Failed to read clock synchronization: {}", e)) // TODO: Use log::error!
+        .ok()
+        .map(clock_sync_to_response);
 
-    response.hostname = hostname;
-    response.os = os_name;
-    response.uptime = uptime;
-    response.kernel_version = System::kernel_version().unwrap_or_else(|| "No Info".to_string());
-    response.ipv4 = "No Info".to_string();
-    response.networks = networks;
-    response.mac_addresses = mac_addresses;
+    let response = GenericSysInfoResponse {
+        hostname,
+        os: os_name,
+        uptime,
+        kernel_version: System::kernel_version().unwrap_or_else(|| "No Info".to_string()),
+        ipv4: "No Info".to_string(),
+        networks,
+        mac_addresses,
+        clock,
+    };
 
     HttpResponse::Ok().json(response)
 }
+
+/// Returns the stored traffic history of a single network interface.
+///
+/// Each point is the state of the interface at one monitoring snapshot,
+/// oldest first, so clients can plot bandwidth over time per NIC, bridge
+/// or docker veth. The number of points is bounded by the `limit` query
+/// parameter.
+#[get("/network/interfaces/{interface}/history")]
+async fn get_network_interface_history(
+    interface: web::Path<String>,
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting network history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history =
+        query::get_netinfo_history(&mut conn, &interface, params.limit()).map_err(|e| {
+            eprintln!("Database error getting network history: {:?}", e); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to get network history")
+        })?;
+
+    let response: Vec<NetworkInterfaceSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, n)| NetworkInterfaceSampleResponse {
+            timestamp,
+            sample: netinfo_to_response(n),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored I/O history of a single block device.
+///
+/// Each point holds the throughput, IOPS, latency and utilization of the
+/// device at one monitoring snapshot, oldest first. The number of points
+/// is bounded by the `limit` query parameter.
+#[get("/disks/io/{device}/history")]
+async fn get_disk_io_history(
+    device: web::Path<String>,
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting disk I/O history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history = query::get_diskio_history(&mut conn, &device, params.limit()).map_err(|e| {
+        eprintln!("Database error getting disk I/O history: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get disk I/O history")
+    })?;
+
+    let response: Vec<DiskIoSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, d)| DiskIoSampleResponse {
+            timestamp,
+            sample: diskio_to_response(d),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored readings of a single hardware sensor.
+///
+/// A sensor is addressed by its chip and label as returned by `/sysinfo`
+/// (URL-encoded, labels often contain spaces). Points are ordered oldest
+/// first and bounded by the `limit` query parameter.
+#[get("/sensors/{chip}/{label}/history")]
+async fn get_sensor_history(
+    path: web::Path<(String, String)>,
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let (chip, label) = path.into_inner();
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting sensor history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history =
+        query::get_sensorinfo_history(&mut conn, &chip, &label, params.limit()).map_err(|e| {
+            eprintln!("Database error getting sensor history: {:?}", e); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to get sensor history")
+        })?;
+
+    let response: Vec<SensorSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, s)| SensorSampleResponse {
+            timestamp,
+            sample: sensorinfo_to_response(s),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored pressure stall history of one resource.
+///
+/// `resource` is one of `cpu`, `memory` or `io`. Points are ordered oldest
+/// first and bounded by the `limit` query parameter. The list is empty on
+/// hosts whose kernel has no PSI support.
+#[get("/pressure/{resource}/history")]
+async fn get_pressure_history(
+    resource: web::Path<String>,
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting pressure history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history =
+        query::get_psiinfo_history(&mut conn, &resource, params.limit()).map_err(|e| {
+            eprintln!("Database error getting pressure history: {:?}", e); // TODO: Use log::error!
+            ErrorInternalServerError("Failed to get pressure history")
+        })?;
+
+    let response: Vec<PressureSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, p)| PressureSampleResponse {
+            timestamp,
+            sample: psiinfo_to_response(p),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Returns the stored clock synchronization history, oldest first.
+///
+/// Each point is the kernel clock state at one monitoring snapshot. The
+/// number of points is bounded by the `limit` query parameter.
+#[get("/time/sync/history")]
+async fn get_time_sync_history(
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting clock history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history = query::get_timesyncinfo_history(&mut conn, params.limit()).map_err(|e| {
+        eprintln!("Database error getting clock history: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get clock history")
+    })?;
+
+    let response: Vec<ClockSyncSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, t)| ClockSyncSampleResponse {
+            timestamp,
+            sample: timesyncinfo_to_response(t),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Lists the TCP and UDP sockets of the host with the process owning them.
+///
+/// Sockets are read from the kernel at request time. By default only
+/// listening sockets and established connections are returned; the
+/// `state` query parameter selects a single state (e.g. `time_wait`) or
+/// `all`, `protocol` is `tcp` or `udp`, and `port` keeps the sockets bound
+/// to a local port, answering "what is exposing port 8080".
+#[get("/network/sockets")]
+async fn get_network_sockets(
+    params: web::Query<SocketQuery>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, Error> {
+    if let Some(state) = params.state.as_deref()
+        && state != "all"
+        && !sockets::counted_states().iter().any(|(_, s)| *s == state)
+    {
+        return Err(ErrorBadRequest(format!("Unknown socket state `{}`", state)));
+    }
+    if let Some(protocol) = params.protocol.as_deref()
+        && protocol != "tcp"
+        && protocol != "udp"
+    {
+        return Err(ErrorBadRequest(
+            "Invalid `protocol`, expected `tcp` or `udp`",
+        ));
+    }
+
+    /* reading the sockets and the descriptors of every process is
+     * blocking, keep it off the async workers */
+    let procfs_root = PathBuf::from(&config.monitor.procfs_root);
+    let (all_sockets, owners) = web::block(move || {
+        (
+            sockets::read_sockets(&procfs_root),
+            sockets::read_socket_owners(&procfs_root),
+        )
+    })
+    .await
+    .map_err(|e| {
+        eprintln!("Failed to read sockets: {}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to read sockets")
+    })?;
+    let all_sockets = all_sockets.map_err(|e| {
+        eprintln!("Failed to read sockets: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to read sockets")
+    })?;
+    let owners = owners.map_err(|e| {
+        eprintln!("Failed to read socket owners: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to read socket owners")
+    })?;
+
+    let counts = sockets::count_states(&all_sockets)
+        .into_iter()
+        .map(|((protocol, state), count)| SocketStateCountResponse {
+            protocol: protocol.to_string(),
+            state: state.to_string(),
+            count: count as i64,
+        })
+        .collect();
+
+    let mut matching: Vec<SocketEntry> = all_sockets
+        .into_iter()
+        .filter(|socket| params.matches(socket))
+        .collect();
+    matching.sort_by_key(|s| {
+        (
+            s.state != "listen",
+            s.protocol,
+            s.local_port,
+            s.remote_address,
+            s.remote_port,
+        )
+    });
+
+    let sockets = matching
+        .into_iter()
+        .map(|s| {
+            let owner = owners.get(&s.inode);
+            SocketResponse {
+                protocol: s.protocol.to_string(),
+                local_address: s.local_address.to_string(),
+                local_port: s.local_port,
+                remote_address: s.remote_address.to_string(),
+                remote_port: s.remote_port,
+                state: s.state.to_string(),
+                uid: s.uid,
+                inode: s.inode,
+                pid: owner.map(|o| o.pid),
+                process: owner.map(|o| o.name.clone()),
+            }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(SocketListResponse {
+        timestamp: Utc::now().to_rfc3339(),
+        counts,
+        sockets,
+    }))
+}
+
+/// Returns the stored socket counts per protocol and state.
+///
+/// Each point holds the counts of every state at one monitoring snapshot,
+/// oldest first, so clients can plot established connections or spot a
+/// build-up of `time_wait` or `close_wait` sockets. The number of points
+/// is bounded by the `limit` query parameter.
+#[get("/network/sockets/history")]
+async fn get_network_socket_history(
+    params: web::Query<HistoryQuery>,
+    storage: web::Data<Storage>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
+        eprintln!("Mutex poisoned while getting socket history"); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to acquire database lock")
+    })?;
+
+    let history = query::get_socketinfo_history(&mut conn, params.limit()).map_err(|e| {
+        eprintln!("Database error getting socket history: {:?}", e); // TODO: Use log::error!
+        ErrorInternalServerError("Failed to get socket history")
+    })?;
+
+    let response: Vec<SocketStatesSampleResponse> = history
+        .into_iter()
+        .map(|(timestamp, counts)| SocketStatesSampleResponse {
+            timestamp,
+            counts: counts.into_iter().map(socketinfo_to_response).collect(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
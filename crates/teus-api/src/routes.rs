@@ -3,9 +3,11 @@
 
 use std::sync::Mutex;
 
-use crate::handlers::systeminfo;
+use crate::handlers::{
+    cgroups, kernel, logs, metrics, packages, paths, processes, scripts, security, systeminfo,
+};
 use actix_cors::Cors;
-use actix_web::error::ErrorInternalServerError;
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
 use actix_web::{App, Error, HttpResponse, HttpServer, get, http, middleware, web};
 use docker::docker::DockerClient;
 use teus_auth::handlers::{JwtConfig, check, login, signup};
@@ -18,16 +20,20 @@ use teus_docker::handlers::{
 };
 use teus_monitor::query;
 use teus_services::bookmarks::handlers as bookmark_handlers;
-use teus_types::api_models::{DiskInfoResponse, SysInfoResponse};
+use teus_types::api_models::{
+    CpuCoreResponse, DiskInfoResponse, LoadAverageResponse, SysInfoResponse,
+};
 use teus_types::config::Config;
 
 pub type DockerState = web::Data<Mutex<Option<DockerClient>>>;
 
-
 // TODO: move this api into another file `syshandler` or something
 #[get("/sysinfo")]
 async fn sysinfo_handler(storage: web::Data<Storage>) -> Result<HttpResponse, Error> {
-    let mut conn = storage.diesel_conn.lock().map_err(|_| {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
         eprintln!("Mutex poisoned while getting sysinfo"); // TODO: Use log::error!
         ErrorInternalServerError("Failed to acquire database lock")
     })?;
@@ -38,6 +44,64 @@ async fn sysinfo_handler(storage: web::Data<Storage>) -> Result<HttpResponse, Er
     })?;
 
     if let Some((sys_info, disks)) = sys_info_result {
+        let cpus = query::get_cpuinfo_by_sysinfo_id(&mut conn, sys_info.id.unwrap_or_default())
+            .map_err(|e| {
+                eprintln!("Database error getting cpu info: {:?}", e); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to get cpu info")
+            })?
+            .into_iter()
+            .map(|c| CpuCoreResponse {
+                name: c.name,
+                cpu_usage: c.cpu_usage,
+                frequency: c.frequency,
+            })
+            .collect();
+
+        /* collectors may run less often than samples are taken: use their latest records */
+        let interfaces = query::get_latest_netinfo(&mut conn)
+            .map_err(|e| {
+                eprintln!("Database error getting network info: {:?}", e); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to get network info")
+            })?
+            .map(|(_, interfaces)| interfaces)
+            .unwrap_or_default()
+            .into_iter()
+            .map(systeminfo::netinfo_to_response)
+            .collect();
+
+        let disk_io = query::get_latest_diskio(&mut conn)
+            .map_err(|e| {
+                eprintln!("Database error getting disk I/O info: {:?}", e); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to get disk I/O info")
+            })?
+            .map(|(_, devices)| devices)
+            .unwrap_or_default()
+            .into_iter()
+            .map(systeminfo::diskio_to_response)
+            .collect();
+
+        let sensors = query::get_latest_sensorinfo(&mut conn)
+            .map_err(|e| {
+                eprintln!("Database error getting sensor info: {:?}", e); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to get sensor info")
+            })?
+            .map(|(_, sensors)| sensors)
+            .unwrap_or_default()
+            .into_iter()
+            .map(systeminfo::sensorinfo_to_response)
+            .collect();
+
+        let pressure = query::get_latest_psiinfo(&mut conn)
+            .map_err(|e| {
+                eprintln!("Database error getting pressure info: {:?}", e); // TODO: Use log::error!
+                ErrorInternalServerError("Failed to get pressure info")
+            })?
+            .map(|(_, pressure)| pressure)
+            .unwrap_or_default()
+            .into_iter()
+            .map(systeminfo::psiinfo_to_response)
+            .collect();
+
         let timestamp = sys_info.timestamp.clone();
         let disks = disks
             .iter()
@@ -47,6 +111,10 @@ async fn sysinfo_handler(storage: web::Data<Storage>) -> Result<HttpResponse, Er
                 total_space: d.size,
                 available_space: d.available,
                 used_space: d.used,
+                inodes_total: d.inodes_total,
+                inodes_used: d.inodes_used,
+                inodes_free: d.inodes_free,
+                inodes_used_percentage: d.inodes_used_percentage,
             })
             .collect();
 
@@ -57,7 +125,27 @@ async fn sysinfo_handler(storage: web::Data<Storage>) -> Result<HttpResponse, Er
             total_ram: sys_info.total_ram,
             free_ram: sys_info.free_ram,
             used_swap: sys_info.used_swap,
-            disks: disks,
+            available_ram: sys_info.available_ram,
+            cached_ram: sys_info.cached_ram,
+            buffers_ram: sys_info.buffers_ram,
+            shared_ram: sys_info.shared_ram,
+            total_swap: sys_info.total_swap,
+            free_swap: sys_info.free_swap,
+            dirty_ram: sys_info.dirty_ram,
+            hugepages_total: sys_info.hugepages_total,
+            hugepages_free: sys_info.hugepages_free,
+            hugepage_size: sys_info.hugepage_size,
+            load_average: LoadAverageResponse {
+                one: sys_info.load_avg_one,
+                five: sys_info.load_avg_five,
+                fifteen: sys_info.load_avg_fifteen,
+            },
+            cpus,
+            interfaces,
+            disk_io,
+            sensors,
+            pressure,
+            disks,
         };
 
         Ok(HttpResponse::Ok().json(response))
@@ -112,7 +200,30 @@ pub async fn start_webserver(config: &Config, storage: Storage) -> std::io::Resu
                     .wrap(AuthMiddlewareFactory::new(jwt_secret.to_string()))
                     .service(check) // check for auth
                     .service(sysinfo_handler)
+                    .service(metrics::get_sysinfo_history)
                     .service(systeminfo::get_sysinfo)
+                    .service(systeminfo::get_network_interface_history)
+                    .service(systeminfo::get_network_sockets)
+                    .service(systeminfo::get_network_socket_history)
+                    .service(systeminfo::get_disk_io_history)
+                    .service(systeminfo::get_sensor_history)
+                    .service(systeminfo::get_pressure_history)
+                    .service(systeminfo::get_time_sync_history)
+                    .service(processes::get_processes)
+                    .service(cgroups::get_cgroups)
+                    .service(security::get_login_sessions)
+                    .service(security::get_security_events)
+                    .service(security::get_auth_failures)
+                    .service(kernel::get_kernel_events)
+                    .service(logs::get_log_matches)
+                    .service(logs::get_log_events)
+                    .service(logs::get_log_match_history)
+                    .service(scripts::get_script_metrics)
+                    .service(scripts::get_script_metric_history)
+                    .service(packages::get_packages)
+                    .service(packages::get_package_history)
+                    .service(paths::get_paths)
+                    .service(paths::get_path_history)
                     .service(get_docker_version)
                     .service(get_docker_containers)
                     .service(get_docker_container)
@@ -9,8 +9,7 @@ use chrono::{Duration, Utc};
 use jsonwebtoken::{EncodingKey, Header, encode};
 use serde::{Deserialize, Serialize};
 use teus_config::config::schema::TeusConfig;
-use teus_database::storage::Storage;
-use teus_types::config::Config;
+use teus_database::storage::{PoolError, Storage};
 
 /// Request structure for user authentication login endpoint.
 ///
@@ -285,14 +284,26 @@ fn is_same_password(password_hash: &str, clear_pass: &str, _salt: &str) -> bool
 pub async fn login(
     login_data: web::Json<LoginRequest>,
     jwt_config: web::Data<JwtConfig>,
-    config: actix_web::web::Data<Config>,
+    storage: web::Data<Storage>,
 ) -> impl Responder {
-    let storage = Storage::new(&config.database.path).unwrap();
-    let mut conn = storage.diesel_conn.lock().unwrap();
-    let user = User::find_by_username(&mut *conn, &login_data.username).unwrap();
-    let user_id: i32;
-
-    match user {
+    let mut conn = match storage.read() {
+        Ok(conn) => conn,
+        Err(e) if e.is_busy() => {
+            let response = GenericResponse {
+                message: "All database connections are busy, try again".to_string(),
+            };
+            return HttpResponse::ServiceUnavailable().json(response);
+        }
+        Err(e) => {
+            eprintln!("Failed to acquire the database for login: {}", e); // TODO: Use log::error!
+            let response = GenericResponse {
+                message: "Failed to acquire database lock".to_string(),
+            };
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+    let user = User::find_by_username(&mut conn, &login_data.username).unwrap();
+    let user_id: i32 = match user {
         Some(user) => {
             println!("User found: {:?}", user);
             let is_password_correct =
@@ -305,7 +316,7 @@ pub async fn login(
                 return HttpResponse::Unauthorized().json(response);
             }
 
-            user_id = user.id.unwrap();
+            user.id.unwrap()
         }
         None => {
             println!("User not found");
@@ -314,7 +325,7 @@ pub async fn login(
             };
             return HttpResponse::Unauthorized().json(response);
         }
-    }
+    };
 
     let access_expiration = Utc::now()
         .checked_add_signed(Duration::hours(jwt_config.expiration_hours))
@@ -366,28 +377,53 @@ pub async fn login(
 #[post("/signup")]
 pub async fn signup(
     signup_data: web::Json<SignupRequest>,
-    config: actix_web::web::Data<Config>,
+    storage: web::Data<Storage>,
 ) -> impl Responder {
-    let storage = Storage::new(&config.database.path).unwrap();
-    let mut conn = storage.diesel_conn.lock().unwrap();
+    /* the writer, so the username check and the insert can't interleave
+     * with another signup; the background jobs can hold it for a while,
+     * so it's waited for on a blocking thread */
+    let signup_data = signup_data.into_inner();
+    let created = web::block(move || {
+        let mut conn = storage.write()?;
 
-    let existing_user = User::find_by_username(&mut *conn, &signup_data.username).unwrap();
-    if existing_user.is_some() {
-        let response = GenericResponse {
-            message: "Username already exists".to_string(),
-        };
-        return HttpResponse::Conflict().json(response);
-    }
-    let user = User::create(&mut *conn, &signup_data.username, &signup_data.password).unwrap();
-    TeusConfig::set_first_visit(&mut *conn, false).unwrap();
+        let existing_user = User::find_by_username(&mut conn, &signup_data.username).unwrap();
+        if existing_user.is_some() {
+            return Ok(None);
+        }
+        let user = User::create(&mut conn, &signup_data.username, &signup_data.password).unwrap();
+        TeusConfig::set_first_visit(&mut conn, false).unwrap();
 
-    // Create a response without the sensitive data
-    let user_response = NewUserResponse {
-        id: user.id.unwrap(),
-        username: user.username,
-    };
+        // Create a response without the sensitive data
+        Ok::<_, PoolError>(Some(NewUserResponse {
+            id: user.id.unwrap(),
+            username: user.username,
+        }))
+    })
+    .await;
 
-    HttpResponse::Created().json(user_response)
+    match created {
+        Ok(Ok(Some(user_response))) => HttpResponse::Created().json(user_response),
+        Ok(Ok(None)) => {
+            let response = GenericResponse {
+                message: "Username already exists".to_string(),
+            };
+            HttpResponse::Conflict().json(response)
+        }
+        Ok(Err(e)) => {
+            eprintln!("Failed to acquire the database for signup: {}", e); // TODO: Use log::error!
+            let response = GenericResponse {
+                message: "Failed to acquire database lock".to_string(),
+            };
+            HttpResponse::InternalServerError().json(response)
+        }
+        Err(e) => {
+            eprintln!("Signup failed: {}", e); // TODO: Use log::error!
+            let response = GenericResponse {
+                message: "Failed to create the user".to_string(),
+            };
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
 }
 
 // TODO: Move this `health` somewhere else
@@ -17,6 +17,7 @@ use std::{
     rc::Rc,
 };
 
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct NotAuth {
     message: String,
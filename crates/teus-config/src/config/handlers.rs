@@ -1,22 +1,21 @@
 use crate::config::schema;
-use actix_web::error::ErrorInternalServerError;
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
 use actix_web::{Error, HttpResponse, get, web};
 use teus_database::storage::Storage;
-use teus_types::config::{Config, IsFirstVisitResponse};
+use teus_types::config::IsFirstVisitResponse;
 
 /* is this really useful? */
 #[get("/teus-config")]
-pub async fn get_teus_config(config: web::Data<Config>) -> Result<HttpResponse, Error> {
-    let storage = Storage::new(&config.database.path).map_err(|e| {
-        eprintln!("Failed to initialize storage: {:?}", e); // TODO: Use log::error!
-        ErrorInternalServerError("Failed to initialize storage")
-    })?;
-    let mut conn = storage.diesel_conn.lock().map_err(|_| {
+pub async fn get_teus_config(storage: web::Data<Storage>) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
         eprintln!("Mutex poisoned while getting Teus config"); // TODO: Use log::error!
         ErrorInternalServerError("Failed to acquire database lock")
     })?;
     let latest_teusconfig_option =
-        schema::TeusConfig::get_teus_server_config(&mut *conn).map_err(|e| {
+        schema::TeusConfig::get_teus_server_config(&mut conn).map_err(|e| {
             eprintln!("Database error getting Teus config: {:?}", e); // TODO: Use log::error!
             ErrorInternalServerError("Error fetching TeusConfig")
         })?;
@@ -28,19 +27,15 @@ pub async fn get_teus_config(config: web::Data<Config>) -> Result<HttpResponse,
 }
 
 #[get("/teus-config/first-visit")]
-pub async fn is_first_visit(config: web::Data<Config>) -> Result<HttpResponse, Error> {
-    let storage = Storage::new(&config.database.path).map_err(|e| {
-        eprintln!(
-            "Failed to initialize storage for first-visit check: {:?}",
-            e
-        ); // TODO: Use log::error!
-        ErrorInternalServerError("Failed to initialize storage")
-    })?;
-    let mut conn = storage.diesel_conn.lock().map_err(|_| {
+pub async fn is_first_visit(storage: web::Data<Storage>) -> Result<HttpResponse, Error> {
+    let mut conn = storage.read().map_err(|e| {
+        if e.is_busy() {
+            return ErrorServiceUnavailable("All database connections are busy, try again");
+        }
         eprintln!("Mutex poisoned while checking first visit"); // TODO: Use log::error!
         ErrorInternalServerError("Failed to acquire database lock")
     })?;
-    let first_visit = schema::TeusConfig::is_first_visit(&mut *conn).map_err(|e| {
+    let first_visit = schema::TeusConfig::is_first_visit(&mut conn).map_err(|e| {
         eprintln!("Database error checking first visit: {:?}", e); // TODO: Use log::error!
         ErrorInternalServerError("Error checking first visit status")
     })?;
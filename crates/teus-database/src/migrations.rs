@@ -0,0 +1,259 @@
+//! Schema migrations, embedded from the `migrations/` directory.
+//!
+//! Pending migrations are applied when Teus starts, so a new database path
+//! gets its tables without the diesel CLI. The applied versions are
+//! recorded in `__diesel_schema_migrations`, like the CLI does, so
+//! databases migrated by hand are picked up where they are.
+
+use crate::storage::is_in_memory;
+use diesel::SqliteConnection;
+use diesel::connection::SimpleConnection;
+use diesel::migration::{Migration, MigrationSource};
+use diesel::sqlite::Sqlite;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Migrations of this binary.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../migrations");
+
+pub type MigrationError = Box<dyn Error + Send + Sync>;
+
+/// Migrations of a database compared to those of this binary.
+#[derive(Debug, PartialEq)]
+pub struct MigrationStatus {
+    /// Migrations of this binary, oldest first, with whether each is
+    /// applied.
+    pub known: Vec<(String, bool)>,
+    /// Versions applied to the database that this binary doesn't know,
+    /// written by a newer Teus.
+    pub unknown: Vec<String>,
+}
+
+impl MigrationStatus {
+    /// Names of the migrations not applied yet, oldest first.
+    pub fn pending(&self) -> Vec<&str> {
+        self.known
+            .iter()
+            .filter(|(_, applied)| !applied)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Returns whether the database holds a schema at all.
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty() && self.known.iter().all(|(_, applied)| !applied)
+    }
+}
+
+fn embedded() -> Result<Vec<Box<dyn Migration<Sqlite>>>, MigrationError> {
+    let mut migrations = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)?;
+    migrations.sort_by_key(|m| m.name().version().to_string());
+    Ok(migrations)
+}
+
+/// Compares the migrations applied to the database with those of this
+/// binary.
+pub fn status(conn: &mut SqliteConnection) -> Result<MigrationStatus, MigrationError> {
+    let mut applied: Vec<String> = conn
+        .applied_migrations()?
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    let known = embedded()?
+        .iter()
+        .map(|m| {
+            let version = m.name().version().to_string();
+            let is_applied = applied.contains(&version);
+            applied.retain(|v| *v != version);
+            (m.name().to_string(), is_applied)
+        })
+        .collect();
+    applied.sort();
+    Ok(MigrationStatus {
+        known,
+        unknown: applied,
+    })
+}
+
+/// Exclusive lock on `<db_path>.migrate.lock`, held until dropped.
+///
+/// The lock is released by the system if the process dies, so a crash
+/// never leaves a database that can't be migrated.
+struct MigrationLock {
+    _file: Option<File>,
+}
+
+impl MigrationLock {
+    fn acquire(db_path: &str) -> Result<Self, MigrationError> {
+        if is_in_memory(db_path) {
+            return Ok(Self { _file: None });
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(format!("{}.migrate.lock", db_path))?;
+        file.lock()?;
+        Ok(Self { _file: Some(file) })
+    }
+}
+
+/// Copies the database at `db_path` to `<db_path>.<unix time ms>.bak` with
+/// `VACUUM INTO`, which is consistent even while other connections write.
+/// Returns the path of the copy.
+pub fn backup(conn: &mut SqliteConnection, db_path: &str) -> Result<PathBuf, MigrationError> {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let path = PathBuf::from(format!("{}.{}.bak", db_path, millis));
+    let quoted = path.to_string_lossy().replace('\'', "''");
+    conn.batch_execute(&format!("VACUUM INTO '{}'", quoted))?;
+    Ok(path)
+}
+
+/// Applies the migrations of this binary missing from the database at
+/// `db_path`, opened as `conn`. Returns the names of the migrations
+/// applied, oldest first.
+///
+/// A database holding migrations this binary doesn't know was written by a
+/// newer Teus and is refused. Processes migrating the same database are
+/// serialized by a lock file next to it, and a database that already has a
+/// schema is backed up before it's changed. Migrations are applied in a
+/// single exclusive transaction: all of them or none.
+pub fn apply_pending(
+    conn: &mut SqliteConnection,
+    db_path: &str,
+) -> Result<Vec<String>, MigrationError> {
+    let _lock = MigrationLock::acquire(db_path)?;
+
+    let status = status(conn)?;
+    if !status.unknown.is_empty() {
+        return Err(format!(
+            "the database schema is newer than this binary (unknown migrations: {}), upgrade Teus",
+            status.unknown.join(", ")
+        )
+        .into());
+    }
+    let pending: Vec<String> = status.pending().into_iter().map(String::from).collect();
+    if pending.is_empty() {
+        return Ok(pending);
+    }
+
+    if !status.is_empty() && !is_in_memory(db_path) {
+        let path = backup(conn, db_path)?;
+        println!("Database backed up to {}", path.display());
+    }
+    conn.exclusive_transaction(|conn| {
+        conn.run_pending_migrations(MIGRATIONS)
+            .map(|_| ())
+            .map_err(|e| -> MigrationError { e })
+    })?;
+    Ok(pending)
+}
+
+/// Reverts the latest migration applied to the database at `db_path`,
+/// after a backup. Returns its name.
+pub fn revert_last(conn: &mut SqliteConnection, db_path: &str) -> Result<String, MigrationError> {
+    let _lock = MigrationLock::acquire(db_path)?;
+
+    let status = status(conn)?;
+    if let Some(version) = status.unknown.last() {
+        return Err(format!(
+            "the latest migration ({}) is unknown to this binary, revert it with the Teus that applied it",
+            version
+        )
+        .into());
+    }
+    let Some((name, _)) = status.known.iter().rev().find(|(_, applied)| *applied) else {
+        return Err("no migration is applied".into());
+    };
+
+    if !is_in_memory(db_path) {
+        let path = backup(conn, db_path)?;
+        println!("Database backed up to {}", path.display());
+    }
+    conn.exclusive_transaction(|conn| {
+        conn.revert_last_migration(MIGRATIONS)
+            .map(|_| ())
+            .map_err(|e| -> MigrationError { e })
+    })?;
+    Ok(name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::{Connection, RunQueryDsl, sql_query};
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir) -> (String, SqliteConnection) {
+        let path = dir.path().join("teus.db").to_str().unwrap().to_string();
+        let conn = SqliteConnection::establish(&path).unwrap();
+        (path, conn)
+    }
+
+    fn backups(dir: &TempDir) -> usize {
+        std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().path().extension() == Some("bak".as_ref()))
+            .count()
+    }
+
+    #[test]
+    fn test_apply_pending_creates_the_schema() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let (path, mut conn) = open(&dir);
+
+        let applied = apply_pending(&mut conn, &path).unwrap();
+        assert_eq!(applied.len(), embedded().unwrap().len());
+        assert!(applied[0].ends_with("_create_user"));
+        // Nothing to back up in a new database
+        assert_eq!(backups(&dir), 0);
+
+        sql_query("SELECT * FROM sysinfo")
+            .execute(&mut conn)
+            .unwrap();
+        assert!(status(&mut conn).unwrap().pending().is_empty());
+        assert!(apply_pending(&mut conn, &path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revert_and_reapply_with_backups() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let (path, mut conn) = open(&dir);
+        apply_pending(&mut conn, &path).unwrap();
+
+        let last = embedded().unwrap().last().unwrap().name().to_string();
+        assert_eq!(revert_last(&mut conn, &path).unwrap(), last);
+        assert_eq!(status(&mut conn).unwrap().pending(), vec![last.as_str()]);
+        assert_eq!(backups(&dir), 1);
+
+        assert_eq!(apply_pending(&mut conn, &path).unwrap(), vec![last]);
+        assert_eq!(backups(&dir), 2);
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let (path, mut conn) = open(&dir);
+        apply_pending(&mut conn, &path).unwrap();
+        conn.batch_execute(
+            "INSERT INTO __diesel_schema_migrations (version) VALUES ('99991231000000')",
+        )
+        .unwrap();
+
+        let status = status(&mut conn).unwrap();
+        assert_eq!(status.unknown, vec!["99991231000000"]);
+        let error = apply_pending(&mut conn, &path).unwrap_err();
+        assert!(error.to_string().contains("newer than this binary"));
+        assert!(revert_last(&mut conn, &path).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_database() {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        assert!(status(&mut conn).unwrap().is_empty());
+        assert!(!apply_pending(&mut conn, ":memory:").unwrap().is_empty());
+    }
+}
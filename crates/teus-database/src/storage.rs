@@ -1,16 +1,136 @@
 use diesel::connection::SimpleConnection; // Added
 use diesel::{Connection as ConnectionDiesel, SqliteConnection};
 use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
-use std::sync::{Arc, Mutex}; // Added for Box<dyn Error>
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, TryLockError};
 
 /* to avoid to add diesel dep on crates */
 pub type TeuSQLiteConnection = SqliteConnection;
 
+/// Read connections opened by `Storage::new`.
+pub const DEFAULT_READERS: usize = 4;
+
+/// Connections to the Teus database, shared by the monitor, the background
+/// jobs and every request of the API.
+///
+/// SQLite allows a single writer at a time but, in WAL mode, readers that
+/// don't wait for it. The storage holds one write connection, taken with
+/// [`Storage::write`], and a pool of read-only connections taken with
+/// [`Storage::read`], so reads of the API don't queue behind the monitor
+/// or the retention job. `read` doesn't wait when every reader is in use,
+/// so requests get a 503 rather than blocking their worker; blocking
+/// threads can wait with [`Storage::read_blocking`]. Connections go back to the storage when the
+/// returned guard is dropped; keep them no longer than a query or a
+/// transaction.
+///
+/// Clones share the same connections.
 #[derive(Clone)]
 pub struct Storage {
-    // pub conn: Arc<Connection>, // @Info: old Arc reference to don't break the code
-    pub diesel_conn: Arc<Mutex<SqliteConnection>>, // @Info: use to test diesel for now
+    pool: Arc<Pool>,
+}
+
+struct Pool {
+    writer: Mutex<SqliteConnection>,
+    /* idle read connections */
+    readers: Mutex<Vec<SqliteConnection>>,
+    reader_returned: Condvar,
+    /* without readers, reads use the writer */
+    has_readers: bool,
+}
+
+/// Why a connection couldn't be taken from the storage.
+#[derive(Debug)]
+pub enum PoolError {
+    /// Every connection that could serve the request is in use.
+    Busy,
+    /// A thread panicked while holding the connection.
+    Poisoned,
+}
+
+impl PoolError {
+    /// Whether the request can be retried once a connection is given back.
+    pub fn is_busy(&self) -> bool {
+        matches!(self, PoolError::Busy)
+    }
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Busy => write!(f, "every database connection is in use"),
+            PoolError::Poisoned => write!(f, "database connection poisoned by a panic"),
+        }
+    }
+}
+
+impl Error for PoolError {}
+
+impl<T> From<PoisonError<T>> for PoolError {
+    fn from(_: PoisonError<T>) -> Self {
+        PoolError::Poisoned
+    }
+}
+
+impl<T> From<TryLockError<T>> for PoolError {
+    fn from(e: TryLockError<T>) -> Self {
+        match e {
+            TryLockError::WouldBlock => PoolError::Busy,
+            TryLockError::Poisoned(_) => PoolError::Poisoned,
+        }
+    }
+}
+
+/// A connection of the storage, given back when dropped.
+pub struct PooledConnection<'a> {
+    inner: Pooled<'a>,
+}
+
+enum Pooled<'a> {
+    Writer(MutexGuard<'a, SqliteConnection>),
+    /* the connection is only taken back on drop */
+    Reader(Option<SqliteConnection>, &'a Pool),
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &SqliteConnection {
+        match &self.inner {
+            Pooled::Writer(conn) => conn,
+            Pooled::Reader(conn, _) => conn.as_ref().expect("reader taken back before drop"),
+        }
+    }
+}
+
+impl DerefMut for PooledConnection<'_> {
+    fn deref_mut(&mut self) -> &mut SqliteConnection {
+        match &mut self.inner {
+            Pooled::Writer(conn) => conn,
+            Pooled::Reader(conn, _) => conn.as_mut().expect("reader taken back before drop"),
+        }
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Pooled::Reader(conn, pool) = &mut self.inner
+            && let Some(conn) = conn.take()
+        {
+            pool.readers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(conn);
+            pool.reader_returned.notify_one();
+        }
+    }
+}
+
+/// Returns whether `db_path` names an in-memory database, private to each
+/// connection.
+pub(crate) fn is_in_memory(db_path: &str) -> bool {
+    db_path.is_empty() || db_path == ":memory:" || db_path.contains("mode=memory")
 }
 
 mod storage_utils {
@@ -27,32 +147,102 @@ mod storage_utils {
     }
 }
 
-// TODO: Migrate Connection -> SqliteConnection // This TODO can be removed after this refactor
 impl Storage {
+    /// Opens the database at `db_path` with `DEFAULT_READERS` read
+    /// connections.
     pub fn new(db_path: &str) -> Result<Self, Box<dyn Error>> {
-        // Changed return type
-        if let Some(parent) = Path::new(db_path).parent() {
-            if let Some(parent_str) = parent.to_str() {
-                storage_utils::ensure_directory_exists(parent_str)?; // Changed from expect
-            }
-        }
-
-        // Removed rusqlite connection logic
+        Self::with_readers(db_path, DEFAULT_READERS)
+    }
 
-        let mut conn_new = SqliteConnection::establish(&db_path)?; // Changed from unwrap_or_else
+    /// Opens the database at `db_path`, creating it and its directory if
+    /// needed, with one write connection and `readers` read connections.
+    ///
+    /// Each connection to an in-memory database would see a database of
+    /// its own, so these get no readers: reads share the write connection.
+    pub fn with_readers(db_path: &str, readers: usize) -> Result<Self, Box<dyn Error>> {
+        if let Some(parent_str) = Path::new(db_path).parent().and_then(|p| p.to_str()) {
+            storage_utils::ensure_directory_exists(parent_str)?; // Changed from expect
+        }
 
+        let mut writer = SqliteConnection::establish(db_path)?;
         // Apply PRAGMAs to Diesel connection
         // Note: busy_timeout is set in milliseconds for SQLite PRAGMA
-        conn_new.batch_execute(
-            "PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA busy_timeout = 5000;",
+        // foreign_keys is off by default and per connection, it makes the
+        // rows of a sample go with it
+        writer.batch_execute(
+            "PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA busy_timeout = 5000; PRAGMA foreign_keys = ON;",
         )?;
 
+        let readers = if is_in_memory(db_path) { 0 } else { readers };
+        let mut idle = Vec::with_capacity(readers);
+        for _ in 0..readers {
+            // journal_mode is stored in the database, the writer set it
+            let mut reader = SqliteConnection::establish(db_path)?;
+            reader.batch_execute(
+                "PRAGMA query_only = ON; PRAGMA busy_timeout = 5000; PRAGMA foreign_keys = ON;",
+            )?;
+            idle.push(reader);
+        }
+
         Ok(Self {
-            diesel_conn: Arc::new(Mutex::new(conn_new)),
+            pool: Arc::new(Pool {
+                writer: Mutex::new(writer),
+                readers: Mutex::new(idle),
+                reader_returned: Condvar::new(),
+                has_readers: readers > 0,
+            }),
+        })
+    }
+
+    /// Takes the write connection, waiting for its current holder.
+    ///
+    /// Use it for anything that writes and for reads that must see the
+    /// writes made just before on it. The background jobs hold it for
+    /// whole batches, so async handlers take it on a blocking thread
+    /// (`web::block`).
+    pub fn write(&self) -> Result<PooledConnection<'_>, PoolError> {
+        Ok(PooledConnection {
+            inner: Pooled::Writer(self.pool.writer.lock()?),
         })
     }
 
-    // Removed _init_db method
+    /// Takes a read-only connection without waiting: fails with
+    /// `PoolError::Busy` if they are all in use. It sees every transaction
+    /// committed before its queries start.
+    ///
+    /// This is the one for async handlers, which mustn't block their
+    /// worker thread; answer `Busy` with a 503.
+    pub fn read(&self) -> Result<PooledConnection<'_>, PoolError> {
+        if !self.pool.has_readers {
+            return Ok(PooledConnection {
+                inner: Pooled::Writer(self.pool.writer.try_lock()?),
+            });
+        }
+        match self.pool.readers.lock()?.pop() {
+            Some(conn) => Ok(PooledConnection {
+                inner: Pooled::Reader(Some(conn), &self.pool),
+            }),
+            None => Err(PoolError::Busy),
+        }
+    }
+
+    /// Takes a read-only connection like [`Storage::read`], but waits for
+    /// one to be given back if they are all in use. Only call it from
+    /// threads that may block.
+    pub fn read_blocking(&self) -> Result<PooledConnection<'_>, PoolError> {
+        if !self.pool.has_readers {
+            return self.write();
+        }
+        let mut idle = self.pool.readers.lock()?;
+        loop {
+            if let Some(conn) = idle.pop() {
+                return Ok(PooledConnection {
+                    inner: Pooled::Reader(Some(conn), &self.pool),
+                });
+            }
+            idle = self.pool.reader_returned.wait(idle)?;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -72,7 +262,7 @@ mod tests {
         let storage = storage.unwrap();
 
         // Test that we can acquire the mutex lock
-        let conn_guard = storage.diesel_conn.lock();
+        let conn_guard = storage.write();
         assert!(conn_guard.is_ok());
     }
 
@@ -96,7 +286,7 @@ mod tests {
         assert!(storage.is_ok());
 
         let storage = storage.unwrap();
-        let conn_guard = storage.diesel_conn.lock();
+        let conn_guard = storage.write();
         assert!(conn_guard.is_ok());
     }
 
@@ -129,11 +319,11 @@ mod tests {
         let cloned_storage = storage.clone();
 
         // Both should be able to access the same connection
-        let conn1 = storage.diesel_conn.lock();
+        let conn1 = storage.write();
         assert!(conn1.is_ok());
         drop(conn1); // Release lock before trying with clone
 
-        let conn2 = cloned_storage.diesel_conn.lock();
+        let conn2 = cloned_storage.write();
         assert!(conn2.is_ok());
     }
 
@@ -150,7 +340,7 @@ mod tests {
         let storage_clone = storage.clone();
 
         let handle = thread::spawn(move || {
-            let conn = storage_clone.diesel_conn.lock();
+            let conn = storage_clone.write();
             assert!(conn.is_ok());
             thread::sleep(Duration::from_millis(10));
         });
@@ -161,7 +351,7 @@ mod tests {
         // This should be able to access after the thread releases the lock
         handle.join().expect("Thread panicked");
 
-        let conn = storage.diesel_conn.lock();
+        let conn = storage.write();
         assert!(conn.is_ok());
     }
 
@@ -202,4 +392,84 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_readers_see_committed_writes() {
+        use diesel::dsl::sql;
+        use diesel::sql_types::Integer;
+        use diesel::{RunQueryDsl, select};
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db_path = temp_dir.path().join("test.db");
+        let storage = Storage::new(db_path.to_str().unwrap()).unwrap();
+
+        storage
+            .write()
+            .unwrap()
+            .batch_execute("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);")
+            .unwrap();
+
+        // A reader works while the writer is held
+        let _writer = storage.write().unwrap();
+        let mut reader = storage.read().unwrap();
+        let x: i32 = select(sql::<Integer>("(SELECT x FROM t)"))
+            .get_result(&mut *reader)
+            .unwrap();
+        assert_eq!(x, 1);
+        // and can't write
+        assert!(reader.batch_execute("INSERT INTO t VALUES (2);").is_err());
+    }
+
+    #[test]
+    fn test_read_fails_fast_when_busy() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db_path = temp_dir.path().join("test.db");
+        let storage = Storage::with_readers(db_path.to_str().unwrap(), 1).unwrap();
+
+        let reader = storage.read().unwrap();
+        assert!(storage.read().is_err_and(|e| e.is_busy()));
+        drop(reader);
+        assert!(storage.read().is_ok());
+    }
+
+    #[test]
+    fn test_read_blocking_waits_for_a_reader() {
+        use std::thread;
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db_path = temp_dir.path().join("test.db");
+        let storage = Storage::with_readers(db_path.to_str().unwrap(), 1).unwrap();
+
+        let reader = storage.read().unwrap();
+        let storage_clone = storage.clone();
+        let handle = thread::spawn(move || {
+            let _reader = storage_clone.read_blocking().unwrap();
+        });
+        thread::sleep(Duration::from_millis(20));
+        assert!(!handle.is_finished());
+
+        drop(reader);
+        handle.join().expect("Thread panicked");
+    }
+
+    #[test]
+    fn test_memory_database_reads_share_the_writer() {
+        use diesel::dsl::sql;
+        use diesel::sql_types::Integer;
+        use diesel::{RunQueryDsl, select};
+
+        let storage = Storage::new(":memory:").unwrap();
+        storage
+            .write()
+            .unwrap()
+            .batch_execute("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);")
+            .unwrap();
+
+        let mut reader = storage.read().unwrap();
+        let x: i32 = select(sql::<Integer>("(SELECT x FROM t)"))
+            .get_result(&mut *reader)
+            .unwrap();
+        assert_eq!(x, 1);
+    }
 }
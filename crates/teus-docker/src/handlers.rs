@@ -39,7 +39,7 @@ struct ContainersQuery {
 
 #[get("/docker/version")]
 async fn get_docker_version(docker_state: DockerState) -> Result<HttpResponse, ApiError> {
-    let mut docker_client = get_client(&docker_state).map_err(|e| ApiError::Docker(e))?;
+    let mut docker_client = get_client(&docker_state).map_err(ApiError::Docker)?;
 
     match docker_client.get_version() {
         Ok(version) => Ok(HttpResponse::Ok().json(version)),
@@ -54,7 +54,7 @@ async fn get_docker_containers(
 ) -> Result<HttpResponse, ApiError> {
     let query_params: ContainersQuery = query.into_inner();
     let query_string = to_string(&query_params).unwrap();
-    let mut docker_client = get_client(&docker_state).map_err(|e| ApiError::Docker(e))?;
+    let mut docker_client = get_client(&docker_state).map_err(ApiError::Docker)?;
 
     match docker_client.get_containers(Some(query_string)) {
         Ok(containers) => Ok(HttpResponse::Ok().json(containers)),
@@ -67,7 +67,7 @@ async fn get_docker_container(
     id: web::Path<String>,
     docker_state: DockerState,
 ) -> Result<HttpResponse, ApiError> {
-    let mut docker_client = get_client(&docker_state).map_err(|e| ApiError::Docker(e))?;
+    let mut docker_client = get_client(&docker_state).map_err(ApiError::Docker)?;
     let container_id_clone = id.clone();
 
     match docker_client.get_container_details(container_id_clone) {
@@ -78,7 +78,7 @@ async fn get_docker_container(
 
 #[get("/docker/volumes")]
 async fn get_docker_volumes(docker_state: DockerState) -> Result<HttpResponse, ApiError> {
-    let mut docker_client = get_client(&docker_state).map_err(|e| ApiError::Docker(e))?;
+    let mut docker_client = get_client(&docker_state).map_err(ApiError::Docker)?;
 
     match docker_client.get_volumes() {
         Ok(volumes) => Ok(HttpResponse::Ok().json(volumes)),
@@ -91,7 +91,7 @@ async fn get_docker_volume(
     id: web::Path<String>,
     docker_state: DockerState,
 ) -> Result<HttpResponse, ApiError> {
-    let mut docker_client = get_client(&docker_state).map_err(|e| ApiError::Docker(e))?;
+    let mut docker_client = get_client(&docker_state).map_err(ApiError::Docker)?;
 
     let cloned_id = id.clone();
     match docker_client.get_volume_details(cloned_id) {
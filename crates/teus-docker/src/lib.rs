@@ -1,2 +1,2 @@
 pub mod errors;
-pub mod handlers;
\ No newline at end of file
+pub mod handlers;
@@ -0,0 +1,296 @@
+//! Metric collectors and the registry that schedules them.
+//!
+//! Every monitoring cycle records a sample: the `sysinfo` row with CPU,
+//! memory and load figures. Collectors read the other metric sources and
+//! store their rows against that sample. Reading happens before the
+//! database is locked, so slow sources don't block the API; only the
+//! inserts run under the lock. Each collector can be disabled
+//! or run less often than the monitor interval through its
+//! `[monitor.collectors.<name>]` section, so expensive sources don't have
+//! to be read on every cycle.
+
+use std::{collections::BTreeMap, fmt::Display, io};
+use teus_database::storage::TeuSQLiteConnection;
+use teus_types::config::{CollectorConfig, MonitorConfig};
+
+/// The `sysinfo` row collectors attach their rows to.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub id: i32,
+    /// RFC3339 time at which the sample was taken.
+    pub timestamp: String,
+}
+
+#[derive(Debug)]
+pub enum CollectorError {
+    Io(io::Error),
+    Database(diesel::result::Error),
+}
+
+impl Display for CollectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollectorError::Io(e) => write!(f, "Failed to read metrics: {}", e),
+            CollectorError::Database(e) => write!(f, "Failed to store metrics: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for CollectorError {
+    fn from(e: io::Error) -> Self {
+        CollectorError::Io(e)
+    }
+}
+
+impl From<diesel::result::Error> for CollectorError {
+    fn from(e: diesel::result::Error) -> Self {
+        CollectorError::Database(e)
+    }
+}
+
+/// A source of metrics stored with each sample.
+///
+/// Collectors are created once and live as long as the monitor, so they
+/// can keep state between runs. A run is split in two: `collect` reads the
+/// source without the database, and `store` inserts what was read once the
+/// sample is stored.
+pub trait Collector: Send {
+    /// Name of the collector in `[monitor.collectors.<name>]` and in logs.
+    fn name(&self) -> &'static str;
+
+    /// Reads the metrics and keeps them until `store`.
+    fn collect(&mut self) -> Result<(), CollectorError>;
+
+    /// Stores what the previous `collect` read against `sample`. Called
+    /// even when `collect` failed, to store what it read before failing.
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError>;
+}
+
+struct ScheduledCollector {
+    collector: Box<dyn Collector>,
+    /// The collector runs once every `every_cycles` monitoring cycles.
+    every_cycles: u64,
+    /// Whether the collector ran this cycle and has to store its metrics.
+    collected: bool,
+}
+
+/// The collectors run by the monitor, with their schedules.
+pub struct CollectorRegistry {
+    interval_secs: u64,
+    schedules: BTreeMap<String, CollectorConfig>,
+    collectors: Vec<ScheduledCollector>,
+    known: Vec<&'static str>,
+    cycle: u64,
+}
+
+impl CollectorRegistry {
+    pub fn new(config: &MonitorConfig) -> Self {
+        Self {
+            interval_secs: config.interval_secs.max(1),
+            schedules: config.collectors.clone(),
+            collectors: Vec::new(),
+            known: Vec::new(),
+            cycle: 0,
+        }
+    }
+
+    /// Adds a collector, scheduled as configured in its
+    /// `[monitor.collectors.<name>]` section. Disabled collectors are dropped.
+    pub fn register(&mut self, collector: impl Collector + 'static) {
+        let name = collector.name();
+        self.known.push(name);
+
+        let schedule = self.schedules.get(name);
+        if schedule.is_some_and(|s| !s.enabled) {
+            return;
+        }
+        let interval_secs = schedule
+            .and_then(|s| s.interval_secs)
+            .unwrap_or(self.interval_secs);
+
+        self.collectors.push(ScheduledCollector {
+            collector: Box::new(collector),
+            every_cycles: interval_secs.div_ceil(self.interval_secs).max(1),
+            collected: false,
+        });
+    }
+
+    /// Names of the collectors that will run.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        self.collectors.iter().map(|c| c.collector.name()).collect()
+    }
+
+    /// Names of the configured schedules that match no registered
+    /// collector, usually typos.
+    pub fn unknown_schedules(&self) -> Vec<&str> {
+        self.schedules
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !self.known.contains(name))
+            .collect()
+    }
+
+    /// Reads the collectors due this cycle, without the database.
+    ///
+    /// Every collector runs on the first cycle. A failing collector is
+    /// logged and doesn't prevent the others from running.
+    pub fn collect_due(&mut self) {
+        for scheduled in &mut self.collectors {
+            if !self.cycle.is_multiple_of(scheduled.every_cycles) {
+                continue;
+            }
+            if let Err(e) = scheduled.collector.collect() {
+                eprintln!("Collector {} failed: {}", scheduled.collector.name(), e); // TODO: Use log::error!
+            }
+            scheduled.collected = true;
+        }
+        self.cycle += 1;
+    }
+
+    /// Stores what the collectors read by `collect_due` against `sample`.
+    pub fn store_collected(&mut self, conn: &mut TeuSQLiteConnection, sample: &Sample) {
+        for scheduled in &mut self.collectors {
+            if !std::mem::take(&mut scheduled.collected) {
+                continue;
+            }
+            if let Err(e) = scheduled.collector.store(conn, sample) {
+                eprintln!("Collector {} failed: {}", scheduled.collector.name(), e); // TODO: Use log::error!
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::Connection;
+    use std::sync::{Arc, Mutex};
+    use teus_types::config::{DiskFilterConfig, SecurityConfig};
+
+    struct CountingCollector {
+        name: &'static str,
+        runs: Arc<Mutex<Vec<i32>>>,
+        collected: bool,
+    }
+
+    impl Collector for CountingCollector {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn collect(&mut self) -> Result<(), CollectorError> {
+            self.collected = true;
+            if self.name == "failing" {
+                return Err(io::Error::other("unreadable").into());
+            }
+            Ok(())
+        }
+
+        fn store(
+            &mut self,
+            _conn: &mut TeuSQLiteConnection,
+            sample: &Sample,
+        ) -> Result<(), CollectorError> {
+            assert!(std::mem::take(&mut self.collected));
+            self.runs.lock().unwrap().push(sample.id);
+            Ok(())
+        }
+    }
+
+    fn monitor_config(collectors: &[(&str, bool, Option<u64>)]) -> MonitorConfig {
+        MonitorConfig {
+            interval_secs: 5,
+            procfs_root: "/proc".to_string(),
+            sysfs_root: "/sys".to_string(),
+            cgroup_root: "/sys/fs/cgroup".to_string(),
+            kmsg_path: "/dev/kmsg".to_string(),
+            host_root: "/".to_string(),
+            top_processes: 10,
+            disks: DiskFilterConfig::default(),
+            security: SecurityConfig::default(),
+            scripts: Vec::new(),
+            paths: Vec::new(),
+            logs: Vec::new(),
+            collectors: collectors
+                .iter()
+                .map(|(name, enabled, interval_secs)| {
+                    (
+                        name.to_string(),
+                        CollectorConfig {
+                            enabled: *enabled,
+                            interval_secs: *interval_secs,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn counting(registry: &mut CollectorRegistry, name: &'static str) -> Arc<Mutex<Vec<i32>>> {
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        registry.register(CountingCollector {
+            name,
+            runs: runs.clone(),
+            collected: false,
+        });
+        runs
+    }
+
+    #[test]
+    fn test_registry_schedules() {
+        let config = monitor_config(&[
+            ("slow", true, Some(15)),
+            ("rounded", true, Some(7)),
+            ("disabled", false, None),
+            ("typo", true, Some(60)),
+        ]);
+        let mut registry = CollectorRegistry::new(&config);
+        let every_cycle = counting(&mut registry, "every_cycle");
+        let slow = counting(&mut registry, "slow");
+        let rounded = counting(&mut registry, "rounded");
+        let disabled = counting(&mut registry, "disabled");
+
+        assert_eq!(registry.enabled(), vec!["every_cycle", "slow", "rounded"]);
+        assert_eq!(registry.unknown_schedules(), vec!["typo"]);
+
+        let mut conn = TeuSQLiteConnection::establish(":memory:").unwrap();
+        for id in 0..7 {
+            let sample = Sample {
+                id,
+                timestamp: String::new(),
+            };
+            registry.collect_due();
+            registry.store_collected(&mut conn, &sample);
+        }
+
+        assert_eq!(*every_cycle.lock().unwrap(), vec![0, 1, 2, 3, 4, 5, 6]);
+        // 15s on a 5s monitor interval: every third cycle
+        assert_eq!(*slow.lock().unwrap(), vec![0, 3, 6]);
+        // 7s is rounded up to two cycles
+        assert_eq!(*rounded.lock().unwrap(), vec![0, 2, 4, 6]);
+        assert!(disabled.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_registry_failing_collector_does_not_stop_others() {
+        let mut registry = CollectorRegistry::new(&monitor_config(&[]));
+        let failing = counting(&mut registry, "failing");
+        let other = counting(&mut registry, "other");
+
+        let mut conn = TeuSQLiteConnection::establish(":memory:").unwrap();
+        let sample = Sample {
+            id: 1,
+            timestamp: String::new(),
+        };
+        registry.collect_due();
+        registry.store_collected(&mut conn, &sample);
+
+        // What the failing collector read before failing is still stored
+        assert_eq!(*failing.lock().unwrap(), vec![1]);
+        assert_eq!(*other.lock().unwrap(), vec![1]);
+    }
+}
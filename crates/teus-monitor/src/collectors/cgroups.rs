@@ -0,0 +1,436 @@
+//! Resource accounting of workloads, read from the cgroup v2 hierarchy.
+//!
+//! systemd gives every slice, service and scope its own cgroup, docker
+//! containers included, so the unified hierarchy provides per-workload CPU,
+//! memory, I/O and task figures without going through the systemd or
+//! docker APIs. Hosts on the legacy cgroup v1 hierarchy have no
+//! `cgroup.controllers` file at the root and report nothing.
+//!
+//! Memory, I/O and pids files only exist in cgroups where the matching
+//! controller is enabled, so those values are optional.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::schema::{CgroupInfo, SchemaCgroupInfo};
+use crate::{mutation, query};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// Accounting of a single cgroup at the time of collection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CgroupStats {
+    /// Path relative to the cgroup root (e.g. "system.slice/nginx.service").
+    pub path: String,
+    /// Total CPU time consumed, in microseconds.
+    pub cpu_usage_usec: u64,
+    pub cpu_user_usec: u64,
+    pub cpu_system_usec: u64,
+    /// Memory currently charged to the cgroup, in bytes.
+    pub memory_current: Option<u64>,
+    /// Memory limit in bytes, `None` when unlimited.
+    pub memory_max: Option<u64>,
+    /// Total bytes read from block devices.
+    pub io_read_bytes: Option<u64>,
+    /// Total bytes written to block devices.
+    pub io_write_bytes: Option<u64>,
+    /// Number of tasks (processes and threads) in the cgroup.
+    pub pids_current: Option<u64>,
+}
+
+/// Parses `cpu.stat` into its usage, user and system times in microseconds.
+///
+/// Returns `None` if `usage_usec` is missing.
+pub fn parse_cpu_stat(content: &str) -> Option<(u64, u64, u64)> {
+    let mut usage = None;
+    let mut user = 0;
+    let mut system = 0;
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<u64>() else {
+            continue;
+        };
+        match key {
+            "usage_usec" => usage = Some(value),
+            "user_usec" => user = value,
+            "system_usec" => system = value,
+            _ => {}
+        }
+    }
+    Some((usage?, user, system))
+}
+
+/// Parses `io.stat` into the bytes read and written, summed over devices.
+///
+/// Every line has the form `MAJ:MIN rbytes=N wbytes=N rios=N wios=N ...`.
+pub fn parse_io_stat(content: &str) -> (u64, u64) {
+    let mut read = 0;
+    let mut written = 0;
+    for field in content.split_whitespace() {
+        let Some((key, value)) = field.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+        match key {
+            "rbytes" => read += value,
+            "wbytes" => written += value,
+            _ => {}
+        }
+    }
+    (read, written)
+}
+
+/// Parses a single-value file such as `memory.current` or `memory.max`.
+///
+/// Returns `None` for `max` (no limit) and for malformed content.
+pub fn parse_value(content: &str) -> Option<u64> {
+    content.trim().parse::<u64>().ok()
+}
+
+/// Whether a cgroup is a workload worth recording: a systemd slice,
+/// service or scope, or a container created by docker's cgroupfs driver.
+fn is_workload(name: &str, parent_name: &str) -> bool {
+    name.ends_with(".slice")
+        || name.ends_with(".service")
+        || name.ends_with(".scope")
+        || parent_name == "docker"
+}
+
+fn read_optional(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Reads the accounting files of the cgroup at `dir`.
+///
+/// Returns `None` when `cpu.stat` can't be read, which happens when the
+/// cgroup is removed while the hierarchy is being walked.
+fn read_cgroup(dir: &Path, path: String) -> Option<CgroupStats> {
+    let (cpu_usage_usec, cpu_user_usec, cpu_system_usec) =
+        parse_cpu_stat(&read_optional(&dir.join("cpu.stat"))?)?;
+    let io = read_optional(&dir.join("io.stat")).map(|content| parse_io_stat(&content));
+
+    Some(CgroupStats {
+        path,
+        cpu_usage_usec,
+        cpu_user_usec,
+        cpu_system_usec,
+        memory_current: read_optional(&dir.join("memory.current")).and_then(|c| parse_value(&c)),
+        memory_max: read_optional(&dir.join("memory.max")).and_then(|c| parse_value(&c)),
+        io_read_bytes: io.map(|(read, _)| read),
+        io_write_bytes: io.map(|(_, written)| written),
+        pids_current: read_optional(&dir.join("pids.current")).and_then(|c| parse_value(&c)),
+    })
+}
+
+fn walk(root: &Path, relative: &Path, stats: &mut Vec<CgroupStats>) -> io::Result<()> {
+    let dir = root.join(relative);
+    let mut children: Vec<_> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name())
+        .collect();
+    children.sort();
+
+    let parent_name = relative
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    for child in children {
+        let child_relative = relative.join(&child);
+        if is_workload(&child.to_string_lossy(), &parent_name)
+            && let Some(cgroup) = read_cgroup(
+                &root.join(&child_relative),
+                child_relative.to_string_lossy().to_string(),
+            )
+        {
+            stats.push(cgroup);
+        }
+        // A cgroup removed in the meantime is skipped with its children
+        match walk(root, &child_relative, stats) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Reads the accounting of every workload cgroup under `cgroup_root`,
+/// parents before their children.
+///
+/// The root cgroup itself is not included. Returns an empty list when
+/// `cgroup_root` is not a cgroup v2 hierarchy.
+pub fn read_cgroups(cgroup_root: &Path) -> io::Result<Vec<CgroupStats>> {
+    if !cgroup_root.join("cgroup.controllers").exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut stats = Vec::new();
+    walk(cgroup_root, Path::new(""), &mut stats)?;
+    Ok(stats)
+}
+
+/// Per-workload accounting, from the cgroup v2 hierarchy.
+pub struct CgroupsCollector {
+    cgroup_root: PathBuf,
+    stats: Option<Vec<CgroupStats>>,
+}
+
+impl CgroupsCollector {
+    pub fn new(cgroup_root: PathBuf) -> Self {
+        Self {
+            cgroup_root,
+            stats: None,
+        }
+    }
+}
+
+impl Collector for CgroupsCollector {
+    fn name(&self) -> &'static str {
+        "cgroups"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.stats = Some(read_cgroups(&self.cgroup_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(stats) = self.stats.take() else {
+            return Ok(());
+        };
+        // Baseline for rates: the cgroups of the previous run
+        let previous = query::get_latest_cgroupinfo(conn)?.map(|(timestamp, cgroups)| {
+            (super::elapsed_secs(&timestamp, &sample.timestamp), cgroups)
+        });
+        let rows = build_cgroupinfo_rows(
+            sample.id,
+            &stats,
+            previous
+                .as_ref()
+                .map(|(elapsed, cgroups)| (*elapsed, cgroups.as_slice())),
+        );
+
+        if !rows.is_empty() {
+            mutation::insert_multiple_cgroupinfo(conn, &rows)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the `cgroupinfo` rows of a snapshot, deriving CPU usage and I/O
+/// throughput from the previous snapshot of the same cgroup.
+fn build_cgroupinfo_rows(
+    sysinfo_id: i32,
+    stats: &[CgroupStats],
+    previous: Option<(f64, &[CgroupInfo])>,
+) -> Vec<SchemaCgroupInfo> {
+    stats
+        .iter()
+        .map(|s| {
+            let baseline = previous.and_then(|(elapsed, cgroups)| {
+                cgroups
+                    .iter()
+                    .find(|p| p.path == s.path)
+                    .map(|p| (elapsed, p))
+            });
+            let rate = |previous: Option<i64>, current: Option<u64>| {
+                let (elapsed, _) = baseline?;
+                super::counter_rate(previous? as u64, current?, elapsed).map(|r| r as f32)
+            };
+            // CPU microseconds per second of wall time, as a share of one CPU
+            let cpu_usage_percentage = rate(
+                baseline.map(|(_, p)| p.cpu_usage_usec),
+                Some(s.cpu_usage_usec),
+            )
+            .map(|usec_per_sec| usec_per_sec / 10_000.0);
+
+            SchemaCgroupInfo {
+                sysinfo_id,
+                path: s.path.clone(),
+                cpu_usage_usec: s.cpu_usage_usec as i64,
+                cpu_user_usec: s.cpu_user_usec as i64,
+                cpu_system_usec: s.cpu_system_usec as i64,
+                memory_current: s.memory_current.map(|v| v as i64),
+                memory_max: s.memory_max.map(|v| v as i64),
+                io_read_bytes: s.io_read_bytes.map(|v| v as i64),
+                io_write_bytes: s.io_write_bytes.map(|v| v as i64),
+                pids_current: s.pids_current.map(|v| v as i64),
+                cpu_usage_percentage,
+                io_read_bytes_per_sec: rate(
+                    baseline.and_then(|(_, p)| p.io_read_bytes),
+                    s.io_read_bytes,
+                ),
+                io_write_bytes_per_sec: rate(
+                    baseline.and_then(|(_, p)| p.io_write_bytes),
+                    s.io_write_bytes,
+                ),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CPU_STAT: &str = "\
+usage_usec 5500000
+user_usec 4000000
+system_usec 1500000
+nr_periods 0
+nr_throttled 0
+throttled_usec 0
+";
+
+    const IO_STAT: &str = "\
+8:0 rbytes=1048576 wbytes=4096 rios=20 wios=1 dbytes=0 dios=0
+259:0 rbytes=1024 wbytes=0 rios=1 wios=0 dbytes=0 dios=0
+";
+
+    fn write_cgroup(dir: &Path, files: &[(&str, &str)]) {
+        fs::create_dir_all(dir).unwrap();
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+    }
+
+    fn fixture_tree() -> TempDir {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        let path = root.path();
+        write_cgroup(path, &[("cgroup.controllers", "cpu io memory pids\n")]);
+        write_cgroup(
+            &path.join("system.slice"),
+            &[("cpu.stat", CPU_STAT), ("memory.current", "734003200\n")],
+        );
+        write_cgroup(
+            &path.join("system.slice").join("nginx.service"),
+            &[
+                ("cpu.stat", CPU_STAT),
+                ("memory.current", "52428800\n"),
+                ("memory.max", "536870912\n"),
+                ("io.stat", IO_STAT),
+                ("pids.current", "5\n"),
+            ],
+        );
+        write_cgroup(
+            &path.join("system.slice").join("docker-0123abcd.scope"),
+            &[("cpu.stat", CPU_STAT), ("memory.max", "max\n")],
+        );
+        // cgroupfs driver: containers are plain directories under docker/
+        write_cgroup(&path.join("docker"), &[("cpu.stat", CPU_STAT)]);
+        write_cgroup(
+            &path.join("docker").join("4567ef"),
+            &[("cpu.stat", CPU_STAT)],
+        );
+        // Not a workload, and a removed cgroup without accounting files
+        write_cgroup(&path.join("system.slice").join("other"), &[]);
+        write_cgroup(&path.join("gone.service"), &[]);
+        root
+    }
+
+    #[test]
+    fn test_parse_cgroup_files() {
+        assert_eq!(parse_cpu_stat(CPU_STAT), Some((5500000, 4000000, 1500000)));
+        assert_eq!(parse_cpu_stat("user_usec 10\n"), None);
+        assert_eq!(parse_io_stat(IO_STAT), (1049600, 4096));
+        assert_eq!(parse_io_stat(""), (0, 0));
+        assert_eq!(parse_value("536870912\n"), Some(536870912));
+        assert_eq!(parse_value("max\n"), None);
+    }
+
+    #[test]
+    fn test_read_cgroups_from_fixture_tree() {
+        let root = fixture_tree();
+        let stats = read_cgroups(root.path()).unwrap();
+        let paths: Vec<&str> = stats.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "docker/4567ef",
+                "system.slice",
+                "system.slice/docker-0123abcd.scope",
+                "system.slice/nginx.service",
+            ]
+        );
+
+        let nginx = &stats[3];
+        assert_eq!(nginx.cpu_usage_usec, 5500000);
+        assert_eq!(nginx.cpu_user_usec, 4000000);
+        assert_eq!(nginx.cpu_system_usec, 1500000);
+        assert_eq!(nginx.memory_current, Some(52428800));
+        assert_eq!(nginx.memory_max, Some(536870912));
+        assert_eq!(nginx.io_read_bytes, Some(1049600));
+        assert_eq!(nginx.io_write_bytes, Some(4096));
+        assert_eq!(nginx.pids_current, Some(5));
+
+        // Controllers not enabled in the container scope
+        let container = &stats[2];
+        assert_eq!(container.memory_max, None);
+        assert_eq!(container.io_read_bytes, None);
+        assert_eq!(container.pids_current, None);
+    }
+
+    #[test]
+    fn test_read_cgroups_without_unified_hierarchy() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        // cgroup v1 layout: one directory per controller
+        write_cgroup(&root.path().join("cpu").join("system.slice"), &[]);
+        assert!(read_cgroups(root.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_build_cgroupinfo_rows_with_rates() {
+        let previous = vec![CgroupInfo {
+            id: Some(1),
+            sysinfo_id: 1,
+            path: "system.slice/nginx.service".to_string(),
+            cpu_usage_usec: 1_000_000,
+            cpu_user_usec: 800_000,
+            cpu_system_usec: 200_000,
+            memory_current: Some(1024),
+            memory_max: None,
+            io_read_bytes: Some(0),
+            io_write_bytes: None,
+            pids_current: Some(3),
+            cpu_usage_percentage: None,
+            io_read_bytes_per_sec: None,
+            io_write_bytes_per_sec: None,
+        }];
+        let stats = vec![
+            CgroupStats {
+                path: "system.slice/nginx.service".to_string(),
+                cpu_usage_usec: 6_000_000,
+                io_read_bytes: Some(10_240),
+                io_write_bytes: Some(4096),
+                ..Default::default()
+            },
+            CgroupStats {
+                path: "system.slice/new.service".to_string(),
+                cpu_usage_usec: 500,
+                ..Default::default()
+            },
+        ];
+
+        let rows = build_cgroupinfo_rows(2, &stats, Some((10.0, &previous)));
+        assert_eq!(rows.len(), 2);
+        // 5 CPU seconds over 10 seconds: half of one CPU
+        assert_eq!(rows[0].cpu_usage_percentage, Some(50.0));
+        assert_eq!(rows[0].io_read_bytes_per_sec, Some(1024.0));
+        // No previous value to compare with
+        assert_eq!(rows[0].io_write_bytes_per_sec, None);
+        // Cgroup without a baseline has no derived values
+        assert_eq!(rows[1].cpu_usage_usec, 500);
+        assert_eq!(rows[1].cpu_usage_percentage, None);
+    }
+}
@@ -0,0 +1,488 @@
+//! Disk usage of watched directories.
+//!
+//! A directory is measured like `du -x`: sizes are the blocks allocated on
+//! disk, symbolic links are not followed, mount points of other
+//! filesystems are not entered and hard-linked files are counted once.
+//! Subdirectories that can't be read are skipped.
+//!
+//! Walks are bounded by a number of entries and a deadline, so a huge or
+//! slow tree (a network mount, millions of small files) returns partial
+//! figures marked incomplete instead of running forever.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaPathInfo;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+    fs, io,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use teus_database::storage::TeuSQLiteConnection;
+use teus_types::config::PathWatchConfig;
+
+/* the deadline is checked once every this many entries */
+const DEADLINE_CHECK_EVERY: u64 = 1024;
+
+/// Bounds of a directory walk.
+#[derive(Clone, Debug)]
+pub struct ScanLimits {
+    /// Files and directories visited before the walk stops.
+    pub max_entries: u64,
+    /// Time the walk may take.
+    pub timeout: Duration,
+    /// Number of largest files returned.
+    pub largest_files: usize,
+}
+
+/// A file of a measured directory, with its size on disk.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LargeFile {
+    pub bytes: u64,
+    pub path: String,
+}
+
+/// Disk usage of a directory tree.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DirUsage {
+    /// Bytes allocated on disk, directories included.
+    pub total_bytes: u64,
+    pub file_count: u64,
+    /// Subdirectories, the measured directory excluded.
+    pub dir_count: u64,
+    /// Largest files, biggest first.
+    pub largest_files: Vec<LargeFile>,
+    /// False when the walk hit its entry or time limit.
+    pub complete: bool,
+}
+
+/// Largest files as stored in the database, a JSON array.
+pub fn encode_largest_files(files: &[LargeFile]) -> String {
+    serde_json::to_string(files).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Largest files stored by `encode_largest_files`.
+pub fn decode_largest_files(files: &str) -> Vec<LargeFile> {
+    serde_json::from_str(files).unwrap_or_default()
+}
+
+/// Measures the directory tree at `root`, within `limits`.
+pub fn measure_dir(root: &Path, limits: &ScanLimits) -> io::Result<DirUsage> {
+    let metadata = fs::symlink_metadata(root)?;
+    if !metadata.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a directory", root.display()),
+        ));
+    }
+    let device = metadata.dev();
+    let deadline = Instant::now() + limits.timeout;
+
+    let mut usage = DirUsage {
+        total_bytes: metadata.blocks() * 512,
+        complete: true,
+        ..Default::default()
+    };
+    let mut largest: BinaryHeap<Reverse<(u64, PathBuf)>> = BinaryHeap::new();
+    let mut hard_links = HashSet::new();
+    let mut entries = 0u64;
+    let mut pending = vec![root.to_path_buf()];
+
+    'walk: while let Some(dir) = pending.pop() {
+        // Unreadable subdirectories are skipped, the root is not
+        let listing = match fs::read_dir(&dir) {
+            Ok(listing) => listing,
+            Err(e) if dir == root => return Err(e),
+            Err(_) => continue,
+        };
+        for entry in listing {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            entries += 1;
+            if entries > limits.max_entries
+                || (entries.is_multiple_of(DEADLINE_CHECK_EVERY) && Instant::now() >= deadline)
+            {
+                usage.complete = false;
+                break 'walk;
+            }
+            // The entry may have been removed since the directory was listed
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.dev() != device {
+                continue;
+            }
+            if metadata.nlink() > 1
+                && !metadata.is_dir()
+                && !hard_links.insert((metadata.dev(), metadata.ino()))
+            {
+                continue;
+            }
+
+            let bytes = metadata.blocks() * 512;
+            usage.total_bytes += bytes;
+            if metadata.is_dir() {
+                usage.dir_count += 1;
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                usage.file_count += 1;
+                if limits.largest_files > 0 {
+                    largest.push(Reverse((bytes, entry.path())));
+                    if largest.len() > limits.largest_files {
+                        largest.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    usage.largest_files = largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((bytes, path))| LargeFile {
+            bytes,
+            path: path.to_string_lossy().into_owned(),
+        })
+        .collect();
+    Ok(usage)
+}
+
+/// Growth of a directory in bytes per hour between two measurements taken
+/// at RFC3339 times `from` and `to`. Negative when it shrank.
+///
+/// Returns `None` when the times can't be parsed or no time elapsed.
+pub fn growth_per_hour(from: (&str, u64), to: (&str, u64)) -> Option<f64> {
+    let elapsed =
+        DateTime::parse_from_rfc3339(to.0).ok()? - DateTime::parse_from_rfc3339(from.0).ok()?;
+    let hours = elapsed.num_milliseconds() as f64 / 3_600_000.0;
+    if hours <= 0.0 {
+        return None;
+    }
+    Some((to.1 as f64 - from.1 as f64) / hours)
+}
+
+struct WatchedPath {
+    config: PathWatchConfig,
+    last_start: Option<std::time::Instant>,
+    running: bool,
+}
+
+/* index of the path in `PathsCollector::paths`, and its usage */
+type PathResult = (usize, io::Result<DirUsage>);
+
+/// Disk usage of the `[[monitor.paths]]` directories.
+///
+/// Like scripts, directories are walked on their own threads so a large
+/// tree doesn't hold up the monitoring cycle, and their usage is stored by
+/// the first run of the collector after the walk ends. A directory isn't
+/// walked again while its previous walk is still going.
+pub struct PathsCollector {
+    paths: Vec<WatchedPath>,
+    results_tx: mpsc::Sender<PathResult>,
+    results: mpsc::Receiver<PathResult>,
+    /* usage of the walks that ended, attached to the sample by `store` */
+    rows: Vec<SchemaPathInfo>,
+}
+
+impl PathsCollector {
+    pub fn new(paths: &[PathWatchConfig]) -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            paths: paths
+                .iter()
+                .map(|config| WatchedPath {
+                    config: config.clone(),
+                    last_start: None,
+                    running: false,
+                })
+                .collect(),
+            results_tx,
+            results,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Walks the directory at `index` on its own thread.
+    fn start(&mut self, index: usize) -> io::Result<()> {
+        let config = &self.paths[index].config;
+        let path = PathBuf::from(&config.path);
+        let limits = ScanLimits {
+            max_entries: config.max_entries,
+            timeout: Duration::from_secs(config.timeout_secs),
+            largest_files: config.largest_files,
+        };
+        let results_tx = self.results_tx.clone();
+
+        thread::Builder::new()
+            .name(format!("path-{}", index))
+            .spawn(move || {
+                let _ = results_tx.send((index, measure_dir(&path, &limits)));
+            })?;
+
+        let watched = &mut self.paths[index];
+        watched.running = true;
+        watched.last_start = Some(std::time::Instant::now());
+        Ok(())
+    }
+}
+
+impl Collector for PathsCollector {
+    fn name(&self) -> &'static str {
+        "paths"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        let mut errors = Vec::new();
+        while let Ok((index, result)) = self.results.try_recv() {
+            let watched = &mut self.paths[index];
+            watched.running = false;
+            match result {
+                Ok(usage) => {
+                    if !usage.complete {
+                        errors.push(format!(
+                            "path {}: walk stopped after {} entries or {} seconds, its size is incomplete",
+                            watched.config.path,
+                            watched.config.max_entries,
+                            watched.config.timeout_secs
+                        ));
+                    }
+                    self.rows.push(SchemaPathInfo {
+                        sysinfo_id: 0,
+                        path: watched.config.path.clone(),
+                        total_bytes: usage.total_bytes as i64,
+                        file_count: usage.file_count as i64,
+                        dir_count: usage.dir_count as i64,
+                        complete: usage.complete,
+                        largest_files: encode_largest_files(&usage.largest_files),
+                    });
+                }
+                Err(e) => errors.push(format!("path {}: {}", watched.config.path, e)),
+            }
+        }
+
+        for index in 0..self.paths.len() {
+            let watched = &self.paths[index];
+            let interval = Duration::from_secs(watched.config.interval_secs);
+            let due = watched
+                .last_start
+                .is_none_or(|last| last.elapsed() + super::SCHEDULE_SLACK >= interval);
+            if watched.running || !due {
+                continue;
+            }
+            if let Err(e) = self.start(index) {
+                errors.push(format!("path {}: {}", self.paths[index].config.path, e));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(io::Error::other(errors.join("; ")).into());
+        }
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let mut rows = std::mem::take(&mut self.rows);
+        for row in &mut rows {
+            row.sysinfo_id = sample.id;
+        }
+
+        if !rows.is_empty() {
+            mutation::insert_multiple_pathinfo(conn, &rows)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::sys::SysInfo;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+
+    fn limits() -> ScanLimits {
+        ScanLimits {
+            max_entries: 1000,
+            timeout: Duration::from_secs(10),
+            largest_files: 2,
+        }
+    }
+
+    /* pseudo-random content, so compressing filesystems allocate the full size */
+    fn write(path: &Path, bytes: usize) {
+        let mut state = 0x2545_f491_u32;
+        let content: Vec<u8> = (0..bytes)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_measure_dir() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let root = dir.path();
+        write(&root.join("small.log"), 100);
+        write(&root.join("nginx/access.log"), 64 * 1024);
+        write(&root.join("nginx/error.log"), 16 * 1024);
+        write(&root.join("journal/system.journal"), 128 * 1024);
+        // Counted once
+        fs::hard_link(
+            root.join("journal/system.journal"),
+            root.join("journal/link.journal"),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("/usr", root.join("usr")).unwrap();
+
+        let usage = measure_dir(root, &limits()).unwrap();
+        assert!(usage.complete);
+        assert_eq!(usage.file_count, 4);
+        assert_eq!(usage.dir_count, 2);
+        assert!(usage.total_bytes >= (100 + 64 * 1024 + 16 * 1024 + 128 * 1024) as u64);
+        assert!(usage.total_bytes < 2 * 128 * 1024 + 64 * 1024 + 16 * 1024 + 64 * 1024);
+
+        let largest: Vec<&str> = usage
+            .largest_files
+            .iter()
+            .map(|f| f.path.strip_prefix(root.to_str().unwrap()).unwrap())
+            .collect();
+        assert_eq!(largest.len(), 2);
+        assert!(largest[0].starts_with("/journal/"));
+        assert_eq!(largest[1], "/nginx/access.log");
+        assert!(usage.largest_files[0].bytes >= usage.largest_files[1].bytes);
+    }
+
+    #[test]
+    fn test_measure_dir_stops_at_max_entries() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        for i in 0..20 {
+            write(&dir.path().join(format!("file-{}", i)), 10);
+        }
+
+        let usage = measure_dir(
+            dir.path(),
+            &ScanLimits {
+                max_entries: 5,
+                ..limits()
+            },
+        )
+        .unwrap();
+        assert!(!usage.complete);
+        assert_eq!(usage.file_count, 5);
+    }
+
+    #[test]
+    fn test_measure_dir_rejects_files() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        write(&dir.path().join("file"), 10);
+        assert!(measure_dir(&dir.path().join("file"), &limits()).is_err());
+        assert!(measure_dir(&dir.path().join("missing"), &limits()).is_err());
+    }
+
+    #[test]
+    fn test_growth_per_hour() {
+        assert_eq!(
+            growth_per_hour(
+                ("2024-01-15T10:00:00+00:00", 1_000),
+                ("2024-01-15T12:00:00+00:00", 5_000)
+            ),
+            Some(2_000.0)
+        );
+        assert_eq!(
+            growth_per_hour(
+                ("2024-01-15T10:00:00+00:00", 5_000),
+                ("2024-01-15T10:30:00+00:00", 4_000)
+            ),
+            Some(-2_000.0)
+        );
+        assert_eq!(
+            growth_per_hour(
+                ("2024-01-15T10:00:00+00:00", 1_000),
+                ("2024-01-15T10:00:00+00:00", 5_000)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_largest_files_round_trip() {
+        let files = vec![LargeFile {
+            bytes: 4096,
+            path: "/var/log/syslog".to_string(),
+        }];
+        let encoded = encode_largest_files(&files);
+        assert_eq!(encoded, r#"[{"bytes":4096,"path":"/var/log/syslog"}]"#);
+        assert_eq!(decode_largest_files(&encoded), files);
+        assert!(decode_largest_files("garbage").is_empty());
+    }
+
+    #[test]
+    fn test_paths_collector_stores_usage_and_reports_failures() {
+        let (dir, storage) = migrated_storage();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir_all(logs.join("nginx")).unwrap();
+        std::fs::write(logs.join("nginx/access.log"), vec![b'x'; 8192]).unwrap();
+        std::fs::write(logs.join("syslog"), vec![b'x'; 4096]).unwrap();
+        let path = |path: &Path| PathWatchConfig {
+            path: path.to_string_lossy().into_owned(),
+            interval_secs: 3600,
+            max_entries: 1000,
+            timeout_secs: 10,
+            largest_files: 1,
+        };
+        let mut collector = PathsCollector::new(&[path(&logs), path(&dir.path().join("missing"))]);
+        let mut conn = storage.write().unwrap();
+
+        // Walks are started by the first run and stored by a later one
+        let mut errors = Vec::new();
+        for _ in 0..100 {
+            let sample = SysInfo::default().store(&mut conn).unwrap();
+            if let Err(e) = collect_and_store(&mut collector, &mut conn, &sample) {
+                errors.push(e.to_string());
+            }
+            if collector.paths.iter().all(|p| !p.running) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(
+            errors
+                .join("; ")
+                .contains("missing: No such file or directory")
+        );
+
+        let latest = query::get_latest_pathinfo(&mut conn).unwrap();
+        assert_eq!(latest.len(), 1);
+        let (timestamp, usage) = &latest[0];
+        assert_eq!(usage.file_count, 2);
+        assert_eq!(usage.dir_count, 1);
+        assert!(usage.complete);
+        assert!(usage.total_bytes >= 8192 + 4096);
+        let largest = decode_largest_files(&usage.largest_files);
+        assert_eq!(largest.len(), 1);
+        assert!(largest[0].path.ends_with("nginx/access.log"));
+
+        let first = query::get_first_pathinfo_since(&mut conn, &usage.path, "")
+            .unwrap()
+            .unwrap();
+        assert_eq!(&first.0, timestamp);
+        let history = query::get_pathinfo_history(&mut conn, &usage.path, 10).unwrap();
+        assert_eq!(history.len(), 1);
+    }
+}
@@ -0,0 +1,375 @@
+//! Block device I/O statistics read from `/proc/diskstats`.
+//!
+//! The kernel exposes cumulative counters per block device. Throughput,
+//! IOPS, latency and utilization are derived from the difference between
+//! two readings, the same way `iostat` computes them.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::schema::{DiskIo, SchemaDiskIo};
+use crate::{mutation, query};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// Size of a sector in `/proc/diskstats`, regardless of the device's
+/// physical sector size.
+const SECTOR_SIZE: u64 = 512;
+
+/// Device name prefixes that are never worth reporting: loop devices
+/// (snaps, images) and RAM disks.
+const IGNORED_PREFIXES: [&str; 2] = ["loop", "ram"];
+
+/// Cumulative I/O counters of a single block device since boot.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiskStats {
+    pub device: String,
+    pub reads_completed: u64,
+    pub sectors_read: u64,
+    pub read_time_ms: u64,
+    pub writes_completed: u64,
+    pub sectors_written: u64,
+    pub write_time_ms: u64,
+    pub io_time_ms: u64,
+}
+
+/// I/O activity of a device between two readings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiskIoRates {
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    /// Average time a read request took, in milliseconds (0 if no reads).
+    pub avg_read_latency_ms: f64,
+    /// Average time a write request took, in milliseconds (0 if no writes).
+    pub avg_write_latency_ms: f64,
+    /// Share of the elapsed time the device had requests in flight (0-100).
+    pub busy_percentage: f64,
+}
+
+impl DiskStats {
+    /// Derives the I/O rates since a previous reading of the same device.
+    ///
+    /// Returns `None` if no time elapsed or if any counter went backwards,
+    /// which happens when a device is removed and re-added.
+    pub fn rates_since(&self, previous: &DiskStats, elapsed_secs: f64) -> Option<DiskIoRates> {
+        if elapsed_secs <= 0.0 {
+            return None;
+        }
+
+        let reads = self.reads_completed.checked_sub(previous.reads_completed)?;
+        let writes = self
+            .writes_completed
+            .checked_sub(previous.writes_completed)?;
+        let sectors_read = self.sectors_read.checked_sub(previous.sectors_read)?;
+        let sectors_written = self.sectors_written.checked_sub(previous.sectors_written)?;
+        let read_time = self.read_time_ms.checked_sub(previous.read_time_ms)?;
+        let write_time = self.write_time_ms.checked_sub(previous.write_time_ms)?;
+        let io_time = self.io_time_ms.checked_sub(previous.io_time_ms)?;
+
+        let average = |time: u64, ops: u64| {
+            if ops == 0 {
+                0.0
+            } else {
+                time as f64 / ops as f64
+            }
+        };
+
+        Some(DiskIoRates {
+            read_bytes_per_sec: (sectors_read * SECTOR_SIZE) as f64 / elapsed_secs,
+            write_bytes_per_sec: (sectors_written * SECTOR_SIZE) as f64 / elapsed_secs,
+            read_iops: reads as f64 / elapsed_secs,
+            write_iops: writes as f64 / elapsed_secs,
+            avg_read_latency_ms: average(read_time, reads),
+            avg_write_latency_ms: average(write_time, writes),
+            busy_percentage: (io_time as f64 / (elapsed_secs * 1000.0) * 100.0).min(100.0),
+        })
+    }
+}
+
+/// Parses the content of `/proc/diskstats`.
+///
+/// Each line is `major minor name` followed by at least 11 counters
+/// (kernels 4.18+ add discard and flush counters, which are ignored).
+/// Loop and RAM devices and malformed lines are skipped.
+pub fn parse_proc_diskstats(content: &str) -> Vec<DiskStats> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace().skip(2);
+            let device = parts.next()?;
+            if IGNORED_PREFIXES.iter().any(|p| device.starts_with(p)) {
+                return None;
+            }
+
+            let fields: Vec<u64> = parts
+                .map(|v| v.parse::<u64>())
+                .collect::<Result<_, _>>()
+                .ok()?;
+            if fields.len() < 11 {
+                return None;
+            }
+
+            Some(DiskStats {
+                device: device.to_string(),
+                reads_completed: fields[0],
+                sectors_read: fields[2],
+                read_time_ms: fields[3],
+                writes_completed: fields[4],
+                sectors_written: fields[6],
+                write_time_ms: fields[7],
+                io_time_ms: fields[9],
+            })
+        })
+        .collect()
+}
+
+/// Reads and parses `diskstats` from the given procfs root.
+pub fn read_disk_stats(procfs_root: &Path) -> io::Result<Vec<DiskStats>> {
+    let content = fs::read_to_string(procfs_root.join("diskstats"))?;
+    Ok(parse_proc_diskstats(&content))
+}
+
+/// Block device counters and I/O rates, from `/proc/diskstats`.
+pub struct DiskIoCollector {
+    procfs_root: PathBuf,
+    stats: Option<Vec<DiskStats>>,
+}
+
+impl DiskIoCollector {
+    pub fn new(procfs_root: PathBuf) -> Self {
+        Self {
+            procfs_root,
+            stats: None,
+        }
+    }
+}
+
+impl Collector for DiskIoCollector {
+    fn name(&self) -> &'static str {
+        "diskio"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.stats = Some(read_disk_stats(&self.procfs_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(stats) = self.stats.take() else {
+            return Ok(());
+        };
+        // Baseline for rates: the devices of the previous run
+        let previous = query::get_latest_diskio(conn)?.map(|(timestamp, devices)| {
+            (super::elapsed_secs(&timestamp, &sample.timestamp), devices)
+        });
+        let rows = build_diskio_rows(
+            sample.id,
+            &stats,
+            previous
+                .as_ref()
+                .map(|(elapsed, devices)| (*elapsed, devices.as_slice())),
+        );
+
+        if !rows.is_empty() {
+            mutation::insert_multiple_diskio(conn, &rows)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the `diskio` rows of a snapshot, deriving throughput, IOPS,
+/// latency and utilization from the previous snapshot of the same device.
+fn build_diskio_rows(
+    sysinfo_id: i32,
+    stats: &[DiskStats],
+    previous: Option<(f64, &[DiskIo])>,
+) -> Vec<SchemaDiskIo> {
+    stats
+        .iter()
+        .map(|s| {
+            let rates = previous.and_then(|(elapsed, devices)| {
+                let p = devices.iter().find(|p| p.device == s.device)?;
+                let baseline = DiskStats {
+                    device: p.device.clone(),
+                    reads_completed: p.reads_completed as u64,
+                    sectors_read: p.sectors_read as u64,
+                    read_time_ms: p.read_time_ms as u64,
+                    writes_completed: p.writes_completed as u64,
+                    sectors_written: p.sectors_written as u64,
+                    write_time_ms: p.write_time_ms as u64,
+                    io_time_ms: p.io_time_ms as u64,
+                };
+                s.rates_since(&baseline, elapsed)
+            });
+
+            SchemaDiskIo {
+                sysinfo_id,
+                device: s.device.clone(),
+                reads_completed: s.reads_completed as i64,
+                sectors_read: s.sectors_read as i64,
+                read_time_ms: s.read_time_ms as i64,
+                writes_completed: s.writes_completed as i64,
+                sectors_written: s.sectors_written as i64,
+                write_time_ms: s.write_time_ms as i64,
+                io_time_ms: s.io_time_ms as i64,
+                read_bytes_per_sec: rates.as_ref().map(|r| r.read_bytes_per_sec as f32),
+                write_bytes_per_sec: rates.as_ref().map(|r| r.write_bytes_per_sec as f32),
+                read_iops: rates.as_ref().map(|r| r.read_iops as f32),
+                write_iops: rates.as_ref().map(|r| r.write_iops as f32),
+                avg_read_latency_ms: rates.as_ref().map(|r| r.avg_read_latency_ms as f32),
+                avg_write_latency_ms: rates.as_ref().map(|r| r.avg_write_latency_ms as f32),
+                busy_percentage: rates.as_ref().map(|r| r.busy_percentage as f32),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PROC_DISKSTATS: &str = "\
+   7       0 loop0 52 0 2140 20 0 0 0 0 0 36 20 0 0 0 0
+ 259       0 nvme0n1 120000 3000 9600000 60000 80000 5000 6400000 160000 0 90000 220000 0 0 0 0 1200 400
+ 259       1 nvme0n1p1 1000 0 80000 500 10 0 80 20 0 400 520
+   8       0 sda 10 0 80
+ 253       0 dm-0 5000 0 400000 2500 2000 0 160000 4000 2 3000 6500
+";
+
+    #[test]
+    fn test_parse_proc_diskstats() {
+        let stats = parse_proc_diskstats(PROC_DISKSTATS);
+        // loop0 is ignored, sda is truncated and skipped
+        let devices: Vec<&str> = stats.iter().map(|s| s.device.as_str()).collect();
+        assert_eq!(devices, vec!["nvme0n1", "nvme0n1p1", "dm-0"]);
+
+        let nvme = &stats[0];
+        assert_eq!(nvme.reads_completed, 120000);
+        assert_eq!(nvme.sectors_read, 9600000);
+        assert_eq!(nvme.read_time_ms, 60000);
+        assert_eq!(nvme.writes_completed, 80000);
+        assert_eq!(nvme.sectors_written, 6400000);
+        assert_eq!(nvme.write_time_ms, 160000);
+        assert_eq!(nvme.io_time_ms, 90000);
+    }
+
+    #[test]
+    fn test_read_disk_stats_from_fixture_root() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        fs::write(root.path().join("diskstats"), PROC_DISKSTATS).unwrap();
+
+        let stats = read_disk_stats(root.path()).unwrap();
+        assert_eq!(stats.len(), 3);
+
+        let missing = TempDir::new().expect("Failed to create temp dir");
+        assert!(read_disk_stats(missing.path()).is_err());
+    }
+
+    #[test]
+    fn test_rates_since() {
+        let previous = DiskStats {
+            device: "sda".to_string(),
+            reads_completed: 1000,
+            sectors_read: 8000,
+            read_time_ms: 2000,
+            writes_completed: 500,
+            sectors_written: 4000,
+            write_time_ms: 1000,
+            io_time_ms: 10000,
+        };
+        let current = DiskStats {
+            reads_completed: 1100,     // 100 reads
+            sectors_read: 8000 + 2048, // 1 MiB read
+            read_time_ms: 2000 + 500,  // 5 ms per read
+            writes_completed: 500,     // no writes
+            sectors_written: 4000,
+            write_time_ms: 1000,
+            io_time_ms: 10000 + 2500, // busy 2.5s out of 5s
+            ..previous.clone()
+        };
+
+        let rates = current.rates_since(&previous, 5.0).unwrap();
+        assert_eq!(rates.read_bytes_per_sec, 1024.0 * 1024.0 / 5.0);
+        assert_eq!(rates.write_bytes_per_sec, 0.0);
+        assert_eq!(rates.read_iops, 20.0);
+        assert_eq!(rates.write_iops, 0.0);
+        assert_eq!(rates.avg_read_latency_ms, 5.0);
+        assert_eq!(rates.avg_write_latency_ms, 0.0);
+        assert_eq!(rates.busy_percentage, 50.0);
+    }
+
+    #[test]
+    fn test_rates_since_reset_or_no_elapsed_time() {
+        let previous = DiskStats {
+            device: "sda".to_string(),
+            reads_completed: 1000,
+            ..Default::default()
+        };
+        let current = DiskStats {
+            device: "sda".to_string(),
+            reads_completed: 10,
+            ..Default::default()
+        };
+
+        assert!(current.rates_since(&previous, 5.0).is_none());
+        assert!(previous.rates_since(&previous, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_build_diskio_rows_with_rates() {
+        let previous = vec![DiskIo {
+            id: Some(1),
+            sysinfo_id: 1,
+            device: "sda".to_string(),
+            reads_completed: 100,
+            sectors_read: 0,
+            read_time_ms: 0,
+            writes_completed: 0,
+            sectors_written: 0,
+            write_time_ms: 0,
+            io_time_ms: 0,
+            read_bytes_per_sec: None,
+            write_bytes_per_sec: None,
+            read_iops: None,
+            write_iops: None,
+            avg_read_latency_ms: None,
+            avg_write_latency_ms: None,
+            busy_percentage: None,
+        }];
+        let stats = vec![
+            DiskStats {
+                device: "sda".to_string(),
+                reads_completed: 150,
+                sectors_read: 2048,
+                read_time_ms: 100,
+                io_time_ms: 1000,
+                ..Default::default()
+            },
+            DiskStats {
+                device: "sdb".to_string(),
+                reads_completed: 7,
+                ..Default::default()
+            },
+        ];
+
+        let rows = build_diskio_rows(2, &stats, Some((10.0, &previous)));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].reads_completed, 150);
+        assert_eq!(rows[0].read_iops, Some(5.0));
+        assert_eq!(rows[0].read_bytes_per_sec, Some(104857.6));
+        assert_eq!(rows[0].avg_read_latency_ms, Some(2.0));
+        assert_eq!(rows[0].busy_percentage, Some(10.0));
+        // Device without a baseline has no derived values
+        assert_eq!(rows[1].reads_completed, 7);
+        assert_eq!(rows[1].read_iops, None);
+        assert_eq!(rows[1].busy_percentage, None);
+    }
+}
@@ -0,0 +1,194 @@
+//! Selection of the mounted filesystems stored with each sample.
+//!
+//! The mount table lists the same filesystem more than once when it is
+//! bind mounted (container volumes, `/etc/hosts` in containers, chroots).
+//! Every bind mount shares the device number of its source, which is used
+//! to keep a single entry per filesystem.
+
+use super::inodes::InodeUsage;
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaDiskInfo;
+use std::{collections::HashMap, path::Path};
+use sysinfo::Disks;
+use teus_database::storage::TeuSQLiteConnection;
+use teus_types::config::DiskFilterConfig;
+
+/// Returns the device number of the filesystem mounted at `mount_point`.
+#[cfg(unix)]
+pub fn mount_device_id(mount_point: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::metadata(mount_point).ok().map(|m| m.dev())
+}
+
+/// Device numbers are only available on Unix systems.
+#[cfg(not(unix))]
+pub fn mount_device_id(_mount_point: &Path) -> Option<u64> {
+    None
+}
+
+/// Drops bind mounts, keeping one mount per device number.
+///
+/// The mount with the shortest path is kept for each device, so `/` wins
+/// over `/var/lib/docker/...` and the source over its bind mounts. Mounts
+/// without a device number are always kept. The order of the remaining
+/// mounts is preserved.
+pub fn dedupe_bind_mounts<T>(
+    mounts: Vec<T>,
+    device_id: impl Fn(&T) -> Option<u64>,
+    mount_point: impl Fn(&T) -> &Path,
+) -> Vec<T> {
+    let mut kept: HashMap<u64, usize> = HashMap::new();
+    for (index, mount) in mounts.iter().enumerate() {
+        let Some(device) = device_id(mount) else {
+            continue;
+        };
+        let path_len = mount_point(mount).as_os_str().len();
+        kept.entry(device)
+            .and_modify(|best| {
+                if path_len < mount_point(&mounts[*best]).as_os_str().len() {
+                    *best = index;
+                }
+            })
+            .or_insert(index);
+    }
+
+    mounts
+        .into_iter()
+        .enumerate()
+        .filter(|(index, mount)| match device_id(mount) {
+            Some(device) => kept.get(&device) == Some(index),
+            None => true,
+        })
+        .map(|(_, mount)| mount)
+        .collect()
+}
+
+/// Space and inode usage of the mounted filesystems selected by
+/// `[monitor.disks]`.
+pub struct DisksCollector {
+    filter: DiskFilterConfig,
+    /* rows of the selected filesystems, attached to the sample by `store` */
+    disk_infos: Option<Vec<SchemaDiskInfo>>,
+}
+
+impl DisksCollector {
+    pub fn new(filter: DiskFilterConfig) -> Self {
+        Self {
+            filter,
+            disk_infos: None,
+        }
+    }
+}
+
+impl Collector for DisksCollector {
+    fn name(&self) -> &'static str {
+        "disks"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        let disks = Disks::new_with_refreshed_list();
+        let selected_disks: Vec<_> = disks
+            .list()
+            .iter()
+            .filter(|disk| {
+                self.filter.matches(
+                    &disk.file_system().to_string_lossy(),
+                    &disk.mount_point().to_string_lossy(),
+                    &disk.name().to_string_lossy(),
+                )
+            })
+            .map(|disk| (mount_device_id(disk.mount_point()), disk))
+            .collect();
+        let selected_disks = dedupe_bind_mounts(selected_disks, |d| d.0, |d| d.1.mount_point());
+
+        let mut disk_infos_to_insert: Vec<SchemaDiskInfo> = Vec::new();
+        for (_, disk) in selected_disks {
+            let space_used = disk.total_space() - disk.available_space();
+            // Calculate usage percentage correctly
+            let usage_percentage = if disk.total_space() > 0 {
+                (space_used as f64 / disk.total_space() as f64 * 100.0) as i32
+            } else {
+                0
+            };
+
+            let fs_name = disk.name().to_string_lossy().to_string();
+            let mount_point = disk.mount_point().to_string_lossy().to_string();
+            let inodes = super::inodes::read_inode_usage(disk.mount_point()).unwrap_or_else(|e| {
+                eprintln!("Failed to read inode usage of {}: {}", mount_point, e);
+                InodeUsage::default()
+            });
+
+            disk_infos_to_insert.push(SchemaDiskInfo {
+                sysinfo_id: 0,
+                filesystem: fs_name,
+                size: (disk.total_space() / 1024 / 1024) as i32, // Convert bytes to MB (adjust if needed) and cast usize to i32
+                used: (space_used / 1024 / 1024) as i32, // Convert bytes to MB and cast usize to i32
+                available: (disk.available_space() / 1024 / 1024) as i32, // Convert bytes to MB and cast usize to i32
+                used_percentage: usage_percentage, // Use calculated percentage
+                mounted_path: mount_point,
+                inodes_total: inodes.total as i64,
+                inodes_used: inodes.used as i64,
+                inodes_free: inodes.free as i64,
+                inodes_used_percentage: inodes.used_percentage() as i32,
+            });
+        }
+
+        self.disk_infos = Some(disk_infos_to_insert);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(mut disk_infos_to_insert) = self.disk_infos.take() else {
+            return Ok(());
+        };
+        for disk_info in &mut disk_infos_to_insert {
+            disk_info.sysinfo_id = sample.id;
+        }
+
+        if !disk_infos_to_insert.is_empty() {
+            mutation::insert_multiple_diskinfo(conn, &disk_infos_to_insert)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_dedupe_bind_mounts() {
+        let mounts = vec![
+            (Some(2049), PathBuf::from("/var/lib/docker/volumes/db")),
+            (Some(2049), PathBuf::from("/")),
+            (Some(2050), PathBuf::from("/home")),
+            (None, PathBuf::from("/mnt/unreadable")),
+            (Some(2049), PathBuf::from("/srv/chroot")),
+            (None, PathBuf::from("/mnt/other")),
+        ];
+
+        let kept = dedupe_bind_mounts(mounts, |m| m.0, |m| m.1.as_path());
+        let paths: Vec<&str> = kept.iter().map(|m| m.1.to_str().unwrap()).collect();
+        assert_eq!(paths, vec!["/", "/home", "/mnt/unreadable", "/mnt/other"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mount_device_id() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+
+        // Directories of the same filesystem share its device number
+        assert!(mount_device_id(dir.path()).is_some());
+        assert_eq!(mount_device_id(dir.path()), mount_device_id(&nested));
+        assert_eq!(mount_device_id(&dir.path().join("missing")), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Inode usage of mounted filesystems, read with `statvfs(3)`.
+//!
+//! `sysinfo::Disks` only reports bytes. A filesystem full of small files
+//! (docker overlays, mail spools, caches) can run out of inodes while
+//! plenty of space is left, and writes then fail with ENOSPC all the same.
+
+use std::{io, path::Path};
+
+/// Inode counters of a single filesystem.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InodeUsage {
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+}
+
+impl InodeUsage {
+    /// Builds the usage from the `f_files` and `f_ffree` fields of statvfs.
+    pub fn from_counts(total: u64, free: u64) -> Self {
+        Self {
+            total,
+            used: total.saturating_sub(free),
+            free,
+        }
+    }
+
+    /// Share of inodes in use (0-100).
+    ///
+    /// Filesystems that allocate inodes dynamically (btrfs, vfat, many
+    /// network filesystems) report no inodes at all; they are 0% used.
+    pub fn used_percentage(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Reads the inode counters of the filesystem mounted at `mount_point`.
+#[cfg(unix)]
+pub fn read_inode_usage(mount_point: &Path) -> io::Result<InodeUsage> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(mount_point.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` is a
+    // properly sized, writable statvfs struct.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(InodeUsage::from_counts(
+        stat.f_files as u64,
+        stat.f_ffree as u64,
+    ))
+}
+
+/// Inode counters are only available through statvfs on Unix systems.
+#[cfg(not(unix))]
+pub fn read_inode_usage(_mount_point: &Path) -> io::Result<InodeUsage> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "inode usage is only available on Unix",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inode_usage_from_counts() {
+        let usage = InodeUsage::from_counts(1000, 250);
+        assert_eq!(usage.used, 750);
+        assert_eq!(usage.free, 250);
+        assert_eq!(usage.used_percentage(), 75.0);
+    }
+
+    #[test]
+    fn test_inode_usage_without_inodes() {
+        // btrfs and friends report no inode counts
+        let usage = InodeUsage::from_counts(0, 0);
+        assert_eq!(usage.used, 0);
+        assert_eq!(usage.used_percentage(), 0.0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_inode_usage() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let usage = read_inode_usage(dir.path()).unwrap();
+        assert_eq!(usage.used + usage.free, usage.total);
+
+        assert!(read_inode_usage(&dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,580 @@
+//! Kernel events read from `/dev/kmsg`.
+//!
+//! The kernel log reports what userspace can't observe: processes killed
+//! by the OOM killer (a container that "just died"), block device I/O
+//! errors, segfaults, filesystems remounted read-only after an error and
+//! machine check exceptions. Messages are classified into those categories;
+//! the rest of the log is ignored.
+//!
+//! Every read of `/dev/kmsg` returns one record of the form
+//! `<priority>,<sequence>,<usec since boot>,<flags>;<message>`, followed by
+//! ` KEY=value` continuation lines. Sequence numbers are unique per boot.
+//! A record overwritten in the ring buffer before it was read makes the
+//! read fail with `EPIPE`, and reading simply goes on with the next one.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use super::tail::complete_lines;
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaKernelEvent;
+use chrono::{TimeDelta, Utc};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// Categories of the kernel events, as set in `KernelEvent::category`.
+pub const CATEGORIES: [&str; 5] = [
+    "oom_kill",
+    "io_error",
+    "segfault",
+    "fs_readonly",
+    "hardware_error",
+];
+
+/// A record of the kernel log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KmsgRecord {
+    /// Log level, from 0 (emergency) to 7 (debug).
+    pub level: u8,
+    pub seq: u64,
+    /// Time of the message, in microseconds since boot.
+    pub monotonic_usec: u64,
+    pub message: String,
+}
+
+/// A kernel message classified as an event worth storing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KernelEvent {
+    /// One of `CATEGORIES`.
+    pub category: &'static str,
+    /// Process killed by the OOM killer or that crashed.
+    pub pid: Option<i32>,
+    pub process: Option<String>,
+    /// Device of an I/O error or of a filesystem remounted read-only.
+    pub device: Option<String>,
+    /// Memory cgroup of an OOM kill victim (e.g. "/system.slice/docker-<id>.scope").
+    pub cgroup: Option<String>,
+}
+
+/// Parses one `/dev/kmsg` record line. Continuation lines yield `None`.
+pub fn parse_kmsg_record(line: &str) -> Option<KmsgRecord> {
+    let (prefix, message) = line.split_once(';')?;
+    let mut fields = prefix.split(',');
+    let priority: u32 = fields.next()?.parse().ok()?;
+    let seq = fields.next()?.parse().ok()?;
+    let monotonic_usec = fields.next()?.parse().ok()?;
+    Some(KmsgRecord {
+        level: (priority & 7) as u8,
+        seq,
+        monotonic_usec,
+        message: message.to_string(),
+    })
+}
+
+/// Reads `/dev/kmsg`, or a file of records in tests.
+///
+/// The file is opened non-blocking and kept open, so each call returns
+/// the records logged since the previous one. The first call returns the
+/// records still in the kernel ring buffer.
+pub struct KmsgReader {
+    file: File,
+    /* end of the last read when it split a line, only happens with regular files */
+    partial: Vec<u8>,
+}
+
+impl KmsgReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.read(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_NONBLOCK);
+        }
+        Ok(Self {
+            file: options.open(path)?,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Returns the records available without blocking.
+    pub fn read_available(&mut self) -> io::Result<Vec<KmsgRecord>> {
+        // Larger than the longest record, smaller reads fail with EINVAL
+        let mut buffer = vec![0u8; 16 * 1024];
+        let mut data = std::mem::take(&mut self.partial);
+        loop {
+            match self.file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => data.extend_from_slice(&buffer[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                // Records were overwritten before being read
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => continue,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.partial = data.split_off(complete_lines(&data));
+        Ok(String::from_utf8_lossy(&data)
+            .lines()
+            .filter(|line| !line.starts_with(' '))
+            .filter_map(parse_kmsg_record)
+            .collect())
+    }
+}
+
+/// Parses `<name>[<pid>]` or `<pid> (<name>)`.
+fn name_and_pid(text: &str) -> Option<(String, i32)> {
+    if let Some((pid, rest)) = text.split_once(" (") {
+        return Some((rest.split_once(')')?.0.to_string(), pid.parse().ok()?));
+    }
+    let (name, rest) = text.rsplit_once('[')?;
+    Some((name.to_string(), rest.split_once(']')?.0.parse().ok()?))
+}
+
+/// Extracts the value of `key=` from a comma separated list.
+fn field<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    text.split(',')
+        .find_map(|part| part.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// Classifies kernel messages into events.
+///
+/// The OOM killer logs its decision (`oom-kill:...,task_memcg=...,pid=N`)
+/// just before the kill itself (`Killed process N (name)`); the
+/// classifier remembers the former to attach the cgroup to the latter.
+#[derive(Debug, Default)]
+pub struct KmsgClassifier {
+    oom_victim_cgroup: Option<(i32, String)>,
+}
+
+impl KmsgClassifier {
+    pub fn classify(&mut self, message: &str) -> Option<KernelEvent> {
+        if let Some(details) = message.strip_prefix("oom-kill:") {
+            if let (Some(pid), Some(cgroup)) = (
+                field(details, "pid").and_then(|pid| pid.parse().ok()),
+                field(details, "task_memcg"),
+            ) {
+                self.oom_victim_cgroup = Some((pid, cgroup.to_string()));
+            }
+            return None;
+        }
+
+        // "Out of memory: Killed process 1234 (java) total-vm:..." and
+        // "Memory cgroup out of memory: Killed process 1234 (java) ..."
+        if let Some((_, victim)) = message.split_once("Killed process ") {
+            let (process, pid) = name_and_pid(victim)?;
+            let cgroup = self
+                .oom_victim_cgroup
+                .take()
+                .filter(|(victim_pid, _)| *victim_pid == pid)
+                .map(|(_, cgroup)| cgroup);
+            return Some(KernelEvent {
+                category: "oom_kill",
+                pid: Some(pid),
+                process: Some(process),
+                cgroup,
+                ..Default::default()
+            });
+        }
+
+        // "java[1234]: segfault at 0 ip 00007f... sp 00007f... error 4 in libc.so.6"
+        if let Some((task, _)) = message.split_once(": segfault at ") {
+            let (process, pid) = name_and_pid(task)?;
+            return Some(KernelEvent {
+                category: "segfault",
+                pid: Some(pid),
+                process: Some(process),
+                ..Default::default()
+            });
+        }
+
+        // "I/O error, dev sda, sector 2048 op 0x0:(READ) ..." (also after
+        // "blk_update_request: "), "Buffer I/O error on dev sda1, logical block 0"
+        if message.contains("I/O error") && message.contains(" dev ") {
+            let device = message
+                .split_once(" dev ")
+                .and_then(|(_, rest)| rest.split([',', ' ']).next())
+                .map(str::to_string);
+            return Some(KernelEvent {
+                category: "io_error",
+                device,
+                ..Default::default()
+            });
+        }
+
+        // "EXT4-fs (sda1): Remounting filesystem read-only",
+        // "BTRFS info (device sdb1): forced readonly"
+        if message.contains("Remounting filesystem read-only")
+            || message.contains("forced readonly")
+        {
+            let device = message
+                .split_once('(')
+                .and_then(|(_, rest)| rest.split_once(')'))
+                .map(|(device, _)| device.trim_start_matches("device ").to_string());
+            return Some(KernelEvent {
+                category: "fs_readonly",
+                device,
+                ..Default::default()
+            });
+        }
+
+        // "mce: [Hardware Error]: Machine check events logged",
+        // "EDAC MC0: 1 CE memory read error on CPU_SrcID#0_Ha#0_Chan#1_DIMM#0"
+        if message.contains("[Hardware Error]") || message.starts_with("EDAC ") {
+            return Some(KernelEvent {
+                category: "hardware_error",
+                ..Default::default()
+            });
+        }
+
+        None
+    }
+}
+
+/// Microseconds elapsed since boot, on the clock used by the kernel log.
+#[cfg(unix)]
+pub fn monotonic_now_usec() -> Option<u64> {
+    let mut now: libc::timespec = unsafe { std::mem::zeroed() };
+    // SAFETY: `now` is a properly sized, writable timespec struct.
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) } != 0 {
+        return None;
+    }
+    Some(now.tv_sec as u64 * 1_000_000 + now.tv_nsec as u64 / 1000)
+}
+
+/// The monotonic clock is only read on Unix systems.
+#[cfg(not(unix))]
+pub fn monotonic_now_usec() -> Option<u64> {
+    None
+}
+
+/// Kernel events (OOM kills, I/O errors, segfaults, ...) from `/dev/kmsg`.
+///
+/// The device stays open between runs, so each run reads the messages
+/// logged since the previous one. After a restart the whole ring buffer is
+/// read again; its events are already stored under the same boot ID and
+/// sequence number and are skipped on insert.
+pub struct KmsgCollector {
+    kmsg_path: PathBuf,
+    procfs_root: PathBuf,
+    reader: Option<(KmsgReader, String)>,
+    classifier: KmsgClassifier,
+    /* set when the device is missing or not readable (no `CAP_SYSLOG`) */
+    unavailable: bool,
+    /* events read and not stored yet */
+    events: Vec<SchemaKernelEvent>,
+}
+
+impl KmsgCollector {
+    pub fn new(kmsg_path: PathBuf, procfs_root: PathBuf) -> Self {
+        Self {
+            kmsg_path,
+            procfs_root,
+            reader: None,
+            classifier: KmsgClassifier::default(),
+            unavailable: false,
+            events: Vec::new(),
+        }
+    }
+
+    /// Opens the kernel log, with the ID of the current boot.
+    fn open(&self) -> io::Result<(KmsgReader, String)> {
+        let reader = match KmsgReader::open(&self.kmsg_path) {
+            Ok(reader) => reader,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied
+                ) =>
+            {
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!(
+                        "cannot read {}, not reading kernel events anymore: {}",
+                        self.kmsg_path.display(),
+                        e
+                    ),
+                ));
+            }
+            Err(e) => return Err(e),
+        };
+        let boot_id = std::fs::read_to_string(self.procfs_root.join("sys/kernel/random/boot_id"))?;
+        Ok((reader, boot_id.trim().to_string()))
+    }
+}
+
+impl Collector for KmsgCollector {
+    fn name(&self) -> &'static str {
+        "kmsg"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        if self.unavailable {
+            return Ok(());
+        }
+        if self.reader.is_none() {
+            match self.open() {
+                Ok(opened) => self.reader = Some(opened),
+                Err(e) => {
+                    // Reported once, the device won't become readable
+                    self.unavailable = matches!(
+                        e.kind(),
+                        io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied
+                    );
+                    return Err(e.into());
+                }
+            }
+        }
+        let Some((reader, boot_id)) = self.reader.as_mut() else {
+            return Ok(());
+        };
+
+        let records = reader.read_available()?;
+        // Records are timed from boot, on the monotonic clock
+        let now = Utc::now();
+        let uptime_usec = monotonic_now_usec();
+        for record in records {
+            let Some(event) = self.classifier.classify(&record.message) else {
+                continue;
+            };
+            let age_usec =
+                uptime_usec.map_or(0, |uptime| uptime.saturating_sub(record.monotonic_usec));
+            self.events.push(SchemaKernelEvent {
+                boot_id: boot_id.clone(),
+                seq: record.seq as i64,
+                timestamp: (now - TimeDelta::microseconds(age_usec as i64)).to_rfc3339(),
+                level: record.level as i32,
+                category: event.category.to_string(),
+                message: record.message,
+                pid: event.pid,
+                process: event.process,
+                device: event.device,
+                cgroup: event.cgroup,
+            });
+        }
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        _sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        // Kept for the next run if the insert fails, the device won't
+        // return them again
+        if !self.events.is_empty() {
+            mutation::insert_multiple_kernelevents(conn, &self.events)?;
+            self.events.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+    use std::io::Write;
+
+    const KMSG: &str = "\
+6,1021,5000000,-;EXT4-fs (sda1): mounted filesystem with ordered data mode
+4,1022,7250000,-;oom-kill:constraint=CONSTRAINT_MEMCG,nodemask=(null),cpuset=/,mems_allowed=0,oom_memcg=/system.slice/docker-ab12.scope,task_memcg=/system.slice/docker-ab12.scope,task=java,pid=4321,uid=0
+3,1023,7250100,-;Memory cgroup out of memory: Killed process 4321 (java) total-vm:4508124kB, anon-rss:1048576kB, file-rss:0kB, shmem-rss:0kB, UID:0 pgtables:2400kB oom_score_adj:0
+ SUBSYSTEM=memory
+6,1024,8000000,-;nginx[812]: segfault at 0 ip 000055d4c3a0b2f1 sp 00007ffd2c1b6f40 error 4 in nginx[55d4c3a00000+100000] likely on CPU 1 (core 1, socket 0)
+3,1025,9000000,-;I/O error, dev sdb, sector 2048 op 0x0:(READ) flags 0x0 phys_seg 1 prio class 2
+ SUBSYSTEM=block
+ DEVICE=b8:16
+3,1026,9000100,-;Buffer I/O error on dev sdb1, logical block 0, async page read
+2,1027,9500000,-;EXT4-fs (sdb1): Remounting filesystem read-only
+4,1028,9600000,-;mce: [Hardware Error]: Machine check events logged
+3,1029,9700000,-;Out of memory: Killed process 999 (stress) total-vm:100kB
+";
+
+    fn classify_all(log: &str) -> Vec<(u64, KernelEvent)> {
+        let mut classifier = KmsgClassifier::default();
+        log.lines()
+            .filter_map(parse_kmsg_record)
+            .filter_map(|record| Some((record.seq, classifier.classify(&record.message)?)))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_kmsg_record() {
+        let record = parse_kmsg_record("3,1025,9000000,-;I/O error, dev sdb").unwrap();
+        assert_eq!(record.level, 3);
+        assert_eq!(record.seq, 1025);
+        assert_eq!(record.monotonic_usec, 9000000);
+        assert_eq!(record.message, "I/O error, dev sdb");
+
+        // Userspace messages carry a facility; the level is kept
+        assert_eq!(
+            parse_kmsg_record("30,1,2,-;systemd[1]: x").unwrap().level,
+            6
+        );
+        assert_eq!(parse_kmsg_record(" SUBSYSTEM=block"), None);
+        assert_eq!(parse_kmsg_record("garbage"), None);
+    }
+
+    #[test]
+    fn test_classify_kernel_messages() {
+        let events = classify_all(KMSG);
+        let categories: Vec<(u64, &str)> = events.iter().map(|(s, e)| (*s, e.category)).collect();
+        assert_eq!(
+            categories,
+            vec![
+                (1023, "oom_kill"),
+                (1024, "segfault"),
+                (1025, "io_error"),
+                (1026, "io_error"),
+                (1027, "fs_readonly"),
+                (1028, "hardware_error"),
+                (1029, "oom_kill"),
+            ]
+        );
+
+        let oom = &events[0].1;
+        assert_eq!(oom.pid, Some(4321));
+        assert_eq!(oom.process.as_deref(), Some("java"));
+        assert_eq!(
+            oom.cgroup.as_deref(),
+            Some("/system.slice/docker-ab12.scope")
+        );
+        // No oom-kill line for this one
+        assert_eq!(events[6].1.cgroup, None);
+        assert_eq!(events[6].1.process.as_deref(), Some("stress"));
+
+        let segfault = &events[1].1;
+        assert_eq!(segfault.pid, Some(812));
+        assert_eq!(segfault.process.as_deref(), Some("nginx"));
+
+        assert_eq!(events[2].1.device.as_deref(), Some("sdb"));
+        assert_eq!(events[3].1.device.as_deref(), Some("sdb1"));
+        assert_eq!(events[4].1.device.as_deref(), Some("sdb1"));
+    }
+
+    #[test]
+    fn test_classify_btrfs_forced_readonly() {
+        let mut classifier = KmsgClassifier::default();
+        let event = classifier
+            .classify("BTRFS info (device sdc1): forced readonly")
+            .unwrap();
+        assert_eq!(event.category, "fs_readonly");
+        assert_eq!(event.device.as_deref(), Some("sdc1"));
+        assert_eq!(
+            classifier.classify("BTRFS info (device sdc1): disk space caching is enabled"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_kmsg_reader_streams_appended_records() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("kmsg");
+        std::fs::write(&path, &KMSG[..KMSG.find("6,1024").unwrap()]).unwrap();
+
+        let mut reader = KmsgReader::open(&path).unwrap();
+        let records = reader.read_available().unwrap();
+        let seqs: Vec<u64> = records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![1021, 1022, 1023]);
+        assert!(reader.read_available().unwrap().is_empty());
+
+        // A record written in two parts is returned once complete
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"6,1024,8000000,-;nginx[812]: seg").unwrap();
+        assert!(reader.read_available().unwrap().is_empty());
+        file.write_all(b"fault at 0 ip 0 sp 0 error 4\n").unwrap();
+        let records = reader.read_available().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].message,
+            "nginx[812]: segfault at 0 ip 0 sp 0 error 4"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_monotonic_now_usec() {
+        let first = monotonic_now_usec().unwrap();
+        let second = monotonic_now_usec().unwrap();
+        assert!(first > 0);
+        assert!(second >= first);
+    }
+
+    #[test]
+    fn test_kmsg_collector_stores_events_once() {
+        use std::io::Write;
+
+        let (dir, storage) = migrated_storage();
+        let kmsg = dir.path().join("kmsg");
+        std::fs::create_dir_all(dir.path().join("sys/kernel/random")).unwrap();
+        std::fs::write(
+            dir.path().join("sys/kernel/random/boot_id"),
+            "0f3c5e1a-7d2b-4c1e-9a8f-2b6d4e8c1a3f\n",
+        )
+        .unwrap();
+        let append = |lines: &str| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&kmsg)
+                .unwrap()
+                .write_all(lines.as_bytes())
+                .unwrap();
+        };
+        let sample = Sample {
+            id: 0,
+            timestamp: String::new(),
+        };
+        let mut conn = storage.write().unwrap();
+        let stored = |conn: &mut TeuSQLiteConnection| {
+            query::get_kernelevents(conn, None, None, 100).unwrap()
+        };
+
+        append(
+            "6,10,1000000,-;EXT4-fs (sda1): mounted filesystem with ordered data mode\n\
+             3,11,2000000,-;Out of memory: Killed process 4321 (java) total-vm:100kB\n",
+        );
+        let mut collector = KmsgCollector::new(kmsg.clone(), dir.path().to_path_buf());
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert_eq!(stored(&mut conn).len(), 1);
+
+        append("6,12,3000000,-;nginx[812]: segfault at 0 ip 0 sp 0 error 4\n");
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert_eq!(stored(&mut conn).len(), 2);
+
+        // A restarted monitor reads the whole buffer again
+        let mut collector = KmsgCollector::new(kmsg, dir.path().to_path_buf());
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        let events = stored(&mut conn);
+        assert_eq!(events.len(), 2);
+
+        let oom = query::get_kernelevents(&mut conn, Some("oom_kill"), None, 100).unwrap();
+        assert_eq!(oom.len(), 1);
+        assert_eq!(oom[0].seq, 11);
+        assert_eq!(oom[0].level, 3);
+        assert_eq!(oom[0].process.as_deref(), Some("java"));
+        assert_eq!(oom[0].boot_id, "0f3c5e1a-7d2b-4c1e-9a8f-2b6d4e8c1a3f");
+        assert!(oom[0].timestamp < events[0].timestamp);
+    }
+
+    #[test]
+    fn test_kmsg_collector_reports_missing_device_once() {
+        let (dir, storage) = migrated_storage();
+        let sample = Sample {
+            id: 0,
+            timestamp: String::new(),
+        };
+        let mut conn = storage.write().unwrap();
+
+        let mut collector =
+            KmsgCollector::new(dir.path().join("missing"), dir.path().to_path_buf());
+        assert!(collect_and_store(&mut collector, &mut conn, &sample).is_err());
+        assert!(collect_and_store(&mut collector, &mut conn, &sample).is_ok());
+    }
+}
@@ -0,0 +1,342 @@
+//! Lines of watched log files matching the patterns of `[[monitor.logs]]`.
+//!
+//! New lines are read with a `FileCursor`, so rotation and truncation are
+//! handled there; this module only counts the matches and keeps the
+//! latest matching lines of each pattern. At most `MAX_READ_BYTES` of a
+//! log are read per collection, a burst of lines is counted over the next
+//! ones.
+
+use super::tail::{FileCursor, MAX_READ_BYTES, complete_lines};
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::{SchemaLogEvent, SchemaLogMatch};
+use std::{collections::VecDeque, io, path::Path};
+use teus_database::storage::TeuSQLiteConnection;
+use teus_types::config::{LogPatternConfig, LogWatchConfig};
+
+/// Bytes of a matching line that are stored, longer lines are cut.
+pub const MAX_LINE_BYTES: usize = 1024;
+
+/// Lines matching one pattern.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternMatches {
+    /// Number of matching lines.
+    pub count: u64,
+    /// Latest matching lines, oldest first.
+    pub lines: Vec<String>,
+}
+
+/// Cuts `line` to at most `MAX_LINE_BYTES` bytes, on a character boundary.
+fn truncate_line(line: &str) -> String {
+    if line.len() <= MAX_LINE_BYTES {
+        return line.to_string();
+    }
+    let mut end = MAX_LINE_BYTES;
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end].to_string()
+}
+
+/// Matches every line of `text` against `patterns`, returning the matches
+/// of each pattern in the same order. At most `max_lines` of the latest
+/// matching lines are kept per pattern.
+pub fn match_lines(
+    text: &str,
+    patterns: &[LogPatternConfig],
+    max_lines: usize,
+) -> Vec<PatternMatches> {
+    let mut counts = vec![0u64; patterns.len()];
+    let mut latest: Vec<VecDeque<&str>> = vec![VecDeque::new(); patterns.len()];
+
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        for (index, pattern) in patterns.iter().enumerate() {
+            if !pattern.regex.is_match(line) {
+                continue;
+            }
+            counts[index] += 1;
+            if max_lines > 0 {
+                if latest[index].len() == max_lines {
+                    latest[index].pop_front();
+                }
+                latest[index].push_back(line);
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .zip(latest)
+        .map(|(count, lines)| PatternMatches {
+            count,
+            lines: lines.into_iter().map(truncate_line).collect(),
+        })
+        .collect()
+}
+
+/// Rate of `count` matches over `elapsed_secs`, per minute. 0 when no time
+/// elapsed.
+pub fn per_minute(count: u64, elapsed_secs: f64) -> f64 {
+    if elapsed_secs > 0.0 {
+        count as f64 * 60.0 / elapsed_secs
+    } else {
+        0.0
+    }
+}
+
+/// Lines of the log files of `[[monitor.logs]]` matching their patterns.
+///
+/// Each run reads the lines appended since the previous run and stores,
+/// per pattern, the number of matches and their rate per minute, so error
+/// rates can be charted alongside the other metrics of the sample. The
+/// latest matching lines are stored as well. The first run only moves past
+/// what the files already contain.
+pub struct LogsCollector {
+    logs: Vec<WatchedLog>,
+    /* timestamp of the previous run, the baseline of the rates */
+    previous: Option<String>,
+    /* matches of every pattern per log read since the previous run, until stored */
+    found: Vec<(usize, Vec<PatternMatches>)>,
+}
+
+struct WatchedLog {
+    config: LogWatchConfig,
+    cursor: FileCursor,
+}
+
+impl LogsCollector {
+    pub fn new(logs: &[LogWatchConfig]) -> Self {
+        Self {
+            logs: logs
+                .iter()
+                .map(|config| WatchedLog {
+                    config: config.clone(),
+                    cursor: FileCursor::default(),
+                })
+                .collect(),
+            previous: None,
+            found: Vec::new(),
+        }
+    }
+}
+
+impl Collector for LogsCollector {
+    fn name(&self) -> &'static str {
+        "logs"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        let mut errors = Vec::new();
+        if self.previous.is_none() {
+            for log in &mut self.logs {
+                if let Err(e) = log.cursor.skip_to_end(Path::new(&log.config.path)) {
+                    errors.push(format!("log {}: {}", log.config.name, e));
+                }
+            }
+            if !errors.is_empty() {
+                return Err(io::Error::other(errors.join("; ")).into());
+            }
+            return Ok(());
+        }
+
+        self.found.clear();
+        for (index, log) in self.logs.iter_mut().enumerate() {
+            let lines = match log.cursor.read_new(
+                Path::new(&log.config.path),
+                complete_lines,
+                MAX_READ_BYTES,
+            ) {
+                Ok(lines) => lines,
+                Err(e) => {
+                    errors.push(format!("log {}: {}", log.config.name, e));
+                    continue;
+                }
+            };
+            let found = match_lines(
+                &String::from_utf8_lossy(&lines),
+                &log.config.patterns,
+                log.config.max_lines,
+            );
+            self.found.push((index, found));
+        }
+
+        if !errors.is_empty() {
+            return Err(io::Error::other(errors.join("; ")).into());
+        }
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(previous) = self.previous.replace(sample.timestamp.clone()) else {
+            return Ok(());
+        };
+        let elapsed = super::elapsed_secs(&previous, &sample.timestamp);
+
+        let mut matches = Vec::new();
+        let mut events = Vec::new();
+        for (index, found) in std::mem::take(&mut self.found) {
+            let log = &self.logs[index];
+            for (pattern, found) in log.config.patterns.iter().zip(found) {
+                matches.push(SchemaLogMatch {
+                    sysinfo_id: sample.id,
+                    log: log.config.name.clone(),
+                    pattern: pattern.name.clone(),
+                    count: found.count as i64,
+                    per_minute: per_minute(found.count, elapsed),
+                });
+                events.extend(found.lines.into_iter().map(|line| SchemaLogEvent {
+                    sysinfo_id: sample.id,
+                    log: log.config.name.clone(),
+                    pattern: pattern.name.clone(),
+                    line,
+                }));
+            }
+        }
+
+        if !matches.is_empty() {
+            mutation::insert_multiple_logmatches(conn, &matches)?;
+        }
+        if !events.is_empty() {
+            mutation::insert_multiple_logevents(conn, &events)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::sys::SysInfo;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+    use regex::Regex;
+
+    fn pattern(name: &str, regex: &str) -> LogPatternConfig {
+        LogPatternConfig {
+            name: name.to_string(),
+            regex: Regex::new(regex).unwrap(),
+        }
+    }
+
+    const NGINX_ERROR_LOG: &str = "\
+2024/01/15 10:30:01 [error] 812#812: *1 connect() failed (111: Connection refused) while connecting to upstream
+2024/01/15 10:30:02 [notice] 812#812: signal process started
+2024/01/15 10:30:05 [error] 812#812: *7 upstream timed out (110: Connection timed out)\r
+2024/01/15 10:30:09 [crit] 812#812: *9 SSL_do_handshake() failed
+2024/01/15 10:30:12 [error] 812#812: *12 upstream timed out (110: Connection timed out)
+";
+
+    #[test]
+    fn test_match_lines() {
+        let patterns = [
+            pattern("error", r"\[(error|crit)\]"),
+            pattern("timeout", "upstream timed out"),
+            pattern("oom", "Out of memory"),
+        ];
+        let matches = match_lines(NGINX_ERROR_LOG, &patterns, 2);
+
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].count, 4);
+        // Only the latest lines are kept, oldest first
+        assert_eq!(matches[0].lines.len(), 2);
+        assert!(matches[0].lines[0].contains("SSL_do_handshake"));
+        assert!(matches[0].lines[1].contains("*12 upstream"));
+
+        // A line matches every pattern it can, without its carriage return
+        assert_eq!(matches[1].count, 2);
+        assert!(matches[1].lines[0].ends_with("(110: Connection timed out)"));
+
+        assert_eq!(matches[2], PatternMatches::default());
+    }
+
+    #[test]
+    fn test_match_lines_without_stored_lines() {
+        let matches = match_lines(NGINX_ERROR_LOG, &[pattern("error", r"\[error\]")], 0);
+        assert_eq!(matches[0].count, 3);
+        assert!(matches[0].lines.is_empty());
+    }
+
+    #[test]
+    fn test_long_lines_are_truncated() {
+        let line = format!("ERROR {}\n", "é".repeat(MAX_LINE_BYTES));
+        let matches = match_lines(&line, &[pattern("error", "^ERROR")], 5);
+        let stored = &matches[0].lines[0];
+        assert!(stored.len() <= MAX_LINE_BYTES);
+        assert!(stored.starts_with("ERROR é"));
+    }
+
+    #[test]
+    fn test_per_minute() {
+        assert_eq!(per_minute(30, 60.0), 30.0);
+        assert_eq!(per_minute(5, 30.0), 10.0);
+        assert_eq!(per_minute(5, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_logs_collector_counts_new_matching_lines() {
+        let (dir, storage) = migrated_storage();
+        let path = dir.path().join("error.log");
+        let append = |content: &str| {
+            use std::io::Write;
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .unwrap()
+                .write_all(content.as_bytes())
+                .unwrap();
+        };
+        append("[error] before start\n");
+
+        let mut collector = LogsCollector::new(&[LogWatchConfig {
+            name: "nginx".to_string(),
+            path: path.to_string_lossy().into_owned(),
+            patterns: vec![LogPatternConfig {
+                name: "error".to_string(),
+                regex: regex::Regex::new(r"\[error\]").unwrap(),
+            }],
+            max_lines: 1,
+        }]);
+        let mut conn = storage.write().unwrap();
+        let mut sample_at = |timestamp: &str| {
+            let id = SysInfo::default().store(&mut conn).unwrap().id;
+            Sample {
+                id,
+                timestamp: timestamp.to_string(),
+            }
+        };
+        let first = sample_at("2024-01-15T10:00:00+00:00");
+        let second = sample_at("2024-01-15T10:00:30+00:00");
+        let third = sample_at("2024-01-15T10:01:00+00:00");
+
+        // Existing lines are skipped
+        collect_and_store(&mut collector, &mut conn, &first).unwrap();
+        append("[error] one\n[notice] two\n[error] three\n");
+        collect_and_store(&mut collector, &mut conn, &second).unwrap();
+        // Written to just before logrotate renamed it, then to the new file
+        append("[error] late\n");
+        std::fs::rename(&path, dir.path().join("error.log.1")).unwrap();
+        append("[error] four\n");
+        collect_and_store(&mut collector, &mut conn, &third).unwrap();
+
+        let history = query::get_logmatch_history(&mut conn, "nginx", "error", 10).unwrap();
+        let counts: Vec<(i64, f64)> = history
+            .iter()
+            .map(|(_, m)| (m.count, m.per_minute))
+            .collect();
+        assert_eq!(counts, vec![(2, 4.0), (2, 4.0)]);
+
+        let (_, latest) = query::get_latest_logmatches(&mut conn).unwrap().unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].sysinfo_id, third.id);
+
+        let events = query::get_logevents(&mut conn, Some("nginx"), None, 10).unwrap();
+        let lines: Vec<&str> = events.iter().map(|(_, e)| e.line.as_str()).collect();
+        assert_eq!(lines, vec!["[error] four", "[error] three"]);
+    }
+}
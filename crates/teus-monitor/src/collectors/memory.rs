@@ -0,0 +1,139 @@
+//! Memory breakdown read from `/proc/meminfo`.
+//!
+//! `sysinfo` only exposes total, free, used and swap figures. The kernel
+//! table also has the available estimate, page cache, buffers, shared
+//! memory, dirty pages and huge pages, which are needed to tell real
+//! memory pressure from a healthy page cache.
+
+use std::{fs, io, path::Path};
+
+/// Memory breakdown of the system. Sizes are in bytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    /// Kernel estimate of the memory available for new workloads without
+    /// swapping, including reclaimable cache.
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    /// Shared memory, including tmpfs.
+    pub shared: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    /// Memory waiting to be written back to disk.
+    pub dirty: u64,
+    /// Number of huge pages in the pool.
+    pub hugepages_total: u64,
+    /// Number of huge pages not yet allocated.
+    pub hugepages_free: u64,
+    pub hugepage_size: u64,
+}
+
+/// Parses the content of `/proc/meminfo`.
+///
+/// Every line has the form `Key:   value [kB]`. Sizes are converted to
+/// bytes; huge page counts have no unit and are kept as is. Unknown keys
+/// and malformed lines are ignored, missing keys are left at 0.
+pub fn parse_proc_meminfo(content: &str) -> MemInfo {
+    let mut info = MemInfo::default();
+
+    for line in content.lines() {
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = rest.split_whitespace();
+        let Some(Ok(value)) = parts.next().map(|v| v.parse::<u64>()) else {
+            continue;
+        };
+        let value = match parts.next() {
+            Some("kB") => value * 1024,
+            _ => value,
+        };
+
+        let field = match key {
+            "MemTotal" => &mut info.total,
+            "MemFree" => &mut info.free,
+            "MemAvailable" => &mut info.available,
+            "Buffers" => &mut info.buffers,
+            "Cached" => &mut info.cached,
+            "Shmem" => &mut info.shared,
+            "SwapTotal" => &mut info.swap_total,
+            "SwapFree" => &mut info.swap_free,
+            "Dirty" => &mut info.dirty,
+            "HugePages_Total" => &mut info.hugepages_total,
+            "HugePages_Free" => &mut info.hugepages_free,
+            "Hugepagesize" => &mut info.hugepage_size,
+            _ => continue,
+        };
+        *field = value;
+    }
+
+    info
+}
+
+/// Reads and parses `meminfo` from the given procfs root.
+pub fn read_meminfo(procfs_root: &Path) -> io::Result<MemInfo> {
+    let content = fs::read_to_string(procfs_root.join("meminfo"))?;
+    Ok(parse_proc_meminfo(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PROC_MEMINFO: &str = "\
+MemTotal:       16303428 kB
+MemFree:          812344 kB
+MemAvailable:   11534120 kB
+Buffers:          402112 kB
+Cached:         10123456 kB
+SwapCached:            0 kB
+Shmem:            523004 kB
+SwapTotal:       2097148 kB
+SwapFree:        2000000 kB
+Dirty:               136 kB
+HugePages_Total:       4
+HugePages_Free:        3
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn test_parse_proc_meminfo() {
+        let info = parse_proc_meminfo(PROC_MEMINFO);
+        assert_eq!(info.total, 16303428 * 1024);
+        assert_eq!(info.free, 812344 * 1024);
+        assert_eq!(info.available, 11534120 * 1024);
+        assert_eq!(info.buffers, 402112 * 1024);
+        assert_eq!(info.cached, 10123456 * 1024);
+        assert_eq!(info.shared, 523004 * 1024);
+        assert_eq!(info.swap_total, 2097148 * 1024);
+        assert_eq!(info.swap_free, 2000000 * 1024);
+        assert_eq!(info.dirty, 136 * 1024);
+        // Huge page counts have no unit
+        assert_eq!(info.hugepages_total, 4);
+        assert_eq!(info.hugepages_free, 3);
+        assert_eq!(info.hugepage_size, 2048 * 1024);
+    }
+
+    #[test]
+    fn test_parse_proc_meminfo_missing_and_malformed_keys() {
+        let info = parse_proc_meminfo("MemTotal: 1024 kB\nMemFree: lots\ngarbage\n");
+        assert_eq!(info.total, 1024 * 1024);
+        assert_eq!(info.free, 0);
+        assert_eq!(info.available, 0);
+    }
+
+    #[test]
+    fn test_read_meminfo_from_fixture_root() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        fs::write(root.path().join("meminfo"), PROC_MEMINFO).unwrap();
+
+        let info = read_meminfo(root.path()).unwrap();
+        assert_eq!(info.hugepages_total, 4);
+
+        let missing = TempDir::new().expect("Failed to create temp dir");
+        assert!(read_meminfo(missing.path()).is_err());
+    }
+}
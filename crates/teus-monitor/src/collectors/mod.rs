@@ -0,0 +1,82 @@
+//! Metric sources read by the system monitor.
+//!
+//! Each submodule knows how to read and parse one source of metrics
+//! (usually a file under `/proc` or `/sys`, or the `sysinfo` crate). Parsing is kept separate
+//! from file access so the parsers can be tested against fixture data. The
+//! `Collector` that stores a source with each sample lives next to its parser.
+
+pub mod cgroups;
+pub mod dirsize;
+pub mod diskio;
+pub mod disks;
+pub mod inodes;
+pub mod kmsg;
+pub mod logwatch;
+pub mod memory;
+pub mod network;
+pub mod packages;
+pub mod pressure;
+pub mod processes;
+pub mod scripts;
+pub mod security;
+pub mod sensors;
+pub mod sockets;
+pub mod tail;
+pub mod timesync;
+
+use chrono::DateTime;
+use std::time::Duration;
+
+/// Computes a per-second rate from two readings of a monotonic counter.
+///
+/// Returns `None` when no time has elapsed or when the counter went
+/// backwards, which happens when an interface or device is recreated
+/// or a 32-bit kernel counter wraps around.
+pub fn counter_rate(previous: u64, current: u64, elapsed_secs: f64) -> Option<f64> {
+    if elapsed_secs <= 0.0 || current < previous {
+        return None;
+    }
+    Some((current - previous) as f64 / elapsed_secs)
+}
+
+/// Seconds elapsed between two RFC3339 timestamps, 0.0 if either is invalid.
+pub fn elapsed_secs(from: &str, to: &str) -> f64 {
+    match (
+        DateTime::parse_from_rfc3339(from),
+        DateTime::parse_from_rfc3339(to),
+    ) {
+        (Ok(from), Ok(to)) => (to - from).num_milliseconds() as f64 / 1000.0,
+        _ => 0.0,
+    }
+}
+
+/* background jobs are due slightly early, so the jitter of the monitoring cycle doesn't skip a cycle */
+const SCHEDULE_SLACK: Duration = Duration::from_millis(500);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_rate() {
+        assert_eq!(counter_rate(1000, 6000, 5.0), Some(1000.0));
+        assert_eq!(counter_rate(1000, 1000, 5.0), Some(0.0));
+    }
+
+    #[test]
+    fn test_counter_rate_reset_and_zero_elapsed() {
+        // Counter went backwards (interface recreated)
+        assert_eq!(counter_rate(6000, 1000, 5.0), None);
+        // No time elapsed between readings
+        assert_eq!(counter_rate(1000, 6000, 0.0), None);
+    }
+
+    #[test]
+    fn test_elapsed_secs() {
+        assert_eq!(
+            elapsed_secs("2024-01-01T00:00:00+00:00", "2024-01-01T00:00:05.500+00:00"),
+            5.5
+        );
+        assert_eq!(elapsed_secs("invalid", "2024-01-01T00:00:05+00:00"), 0.0);
+    }
+}
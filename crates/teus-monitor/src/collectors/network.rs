@@ -0,0 +1,264 @@
+//! Network interface counters read from `/proc/net/dev`.
+//!
+//! `sysinfo::Networks` exposes bytes, packets and errors but not dropped
+//! packets, so the kernel table is parsed directly. Every interface is
+//! reported, including bridges, docker veths and the loopback device.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::schema::{NetInfo, SchemaNetInfo};
+use crate::{mutation, query};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// Cumulative counters of a single network interface since boot.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceCounters {
+    pub interface: String,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+}
+
+/// Parses the content of `/proc/net/dev`.
+///
+/// The first two lines are headers. Every other line has the form
+/// `iface: rx_bytes rx_packets rx_errs rx_drop rx_fifo rx_frame rx_compressed
+/// rx_multicast tx_bytes tx_packets tx_errs tx_drop ...`. Malformed lines
+/// are skipped instead of failing the whole read.
+pub fn parse_proc_net_dev(content: &str) -> Vec<InterfaceCounters> {
+    content
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, stats) = line.split_once(':')?;
+            let fields: Vec<u64> = stats
+                .split_whitespace()
+                .map(|v| v.parse::<u64>())
+                .collect::<Result<_, _>>()
+                .ok()?;
+            if fields.len() < 12 {
+                return None;
+            }
+
+            Some(InterfaceCounters {
+                interface: name.trim().to_string(),
+                rx_bytes: fields[0],
+                rx_packets: fields[1],
+                rx_errors: fields[2],
+                rx_dropped: fields[3],
+                tx_bytes: fields[8],
+                tx_packets: fields[9],
+                tx_errors: fields[10],
+                tx_dropped: fields[11],
+            })
+        })
+        .collect()
+}
+
+/// Reads and parses `net/dev` from the given procfs root.
+pub fn read_interface_counters(procfs_root: &Path) -> io::Result<Vec<InterfaceCounters>> {
+    let content = fs::read_to_string(procfs_root.join("net").join("dev"))?;
+    Ok(parse_proc_net_dev(&content))
+}
+
+/// Network interface counters and throughput, from `/proc/net/dev`.
+pub struct NetworkCollector {
+    procfs_root: PathBuf,
+    counters: Option<Vec<InterfaceCounters>>,
+}
+
+impl NetworkCollector {
+    pub fn new(procfs_root: PathBuf) -> Self {
+        Self {
+            procfs_root,
+            counters: None,
+        }
+    }
+}
+
+impl Collector for NetworkCollector {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.counters = Some(read_interface_counters(&self.procfs_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(counters) = self.counters.take() else {
+            return Ok(());
+        };
+        // Baseline for rates: the interfaces of the previous run
+        let previous = query::get_latest_netinfo(conn)?.map(|(timestamp, interfaces)| {
+            (
+                super::elapsed_secs(&timestamp, &sample.timestamp),
+                interfaces,
+            )
+        });
+        let rows = build_netinfo_rows(
+            sample.id,
+            &counters,
+            previous
+                .as_ref()
+                .map(|(elapsed, interfaces)| (*elapsed, interfaces.as_slice())),
+        );
+
+        if !rows.is_empty() {
+            mutation::insert_multiple_netinfo(conn, &rows)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the `netinfo` rows of a snapshot, computing throughput rates from
+/// the counters of the same interface in the previous snapshot, if any.
+fn build_netinfo_rows(
+    sysinfo_id: i32,
+    counters: &[InterfaceCounters],
+    previous: Option<(f64, &[NetInfo])>,
+) -> Vec<SchemaNetInfo> {
+    counters
+        .iter()
+        .map(|c| {
+            let baseline = previous.and_then(|(elapsed, interfaces)| {
+                interfaces
+                    .iter()
+                    .find(|p| p.interface == c.interface)
+                    .map(|p| (elapsed, p))
+            });
+            let (rx_rate, tx_rate) = match baseline {
+                Some((elapsed, p)) => (
+                    super::counter_rate(p.rx_bytes as u64, c.rx_bytes, elapsed),
+                    super::counter_rate(p.tx_bytes as u64, c.tx_bytes, elapsed),
+                ),
+                None => (None, None),
+            };
+
+            SchemaNetInfo {
+                sysinfo_id,
+                interface: c.interface.clone(),
+                rx_bytes: c.rx_bytes as i64,
+                tx_bytes: c.tx_bytes as i64,
+                rx_packets: c.rx_packets as i64,
+                tx_packets: c.tx_packets as i64,
+                rx_errors: c.rx_errors as i64,
+                tx_errors: c.tx_errors as i64,
+                rx_dropped: c.rx_dropped as i64,
+                tx_dropped: c.tx_dropped as i64,
+                rx_bytes_per_sec: rx_rate.map(|r| r as f32),
+                tx_bytes_per_sec: tx_rate.map(|r| r as f32),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PROC_NET_DEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 2776770   23042    0    0    0     0          0         0  2776770   23042    0    0    0     0       0          0
+  eth0: 1073741824  812345   3   17    0     0          0      1024 536870912  612345    1    2    0     0       0          0
+docker0:       0       0    0    0    0     0          0         0        0       0    0    0    0     0       0          0
+";
+
+    #[test]
+    fn test_parse_proc_net_dev() {
+        let counters = parse_proc_net_dev(PROC_NET_DEV);
+        assert_eq!(counters.len(), 3);
+
+        let eth0 = &counters[1];
+        assert_eq!(eth0.interface, "eth0");
+        assert_eq!(eth0.rx_bytes, 1073741824);
+        assert_eq!(eth0.rx_packets, 812345);
+        assert_eq!(eth0.rx_errors, 3);
+        assert_eq!(eth0.rx_dropped, 17);
+        assert_eq!(eth0.tx_bytes, 536870912);
+        assert_eq!(eth0.tx_packets, 612345);
+        assert_eq!(eth0.tx_errors, 1);
+        assert_eq!(eth0.tx_dropped, 2);
+
+        // Names without padding after the colon are handled too
+        assert_eq!(counters[2].interface, "docker0");
+        assert_eq!(counters[2].rx_bytes, 0);
+    }
+
+    #[test]
+    fn test_parse_proc_net_dev_skips_malformed_lines() {
+        let content = "header\nheader\n  eth0: 1 2 3\n  eth1: not numbers at all\n";
+        assert!(parse_proc_net_dev(content).is_empty());
+        assert!(parse_proc_net_dev("").is_empty());
+    }
+
+    #[test]
+    fn test_read_interface_counters_from_fixture_root() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        fs::create_dir(root.path().join("net")).unwrap();
+        fs::write(root.path().join("net").join("dev"), PROC_NET_DEV).unwrap();
+
+        let counters = read_interface_counters(root.path()).unwrap();
+        assert_eq!(counters.len(), 3);
+        assert_eq!(counters[0].interface, "lo");
+    }
+
+    #[test]
+    fn test_build_netinfo_rows_with_rates() {
+        let previous = vec![NetInfo {
+            id: Some(1),
+            sysinfo_id: 1,
+            interface: "eth0".to_string(),
+            rx_bytes: 1000,
+            tx_bytes: 5000,
+            rx_packets: 10,
+            tx_packets: 20,
+            rx_errors: 0,
+            tx_errors: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            rx_bytes_per_sec: None,
+            tx_bytes_per_sec: None,
+        }];
+        let counters = vec![
+            InterfaceCounters {
+                interface: "eth0".to_string(),
+                rx_bytes: 6000,
+                tx_bytes: 1000, // counter reset
+                ..Default::default()
+            },
+            InterfaceCounters {
+                interface: "veth1234".to_string(),
+                rx_bytes: 42,
+                ..Default::default()
+            },
+        ];
+
+        let rows = build_netinfo_rows(2, &counters, Some((5.0, &previous)));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].sysinfo_id, 2);
+        assert_eq!(rows[0].rx_bytes_per_sec, Some(1000.0));
+        assert_eq!(rows[0].tx_bytes_per_sec, None);
+        // New interface has no baseline yet
+        assert_eq!(rows[1].rx_bytes, 42);
+        assert_eq!(rows[1].rx_bytes_per_sec, None);
+
+        let rows = build_netinfo_rows(1, &counters, None);
+        assert!(rows.iter().all(|r| r.rx_bytes_per_sec.is_none()));
+    }
+}
@@ -0,0 +1,937 @@
+//! Pending package updates and reboot-required flag.
+//!
+//! Updates are computed from the package databases alone, without running
+//! the package manager: the installed versions are compared with the
+//! newest versions in the package lists fetched by the last `apt update`
+//! or `apk update`. Hosts where the lists are never refreshed report no
+//! updates, so the age of the lists is reported too.
+//!
+//! - Debian: installed packages from `/var/lib/dpkg/status`, available
+//!   ones from the uncompressed `/var/lib/apt/lists/*_Packages` files.
+//!   Updates found in the lists of a `-security` suite are security
+//!   updates. Held packages are skipped, as `apt upgrade` does.
+//! - Alpine: installed packages from `/lib/apk/db/installed`, available
+//!   ones from the `/var/cache/apk/APKINDEX.*.tar.gz` indexes. Alpine has
+//!   no separate security repository, so security updates are unknown.
+//!
+//! A reboot is required when `/var/run/reboot-required` exists, as created
+//! by the Debian and Ubuntu kernel and libc packages.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::{SchemaPackageStatus, SchemaPackageUpdate};
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// An installed package with a newer version available.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingUpdate {
+    pub name: String,
+    pub installed_version: String,
+    pub available_version: String,
+    /// Whether the new version comes from a security repository.
+    pub security: bool,
+}
+
+/// Package state of the host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackageStatus {
+    /// "apt" or "apk".
+    pub manager: &'static str,
+    pub updates: Vec<PendingUpdate>,
+    /// Whether security updates can be told apart from the others.
+    pub security_known: bool,
+    /// Modification time of the newest package list, `None` when the lists
+    /// were never fetched.
+    pub lists_updated_at: Option<SystemTime>,
+    pub reboot_required: bool,
+    /// Packages that asked for the reboot.
+    pub reboot_packages: Vec<String>,
+}
+
+/// Package names as stored in the database, a JSON array.
+pub fn encode_names(names: &[String]) -> String {
+    serde_json::to_string(names).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Package names stored by `encode_names`.
+pub fn decode_names(names: &str) -> Vec<String> {
+    serde_json::from_str(names).unwrap_or_default()
+}
+
+/// A package of a control file (dpkg status, apt lists, apk databases).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackageVersion {
+    pub name: String,
+    pub arch: String,
+    pub version: String,
+}
+
+/// Splits a control file into stanzas of `(field, value)` pairs.
+/// Continuation lines (starting with a space) are skipped.
+fn stanzas<'a>(
+    content: &'a str,
+    separator: char,
+) -> impl Iterator<Item = Vec<(&'a str, &'a str)>> + 'a {
+    content.split("\n\n").map(move |stanza| {
+        stanza
+            .lines()
+            .filter(|line| !line.starts_with([' ', '\t']))
+            .filter_map(|line| line.split_once(separator))
+            .map(|(field, value)| (field, value.trim()))
+            .collect()
+    })
+}
+
+fn field<'a>(stanza: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    stanza.iter().find(|(f, _)| *f == name).map(|(_, v)| *v)
+}
+
+/// Parses a dpkg status file or an apt `Packages` list. For the status
+/// file, only installed packages that aren't held are returned.
+pub fn parse_deb_packages(content: &str) -> Vec<PackageVersion> {
+    stanzas(content, ':')
+        .filter(|stanza| field(stanza, "Status").is_none_or(|s| s == "install ok installed"))
+        .filter_map(|stanza| {
+            Some(PackageVersion {
+                name: field(&stanza, "Package")?.to_string(),
+                arch: field(&stanza, "Architecture").unwrap_or("all").to_string(),
+                version: field(&stanza, "Version")?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the apk database of installed packages or an `APKINDEX`.
+pub fn parse_apk_packages(content: &str) -> Vec<PackageVersion> {
+    stanzas(content, ':')
+        .filter_map(|stanza| {
+            Some(PackageVersion {
+                name: field(&stanza, "P")?.to_string(),
+                arch: field(&stanza, "A").unwrap_or_default().to_string(),
+                version: field(&stanza, "V")?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/* dpkg's ordering of the non-digit characters: `~` first, then the end of the string, letters, and the rest */
+fn deb_char_order(c: Option<&u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => *c as i32,
+        Some(c) => *c as i32 + 256,
+    }
+}
+
+/* compares an upstream version or a revision, as dpkg's `verrevcmp` */
+fn deb_compare_part(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while a.get(i).is_some_and(|c| !c.is_ascii_digit())
+            || b.get(j).is_some_and(|c| !c.is_ascii_digit())
+        {
+            let (ac, bc) = (deb_char_order(a.get(i)), deb_char_order(b.get(j)));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_diff = Ordering::Equal;
+        while a.get(i).is_some_and(u8::is_ascii_digit) && b.get(j).is_some_and(u8::is_ascii_digit) {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if a.get(i).is_some_and(u8::is_ascii_digit) {
+            return Ordering::Greater;
+        }
+        if b.get(j).is_some_and(u8::is_ascii_digit) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compares two Debian versions (`[epoch:]upstream[-revision]`).
+pub fn compare_deb_versions(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (u64, &str, &str) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+            None => (0, version),
+        };
+        let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, upstream, revision)
+    }
+
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| deb_compare_part(a_upstream, b_upstream))
+        .then_with(|| deb_compare_part(a_revision, b_revision))
+}
+
+/* rank of an apk version suffix; a version without suffix ranks between pre-releases and patches */
+const APK_NO_SUFFIX: u8 = 4;
+
+fn apk_suffix_rank(suffix: &str) -> u8 {
+    match suffix {
+        "alpha" => 0,
+        "beta" => 1,
+        "pre" => 2,
+        "rc" => 3,
+        "cvs" => 5,
+        "svn" => 6,
+        "git" => 7,
+        "hg" => 8,
+        "p" => 9,
+        _ => APK_NO_SUFFIX,
+    }
+}
+
+/* numbers, trailing letter, suffixes and revision of an apk version */
+type ApkVersion = (Vec<u64>, Option<char>, Vec<(u8, u64)>, u64);
+
+fn parse_apk_version(version: &str) -> ApkVersion {
+    let (version, revision) = match version.rsplit_once("-r") {
+        Some((version, revision)) if revision.parse::<u64>().is_ok() => {
+            (version, revision.parse().unwrap_or(0))
+        }
+        _ => (version, 0),
+    };
+    let mut parts = version.split('_');
+    let mut release = parts.next().unwrap_or_default().to_string();
+    let letter = release
+        .chars()
+        .last()
+        .filter(char::is_ascii_alphabetic)
+        .inspect(|_| {
+            release.pop();
+        });
+    let numbers = release.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+    let suffixes = parts
+        .map(|suffix| {
+            let digits = suffix
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(suffix.len());
+            (
+                apk_suffix_rank(&suffix[..digits]),
+                suffix[digits..].parse().unwrap_or(0),
+            )
+        })
+        .collect();
+    (numbers, letter, suffixes, revision)
+}
+
+/// Compares two Alpine versions (`1.2.3[a][_suffix[N]]...[-rN]`).
+pub fn compare_apk_versions(a: &str, b: &str) -> Ordering {
+    let (a_numbers, a_letter, a_suffixes, a_revision) = parse_apk_version(a);
+    let (b_numbers, b_letter, b_suffixes, b_revision) = parse_apk_version(b);
+
+    let suffixes = || {
+        let len = a_suffixes.len().max(b_suffixes.len());
+        let missing = (APK_NO_SUFFIX, 0);
+        (0..len)
+            .map(|i| {
+                let a = a_suffixes.get(i).unwrap_or(&missing);
+                let b = b_suffixes.get(i).unwrap_or(&missing);
+                a.cmp(b)
+            })
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    };
+    a_numbers
+        .cmp(&b_numbers)
+        .then_with(|| a_letter.cmp(&b_letter))
+        .then_with(suffixes)
+        .then_with(|| a_revision.cmp(&b_revision))
+}
+
+/// Finds the installed packages with a newer version available.
+///
+/// `available` holds the packages of each list, with whether the list is
+/// from a security repository. An update is a security update when any
+/// list with a newer version is a security one. Packages installed for
+/// several architectures are reported once.
+pub fn find_updates(
+    installed: &[PackageVersion],
+    available: &[(Vec<PackageVersion>, bool)],
+    compare: fn(&str, &str) -> Ordering,
+) -> Vec<PendingUpdate> {
+    let arch_matches = |installed: &PackageVersion, candidate: &PackageVersion| {
+        installed.arch == candidate.arch || installed.arch.is_empty() || candidate.arch.is_empty()
+    };
+
+    let mut by_name: HashMap<&str, Vec<(&PackageVersion, bool)>> = HashMap::new();
+    for (packages, security) in available {
+        for package in packages {
+            by_name
+                .entry(package.name.as_str())
+                .or_default()
+                .push((package, *security));
+        }
+    }
+
+    let mut updates: Vec<PendingUpdate> = installed
+        .iter()
+        .filter_map(|package| {
+            let candidates: Vec<&(&PackageVersion, bool)> = by_name
+                .get(package.name.as_str())?
+                .iter()
+                .filter(|(candidate, _)| arch_matches(package, candidate))
+                .filter(|(candidate, _)| {
+                    compare(&candidate.version, &package.version) == Ordering::Greater
+                })
+                .collect();
+            let best = candidates
+                .iter()
+                .max_by(|(a, _), (b, _)| compare(&a.version, &b.version))?;
+            Some(PendingUpdate {
+                name: package.name.clone(),
+                installed_version: package.version.clone(),
+                available_version: best.0.version.clone(),
+                security: candidates.iter().any(|(_, security)| *security),
+            })
+        })
+        .collect();
+    updates.sort_by(|a, b| a.name.cmp(&b.name));
+    updates.dedup_by(|a, b| a.name == b.name && a.available_version == b.available_version);
+    updates
+}
+
+/// Reads the `APKINDEX` file out of an `APKINDEX.tar.gz`.
+///
+/// The archive is a signature and the index concatenated, each gzipped
+/// separately.
+pub fn read_apkindex(path: &Path) -> io::Result<String> {
+    let mut archive = tar::Archive::new(MultiGzDecoder::new(File::open(path)?));
+    archive.set_ignore_zeros(true);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_os_str() == "APKINDEX" {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            return Ok(content);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("no APKINDEX in {}", path.display()),
+    ))
+}
+
+/* files of `dir` whose name passes `keep`, with their modification time */
+fn list_files(
+    dir: &Path,
+    keep: impl Fn(&str) -> bool,
+) -> io::Result<Vec<(std::path::PathBuf, SystemTime)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name().to_str().is_some_and(&keep) {
+            let modified = entry.metadata()?.modified()?;
+            files.push((entry.path(), modified));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn read_apt_status(
+    root: &Path,
+    installed: &str,
+) -> io::Result<(Vec<PendingUpdate>, Option<SystemTime>)> {
+    let lists = list_files(&root.join("var/lib/apt/lists"), |name| {
+        name.ends_with("_Packages")
+    })?;
+    let mut available = Vec::new();
+    for (path, _) in &lists {
+        let security = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.contains("-security_") || name.contains("_security_"));
+        available.push((parse_deb_packages(&fs::read_to_string(path)?), security));
+    }
+
+    let updates = find_updates(
+        &parse_deb_packages(installed),
+        &available,
+        compare_deb_versions,
+    );
+    Ok((updates, lists.iter().map(|(_, modified)| *modified).max()))
+}
+
+fn read_apk_status(
+    root: &Path,
+    installed: &str,
+) -> io::Result<(Vec<PendingUpdate>, Option<SystemTime>)> {
+    let indexes = list_files(&root.join("var/cache/apk"), |name| {
+        name.starts_with("APKINDEX.") && name.ends_with(".tar.gz")
+    })?;
+    let mut available = Vec::new();
+    for (path, _) in &indexes {
+        available.push((parse_apk_packages(&read_apkindex(path)?), false));
+    }
+
+    let updates = find_updates(
+        &parse_apk_packages(installed),
+        &available,
+        compare_apk_versions,
+    );
+    Ok((updates, indexes.iter().map(|(_, modified)| *modified).max()))
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Modification times of the package databases, lists and reboot flag
+/// under `root`, `None` for the missing ones.
+///
+/// The package state only changes when one of them does, so it is cheap
+/// to check on every cycle. `apt update` and `apk update` move the new
+/// lists into place, which updates the modification time of their
+/// directory.
+pub fn state_fingerprint(root: &Path) -> Vec<Option<SystemTime>> {
+    [
+        "var/lib/dpkg/status",
+        "var/lib/apt/lists",
+        "lib/apk/db/installed",
+        "var/cache/apk",
+        "run/reboot-required",
+        "var/run/reboot-required",
+    ]
+    .iter()
+    .map(|path| {
+        fs::metadata(root.join(path))
+            .and_then(|m| m.modified())
+            .ok()
+    })
+    .collect()
+}
+
+/// Reads the package state of the filesystem at `root`.
+///
+/// Returns `None` on hosts with neither dpkg nor apk.
+pub fn read_package_status(root: &Path) -> io::Result<Option<PackageStatus>> {
+    let (manager, security_known, (updates, lists_updated_at)) =
+        if let Some(installed) = read_optional(&root.join("var/lib/dpkg/status"))? {
+            ("apt", true, read_apt_status(root, &installed)?)
+        } else if let Some(installed) = read_optional(&root.join("lib/apk/db/installed"))? {
+            ("apk", false, read_apk_status(root, &installed)?)
+        } else {
+            return Ok(None);
+        };
+
+    // `/var/run` links to `/run`, which doesn't resolve under another root
+    let mut reboot_required = false;
+    let mut reboot_packages = Vec::new();
+    for run in ["run", "var/run"] {
+        let flag = root.join(run).join("reboot-required");
+        if flag.exists() {
+            reboot_required = true;
+            let packages = read_optional(&root.join(run).join("reboot-required.pkgs"))?;
+            reboot_packages = packages
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+            reboot_packages.sort();
+            reboot_packages.dedup();
+            break;
+        }
+    }
+
+    Ok(Some(PackageStatus {
+        manager,
+        updates,
+        security_known,
+        lists_updated_at,
+        reboot_required,
+        reboot_packages,
+    }))
+}
+
+/// Pending package updates and the reboot-required flag, from the dpkg or
+/// apk databases under the host root.
+///
+/// The databases are only read again when one of them changes or a new
+/// day starts, and each read replaces the row of the current day, so one
+/// row per day is kept. Hosts without dpkg or apk store nothing.
+pub struct PackagesCollector {
+    host_root: PathBuf,
+    /* day and package database fingerprint of the last read */
+    last_read: Option<(String, Vec<Option<std::time::SystemTime>>)>,
+    /* rows of the last read, until stored */
+    status: Option<(SchemaPackageStatus, Vec<SchemaPackageUpdate>)>,
+}
+
+impl PackagesCollector {
+    pub fn new(host_root: PathBuf) -> Self {
+        Self {
+            host_root,
+            last_read: None,
+            status: None,
+        }
+    }
+}
+
+impl Collector for PackagesCollector {
+    fn name(&self) -> &'static str {
+        "packages"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        let now = Utc::now();
+        let day = now.format("%Y-%m-%d").to_string();
+        let fingerprint = state_fingerprint(&self.host_root);
+        if self
+            .last_read
+            .as_ref()
+            .is_some_and(|(last_day, last)| *last_day == day && *last == fingerprint)
+        {
+            return Ok(());
+        }
+
+        let status = read_package_status(&self.host_root)?;
+        self.last_read = Some((day.clone(), fingerprint));
+        let Some(status) = status else {
+            return Ok(());
+        };
+
+        let updates: Vec<SchemaPackageUpdate> = status
+            .updates
+            .iter()
+            .map(|update| SchemaPackageUpdate {
+                day: day.clone(),
+                name: update.name.clone(),
+                installed_version: update.installed_version.clone(),
+                available_version: update.available_version.clone(),
+                security: update.security,
+            })
+            .collect();
+        let security_updates = status.updates.iter().filter(|u| u.security).count();
+        let row = SchemaPackageStatus {
+            day,
+            timestamp: now.to_rfc3339(),
+            manager: status.manager.to_string(),
+            pending_updates: status.updates.len() as i32,
+            security_updates: status.security_known.then_some(security_updates as i32),
+            reboot_required: status.reboot_required,
+            reboot_packages: encode_names(&status.reboot_packages),
+            lists_updated_at: status
+                .lists_updated_at
+                .map(|modified| DateTime::<Utc>::from(modified).to_rfc3339()),
+        };
+
+        self.status = Some((row, updates));
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        _sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        // Kept for the next run if the insert fails, the databases are
+        // only read again once they change
+        if let Some((row, updates)) = &self.status {
+            mutation::replace_packagestatus(conn, row, updates)?;
+            self.status = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    const DPKG_STATUS: &str = "\
+Package: libc6
+Status: install ok installed
+Priority: optional
+Architecture: amd64
+Version: 2.36-9+deb12u3
+Description: GNU C Library: Shared libraries
+ Contains the standard libraries that are used by nearly all programs on
+ the system.
+
+Package: openssl
+Status: install ok installed
+Architecture: amd64
+Version: 3.0.11-1~deb12u2
+
+Package: curl
+Status: hold ok installed
+Architecture: amd64
+Version: 7.88.1-10+deb12u4
+
+Package: tzdata
+Status: install ok installed
+Architecture: all
+Version: 2024a-0+deb12u1
+
+Package: removed-package
+Status: deinstall ok config-files
+Architecture: amd64
+Version: 1.0-1
+";
+
+    const MAIN_PACKAGES: &str = "\
+Package: libc6
+Version: 2.36-9+deb12u4
+Architecture: amd64
+
+Package: openssl
+Version: 3.0.11-1~deb12u2
+Architecture: amd64
+
+Package: curl
+Version: 7.88.1-10+deb12u5
+Architecture: amd64
+
+Package: tzdata
+Version: 2024a-0+deb12u1
+Architecture: all
+";
+
+    const SECURITY_PACKAGES: &str = "\
+Package: openssl
+Version: 3.0.13-1~deb12u1
+Architecture: amd64
+
+Package: libc6
+Version: 2.36-9+deb12u4
+Architecture: amd64
+";
+
+    const APK_INSTALLED: &str = "\
+C:Q1abc=
+P:musl
+V:1.2.4-r2
+A:x86_64
+
+C:Q1def=
+P:busybox
+V:1.36.1-r15
+A:x86_64
+
+C:Q1ghi=
+P:ca-certificates-bundle
+V:20240226-r0
+A:x86_64
+";
+
+    const APKINDEX: &str = "\
+C:Q1abc=
+P:musl
+V:1.2.4_git20230717-r4
+A:x86_64
+
+C:Q1def=
+P:busybox
+V:1.36.1-r15
+A:x86_64
+
+C:Q1jkl=
+P:ca-certificates-bundle
+V:20240705-r0
+A:x86_64
+";
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /* APKINDEX.tar.gz as written by `apk update`: a signature and an index, gzipped separately */
+    fn write_apkindex(path: &Path, index: &str) {
+        fn gzipped_tar(name: &str, content: &[u8]) -> Vec<u8> {
+            let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content).unwrap();
+            builder.into_inner().unwrap().finish().unwrap()
+        }
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut file = File::create(path).unwrap();
+        file.write_all(&gzipped_tar(".SIGN.RSA.alpine-devel.rsa.pub", b"signature"))
+            .unwrap();
+        file.write_all(&gzipped_tar("APKINDEX", index.as_bytes()))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_compare_deb_versions() {
+        let cases = [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0", "1.1", Ordering::Less),
+            ("1.10", "1.9", Ordering::Greater),
+            ("1:1.0", "2.0", Ordering::Greater),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0~~", "1.0~", Ordering::Less),
+            ("1.0a", "1.0", Ordering::Greater),
+            ("1.0+b1", "1.0", Ordering::Greater),
+            ("2.36-9+deb12u3", "2.36-9+deb12u4", Ordering::Less),
+            ("3.0.11-1~deb12u2", "3.0.11-1", Ordering::Less),
+            ("1.01", "1.1", Ordering::Equal),
+            ("7.88.1-10+deb12u5", "7.88.1-10+deb12u4", Ordering::Greater),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_deb_versions(a, b), expected, "{} vs {}", a, b);
+            assert_eq!(
+                compare_deb_versions(b, a),
+                expected.reverse(),
+                "{} vs {}",
+                b,
+                a
+            );
+        }
+    }
+
+    #[test]
+    fn test_compare_apk_versions() {
+        let cases = [
+            ("1.2.4-r2", "1.2.4-r2", Ordering::Equal),
+            ("1.2.4-r2", "1.2.4-r10", Ordering::Less),
+            ("1.2.4", "1.2.4.1", Ordering::Less),
+            ("1.2.10", "1.2.9", Ordering::Greater),
+            ("1.2.4_rc1", "1.2.4", Ordering::Less),
+            ("1.2.4_alpha2", "1.2.4_beta1", Ordering::Less),
+            ("1.2.4_p1", "1.2.4", Ordering::Greater),
+            ("1.2.4_git20230717-r4", "1.2.4-r2", Ordering::Greater),
+            ("1.1.1w", "1.1.1v", Ordering::Greater),
+            ("20240705-r0", "20240226-r0", Ordering::Greater),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_apk_versions(a, b), expected, "{} vs {}", a, b);
+            assert_eq!(
+                compare_apk_versions(b, a),
+                expected.reverse(),
+                "{} vs {}",
+                b,
+                a
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_deb_packages_skips_held_and_removed() {
+        let installed = parse_deb_packages(DPKG_STATUS);
+        let names: Vec<&str> = installed.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["libc6", "openssl", "tzdata"]);
+        assert_eq!(installed[0].arch, "amd64");
+        assert_eq!(installed[0].version, "2.36-9+deb12u3");
+    }
+
+    #[test]
+    fn test_read_apt_updates() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let root = dir.path();
+        write(&root.join("var/lib/dpkg/status"), DPKG_STATUS);
+        let lists = root.join("var/lib/apt/lists");
+        write(
+            &lists.join("deb.debian.org_debian_dists_bookworm_main_binary-amd64_Packages"),
+            MAIN_PACKAGES,
+        );
+        write(
+            &lists.join(
+                "deb.debian.org_debian-security_dists_bookworm-security_main_binary-amd64_Packages",
+            ),
+            SECURITY_PACKAGES,
+        );
+        // Compressed lists and release files are ignored
+        write(
+            &lists.join("deb.debian.org_debian_dists_bookworm_InRelease"),
+            "garbage",
+        );
+
+        let status = read_package_status(root).unwrap().unwrap();
+        assert_eq!(status.manager, "apt");
+        assert!(status.security_known);
+        assert!(status.lists_updated_at.is_some());
+        assert!(!status.reboot_required);
+        assert_eq!(
+            status.updates,
+            vec![
+                // Same version in both lists, it comes from the security repository
+                PendingUpdate {
+                    name: "libc6".to_string(),
+                    installed_version: "2.36-9+deb12u3".to_string(),
+                    available_version: "2.36-9+deb12u4".to_string(),
+                    security: true,
+                },
+                PendingUpdate {
+                    name: "openssl".to_string(),
+                    installed_version: "3.0.11-1~deb12u2".to_string(),
+                    available_version: "3.0.13-1~deb12u1".to_string(),
+                    security: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_updates_regular_and_security() {
+        let package = |name: &str, version: &str| PackageVersion {
+            name: name.to_string(),
+            arch: "amd64".to_string(),
+            version: version.to_string(),
+        };
+        let installed = vec![package("nginx", "1.22.1-9"), package("bash", "5.2.15-2")];
+        let available = vec![
+            (
+                vec![
+                    package("nginx", "1.22.1-9+deb12u1"),
+                    package("bash", "5.2.15-2+b7"),
+                ],
+                false,
+            ),
+            (vec![package("bash", "5.2.15-2")], true),
+        ];
+
+        let updates = find_updates(&installed, &available, compare_deb_versions);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].name, "bash");
+        // The security list has no newer version
+        assert!(!updates[0].security);
+        assert_eq!(updates[1].available_version, "1.22.1-9+deb12u1");
+    }
+
+    #[test]
+    fn test_read_apk_updates_and_reboot_flag() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let root = dir.path();
+        write(&root.join("lib/apk/db/installed"), APK_INSTALLED);
+        write_apkindex(
+            &root.join("var/cache/apk/APKINDEX.4a3e2b1c.tar.gz"),
+            APKINDEX,
+        );
+        write(
+            &root.join("var/run/reboot-required"),
+            "*** System restart required ***\n",
+        );
+        write(
+            &root.join("var/run/reboot-required.pkgs"),
+            "linux-lts\nmusl\nlinux-lts\n",
+        );
+
+        let status = read_package_status(root).unwrap().unwrap();
+        assert_eq!(status.manager, "apk");
+        assert!(!status.security_known);
+        let updates: Vec<(&str, &str)> = status
+            .updates
+            .iter()
+            .map(|u| (u.name.as_str(), u.available_version.as_str()))
+            .collect();
+        assert_eq!(
+            updates,
+            vec![
+                ("ca-certificates-bundle", "20240705-r0"),
+                ("musl", "1.2.4_git20230717-r4"),
+            ]
+        );
+        assert!(status.reboot_required);
+        assert_eq!(status.reboot_packages, vec!["linux-lts", "musl"]);
+    }
+
+    #[test]
+    fn test_read_package_status_without_package_manager() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        assert_eq!(read_package_status(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_packages_collector_replaces_the_row_of_the_day() {
+        let (dir, storage) = migrated_storage();
+        let root = dir.path().join("host");
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        let sample = Sample {
+            id: 0,
+            timestamp: String::new(),
+        };
+        let mut conn = storage.write().unwrap();
+
+        // No package manager, nothing stored
+        let mut collector = PackagesCollector::new(root.clone());
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert!(
+            query::get_latest_packagestatus(&mut conn)
+                .unwrap()
+                .is_none()
+        );
+
+        write(
+            "var/lib/dpkg/status",
+            "Package: openssl\nStatus: install ok installed\nArchitecture: amd64\nVersion: 3.0.11-1\n",
+        );
+        write(
+            "var/lib/apt/lists/deb.debian.org_debian-security_dists_bookworm-security_main_binary-amd64_Packages",
+            "Package: openssl\nArchitecture: amd64\nVersion: 3.0.13-1\n",
+        );
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        let (status, updates) = query::get_latest_packagestatus(&mut conn).unwrap().unwrap();
+        assert_eq!(status.manager, "apt");
+        assert_eq!(status.pending_updates, 1);
+        assert_eq!(status.security_updates, Some(1));
+        assert!(!status.reboot_required);
+        assert_eq!(updates[0].available_version, "3.0.13-1");
+
+        // A new check of the same day replaces its row
+        write("var/run/reboot-required", "");
+        write("var/run/reboot-required.pkgs", "openssl\n");
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        let history = query::get_packagestatus_history(&mut conn, 30).unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].reboot_required);
+        assert_eq!(history[0].reboot_packages, "[\"openssl\"]");
+    }
+}
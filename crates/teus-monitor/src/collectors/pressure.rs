@@ -0,0 +1,217 @@
+//! Pressure stall information read from `/proc/pressure`.
+//!
+//! PSI reports the share of wall time in which some (or all) runnable
+//! tasks were stalled waiting for CPU, memory or I/O, averaged over 10,
+//! 60 and 300 seconds. Kernels before 4.20, or booted with `psi=0`, don't
+//! provide it; the collector then reports nothing instead of failing.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaPsiInfo;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// Resources with a pressure file under `/proc/pressure`.
+pub const PRESSURE_RESOURCES: [&str; 3] = ["cpu", "memory", "io"];
+
+/// Error returned by Linux when reading PSI files on a `psi=0` kernel.
+const EOPNOTSUPP: i32 = 95;
+
+/// Stall averages of a single resource, as percentages (0-100).
+///
+/// `some` covers time where at least one task was stalled, `full` time
+/// where all non-idle tasks were stalled at once. The `full` line is
+/// missing for CPU on kernels before 5.13.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PressureStats {
+    pub resource: String,
+    pub some_avg10: f64,
+    pub some_avg60: f64,
+    pub some_avg300: f64,
+    pub full_avg10: Option<f64>,
+    pub full_avg60: Option<f64>,
+    pub full_avg300: Option<f64>,
+}
+
+/// Parses one `some`/`full` line into its avg10, avg60 and avg300 values.
+fn parse_averages(line: &str) -> Option<(f64, f64, f64)> {
+    let mut avg10 = None;
+    let mut avg60 = None;
+    let mut avg300 = None;
+    for field in line.split_whitespace().skip(1) {
+        let (key, value) = field.split_once('=')?;
+        let slot = match key {
+            "avg10" => &mut avg10,
+            "avg60" => &mut avg60,
+            "avg300" => &mut avg300,
+            _ => continue,
+        };
+        *slot = Some(value.parse::<f64>().ok()?);
+    }
+    Some((avg10?, avg60?, avg300?))
+}
+
+/// Parses the content of a `/proc/pressure/<resource>` file.
+///
+/// Returns `None` if the mandatory `some` line is missing or malformed.
+pub fn parse_pressure(resource: &str, content: &str) -> Option<PressureStats> {
+    let mut some = None;
+    let mut full = None;
+    for line in content.lines() {
+        if line.starts_with("some ") {
+            some = parse_averages(line);
+        } else if line.starts_with("full ") {
+            full = parse_averages(line);
+        }
+    }
+
+    let (some_avg10, some_avg60, some_avg300) = some?;
+    Some(PressureStats {
+        resource: resource.to_string(),
+        some_avg10,
+        some_avg60,
+        some_avg300,
+        full_avg10: full.map(|f| f.0),
+        full_avg60: full.map(|f| f.1),
+        full_avg300: full.map(|f| f.2),
+    })
+}
+
+/// Reads the pressure of every resource from the given procfs root.
+///
+/// Returns an empty list when PSI is not available: the `pressure`
+/// directory doesn't exist, or reading it is not supported because PSI
+/// was disabled at boot. Other I/O errors are returned.
+pub fn read_pressure(procfs_root: &Path) -> io::Result<Vec<PressureStats>> {
+    let dir = procfs_root.join("pressure");
+    let mut stats = Vec::new();
+
+    for resource in PRESSURE_RESOURCES {
+        let content = match fs::read_to_string(dir.join(resource)) {
+            Ok(content) => content,
+            Err(e)
+                if e.kind() == io::ErrorKind::NotFound || e.raw_os_error() == Some(EOPNOTSUPP) =>
+            {
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(pressure) = parse_pressure(resource, &content) {
+            stats.push(pressure);
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Pressure stall information, from `/proc/pressure`.
+pub struct PressureCollector {
+    procfs_root: PathBuf,
+    pressure: Option<Vec<PressureStats>>,
+}
+
+impl PressureCollector {
+    pub fn new(procfs_root: PathBuf) -> Self {
+        Self {
+            procfs_root,
+            pressure: None,
+        }
+    }
+}
+
+impl Collector for PressureCollector {
+    fn name(&self) -> &'static str {
+        "pressure"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.pressure = Some(read_pressure(&self.procfs_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(pressure) = self.pressure.take() else {
+            return Ok(());
+        };
+        let psi_infos_to_insert: Vec<SchemaPsiInfo> = pressure
+            .into_iter()
+            .map(|p| SchemaPsiInfo {
+                sysinfo_id: sample.id,
+                resource: p.resource,
+                some_avg10: p.some_avg10 as f32,
+                some_avg60: p.some_avg60 as f32,
+                some_avg300: p.some_avg300 as f32,
+                full_avg10: p.full_avg10.map(|v| v as f32),
+                full_avg60: p.full_avg60.map(|v| v as f32),
+                full_avg300: p.full_avg300.map(|v| v as f32),
+            })
+            .collect();
+
+        if !psi_infos_to_insert.is_empty() {
+            mutation::insert_multiple_psiinfo(conn, &psi_infos_to_insert)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PRESSURE_MEMORY: &str = "\
+some avg10=1.53 avg60=0.87 avg300=0.25 total=1234567
+full avg10=0.50 avg60=0.20 avg300=0.05 total=456789
+";
+
+    #[test]
+    fn test_parse_pressure() {
+        let stats = parse_pressure("memory", PRESSURE_MEMORY).unwrap();
+        assert_eq!(stats.resource, "memory");
+        assert_eq!(stats.some_avg10, 1.53);
+        assert_eq!(stats.some_avg60, 0.87);
+        assert_eq!(stats.some_avg300, 0.25);
+        assert_eq!(stats.full_avg10, Some(0.50));
+        assert_eq!(stats.full_avg60, Some(0.20));
+        assert_eq!(stats.full_avg300, Some(0.05));
+    }
+
+    #[test]
+    fn test_parse_pressure_without_full_line() {
+        // CPU pressure on kernels before 5.13
+        let stats =
+            parse_pressure("cpu", "some avg10=12.00 avg60=8.50 avg300=3.10 total=99\n").unwrap();
+        assert_eq!(stats.some_avg10, 12.0);
+        assert_eq!(stats.full_avg10, None);
+
+        assert!(parse_pressure("cpu", "").is_none());
+        assert!(parse_pressure("cpu", "some avg10=bad avg60=1 avg300=1\n").is_none());
+    }
+
+    #[test]
+    fn test_read_pressure_from_fixture_root() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        let dir = root.path().join("pressure");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("memory"), PRESSURE_MEMORY).unwrap();
+        fs::write(dir.join("io"), PRESSURE_MEMORY).unwrap();
+
+        // cpu is missing and skipped
+        let stats = read_pressure(root.path()).unwrap();
+        let resources: Vec<&str> = stats.iter().map(|s| s.resource.as_str()).collect();
+        assert_eq!(resources, vec!["memory", "io"]);
+    }
+
+    #[test]
+    fn test_read_pressure_without_psi() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        assert!(read_pressure(root.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+//! Process table snapshots built from `sysinfo`.
+//!
+//! The processes collector refreshes every process on its own schedule,
+//! and the API does when the live table is requested; this module turns
+//! them into plain samples and selects the ones worth storing.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaProcessInfo;
+use sysinfo::{ProcessesToUpdate, System, Users};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// State of a single process at the time of collection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub name: String,
+    /// Command line, arguments separated by spaces.
+    pub cmd: String,
+    /// Owner of the process, `None` if the UID has no passwd entry.
+    pub username: Option<String>,
+    /// CPU usage as a percentage of one core (can exceed 100).
+    pub cpu_usage: f32,
+    /// Resident set size, in bytes.
+    pub memory: u64,
+    /// Start time, in seconds since the Unix epoch.
+    pub start_time: u64,
+}
+
+/// Builds a sample of every process known to `sys`.
+///
+/// On Linux `sysinfo` also lists threads as tasks; they are skipped since
+/// their usage is already accounted for in their process.
+///
+/// CPU usage is only meaningful if the processes were refreshed twice,
+/// at least `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL` apart.
+pub fn collect_processes(sys: &System, users: &Users) -> Vec<ProcessSample> {
+    sys.processes()
+        .values()
+        .filter(|process| process.thread_kind().is_none())
+        .map(|process| ProcessSample {
+            pid: process.pid().as_u32(),
+            name: process.name().to_string_lossy().to_string(),
+            cmd: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
+            username: process
+                .user_id()
+                .and_then(|uid| users.get_user_by_id(uid))
+                .map(|user| user.name().to_string()),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            start_time: process.start_time(),
+        })
+        .collect()
+}
+
+/// Selects the top `n` processes by CPU usage and the top `n` by memory.
+///
+/// A process in both rankings is returned once, so the result holds at
+/// most `2 * n` samples, ordered by CPU usage (highest first).
+pub fn top_processes(processes: &[ProcessSample], n: usize) -> Vec<ProcessSample> {
+    let mut by_cpu: Vec<&ProcessSample> = processes.iter().collect();
+    by_cpu.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+
+    let mut by_memory: Vec<&ProcessSample> = processes.iter().collect();
+    by_memory.sort_by_key(|p| std::cmp::Reverse(p.memory));
+
+    let mut top: Vec<ProcessSample> = Vec::with_capacity(n * 2);
+    for process in by_cpu
+        .into_iter()
+        .take(n)
+        .chain(by_memory.into_iter().take(n))
+    {
+        if !top.iter().any(|p| p.pid == process.pid) {
+            top.push(process.clone());
+        }
+    }
+
+    top.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+    top
+}
+
+/// The top processes by CPU and by memory.
+///
+/// Keeps its own `System` between runs, so the CPU usage of a process
+/// covers the whole time since the previous run.
+pub struct ProcessesCollector {
+    top: usize,
+    sys: System,
+    top_processes: Option<Vec<ProcessSample>>,
+}
+
+impl ProcessesCollector {
+    pub fn new(top: usize) -> Self {
+        let mut sys = System::new();
+        // Baseline for the CPU usage of the first run, which the monitor
+        // delays by at least MINIMUM_CPU_UPDATE_INTERVAL
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        Self {
+            top,
+            sys,
+            top_processes: None,
+        }
+    }
+}
+
+impl Collector for ProcessesCollector {
+    fn name(&self) -> &'static str {
+        "processes"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.sys.refresh_processes(ProcessesToUpdate::All, true);
+        let users = Users::new_with_refreshed_list();
+        self.top_processes = Some(top_processes(
+            &collect_processes(&self.sys, &users),
+            self.top,
+        ));
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(top_processes) = self.top_processes.take() else {
+            return Ok(());
+        };
+
+        let process_infos_to_insert: Vec<SchemaProcessInfo> = top_processes
+            .into_iter()
+            .map(|p| SchemaProcessInfo {
+                sysinfo_id: sample.id,
+                pid: p.pid as i32,
+                name: p.name,
+                cmd: p.cmd,
+                username: p.username,
+                cpu_usage: p.cpu_usage,
+                memory: p.memory as i64,
+                start_time: p.start_time as i64,
+            })
+            .collect();
+
+        if !process_infos_to_insert.is_empty() {
+            mutation::insert_multiple_processinfo(conn, &process_infos_to_insert)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(pid: u32, cpu_usage: f32, memory: u64) -> ProcessSample {
+        ProcessSample {
+            pid,
+            name: format!("proc{}", pid),
+            cpu_usage,
+            memory,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_top_processes_union_of_rankings() {
+        let processes = vec![
+            sample(1, 0.1, 10),
+            sample(2, 90.0, 20),        // top CPU
+            sample(3, 50.0, 4_000_000), // top CPU and top memory
+            sample(4, 0.0, 8_000_000),  // top memory
+            sample(5, 10.0, 30),
+        ];
+
+        let top = top_processes(&processes, 2);
+        let pids: Vec<u32> = top.iter().map(|p| p.pid).collect();
+        // 3 appears in both rankings but is returned once, ordered by CPU
+        assert_eq!(pids, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_top_processes_small_tables() {
+        let processes = vec![sample(1, 1.0, 10), sample(2, 2.0, 20)];
+        assert_eq!(top_processes(&processes, 10).len(), 2);
+        assert!(top_processes(&processes, 0).is_empty());
+        assert!(top_processes(&[], 10).is_empty());
+    }
+}
@@ -0,0 +1,646 @@
+//! Metrics produced by user-defined scripts.
+//!
+//! A script prints its values on stdout, either as `name=value` lines or in
+//! the Prometheus text exposition format:
+//!
+//! ```text
+//! # HELP queue_depth Messages waiting in the queue.
+//! # TYPE queue_depth gauge
+//! queue_depth{queue="emails"} 42
+//! queue_depth{queue="webhooks"} 3
+//! ```
+//!
+//! Malformed output is rejected as a whole, so a broken script never
+//! stores half of its values. `NaN` and infinite values are skipped.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaScriptMetric;
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+    process::{Command, ExitStatus, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use teus_database::storage::TeuSQLiteConnection;
+use teus_types::config::{ScriptConfig, ScriptFormat};
+
+/// A value printed by a script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptMetric {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+/// Labels as stored in the database, a JSON object.
+pub fn encode_labels(labels: &BTreeMap<String, String>) -> String {
+    serde_json::to_string(labels).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Labels stored by `encode_labels`.
+pub fn decode_labels(labels: &str) -> BTreeMap<String, String> {
+    serde_json::from_str(labels).unwrap_or_default()
+}
+
+/* how often a running script is checked for exit or timeout */
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+fn is_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+fn is_label_name(name: &str) -> bool {
+    !name.contains(':') && is_metric_name(name)
+}
+
+fn parse_value(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}`", value))
+}
+
+/// Parses `name=value` lines. Blank lines and `#` comments are ignored.
+pub fn parse_key_value(output: &str) -> Result<Vec<ScriptMetric>, String> {
+    let mut metrics = Vec::new();
+    for (index, line) in output.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |e: String| format!("line {}: {}", index + 1, e);
+
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| error("expected `name=value`".to_string()))?;
+        let name = name.trim();
+        if !is_metric_name(name) {
+            return Err(error(format!("invalid metric name `{}`", name)));
+        }
+        let value = parse_value(value.trim()).map_err(error)?;
+        if value.is_finite() {
+            metrics.push(ScriptMetric {
+                name: name.to_string(),
+                labels: BTreeMap::new(),
+                value,
+            });
+        }
+    }
+    Ok(metrics)
+}
+
+/// Parses the labels of a Prometheus sample, `rest` starting after the
+/// opening brace. Returns the labels and what follows the closing brace.
+fn parse_labels(mut rest: &str) -> Result<(BTreeMap<String, String>, &str), String> {
+    let mut labels = BTreeMap::new();
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix('}') {
+            return Ok((labels, after));
+        }
+
+        let (name, after) = rest
+            .split_once('=')
+            .ok_or_else(|| "expected `label=\"value\"`".to_string())?;
+        let name = name.trim();
+        if !is_label_name(name) {
+            return Err(format!("invalid label name `{}`", name));
+        }
+        let quoted = after
+            .trim_start()
+            .strip_prefix('"')
+            .ok_or_else(|| format!("unquoted value of label `{}`", name))?;
+        let mut chars = quoted.char_indices();
+
+        let mut value = String::new();
+        let end = loop {
+            match chars.next() {
+                Some((i, '"')) => break i,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, c)) => value.push(c),
+                    None => return Err("unterminated label value".to_string()),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("unterminated label value".to_string()),
+            }
+        };
+        labels.insert(name.to_string(), value);
+
+        rest = quoted[end + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest);
+    }
+}
+
+/// Parses the Prometheus text exposition format. `# HELP` and `# TYPE`
+/// lines are ignored, as is the optional timestamp of a sample.
+pub fn parse_prometheus(output: &str) -> Result<Vec<ScriptMetric>, String> {
+    let mut metrics = Vec::new();
+    for (index, line) in output.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |e: String| format!("line {}: {}", index + 1, e);
+
+        let name_end = line
+            .find(|c: char| c == '{' || c.is_whitespace())
+            .ok_or_else(|| error("missing value".to_string()))?;
+        let name = &line[..name_end];
+        if !is_metric_name(name) {
+            return Err(error(format!("invalid metric name `{}`", name)));
+        }
+        let (labels, rest) = match line[name_end..].strip_prefix('{') {
+            Some(labels) => parse_labels(labels).map_err(error)?,
+            None => (BTreeMap::new(), &line[name_end..]),
+        };
+
+        let value = rest
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| error("missing value".to_string()))?;
+        let value = parse_value(value).map_err(error)?;
+        if value.is_finite() {
+            metrics.push(ScriptMetric {
+                name: name.to_string(),
+                labels,
+                value,
+            });
+        }
+    }
+    Ok(metrics)
+}
+
+/// Parses the output of a script in `format`.
+pub fn parse_output(output: &str, format: ScriptFormat) -> Result<Vec<ScriptMetric>, String> {
+    match format {
+        ScriptFormat::KeyValue => parse_key_value(output),
+        ScriptFormat::Prometheus => parse_prometheus(output),
+    }
+}
+
+/* reads a pipe to the end on its own thread, so a chatty script never blocks on a full pipe */
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// Kills the script and every process it started.
+#[cfg(unix)]
+fn kill(child: &mut std::process::Child) {
+    // SAFETY: the script leads its own process group, see `run_script`.
+    unsafe {
+        libc::kill(-(child.id() as i32), libc::SIGKILL);
+    }
+}
+
+/// Without process groups only the shell itself is killed.
+#[cfg(not(unix))]
+fn kill(child: &mut std::process::Child) {
+    let _ = child.kill();
+}
+
+fn describe_failure(status: ExitStatus, stderr: &[u8]) -> String {
+    let failure = match status.code() {
+        Some(code) => format!("exited with code {}", code),
+        // Killed by a signal
+        None => status.to_string(),
+    };
+    let stderr = String::from_utf8_lossy(stderr);
+    match stderr.lines().find(|line| !line.trim().is_empty()) {
+        Some(line) => format!("{}: {}", failure, line.trim()),
+        None => failure,
+    }
+}
+
+/// Runs `command` with `sh -c` and returns its stdout.
+///
+/// Blocks until the script exits and closes its output, or `timeout`
+/// elapses; a script that times out is killed with the processes it
+/// started. A non-zero exit is an error carrying the first line of stderr.
+pub fn run_script(command: &str, timeout: Duration) -> io::Result<String> {
+    let mut shell = Command::new("sh");
+    shell
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        shell.process_group(0);
+    }
+
+    let started = Instant::now();
+    let mut child = shell.spawn()?;
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    // A process left in the background can hold the pipes open after the
+    // shell exits, so the script is done once both are closed too
+    let mut exited = None;
+    let status = loop {
+        if exited.is_none() {
+            exited = child.try_wait()?;
+        }
+        if let Some(status) = exited
+            && stdout.is_finished()
+            && stderr.is_finished()
+        {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            kill(&mut child);
+            let _ = child.wait();
+            let _ = (stdout.join(), stderr.join());
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out after {}s", timeout.as_secs_f64()),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        return Err(io::Error::other(describe_failure(status, &stderr)));
+    }
+    Ok(String::from_utf8_lossy(&stdout).into_owned())
+}
+
+struct ScheduledScript {
+    config: ScriptConfig,
+    interval: Duration,
+    last_start: Option<std::time::Instant>,
+    running: bool,
+}
+
+/* index of the script in `ScriptsCollector::scripts`, and its parsed output */
+type ScriptResult = (usize, Result<Vec<ScriptMetric>, String>);
+
+/// Values printed by the `[[monitor.scripts]]` commands.
+///
+/// Scripts run on their own threads, so a slow one doesn't hold up the
+/// monitoring cycle and its database lock. Their values are stored by the
+/// first run of the collector after they exit, and a script isn't started
+/// again while its previous run is still going. Failures, timeouts and
+/// malformed output are reported as errors of this collector.
+pub struct ScriptsCollector {
+    scripts: Vec<ScheduledScript>,
+    results_tx: mpsc::Sender<ScriptResult>,
+    results: mpsc::Receiver<ScriptResult>,
+    /* values of the scripts that exited, attached to the sample by `store` */
+    rows: Vec<SchemaScriptMetric>,
+}
+
+impl ScriptsCollector {
+    pub fn new(scripts: &[ScriptConfig], default_interval_secs: u64) -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            scripts: scripts
+                .iter()
+                .map(|config| ScheduledScript {
+                    interval: Duration::from_secs(
+                        config.interval_secs.unwrap_or(default_interval_secs),
+                    ),
+                    config: config.clone(),
+                    last_start: None,
+                    running: false,
+                })
+                .collect(),
+            results_tx,
+            results,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Runs the script at `index` on its own thread.
+    fn start(&mut self, index: usize) -> io::Result<()> {
+        let config = &self.scripts[index].config;
+        let command = config.command.clone();
+        let format = config.format;
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let results_tx = self.results_tx.clone();
+
+        thread::Builder::new()
+            .name(format!("script-{}", config.name))
+            .spawn(move || {
+                let result = run_script(&command, timeout)
+                    .map_err(|e| e.to_string())
+                    .and_then(|output| {
+                        parse_output(&output, format)
+                            .map_err(|e| format!("malformed output, {}", e))
+                    });
+                let _ = results_tx.send((index, result));
+            })?;
+
+        let script = &mut self.scripts[index];
+        script.running = true;
+        script.last_start = Some(std::time::Instant::now());
+        Ok(())
+    }
+}
+
+impl Collector for ScriptsCollector {
+    fn name(&self) -> &'static str {
+        "scripts"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        let mut errors = Vec::new();
+        while let Ok((index, result)) = self.results.try_recv() {
+            let script = &mut self.scripts[index];
+            script.running = false;
+            match result {
+                Ok(metrics) => {
+                    self.rows
+                        .extend(metrics.into_iter().map(|metric| SchemaScriptMetric {
+                            sysinfo_id: 0,
+                            script: script.config.name.clone(),
+                            name: metric.name,
+                            labels: encode_labels(&metric.labels),
+                            value: metric.value,
+                        }))
+                }
+                Err(e) => errors.push(format!("script {}: {}", script.config.name, e)),
+            }
+        }
+
+        for index in 0..self.scripts.len() {
+            let script = &self.scripts[index];
+            let due = script
+                .last_start
+                .is_none_or(|last| last.elapsed() + super::SCHEDULE_SLACK >= script.interval);
+            if script.running || !due {
+                continue;
+            }
+            if let Err(e) = self.start(index) {
+                errors.push(format!("script {}: {}", self.scripts[index].config.name, e));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(io::Error::other(errors.join("; ")).into());
+        }
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let mut rows = std::mem::take(&mut self.rows);
+        for row in &mut rows {
+            row.sysinfo_id = sample.id;
+        }
+
+        if !rows.is_empty() {
+            mutation::insert_multiple_scriptmetrics(conn, &rows)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::sys::SysInfo;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_key_value() {
+        let output = "\
+# queue statistics
+queue_depth=42
+backup_age_seconds = 3600.5
+
+temperature=NaN
+";
+        let metrics = parse_key_value(output).unwrap();
+        assert_eq!(
+            metrics,
+            vec![
+                ScriptMetric {
+                    name: "queue_depth".to_string(),
+                    labels: BTreeMap::new(),
+                    value: 42.0,
+                },
+                ScriptMetric {
+                    name: "backup_age_seconds".to_string(),
+                    labels: BTreeMap::new(),
+                    value: 3600.5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_key_value_malformed() {
+        assert_eq!(
+            parse_key_value("queue_depth=42\nqueue depth 3\n").unwrap_err(),
+            "line 2: expected `name=value`"
+        );
+        assert_eq!(
+            parse_key_value("queue_depth=many").unwrap_err(),
+            "line 1: invalid value `many`"
+        );
+        assert_eq!(
+            parse_key_value("2fast=1").unwrap_err(),
+            "line 1: invalid metric name `2fast`"
+        );
+    }
+
+    #[test]
+    fn test_parse_prometheus() {
+        let output = r#"
+# HELP queue_depth Messages waiting in the queue.
+# TYPE queue_depth gauge
+queue_depth{queue="emails",region="eu"} 42
+queue_depth{ queue = "webhooks" , } 3 1700000000000
+process_start_time_seconds 1.7e9
+escaped{path="C:\\tmp",quote="say \"hi\""} 1
+errors_total{kind="io"} +Inf
+"#;
+        let metrics = parse_prometheus(output).unwrap();
+        assert_eq!(metrics.len(), 4);
+        assert_eq!(metrics[0].name, "queue_depth");
+        assert_eq!(
+            metrics[0].labels,
+            labels(&[("queue", "emails"), ("region", "eu")])
+        );
+        assert_eq!(metrics[0].value, 42.0);
+        // The timestamp is ignored
+        assert_eq!(metrics[1].labels, labels(&[("queue", "webhooks")]));
+        assert_eq!(metrics[1].value, 3.0);
+        assert!(metrics[2].labels.is_empty());
+        assert_eq!(metrics[2].value, 1.7e9);
+        assert_eq!(
+            metrics[3].labels,
+            labels(&[("path", "C:\\tmp"), ("quote", "say \"hi\"")])
+        );
+    }
+
+    #[test]
+    fn test_labels_round_trip() {
+        let stored = labels(&[("queue", "emails"), ("path", "C:\\tmp \"x\"")]);
+        let encoded = encode_labels(&stored);
+        assert_eq!(encoded, r#"{"path":"C:\\tmp \"x\"","queue":"emails"}"#);
+        assert_eq!(decode_labels(&encoded), stored);
+        assert_eq!(encode_labels(&BTreeMap::new()), "{}");
+        assert!(decode_labels("not json").is_empty());
+    }
+
+    #[test]
+    fn test_parse_prometheus_malformed() {
+        assert_eq!(
+            parse_prometheus("up 1\nqueue_depth\n").unwrap_err(),
+            "line 2: missing value"
+        );
+        assert_eq!(
+            parse_prometheus("queue_depth{queue=emails} 1").unwrap_err(),
+            "line 1: unquoted value of label `queue`"
+        );
+        assert_eq!(
+            parse_prometheus("queue_depth{queue=\"emails} 1").unwrap_err(),
+            "line 1: unterminated label value"
+        );
+        assert_eq!(
+            parse_prometheus("queue_depth{queue=\"emails\"}").unwrap_err(),
+            "line 1: missing value"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_script() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            run_script("echo queue_depth=42", timeout).unwrap(),
+            "queue_depth=42\n"
+        );
+
+        let error = run_script("echo 'no such queue' >&2; exit 3", timeout).unwrap_err();
+        assert_eq!(error.to_string(), "exited with code 3: no such queue");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_script_timeout_kills_children() {
+        let started = Instant::now();
+        // The background sleep keeps stdout open until it is killed too
+        let error = run_script("sleep 30 & sleep 30", Duration::from_millis(200)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_script_timeout_waits_for_output() {
+        let started = Instant::now();
+        // The shell exits at once, but the background sleep holds stdout
+        let error = run_script(
+            "echo queue_depth=42; sleep 30 &",
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scripts_collector_stores_values_and_reports_failures() {
+        let (_dir, storage) = migrated_storage();
+        let script = |name: &str, command: &str, format| ScriptConfig {
+            name: name.to_string(),
+            command: command.to_string(),
+            format,
+            interval_secs: Some(3600),
+            timeout_secs: 5,
+        };
+        let scripts = vec![
+            script("queue", "echo queue_depth=42", ScriptFormat::KeyValue),
+            script(
+                "app",
+                "printf 'jobs{state=\"done\"} 7\\njobs{state=\"failed\"} 1\\n'",
+                ScriptFormat::Prometheus,
+            ),
+            script(
+                "broken",
+                "echo 'queue down' >&2; exit 2",
+                ScriptFormat::KeyValue,
+            ),
+            script("garbage", "echo not a metric", ScriptFormat::KeyValue),
+        ];
+        let mut collector = ScriptsCollector::new(&scripts, 5);
+        let mut conn = storage.write().unwrap();
+
+        // Scripts are started by the first run and stored by a later one
+        let mut errors = Vec::new();
+        for _ in 0..100 {
+            let sample = SysInfo::default().store(&mut conn).unwrap();
+            if let Err(e) = collect_and_store(&mut collector, &mut conn, &sample) {
+                errors.push(e.to_string());
+            }
+            if collector.scripts.iter().all(|s| !s.running) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let errors = errors.join("; ");
+        assert!(errors.contains("script broken: exited with code 2: queue down"));
+        assert!(errors.contains("script garbage: malformed output, line 1: expected `name=value`"));
+
+        let latest = query::get_latest_scriptmetrics(&mut conn).unwrap();
+        let values: Vec<(&str, &str, &str, f64)> = latest
+            .iter()
+            .map(|(_, m)| {
+                (
+                    m.script.as_str(),
+                    m.name.as_str(),
+                    m.labels.as_str(),
+                    m.value,
+                )
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("app", "jobs", "{\"state\":\"done\"}", 7.0),
+                ("app", "jobs", "{\"state\":\"failed\"}", 1.0),
+                ("queue", "queue_depth", "{}", 42.0),
+            ]
+        );
+
+        // Not due again before their interval
+        let sample = SysInfo::default().store(&mut conn).unwrap();
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert!(collector.scripts.iter().all(|s| !s.running));
+
+        let history = query::get_scriptmetric_history(&mut conn, "app", "jobs", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].1.len(), 2);
+    }
+}
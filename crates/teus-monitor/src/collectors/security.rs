@@ -0,0 +1,775 @@
+//! Login sessions and SSH authentication attempts.
+//!
+//! Logins come from the binary `utmp` and `wtmp` records written by login,
+//! sshd and terminal emulators: `utmp` holds the sessions currently open,
+//! `wtmp` every session ever opened. SSH attempts, including failed ones
+//! that never reach `wtmp`, come from the sshd messages of the syslog
+//! authentication log or of the journal.
+
+use super::tail::{FileCursor, MAX_READ_BYTES, complete_lines};
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::schema::SchemaSecurityEvent;
+use crate::{mutation, query};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+    process::Command,
+};
+use teus_database::storage::TeuSQLiteConnection;
+use teus_types::config::SecurityConfig;
+
+/// Size of a `struct utmp` record on Linux (glibc and musl).
+pub const UTMP_RECORD_SIZE: usize = 384;
+
+/* ut_type of a login session */
+const USER_PROCESS: i16 = 7;
+
+/// A login session from a `utmp` or `wtmp` record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoginRecord {
+    pub username: String,
+    /// Terminal of the session (e.g. "pts/0", "tty1").
+    pub tty: String,
+    /// Remote host as recorded by the login program, empty for local logins.
+    pub host: String,
+    /// Address of the remote host, when known.
+    pub source_ip: Option<IpAddr>,
+    pub pid: i32,
+    pub login_time: DateTime<Utc>,
+}
+
+/// An sshd authentication attempt.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthEvent {
+    pub timestamp: DateTime<Utc>,
+    /// "accepted", "failed", or "invalid_user" for unknown usernames.
+    pub kind: &'static str,
+    pub username: String,
+    pub source_ip: IpAddr,
+    /// Authentication method (e.g. "password", "publickey"), not logged
+    /// for invalid users.
+    pub method: Option<String>,
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+fn i32_at(record: &[u8], offset: usize) -> i32 {
+    i32::from_ne_bytes(record[offset..offset + 4].try_into().unwrap())
+}
+
+/* ut_addr_v6 holds an IPv4 address in its first word, the others zero */
+fn record_address(record: &[u8]) -> Option<IpAddr> {
+    let bytes: [u8; 16] = record[348..364].try_into().unwrap();
+    if bytes == [0; 16] {
+        None
+    } else if bytes[4..] == [0; 12] {
+        Some(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        )))
+    } else {
+        Some(IpAddr::V6(Ipv6Addr::from(bytes)))
+    }
+}
+
+/// Parses the login sessions out of `utmp` or `wtmp` records.
+///
+/// Only `USER_PROCESS` records are returned; boot, run level and logout
+/// records are skipped, as well as a trailing partial record.
+pub fn parse_utmp_records(bytes: &[u8]) -> Vec<LoginRecord> {
+    bytes
+        .chunks_exact(UTMP_RECORD_SIZE)
+        .filter(|record| i16::from_ne_bytes([record[0], record[1]]) == USER_PROCESS)
+        .filter_map(|record| {
+            let host = c_string(&record[76..332]);
+            Some(LoginRecord {
+                username: c_string(&record[44..76]),
+                tty: c_string(&record[8..40]),
+                source_ip: record_address(record).or_else(|| host.parse().ok()),
+                host,
+                pid: i32_at(record, 4),
+                login_time: DateTime::from_timestamp(
+                    i32_at(record, 340) as i64,
+                    i32_at(record, 344).max(0) as u32 * 1000,
+                )?,
+            })
+        })
+        .collect()
+}
+
+/// Length of the complete records at the start of `bytes`.
+pub fn complete_utmp_records(bytes: &[u8]) -> usize {
+    bytes.len() - bytes.len() % UTMP_RECORD_SIZE
+}
+
+/// Parses the timestamp at the start of a log line, returning it with the
+/// rest of the line.
+///
+/// Both the traditional syslog format (`Jan 15 10:30:00`, local time
+/// without a year) and ISO 8601 (rsyslog high precision timestamps,
+/// `journalctl -o short-iso-precise`) are recognized. The year of syslog
+/// timestamps is the one of `now`, or the previous one for dates in the
+/// future (a log spanning New Year).
+fn parse_log_timestamp<'a, Tz: TimeZone>(
+    line: &'a str,
+    now: &DateTime<Tz>,
+) -> Option<(DateTime<Utc>, &'a str)> {
+    if line.starts_with(|c: char| c.is_ascii_digit()) {
+        let (timestamp, rest) = line.split_once(' ')?;
+        let timestamp = DateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f%z").ok()?;
+        return Some((timestamp.with_timezone(&Utc), rest));
+    }
+
+    let (timestamp, rest) = (line.get(..15)?, line.get(16..)?);
+    let parse = |year: i32| {
+        NaiveDateTime::parse_from_str(&format!("{} {}", year, timestamp), "%Y %b %e %H:%M:%S")
+            .ok()
+            .and_then(|naive| now.timezone().from_local_datetime(&naive).earliest())
+    };
+    let mut parsed = parse(now.year())?;
+    if parsed.clone() - TimeDelta::days(1) > *now {
+        parsed = parse(now.year() - 1)?;
+    }
+    Some((parsed.with_timezone(&Utc), rest))
+}
+
+/// Parses the username and address of `<user> from <ip> port ...`.
+fn user_and_address(rest: &str) -> Option<(String, IpAddr)> {
+    let (username, address) = rest.rsplit_once(" from ")?;
+    let address = address.split_whitespace().next()?.parse().ok()?;
+    Some((username.to_string(), address))
+}
+
+/// Parses an sshd message into the attempt it reports, if any.
+fn parse_sshd_message(message: &str, timestamp: DateTime<Utc>) -> Option<AuthEvent> {
+    let event = |kind, (username, source_ip), method: Option<&str>| AuthEvent {
+        timestamp,
+        kind,
+        username,
+        source_ip,
+        method: method.map(str::to_string),
+    };
+
+    if let Some(rest) = message.strip_prefix("Accepted ") {
+        let (method, rest) = rest.split_once(" for ")?;
+        Some(event("accepted", user_and_address(rest)?, Some(method)))
+    } else if let Some(rest) = message.strip_prefix("Failed ") {
+        let (method, rest) = rest.split_once(" for ")?;
+        let rest = rest.strip_prefix("invalid user ").unwrap_or(rest);
+        Some(event("failed", user_and_address(rest)?, Some(method)))
+    } else if let Some(rest) = message.strip_prefix("Invalid user ") {
+        Some(event("invalid_user", user_and_address(rest)?, None))
+    } else {
+        None
+    }
+}
+
+/// Parses a line of the authentication log into the sshd attempts it
+/// reports.
+///
+/// Lines of other programs, and sshd messages that aren't authentication
+/// results, yield nothing. A `message repeated N times: [ ... ]` line,
+/// written by rsyslog for identical consecutive messages, yields the
+/// attempt N times. Syslog timestamps are read in the timezone of `now`.
+pub fn parse_auth_log_line<Tz: TimeZone>(line: &str, now: &DateTime<Tz>) -> Vec<AuthEvent> {
+    let Some((timestamp, rest)) = parse_log_timestamp(line, now) else {
+        return Vec::new();
+    };
+    // `<host> <program>[<pid>]: <message>`
+    let Some((_host, rest)) = rest.split_once(' ') else {
+        return Vec::new();
+    };
+    let Some((program, message)) = rest.split_once(": ") else {
+        return Vec::new();
+    };
+    // OpenSSH 9.8 moved authentication to `sshd-session`
+    if !program.starts_with("sshd") {
+        return Vec::new();
+    }
+
+    let (repeat, message) = match message
+        .strip_prefix("message repeated ")
+        .and_then(|rest| rest.split_once(" times: [ "))
+    {
+        Some((count, repeated)) => (
+            count.parse().unwrap_or(1),
+            repeated.strip_suffix(']').unwrap_or(repeated).trim_end(),
+        ),
+        None => (1, message),
+    };
+
+    parse_sshd_message(message, timestamp)
+        .map(|event| vec![event; repeat])
+        .unwrap_or_default()
+}
+
+/// Parses the output of `journalctl -o short-iso-precise --show-cursor`
+/// into the sshd attempts and the cursor of the last entry.
+pub fn parse_journal_output<Tz: TimeZone>(
+    output: &str,
+    now: &DateTime<Tz>,
+) -> (Vec<AuthEvent>, Option<String>) {
+    let mut events = Vec::new();
+    let mut cursor = None;
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix("-- cursor: ") {
+            cursor = Some(value.to_string());
+        } else if !line.starts_with("-- ") {
+            events.extend(parse_auth_log_line(line, now));
+        }
+    }
+    (events, cursor)
+}
+
+/// Lines of the journal read on the first run, the latest ones.
+pub const FIRST_JOURNAL_LINES: u32 = 10_000;
+
+/// Read positions in the sources of the security events.
+#[derive(Clone, Debug, Default)]
+struct Cursors {
+    wtmp: FileCursor,
+    auth_log: FileCursor,
+    journal: Option<String>,
+}
+
+/// Events stored before the monitor started, for one source.
+#[derive(Clone, Debug)]
+struct Resume {
+    /// Time of the latest stored event.
+    after: DateTime<Utc>,
+    /// The stored events at that time, not read again yet.
+    stored: Vec<SchemaSecurityEvent>,
+}
+
+impl Resume {
+    /// Whether `row`, read at `timestamp`, wasn't stored yet: it's newer
+    /// than the latest stored event, or as old but not one of them.
+    fn is_new(&mut self, timestamp: &DateTime<Utc>, row: &SchemaSecurityEvent) -> bool {
+        match timestamp.cmp(&self.after) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match self.stored.iter().position(|stored| stored == row) {
+                Some(index) => {
+                    self.stored.swap_remove(index);
+                    false
+                }
+                None => true,
+            },
+        }
+    }
+}
+
+/// Logins from `wtmp`, and SSH attempts from the authentication log or the
+/// journal.
+///
+/// Only what was appended since the previous run is read, at most
+/// `MAX_READ_BYTES` of each file per run, and the read positions only
+/// move once the events are stored: events that failed to be stored are
+/// read again. On the first run the files are read from the beginning and
+/// the latest `FIRST_JOURNAL_LINES` of the journal; events already stored
+/// by a previous monitor are skipped until each source is read up to its
+/// end.
+pub struct SecurityCollector {
+    config: SecurityConfig,
+    /* positions of the stored events */
+    cursors: Cursors,
+    /* positions after the events read and not stored yet */
+    read: Option<Cursors>,
+    /* set when `journalctl` isn't installed or can't read the journal */
+    journal_unavailable: bool,
+    /* events stored by a previous monitor per source, loaded on the first run */
+    resume: Option<HashMap<&'static str, Resume>>,
+    /* events read and not stored yet, with their source and time */
+    events: Vec<(&'static str, DateTime<Utc>, SchemaSecurityEvent)>,
+}
+
+impl SecurityCollector {
+    pub fn new(config: SecurityConfig) -> Self {
+        Self {
+            config,
+            cursors: Cursors::default(),
+            read: None,
+            journal_unavailable: false,
+            resume: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Reads the sshd messages logged after `cursor`, or the latest ones
+    /// without it, and moves `cursor` past them.
+    fn read_journal(&mut self, cursor: &mut Option<String>) -> io::Result<Vec<AuthEvent>> {
+        let mut command = Command::new("journalctl");
+        command.args([
+            "--no-pager",
+            "--output=short-iso-precise",
+            "--show-cursor",
+            "_COMM=sshd",
+            "_COMM=sshd-session",
+        ]);
+        match cursor {
+            Some(cursor) => command.arg(format!("--after-cursor={}", cursor)),
+            None => command.arg(format!("--lines={}", FIRST_JOURNAL_LINES)),
+        };
+
+        let output = command.output()?;
+        if !output.status.success() {
+            // No journal (containers) or no permission, reported once
+            self.journal_unavailable = true;
+            return Err(io::Error::other(format!(
+                "journalctl failed, not reading the journal anymore: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let (events, last) =
+            parse_journal_output(&String::from_utf8_lossy(&output.stdout), &Local::now());
+        if last.is_some() {
+            *cursor = last;
+        }
+        Ok(events)
+    }
+
+    /// Reads the SSH attempts logged after `read`, with the name of the
+    /// source they were read from.
+    fn read_auth_events(
+        &mut self,
+        read: &mut Cursors,
+    ) -> io::Result<(&'static str, Vec<AuthEvent>)> {
+        let auth_log_path = PathBuf::from(&self.config.auth_log_path);
+        if auth_log_path.exists() {
+            let lines = read
+                .auth_log
+                .read_new(&auth_log_path, complete_lines, MAX_READ_BYTES)?;
+            let now = Local::now();
+            let events = String::from_utf8_lossy(&lines)
+                .lines()
+                .flat_map(|line| parse_auth_log_line(line, &now))
+                .collect();
+            return Ok(("auth_log", events));
+        }
+        if !self.config.journal || self.journal_unavailable {
+            return Ok(("auth_log", Vec::new()));
+        }
+
+        match self.read_journal(&mut read.journal) {
+            Ok(events) => Ok(("journal", events)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.journal_unavailable = true;
+                Ok(("journal", Vec::new()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads the events after `read` into `events`, moving `read` past them.
+    fn read_events(&mut self, read: &mut Cursors) -> Result<(), CollectorError> {
+        let wtmp = read.wtmp.read_new(
+            Path::new(&self.config.wtmp_path),
+            complete_utmp_records,
+            MAX_READ_BYTES,
+        )?;
+        for login in parse_utmp_records(&wtmp) {
+            self.events.push((
+                "wtmp",
+                login.login_time,
+                SchemaSecurityEvent {
+                    timestamp: login.login_time.to_rfc3339(),
+                    source: "wtmp".to_string(),
+                    kind: "login".to_string(),
+                    username: login.username,
+                    source_ip: login.source_ip.map(|ip| ip.to_string()),
+                    auth_method: None,
+                    tty: Some(login.tty),
+                },
+            ));
+        }
+
+        let (source, events) = self.read_auth_events(read)?;
+        for event in events {
+            self.events.push((
+                source,
+                event.timestamp,
+                SchemaSecurityEvent {
+                    timestamp: event.timestamp.to_rfc3339(),
+                    source: source.to_string(),
+                    kind: event.kind.to_string(),
+                    username: event.username,
+                    source_ip: Some(event.source_ip.to_string()),
+                    auth_method: event.method,
+                    tty: None,
+                },
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Loads the latest events stored per source.
+fn load_resume(
+    conn: &mut TeuSQLiteConnection,
+) -> Result<HashMap<&'static str, Resume>, CollectorError> {
+    let mut resume = HashMap::new();
+    for source in ["wtmp", "auth_log", "journal"] {
+        let stored: Vec<SchemaSecurityEvent> = query::get_latest_securityevents(conn, source)?
+            .into_iter()
+            .map(|event| SchemaSecurityEvent {
+                timestamp: event.timestamp,
+                source: event.source,
+                kind: event.kind,
+                username: event.username,
+                source_ip: event.source_ip,
+                auth_method: event.auth_method,
+                tty: event.tty,
+            })
+            .collect();
+        if let Some(after) = stored
+            .first()
+            .and_then(|event| DateTime::parse_from_rfc3339(&event.timestamp).ok())
+        {
+            let after = after.with_timezone(&Utc);
+            resume.insert(source, Resume { after, stored });
+        }
+    }
+    Ok(resume)
+}
+
+impl Collector for SecurityCollector {
+    fn name(&self) -> &'static str {
+        "security"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        let mut read = self.cursors.clone();
+        let result = self.read_events(&mut read);
+        self.read = Some(read);
+        result
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        _sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        // On failure both are dropped, the events are read again next run
+        let events = std::mem::take(&mut self.events);
+        let Some(read) = self.read.take() else {
+            return Ok(());
+        };
+
+        if self.resume.is_none() {
+            self.resume = Some(load_resume(conn)?);
+        }
+        let mut resume = self.resume.clone().unwrap_or_default();
+        let rows: Vec<SchemaSecurityEvent> = events
+            .into_iter()
+            .filter(|(source, timestamp, row)| {
+                resume
+                    .get_mut(source)
+                    .is_none_or(|resume| resume.is_new(timestamp, row))
+            })
+            .map(|(_, _, row)| row)
+            .collect();
+
+        if !rows.is_empty() {
+            mutation::insert_multiple_securityevents(conn, &rows)?;
+        }
+
+        // Once a source is read up to its end, only new events follow
+        if !read.wtmp.is_behind() {
+            resume.remove("wtmp");
+        }
+        if !read.auth_log.is_behind() {
+            resume.remove("auth_log");
+        }
+        resume.remove("journal");
+        self.resume = Some(resume);
+        self.cursors = read;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+
+    const AUTH_LOG: &str = "\
+Jan 15 10:30:00 web1 sshd[1234]: Accepted publickey for alice from 192.168.1.10 port 51234 ssh2: ED25519 SHA256:abc
+Jan 15 10:31:12 web1 sshd[1240]: Invalid user admin from 203.0.113.7 port 40022
+Jan 15 10:31:14 web1 sshd[1240]: Failed password for invalid user admin from 203.0.113.7 port 40022 ssh2
+Jan 15 10:31:20 web1 sshd[1241]: Failed password for root from 203.0.113.7 port 40030 ssh2
+Jan 15 10:31:50 web1 sshd[1241]: message repeated 2 times: [ Failed password for root from 203.0.113.7 port 40030 ssh2]
+Jan 15 10:32:00 web1 sshd[1242]: Connection closed by authenticating user root 203.0.113.7 port 40030 [preauth]
+Jan 15 10:32:01 web1 CRON[1300]: pam_unix(cron:session): session opened for user root(uid=0) by (uid=0)
+Jan  5 08:00:00 web1 sshd-session[1400]: Accepted password for bob from 2001:db8::1 port 50000 ssh2
+Jan 15 10:35:00 web1 sshd[1500]: Invalid user  from 198.51.100.1 port 1234
+";
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()
+    }
+
+    fn parse_log(log: &str) -> Vec<AuthEvent> {
+        log.lines()
+            .flat_map(|line| parse_auth_log_line(line, &now()))
+            .collect()
+    }
+
+    fn utmp_record(
+        ut_type: i16,
+        user: &str,
+        tty: &str,
+        host: &str,
+        address: [u8; 4],
+        time: i32,
+    ) -> Vec<u8> {
+        let mut record = vec![0u8; UTMP_RECORD_SIZE];
+        record[0..2].copy_from_slice(&ut_type.to_ne_bytes());
+        record[4..8].copy_from_slice(&4242i32.to_ne_bytes());
+        record[8..8 + tty.len()].copy_from_slice(tty.as_bytes());
+        record[44..44 + user.len()].copy_from_slice(user.as_bytes());
+        record[76..76 + host.len()].copy_from_slice(host.as_bytes());
+        record[340..344].copy_from_slice(&time.to_ne_bytes());
+        record[348..352].copy_from_slice(&address);
+        record
+    }
+
+    #[test]
+    fn test_parse_auth_log() {
+        let events = parse_log(AUTH_LOG);
+        let summary: Vec<(&str, &str, String)> = events
+            .iter()
+            .map(|e| (e.kind, e.username.as_str(), e.source_ip.to_string()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("accepted", "alice", "192.168.1.10".to_string()),
+                ("invalid_user", "admin", "203.0.113.7".to_string()),
+                ("failed", "admin", "203.0.113.7".to_string()),
+                ("failed", "root", "203.0.113.7".to_string()),
+                ("failed", "root", "203.0.113.7".to_string()),
+                ("failed", "root", "203.0.113.7".to_string()),
+                ("accepted", "bob", "2001:db8::1".to_string()),
+                ("invalid_user", "", "198.51.100.1".to_string()),
+            ]
+        );
+
+        assert_eq!(events[0].method.as_deref(), Some("publickey"));
+        assert_eq!(
+            events[0].timestamp.to_rfc3339(),
+            "2024-01-15T10:30:00+00:00"
+        );
+        assert_eq!(events[1].method, None);
+        assert_eq!(events[2].method.as_deref(), Some("password"));
+        assert_eq!(
+            events[6].timestamp.to_rfc3339(),
+            "2024-01-05T08:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_auth_log_timestamps() {
+        // rsyslog high precision format
+        let events = parse_auth_log_line(
+            "2024-01-15T10:30:00.123456+01:00 web1 sshd[1]: Failed password for root from 203.0.113.7 port 22 ssh2",
+            &now(),
+        );
+        assert_eq!(
+            events[0].timestamp.to_rfc3339(),
+            "2024-01-15T09:30:00.123456+00:00"
+        );
+
+        // A December line read in January belongs to the previous year
+        let events = parse_auth_log_line(
+            "Dec 31 23:59:00 web1 sshd[1]: Failed password for root from 203.0.113.7 port 22 ssh2",
+            &now(),
+        );
+        assert_eq!(
+            events[0].timestamp.to_rfc3339(),
+            "2023-12-31T23:59:00+00:00"
+        );
+
+        assert!(parse_auth_log_line("garbage", &now()).is_empty());
+        assert!(parse_auth_log_line("", &now()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_journal_output() {
+        let output = "\
+2024-01-15T10:31:20.000100+0000 web1 sshd[1241]: Failed password for root from 203.0.113.7 port 40030 ssh2
+2024-01-15T10:31:22.500000+0000 web1 sshd[1241]: Received disconnect from 203.0.113.7 port 40030:11: Bye [preauth]
+-- cursor: s=abc;i=1f2;b=def
+";
+        let (events, cursor) = parse_journal_output(output, &now());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, "failed");
+        assert_eq!(
+            events[0].timestamp.to_rfc3339(),
+            "2024-01-15T10:31:20.000100+00:00"
+        );
+        assert_eq!(cursor.as_deref(), Some("s=abc;i=1f2;b=def"));
+
+        let (events, cursor) = parse_journal_output("-- No entries --\n", &now());
+        assert!(events.is_empty());
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn test_parse_utmp_records() {
+        let mut bytes = Vec::new();
+        // Boot record, skipped
+        bytes.extend(utmp_record(2, "reboot", "~", "6.1.0", [0; 4], 1705300000));
+        bytes.extend(utmp_record(
+            USER_PROCESS,
+            "alice",
+            "pts/0",
+            "192.168.1.10",
+            [192, 168, 1, 10],
+            1705314600,
+        ));
+        // Local console login
+        bytes.extend(utmp_record(
+            USER_PROCESS,
+            "bob",
+            "tty1",
+            "",
+            [0; 4],
+            1705314700,
+        ));
+        // Trailing partial record, still being written
+        bytes.extend(vec![0u8; 100]);
+
+        let records = parse_utmp_records(&bytes);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].username, "alice");
+        assert_eq!(records[0].tty, "pts/0");
+        assert_eq!(records[0].host, "192.168.1.10");
+        assert_eq!(records[0].source_ip.unwrap().to_string(), "192.168.1.10");
+        assert_eq!(records[0].pid, 4242);
+        assert_eq!(
+            records[0].login_time.to_rfc3339(),
+            "2024-01-15T10:30:00+00:00"
+        );
+        assert_eq!(records[1].source_ip, None);
+
+        assert_eq!(complete_utmp_records(&bytes), 3 * UTMP_RECORD_SIZE);
+    }
+
+    #[test]
+    fn test_security_collector_reads_new_events_once() {
+        use std::io::Write;
+
+        let (dir, storage) = migrated_storage();
+        let auth_log = dir.path().join("auth.log");
+        let security = SecurityConfig {
+            utmp_path: dir.path().join("utmp").to_string_lossy().to_string(),
+            wtmp_path: dir.path().join("wtmp").to_string_lossy().to_string(),
+            auth_log_path: auth_log.to_string_lossy().to_string(),
+            journal: false,
+        };
+        let append = |lines: &str| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&auth_log)
+                .unwrap()
+                .write_all(lines.as_bytes())
+                .unwrap();
+        };
+        let sample = Sample {
+            id: 0,
+            timestamp: String::new(),
+        };
+        let mut conn = storage.write().unwrap();
+        let stored = |conn: &mut TeuSQLiteConnection| {
+            query::get_securityevents(conn, &query::SecurityEventFilter::default(), 100)
+                .unwrap()
+                .len()
+        };
+
+        append(
+            "2024-01-15T10:31:20+00:00 web1 sshd[1]: Failed password for root from 203.0.113.7 port 22 ssh2\n\
+             2024-01-15T10:31:25+00:00 web1 sshd[1]: Accepted password for alice from 192.168.1.10 port 22 ssh2\n",
+        );
+        let mut collector = SecurityCollector::new(security.clone());
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert_eq!(stored(&mut conn), 2);
+
+        append(
+            "2024-01-15T10:32:00+00:00 web1 sshd[2]: Invalid user admin from 203.0.113.7 port 23\n",
+        );
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert_eq!(stored(&mut conn), 3);
+
+        // A restarted monitor reads the log again but only stores new events,
+        // including one logged in the same second as the latest stored one
+        append(
+            "2024-01-15T10:32:00+00:00 web1 sshd[3]: Failed password for root from 203.0.113.8 port 22 ssh2\n",
+        );
+        let mut collector = SecurityCollector::new(security);
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        assert_eq!(stored(&mut conn), 4);
+
+        let failures = query::get_securityevents(
+            &mut conn,
+            &query::SecurityEventFilter {
+                kind: Some("failed"),
+                ..Default::default()
+            },
+            100,
+        )
+        .unwrap();
+        let ips: Vec<_> = failures.iter().map(|e| e.source_ip.as_deref()).collect();
+        assert_eq!(ips, vec![Some("203.0.113.8"), Some("203.0.113.7")]);
+        assert_eq!(failures[1].auth_method.as_deref(), Some("password"));
+        assert_eq!(failures[1].timestamp, "2024-01-15T10:31:20+00:00");
+    }
+
+    #[test]
+    fn test_security_collector_keeps_events_until_stored() {
+        use diesel::Connection;
+
+        let (dir, storage) = migrated_storage();
+        let wtmp = dir.path().join("wtmp");
+        // More than one INSERT of rows
+        let logins = 2500;
+        let records: Vec<u8> = (0..logins)
+            .flat_map(|i| utmp_record(USER_PROCESS, "alice", "pts/0", "", [0; 4], 1705314600 + i))
+            .collect();
+        std::fs::write(&wtmp, records).unwrap();
+        let mut collector = SecurityCollector::new(SecurityConfig {
+            utmp_path: dir.path().join("utmp").to_string_lossy().to_string(),
+            wtmp_path: wtmp.to_string_lossy().to_string(),
+            auth_log_path: dir.path().join("auth.log").to_string_lossy().to_string(),
+            journal: false,
+        });
+        let sample = Sample {
+            id: 0,
+            timestamp: String::new(),
+        };
+
+        // Without the tables the events can't be stored
+        let mut unmigrated = TeuSQLiteConnection::establish(":memory:").unwrap();
+        assert!(collect_and_store(&mut collector, &mut unmigrated, &sample).is_err());
+
+        // so they are read again
+        let mut conn = storage.write().unwrap();
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        let stored =
+            query::get_securityevents(&mut conn, &query::SecurityEventFilter::default(), 10_000)
+                .unwrap();
+        assert_eq!(stored.len(), logins as usize);
+
+        collect_and_store(&mut collector, &mut conn, &sample).unwrap();
+        let stored =
+            query::get_securityevents(&mut conn, &query::SecurityEventFilter::default(), 10_000)
+                .unwrap();
+        assert_eq!(stored.len(), logins as usize);
+    }
+}
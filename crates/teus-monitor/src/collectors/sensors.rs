@@ -0,0 +1,265 @@
+//! Temperature and fan sensors read from `/sys/class/hwmon`.
+//!
+//! Every hwmon device exposes a `name` file and one set of files per
+//! channel: `tempN_input` (millidegrees Celsius) with optional `tempN_max`
+//! and `tempN_crit` thresholds, and `fanN_input` (RPM). Channels without a
+//! `tempN_label`/`fanN_label` file are named after the channel itself.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaSensorInfo;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// A single sensor channel at the time of collection.
+///
+/// Temperature channels have `temperature` set, fan channels `fan_rpm`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SensorReading {
+    /// Name of the hwmon chip (e.g. "coretemp", "nvme", "acpitz").
+    pub chip: String,
+    /// Channel label (e.g. "Package id 0"), or the channel name ("temp1").
+    pub label: String,
+    /// Current temperature, in degrees Celsius.
+    pub temperature: Option<f64>,
+    /// Temperature above which the hardware considers itself hot.
+    pub high: Option<f64>,
+    /// Temperature at which the hardware shuts down or throttles hard.
+    pub critical: Option<f64>,
+    /// Current fan speed, in rotations per minute.
+    pub fan_rpm: Option<f64>,
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    read_trimmed(path)?.parse::<f64>().ok()
+}
+
+/// Reads the temperature and fan channels of one hwmon device directory.
+fn read_hwmon_device(dir: &Path, chip: &str) -> io::Result<Vec<SensorReading>> {
+    let mut channels: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let channel = file_name.strip_suffix("_input")?;
+            (channel.starts_with("temp") || channel.starts_with("fan")).then(|| channel.to_string())
+        })
+        .collect();
+    channels.sort();
+
+    Ok(channels
+        .into_iter()
+        .filter_map(|channel| {
+            let value = read_number(&dir.join(format!("{}_input", channel)))?;
+            let label = read_trimmed(&dir.join(format!("{}_label", channel)))
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| channel.clone());
+
+            let reading = if channel.starts_with("temp") {
+                let millidegrees = |suffix: &str| {
+                    read_number(&dir.join(format!("{}_{}", channel, suffix))).map(|v| v / 1000.0)
+                };
+                SensorReading {
+                    chip: chip.to_string(),
+                    label,
+                    temperature: Some(value / 1000.0),
+                    high: millidegrees("max"),
+                    critical: millidegrees("crit"),
+                    fan_rpm: None,
+                }
+            } else {
+                SensorReading {
+                    chip: chip.to_string(),
+                    label,
+                    fan_rpm: Some(value),
+                    ..Default::default()
+                }
+            };
+            Some(reading)
+        })
+        .collect())
+}
+
+/// Reads every temperature and fan sensor under `class/hwmon` of the
+/// given sysfs root.
+///
+/// Returns an empty list on machines without hwmon support, such as most
+/// virtual machines. Devices that can't be read are skipped. Older drivers
+/// keep their files in the `device` subdirectory, which is used when the
+/// hwmon directory itself has no `name` file.
+pub fn read_sensors(sysfs_root: &Path) -> io::Result<Vec<SensorReading>> {
+    let entries = match fs::read_dir(sysfs_root.join("class").join("hwmon")) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut devices: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    devices.sort();
+
+    let mut readings = Vec::new();
+    for device in devices {
+        let dir = if device.join("name").exists() {
+            device
+        } else {
+            device.join("device")
+        };
+        let Some(chip) = read_trimmed(&dir.join("name")) else {
+            continue;
+        };
+        match read_hwmon_device(&dir, &chip) {
+            Ok(mut device_readings) => readings.append(&mut device_readings),
+            Err(e) => eprintln!("Failed to read hwmon device {}: {}", dir.display(), e),
+        }
+    }
+
+    Ok(readings)
+}
+
+/// Temperature and fan sensors, from `/sys/class/hwmon`.
+pub struct SensorsCollector {
+    sysfs_root: PathBuf,
+    sensors: Option<Vec<SensorReading>>,
+}
+
+impl SensorsCollector {
+    pub fn new(sysfs_root: PathBuf) -> Self {
+        Self {
+            sysfs_root,
+            sensors: None,
+        }
+    }
+}
+
+impl Collector for SensorsCollector {
+    fn name(&self) -> &'static str {
+        "sensors"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.sensors = Some(read_sensors(&self.sysfs_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(sensors) = self.sensors.take() else {
+            return Ok(());
+        };
+        let sensor_infos_to_insert: Vec<SchemaSensorInfo> = sensors
+            .into_iter()
+            .map(|sensor| SchemaSensorInfo {
+                sysinfo_id: sample.id,
+                chip: sensor.chip,
+                label: sensor.label,
+                temperature: sensor.temperature.map(|v| v as f32),
+                high: sensor.high.map(|v| v as f32),
+                critical: sensor.critical.map(|v| v as f32),
+                fan_rpm: sensor.fan_rpm.map(|v| v as f32),
+            })
+            .collect();
+
+        if !sensor_infos_to_insert.is_empty() {
+            mutation::insert_multiple_sensorinfo(conn, &sensor_infos_to_insert)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_files(dir: &Path, files: &[(&str, &str)]) {
+        fs::create_dir_all(dir).unwrap();
+        for (name, content) in files {
+            fs::write(dir.join(name), format!("{}\n", content)).unwrap();
+        }
+    }
+
+    fn fixture_tree() -> TempDir {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        let hwmon = root.path().join("class").join("hwmon");
+        write_files(
+            &hwmon.join("hwmon0"),
+            &[
+                ("name", "coretemp"),
+                ("temp1_input", "54000"),
+                ("temp1_label", "Package id 0"),
+                ("temp1_max", "80000"),
+                ("temp1_crit", "100000"),
+                ("temp2_input", "51500"),
+                ("temp2_label", "Core 0"),
+            ],
+        );
+        write_files(
+            &hwmon.join("hwmon1"),
+            &[("name", "acpitz"), ("temp1_input", "27800")],
+        );
+        // Old-style driver with its files under device/
+        write_files(
+            &hwmon.join("hwmon2").join("device"),
+            &[
+                ("name", "it8728"),
+                ("fan1_input", "1250"),
+                ("fan1_label", "CPU Fan"),
+            ],
+        );
+        root
+    }
+
+    #[test]
+    fn test_read_sensors_from_fixture_tree() {
+        let root = fixture_tree();
+        let readings = read_sensors(root.path()).unwrap();
+        assert_eq!(readings.len(), 4);
+
+        let package = &readings[0];
+        assert_eq!(package.chip, "coretemp");
+        assert_eq!(package.label, "Package id 0");
+        assert_eq!(package.temperature, Some(54.0));
+        assert_eq!(package.high, Some(80.0));
+        assert_eq!(package.critical, Some(100.0));
+        assert_eq!(package.fan_rpm, None);
+
+        assert_eq!(readings[1].label, "Core 0");
+        assert_eq!(readings[1].temperature, Some(51.5));
+        assert_eq!(readings[1].high, None);
+
+        // Unlabelled channels are named after the channel
+        assert_eq!(readings[2].chip, "acpitz");
+        assert_eq!(readings[2].label, "temp1");
+
+        let fan = &readings[3];
+        assert_eq!(fan.chip, "it8728");
+        assert_eq!(fan.label, "CPU Fan");
+        assert_eq!(fan.fan_rpm, Some(1250.0));
+        assert_eq!(fan.temperature, None);
+    }
+
+    #[test]
+    fn test_read_sensors_without_hwmon() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        assert!(read_sensors(root.path()).unwrap().is_empty());
+
+        // A device with an unreadable value is skipped, not fatal
+        let hwmon = root.path().join("class").join("hwmon");
+        write_files(
+            &hwmon.join("hwmon0"),
+            &[("name", "nvme"), ("temp1_input", "garbage")],
+        );
+        assert!(read_sensors(root.path()).unwrap().is_empty());
+    }
+}
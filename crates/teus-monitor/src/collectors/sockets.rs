@@ -0,0 +1,449 @@
+//! TCP and UDP sockets, read from `/proc/net/{tcp,tcp6,udp,udp6}`.
+//!
+//! The kernel tables list every socket with its addresses, state and
+//! inode, but not the process owning it. Owners are found by walking the
+//! `/proc/<pid>/fd` links, which point to `socket:[<inode>]` for sockets.
+//! Without root only the caller's own processes can be inspected, so
+//! sockets of other users are listed without an owner.
+//!
+//! Addresses are printed as the raw in-memory words of the kernel, so they
+//! are decoded in native byte order.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaSocketInfo;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// Names of the TCP states, indexed by the kernel state code.
+const TCP_STATES: [&str; 13] = [
+    "unknown",
+    "established",
+    "syn_sent",
+    "syn_recv",
+    "fin_wait1",
+    "fin_wait2",
+    "time_wait",
+    "close",
+    "close_wait",
+    "last_ack",
+    "listen",
+    "closing",
+    "new_syn_recv",
+];
+
+/// States reported for UDP sockets: bound without a peer, or connected.
+pub const UDP_STATES: [&str; 2] = ["listen", "established"];
+
+/// States whose counts are stored with each sample, per protocol.
+///
+/// Counts are kept for every state, including empty ones, so each sample
+/// of the history has the same shape.
+pub fn counted_states() -> Vec<(&'static str, &'static str)> {
+    TCP_STATES[1..12]
+        .iter()
+        .map(|state| ("tcp", *state))
+        .chain(UDP_STATES.iter().map(|state| ("udp", *state)))
+        .collect()
+}
+
+/// A single socket at the time of collection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocketEntry {
+    /// "tcp" or "udp", for both address families.
+    pub protocol: &'static str,
+    pub local_address: IpAddr,
+    pub local_port: u16,
+    /// Unspecified address and port 0 for sockets without a peer.
+    pub remote_address: IpAddr,
+    pub remote_port: u16,
+    /// Lowercase TCP state (e.g. "listen", "time_wait"); "listen" or
+    /// "established" for UDP.
+    pub state: &'static str,
+    /// UID of the socket owner.
+    pub uid: u32,
+    /// Inode of the socket, 0 for sockets already closed by their process.
+    pub inode: u64,
+}
+
+/// The process holding a socket open.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocketOwner {
+    pub pid: u32,
+    pub name: String,
+}
+
+fn parse_word(hex: &str) -> Option<[u8; 4]> {
+    u32::from_str_radix(hex, 16).ok().map(u32::to_ne_bytes)
+}
+
+/// Parses an `ADDRESS:PORT` pair as printed in `/proc/net/tcp*`.
+///
+/// IPv4 addresses are one 32-bit word, IPv6 addresses four. IPv4-mapped
+/// IPv6 addresses (`::ffff:a.b.c.d`) are returned as IPv4.
+pub fn parse_address(field: &str) -> Option<(IpAddr, u16)> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let address = match address.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(parse_word(address)?)),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                chunk.copy_from_slice(&parse_word(&address[i * 8..(i + 1) * 8])?);
+            }
+            Ipv6Addr::from(bytes).to_canonical()
+        }
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+/// Parses the content of one of the `/proc/net/{tcp,tcp6,udp,udp6}` tables.
+///
+/// `protocol` is "tcp" or "udp". Malformed lines are skipped.
+pub fn parse_socket_table(content: &str, protocol: &'static str) -> Vec<SocketEntry> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+            let (local_address, local_port) = parse_address(fields[1])?;
+            let (remote_address, remote_port) = parse_address(fields[2])?;
+            let code = usize::from_str_radix(fields[3], 16).ok()?;
+            let state = if protocol == "udp" {
+                // Connected UDP sockets are marked established, others close
+                if code == 1 { "established" } else { "listen" }
+            } else {
+                TCP_STATES.get(code).copied().unwrap_or("unknown")
+            };
+
+            Some(SocketEntry {
+                protocol,
+                local_address,
+                local_port,
+                remote_address,
+                remote_port,
+                state,
+                uid: fields[7].parse().ok()?,
+                inode: fields[9].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Reads the TCP and UDP sockets of both address families.
+///
+/// A missing table is skipped, since `tcp6` and `udp6` don't exist when
+/// IPv6 is disabled.
+pub fn read_sockets(procfs_root: &Path) -> io::Result<Vec<SocketEntry>> {
+    let mut sockets = Vec::new();
+    for (file, protocol) in [
+        ("tcp", "tcp"),
+        ("tcp6", "tcp"),
+        ("udp", "udp"),
+        ("udp6", "udp"),
+    ] {
+        match fs::read_to_string(procfs_root.join("net").join(file)) {
+            Ok(content) => sockets.extend(parse_socket_table(&content, protocol)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(sockets)
+}
+
+/// Counts sockets per protocol and state.
+///
+/// Every state of `counted_states` is present, with a count of 0 if no
+/// socket is in it.
+pub fn count_states(sockets: &[SocketEntry]) -> BTreeMap<(&'static str, &'static str), u64> {
+    let mut counts: BTreeMap<_, _> = counted_states().into_iter().map(|key| (key, 0)).collect();
+    for socket in sockets {
+        if let Some(count) = counts.get_mut(&(socket.protocol, socket.state)) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+/// Parses the target of a `/proc/<pid>/fd` link into a socket inode.
+pub fn parse_socket_link(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Maps socket inodes to the process holding them open.
+///
+/// A socket shared by several processes (a server and its forked workers)
+/// is attributed to the lowest PID, usually the parent. Processes whose
+/// file descriptors can't be read are skipped.
+pub fn read_socket_owners(procfs_root: &Path) -> io::Result<HashMap<u64, SocketOwner>> {
+    let mut pids: Vec<u32> = fs::read_dir(procfs_root)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    pids.sort_unstable();
+
+    let mut owners = HashMap::new();
+    for pid in pids {
+        let process_dir = procfs_root.join(pid.to_string());
+        // Permission denied for other users' processes, or already exited
+        let Ok(fds) = fs::read_dir(process_dir.join("fd")) else {
+            continue;
+        };
+        let inodes: Vec<u64> = fds
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| fs::read_link(entry.path()).ok())
+            .filter_map(|target| parse_socket_link(target.to_str()?))
+            .collect();
+        if inodes.is_empty() {
+            continue;
+        }
+
+        let name = fs::read_to_string(process_dir.join("comm"))
+            .map(|comm| comm.trim_end().to_string())
+            .unwrap_or_default();
+        for inode in inodes {
+            owners.entry(inode).or_insert_with(|| SocketOwner {
+                pid,
+                name: name.clone(),
+            });
+        }
+    }
+    Ok(owners)
+}
+
+/// TCP and UDP socket counts per state, from `/proc/net/{tcp,udp}*`.
+pub struct SocketsCollector {
+    procfs_root: PathBuf,
+    sockets: Option<Vec<SocketEntry>>,
+}
+
+impl SocketsCollector {
+    pub fn new(procfs_root: PathBuf) -> Self {
+        Self {
+            procfs_root,
+            sockets: None,
+        }
+    }
+}
+
+impl Collector for SocketsCollector {
+    fn name(&self) -> &'static str {
+        "sockets"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.sockets = Some(read_sockets(&self.procfs_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(sockets) = self.sockets.take() else {
+            return Ok(());
+        };
+        let rows: Vec<SchemaSocketInfo> = count_states(&sockets)
+            .into_iter()
+            .map(|((protocol, state), count)| SchemaSocketInfo {
+                sysinfo_id: sample.id,
+                protocol: protocol.to_string(),
+                state: state.to_string(),
+                socket_count: count as i64,
+            })
+            .collect();
+
+        mutation::insert_multiple_socketinfo(conn, &rows)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::sys::SysInfo;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+    use tempfile::TempDir;
+
+    const TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 41235 1 0000000000000000 100 0 0 10 0
+   2: 0F02000A:1F90 0202000A:D431 01 00000000:00000000 02:000A7F2E 00000000  1000        0 41236 2 0000000000000000 20 4 30 10 -1
+   3: 0F02000A:A2C4 2A00A8C0:01BB 06 00000000:00000000 03:00000D3B 00000000     0        0 0 3 0000000000000000
+";
+
+    const TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 20001 1 0000000000000000 100 0 0 10 0
+   1: 0000000000000000FFFF00000F02000A:1F90 0000000000000000FFFF00000202000A:D432 01 00000000:00000000 02:000A7F2E 00000000  1000        0 41237 2 0000000000000000 20 4 30 10 -1
+";
+
+    const UDP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  100: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 15001 2 0000000000000000 0
+  101: 0F02000A:B7D4 0101A8C0:0035 01 00000000:00000000 00:00000000 00000000  1000        0 15002 2 0000000000000000 0
+";
+
+    fn write_proc_net(root: &Path, tables: &[(&str, &str)]) {
+        let net = root.join("net");
+        fs::create_dir_all(&net).unwrap();
+        for (name, content) in tables {
+            fs::write(net.join(name), content).unwrap();
+        }
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("0100007F:1F90"),
+            Some((IpAddr::V4(Ipv4Addr::LOCALHOST), 8080))
+        );
+        assert_eq!(
+            parse_address("00000000000000000000000001000000:0016"),
+            Some((IpAddr::V6(Ipv6Addr::LOCALHOST), 22))
+        );
+        // IPv4-mapped: ::ffff:10.0.2.15
+        assert_eq!(
+            parse_address("0000000000000000FFFF00000F02000A:1F90"),
+            Some((IpAddr::V4(Ipv4Addr::new(10, 0, 2, 15)), 8080))
+        );
+        assert_eq!(parse_address("0100007F"), None);
+        assert_eq!(parse_address("0100:1F90"), None);
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_socket_table() {
+        let sockets = parse_socket_table(TCP, "tcp");
+        assert_eq!(sockets.len(), 4);
+
+        assert_eq!(sockets[0].local_address, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(sockets[0].local_port, 8080);
+        assert_eq!(sockets[0].state, "listen");
+        assert_eq!(sockets[0].uid, 1000);
+        assert_eq!(sockets[0].inode, 41234);
+
+        let connection = &sockets[2];
+        assert_eq!(connection.state, "established");
+        assert_eq!(connection.local_address.to_string(), "10.0.2.15");
+        assert_eq!(connection.remote_address.to_string(), "10.0.2.2");
+        assert_eq!(connection.remote_port, 54321);
+
+        assert_eq!(sockets[3].state, "time_wait");
+        assert_eq!(sockets[3].inode, 0);
+
+        let udp = parse_socket_table(UDP, "udp");
+        assert_eq!(udp[0].local_address.to_string(), "127.0.0.53");
+        assert_eq!(udp[0].local_port, 53);
+        assert_eq!(udp[0].state, "listen");
+        assert_eq!(udp[1].state, "established");
+
+        // Header only, and truncated lines
+        assert!(parse_socket_table("  sl  local_address\n   0: 0100007F:1F90\n", "tcp").is_empty());
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_read_sockets_and_count_states() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        // No udp6 table: IPv6 disabled for UDP
+        write_proc_net(root.path(), &[("tcp", TCP), ("tcp6", TCP6), ("udp", UDP)]);
+
+        let sockets = read_sockets(root.path()).unwrap();
+        assert_eq!(sockets.len(), 8);
+        assert_eq!(sockets[4].local_address, IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+        assert_eq!(sockets[4].local_port, 22);
+        assert_eq!(sockets[5].local_address.to_string(), "10.0.2.15");
+
+        let counts = count_states(&sockets);
+        assert_eq!(counts.len(), counted_states().len());
+        assert_eq!(counts[&("tcp", "listen")], 3);
+        assert_eq!(counts[&("tcp", "established")], 2);
+        assert_eq!(counts[&("tcp", "time_wait")], 1);
+        assert_eq!(counts[&("tcp", "close_wait")], 0);
+        assert_eq!(counts[&("udp", "listen")], 1);
+        assert_eq!(counts[&("udp", "established")], 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_socket_owners() {
+        use std::os::unix::fs::symlink;
+
+        let root = TempDir::new().expect("Failed to create temp dir");
+        let process = |pid: &str, comm: &str, links: &[&str]| {
+            let dir = root.path().join(pid);
+            fs::create_dir_all(dir.join("fd")).unwrap();
+            fs::write(dir.join("comm"), comm).unwrap();
+            for (fd, target) in links.iter().enumerate() {
+                symlink(target, dir.join("fd").join(fd.to_string())).unwrap();
+            }
+        };
+        process("812", "nginx\n", &["/dev/null", "socket:[41234]"]);
+        // Forked worker sharing the listening socket
+        process("813", "nginx\n", &["socket:[41234]", "socket:[41236]"]);
+        process("1000", "sshd\n", &["pipe:[777]", "socket:[20001]"]);
+        // Not a process directory
+        fs::create_dir_all(root.path().join("net")).unwrap();
+
+        let owners = read_socket_owners(root.path()).unwrap();
+        assert_eq!(owners.len(), 3);
+        assert_eq!(
+            owners[&41234],
+            SocketOwner {
+                pid: 812,
+                name: "nginx".to_string()
+            }
+        );
+        assert_eq!(owners[&41236].pid, 813);
+        assert_eq!(owners[&20001].name, "sshd");
+    }
+
+    #[test]
+    fn test_parse_socket_link() {
+        assert_eq!(parse_socket_link("socket:[41234]"), Some(41234));
+        assert_eq!(parse_socket_link("pipe:[41234]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+    }
+
+    #[test]
+    fn test_sockets_collector_history() {
+        let (_dir, storage) = migrated_storage();
+        let mut collector = SocketsCollector::new(PathBuf::from("/proc"));
+
+        let mut conn = storage.write().unwrap();
+        let first = SysInfo::default().store(&mut conn).unwrap();
+        collect_and_store(&mut collector, &mut conn, &first).unwrap();
+        let second = SysInfo::default().store(&mut conn).unwrap();
+        collect_and_store(&mut collector, &mut conn, &second).unwrap();
+
+        let states = counted_states().len();
+        let history = query::get_socketinfo_history(&mut conn, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].0, first.timestamp);
+        assert_eq!(history[1].0, second.timestamp);
+        // Every state is stored, even without sockets in it
+        assert!(history.iter().all(|(_, counts)| counts.len() == states));
+
+        let latest = query::get_socketinfo_history(&mut conn, 1).unwrap();
+        assert_eq!(latest.len(), 1);
+        assert!(latest[0].1.iter().all(|c| c.sysinfo_id == second.id));
+    }
+}
@@ -0,0 +1,364 @@
+//! Incremental reading of files that are only appended to, like logs and
+//! `wtmp`.
+//!
+//! A cursor remembers how far a file was read, so each collection only
+//! parses what was written since the previous one. Rotation (the path now
+//! points to a new file) and truncation are detected from the inode and
+//! the file size, and reading restarts from the beginning of the file.
+//!
+//! When the previous file was renamed to `<path>.1`, as logrotate does,
+//! what was appended to it after the last read is returned first, up to
+//! its unterminated last record, before the new file is read.
+//!
+//! A read returns at most `max_bytes`, so a file that grew a lot since the
+//! previous collection is read over several ones rather than at once.
+
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Bytes read from a file per collection by the collectors tailing it.
+pub const MAX_READ_BYTES: u64 = 8 << 20;
+
+/// Read position in a file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileCursor {
+    inode: Option<u64>,
+    offset: u64,
+    /* the previous read stopped at its limit */
+    behind: bool,
+}
+
+#[cfg(unix)]
+fn inode(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.ino())
+}
+
+/// Without inodes, only truncation can be detected.
+#[cfg(not(unix))]
+fn inode(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Path logrotate renames `path` to.
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = OsString::from(path.as_os_str());
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+impl FileCursor {
+    /// Returns the bytes appended to `path` since the previous call, at
+    /// most `max_bytes` of them; the rest is returned by the next calls.
+    ///
+    /// `complete` receives the new bytes and returns how many of them form
+    /// complete records (see `complete_lines`); a record still being
+    /// written is left for the next call. A record longer than `max_bytes`
+    /// is returned in pieces. A missing file reads as empty.
+    pub fn read_new(
+        &mut self,
+        path: &Path,
+        complete: impl Fn(&[u8]) -> usize,
+        max_bytes: u64,
+    ) -> io::Result<Vec<u8>> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.behind = false;
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(e),
+        };
+        let metadata = file.metadata()?;
+        let inode = inode(&metadata);
+        let mut bytes = Vec::new();
+        if inode != self.inode {
+            // The rest of the previous file comes first, over as many
+            // reads as needed
+            if let Some(mut rotated) = self.open_rotated(path) {
+                let (rest, full) = read_at(&mut rotated, self.offset, max_bytes)?;
+                if full {
+                    let rest = complete_part(rest, full, &complete);
+                    self.offset += rest.len() as u64;
+                    self.behind = true;
+                    return Ok(rest);
+                }
+                bytes = rest;
+            }
+            self.inode = inode;
+            self.offset = 0;
+            if complete(&bytes) < bytes.len() {
+                // Nothing will complete the last record of the previous
+                // file, it's returned as is, apart from the new file
+                self.behind = true;
+                return Ok(bytes);
+            }
+        } else if metadata.len() < self.offset {
+            self.offset = 0;
+        }
+
+        let limit = max_bytes - bytes.len() as u64;
+        let (appended, full) = read_at(&mut file, self.offset, limit)?;
+        let mut appended = complete_part(appended, full, &complete);
+        self.offset += appended.len() as u64;
+        self.behind = full;
+        bytes.append(&mut appended);
+        Ok(bytes)
+    }
+
+    /// Whether the previous `read_new` stopped at its limit, with more of
+    /// the file possibly left to read.
+    pub fn is_behind(&self) -> bool {
+        self.behind
+    }
+
+    /// Moves the cursor to the end of `path`, so only what is appended
+    /// from now on is read. A missing file will be read from its start
+    /// once created.
+    pub fn skip_to_end(&mut self, path: &Path) -> io::Result<()> {
+        match std::fs::metadata(path) {
+            Ok(metadata) => {
+                self.inode = inode(&metadata);
+                self.offset = metadata.len();
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Opens the file the cursor was reading, when it was renamed to
+    /// `<path>.1` and has bytes left to read.
+    fn open_rotated(&self, path: &Path) -> Option<File> {
+        self.inode?;
+        let file = File::open(rotated_path(path)).ok()?;
+        file.metadata()
+            .is_ok_and(|metadata| inode(&metadata) == self.inode && metadata.len() > self.offset)
+            .then_some(file)
+    }
+}
+
+/// Reads at most `limit` bytes of `file` from `offset`. Returns them with
+/// whether the limit was reached.
+fn read_at(file: &mut File, offset: u64, limit: u64) -> io::Result<(Vec<u8>, bool)> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.take(limit).read_to_end(&mut bytes)?;
+    let full = bytes.len() as u64 == limit;
+    Ok((bytes, full))
+}
+
+/// Cuts `bytes` after their last complete record. When they filled the
+/// limit of the read without completing one, they are a piece of a longer
+/// record and kept.
+fn complete_part(mut bytes: Vec<u8>, full: bool, complete: impl Fn(&[u8]) -> usize) -> Vec<u8> {
+    let complete_len = complete(&bytes);
+    if complete_len > 0 || !full {
+        bytes.truncate(complete_len);
+    }
+    bytes
+}
+
+/// Length of the complete lines at the start of `bytes`, up to and
+/// including the last newline.
+pub fn complete_lines(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |last| last + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    fn append(path: &Path, content: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_complete_lines() {
+        assert_eq!(complete_lines(b"one\ntwo\nthr"), 8);
+        assert_eq!(complete_lines(b"one\n"), 4);
+        assert_eq!(complete_lines(b"partial"), 0);
+        assert_eq!(complete_lines(b""), 0);
+    }
+
+    #[test]
+    fn test_read_new_appended_lines() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("auth.log");
+        let mut cursor = FileCursor::default();
+
+        assert!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap()
+                .is_empty()
+        );
+
+        append(&path, "one\ntwo\nthr");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"one\ntwo\n"
+        );
+        // The partial line is returned once completed
+        append(&path, "ee\n");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"three\n"
+        );
+        assert!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_read_new_at_most_max_bytes() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("access.log");
+        let mut cursor = FileCursor::default();
+
+        // What doesn't fit is left for the next reads
+        append(&path, "one\ntwo\nthree\n");
+        assert_eq!(cursor.read_new(&path, complete_lines, 6).unwrap(), b"one\n");
+        assert_eq!(cursor.read_new(&path, complete_lines, 6).unwrap(), b"two\n");
+        // A line longer than the limit comes in pieces
+        assert_eq!(cursor.read_new(&path, complete_lines, 4).unwrap(), b"thre");
+        assert_eq!(cursor.read_new(&path, complete_lines, 4).unwrap(), b"e\n");
+        assert!(
+            cursor
+                .read_new(&path, complete_lines, 4)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_read_new_after_rotation_and_truncation() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("auth.log");
+        let mut cursor = FileCursor::default();
+
+        append(&path, "before rotation, a long line\n");
+        cursor
+            .read_new(&path, complete_lines, MAX_READ_BYTES)
+            .unwrap();
+
+        // logrotate: the file is moved away and a new one created
+        fs::rename(&path, dir.path().join("auth.log.1")).unwrap();
+        append(&path, "rotated\n");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"rotated\n"
+        );
+
+        // Lines appended to the renamed file before the new one was read
+        append(&path, "late\n");
+        fs::rename(&path, dir.path().join("auth.log.1")).unwrap();
+        append(&path, "second\n");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"late\nsecond\n"
+        );
+
+        // copytruncate: same file, shorter than the read position
+        fs::write(&path, "").unwrap();
+        append(&path, "new\n");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"new\n"
+        );
+    }
+
+    #[test]
+    fn test_read_new_rotation_while_behind() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("auth.log");
+        let mut cursor = FileCursor::default();
+
+        append(&path, "one\ntwo\nthree\nfour");
+        assert_eq!(cursor.read_new(&path, complete_lines, 6).unwrap(), b"one\n");
+        assert!(cursor.is_behind());
+
+        fs::rename(&path, dir.path().join("auth.log.1")).unwrap();
+        append(&path, "new\n");
+        // The rotated file is read to its end first, its unterminated last
+        // line included
+        assert_eq!(cursor.read_new(&path, complete_lines, 6).unwrap(), b"two\n");
+        assert_eq!(
+            cursor.read_new(&path, complete_lines, 6).unwrap(),
+            b"three\n"
+        );
+        assert_eq!(cursor.read_new(&path, complete_lines, 6).unwrap(), b"four");
+        assert!(cursor.is_behind());
+        assert_eq!(cursor.read_new(&path, complete_lines, 6).unwrap(), b"new\n");
+        assert!(!cursor.is_behind());
+        assert!(
+            cursor
+                .read_new(&path, complete_lines, 6)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_skip_to_end() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("error.log");
+        let mut cursor = FileCursor::default();
+
+        // A file created later is read from its start
+        cursor.skip_to_end(&path).unwrap();
+        append(&path, "first\n");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"first\n"
+        );
+
+        append(&path, "old\n");
+        let mut cursor = FileCursor::default();
+        cursor.skip_to_end(&path).unwrap();
+        assert!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap()
+                .is_empty()
+        );
+
+        append(&path, "new\n");
+        assert_eq!(
+            cursor
+                .read_new(&path, complete_lines, MAX_READ_BYTES)
+                .unwrap(),
+            b"new\n"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Clock synchronization state from `adjtimex(2)`.
+//!
+//! The kernel keeps the NTP state last set by the time daemon: whether the
+//! clock is synchronized, its estimated and maximum error, and the offset
+//! it is still slewing away. Calling `adjtimex` without modes only reads
+//! that state and needs no privileges.
+//!
+//! A clock that no daemon disciplines ends up unsynchronized, so the NTP
+//! client running on the host, if any, is reported alongside.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use crate::mutation;
+use crate::schema::SchemaTimeSyncInfo;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use teus_database::storage::TeuSQLiteConnection;
+
+/// NTP clients looked for in `/proc/<pid>/comm`, with the name they are
+/// reported under. `comm` is truncated to 15 characters.
+const NTP_CLIENTS: [(&str, &str); 3] = [
+    ("systemd-timesyn", "systemd-timesyncd"),
+    ("chronyd", "chrony"),
+    ("ntpd", "ntpd"),
+];
+
+/// Synchronization state of the system clock.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockSync {
+    /// Whether the kernel considers the clock synchronized to a time
+    /// source.
+    pub synchronized: bool,
+    /// Offset between the clock and the time source still being
+    /// corrected, in microseconds.
+    pub offset_us: f64,
+    /// Maximum error of the clock, in microseconds. It grows while the
+    /// clock is not disciplined, and the kernel gives up on the clock
+    /// (unsynchronized) at 16 seconds.
+    pub max_error_us: i64,
+    /// Estimated error of the clock, in microseconds.
+    pub est_error_us: i64,
+    /// NTP client running on the host, `None` when there is none.
+    pub ntp_client: Option<String>,
+}
+
+/// Builds the clock state from the result of `adjtimex`: its return
+/// value (`TIME_OK`, ..., `TIME_ERROR`) and the filled `timex`.
+// `c_long` is only 32 bits wide on 32-bit targets
+#[allow(clippy::unnecessary_cast)]
+pub fn clock_sync_from_timex(
+    state: libc::c_int,
+    timex: &libc::timex,
+    ntp_client: Option<String>,
+) -> ClockSync {
+    // The offset is in nanoseconds when the time daemon asked for it
+    let offset_us = if timex.status & libc::STA_NANO != 0 {
+        timex.offset as f64 / 1000.0
+    } else {
+        timex.offset as f64
+    };
+    ClockSync {
+        synchronized: state != libc::TIME_ERROR && timex.status & libc::STA_UNSYNC == 0,
+        offset_us,
+        max_error_us: timex.maxerror as i64,
+        est_error_us: timex.esterror as i64,
+        ntp_client,
+    }
+}
+
+/// Finds the NTP client running on the host from the processes under
+/// `procfs_root`.
+pub fn find_ntp_client(procfs_root: &Path) -> io::Result<Option<String>> {
+    for entry in fs::read_dir(procfs_root)? {
+        let entry = entry?;
+        if !entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()))
+        {
+            continue;
+        }
+        // The process may have exited since the directory was listed
+        let Ok(comm) = fs::read_to_string(entry.path().join("comm")) else {
+            continue;
+        };
+        if let Some((_, client)) = NTP_CLIENTS.iter().find(|(name, _)| *name == comm.trim()) {
+            return Ok(Some(client.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the synchronization state of the system clock, with the NTP
+/// client found under `procfs_root`.
+pub fn read_clock_sync(procfs_root: &Path) -> io::Result<ClockSync> {
+    let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+    // `modes` is 0, so the call only reads the kernel state
+    let state = unsafe { libc::adjtimex(&mut timex) };
+    if state < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(clock_sync_from_timex(
+        state,
+        &timex,
+        find_ntp_client(procfs_root)?,
+    ))
+}
+
+/// Clock synchronization state, from `adjtimex`, with the NTP client
+/// running on the host.
+pub struct TimeSyncCollector {
+    procfs_root: PathBuf,
+    clock: Option<ClockSync>,
+}
+
+impl TimeSyncCollector {
+    pub fn new(procfs_root: PathBuf) -> Self {
+        Self {
+            procfs_root,
+            clock: None,
+        }
+    }
+}
+
+impl Collector for TimeSyncCollector {
+    fn name(&self) -> &'static str {
+        "timesync"
+    }
+
+    fn collect(&mut self) -> Result<(), CollectorError> {
+        self.clock = Some(read_clock_sync(&self.procfs_root)?);
+        Ok(())
+    }
+
+    fn store(
+        &mut self,
+        conn: &mut TeuSQLiteConnection,
+        sample: &Sample,
+    ) -> Result<(), CollectorError> {
+        let Some(clock) = self.clock.take() else {
+            return Ok(());
+        };
+        mutation::insert_timesyncinfo(
+            conn,
+            &SchemaTimeSyncInfo {
+                sysinfo_id: sample.id,
+                synchronized: clock.synchronized,
+                offset_us: clock.offset_us,
+                max_error_us: clock.max_error_us,
+                est_error_us: clock.est_error_us,
+                ntp_client: clock.ntp_client,
+            },
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+    use crate::sys::SysInfo;
+    use crate::test_utils::{collect_and_store, migrated_storage};
+
+    fn timex(status: libc::c_int, offset: libc::c_long) -> libc::timex {
+        let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+        timex.status = status;
+        timex.offset = offset;
+        timex.maxerror = 16_000;
+        timex.esterror = 120;
+        timex
+    }
+
+    #[test]
+    fn test_clock_sync_from_timex() {
+        let synced = clock_sync_from_timex(
+            libc::TIME_OK,
+            &timex(libc::STA_PLL, -250),
+            Some("chrony".to_string()),
+        );
+        assert_eq!(
+            synced,
+            ClockSync {
+                synchronized: true,
+                offset_us: -250.0,
+                max_error_us: 16_000,
+                est_error_us: 120,
+                ntp_client: Some("chrony".to_string()),
+            }
+        );
+
+        // Nanosecond offsets are converted
+        let nano = clock_sync_from_timex(
+            libc::TIME_OK,
+            &timex(libc::STA_PLL | libc::STA_NANO, 1_500),
+            None,
+        );
+        assert_eq!(nano.offset_us, 1.5);
+
+        let unsynced = clock_sync_from_timex(libc::TIME_ERROR, &timex(libc::STA_UNSYNC, 0), None);
+        assert!(!unsynced.synchronized);
+        assert!(
+            !clock_sync_from_timex(libc::TIME_OK, &timex(libc::STA_UNSYNC, 0), None).synchronized
+        );
+    }
+
+    #[test]
+    fn test_find_ntp_client() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let process = |pid: &str, comm: &str| {
+            fs::create_dir_all(dir.path().join(pid)).unwrap();
+            fs::write(dir.path().join(pid).join("comm"), comm).unwrap();
+        };
+        process("1", "systemd\n");
+        process("812", "sshd\n");
+        fs::create_dir_all(dir.path().join("sys")).unwrap();
+        assert_eq!(find_ntp_client(dir.path()).unwrap(), None);
+
+        process("455", "systemd-timesyn\n");
+        assert_eq!(
+            find_ntp_client(dir.path()).unwrap(),
+            Some("systemd-timesyncd".to_string())
+        );
+    }
+
+    #[test]
+    fn test_timesync_collector_history() {
+        let (_dir, storage) = migrated_storage();
+        let mut collector = TimeSyncCollector::new(PathBuf::from("/proc"));
+
+        let mut conn = storage.write().unwrap();
+        let first = SysInfo::default().store(&mut conn).unwrap();
+        collect_and_store(&mut collector, &mut conn, &first).unwrap();
+        let second = SysInfo::default().store(&mut conn).unwrap();
+        collect_and_store(&mut collector, &mut conn, &second).unwrap();
+
+        let history = query::get_timesyncinfo_history(&mut conn, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].0, first.timestamp);
+        assert_eq!(history[1].1.sysinfo_id, second.id);
+        assert!(history.iter().all(|(_, clock)| clock.max_error_us >= 0));
+    }
+}
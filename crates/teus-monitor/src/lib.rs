@@ -1,4 +1,11 @@
+pub mod collector;
+pub mod collectors;
 pub mod mutation;
 pub mod query;
+pub mod retention;
+pub mod rollup;
 pub mod schema;
 pub mod sys;
+
+#[cfg(test)]
+mod test_utils;
@@ -1,8 +1,16 @@
 // src/monitor/mutation.rs
-use crate::schema::{SchemaDiskInfo, SchemaSysInfo};
+use crate::schema::{
+    SchemaCgroupInfo, SchemaCpuInfo, SchemaDiskInfo, SchemaDiskIo, SchemaKernelEvent,
+    SchemaLogEvent, SchemaLogMatch, SchemaNetInfo, SchemaPackageStatus, SchemaPackageUpdate,
+    SchemaPathInfo, SchemaProcessInfo, SchemaPsiInfo, SchemaScriptMetric, SchemaSecurityEvent,
+    SchemaSensorInfo, SchemaSocketInfo, SchemaSysInfo, SchemaTimeSyncInfo,
+};
 use diesel::prelude::*;
 use diesel::result::Error;
 
+/// Rows written per INSERT, within the bound variables of SQLite.
+pub const INSERT_CHUNK: usize = 1000;
+
 /// Inserts system information into the database and returns the ID of the new record.
 pub fn insert_sysinfo(
     conn: &mut SqliteConnection,
@@ -51,3 +59,206 @@ pub fn insert_multiple_diskinfo(
         .values(disk_infos)
         .execute(conn)
 }
+
+/// Inserts the per-core CPU usage rows of a single snapshot.
+pub fn insert_multiple_cpuinfo(
+    conn: &mut SqliteConnection,
+    cpu_infos: &[SchemaCpuInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::cpuinfo::dsl::*;
+
+    diesel::insert_into(cpuinfo).values(cpu_infos).execute(conn)
+}
+
+/// Inserts the network interface rows of a single snapshot.
+pub fn insert_multiple_netinfo(
+    conn: &mut SqliteConnection,
+    net_infos: &[SchemaNetInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::netinfo::dsl::*;
+
+    diesel::insert_into(netinfo).values(net_infos).execute(conn)
+}
+
+/// Inserts the block device I/O rows of a single snapshot.
+pub fn insert_multiple_diskio(
+    conn: &mut SqliteConnection,
+    disk_ios: &[SchemaDiskIo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::diskio::dsl::*;
+
+    diesel::insert_into(diskio).values(disk_ios).execute(conn)
+}
+
+/// Inserts the cgroup accounting rows of a single snapshot.
+pub fn insert_multiple_cgroupinfo(
+    conn: &mut SqliteConnection,
+    cgroup_infos: &[SchemaCgroupInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::cgroupinfo::dsl::*;
+
+    diesel::insert_into(cgroupinfo)
+        .values(cgroup_infos)
+        .execute(conn)
+}
+
+/// Inserts the socket state counts of a single snapshot.
+pub fn insert_multiple_socketinfo(
+    conn: &mut SqliteConnection,
+    socket_infos: &[SchemaSocketInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::socketinfo::dsl::*;
+
+    diesel::insert_into(socketinfo)
+        .values(socket_infos)
+        .execute(conn)
+}
+
+/// Inserts login and SSH authentication events, in chunks of
+/// `INSERT_CHUNK` and all or none.
+pub fn insert_multiple_securityevents(
+    conn: &mut SqliteConnection,
+    events: &[SchemaSecurityEvent],
+) -> Result<usize, Error> {
+    use teus_schema::schema::securityevent::dsl::*;
+
+    conn.transaction(|conn| {
+        let mut inserted = 0;
+        for chunk in events.chunks(INSERT_CHUNK) {
+            inserted += diesel::insert_into(securityevent)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        Ok(inserted)
+    })
+}
+
+/// Inserts the values printed by a script.
+pub fn insert_multiple_scriptmetrics(
+    conn: &mut SqliteConnection,
+    metrics: &[SchemaScriptMetric],
+) -> Result<usize, Error> {
+    use teus_schema::schema::scriptmetric::dsl::*;
+
+    diesel::insert_into(scriptmetric)
+        .values(metrics)
+        .execute(conn)
+}
+
+/// Inserts kernel events, skipping the ones already stored.
+///
+/// The kernel log is read again from the beginning of its ring buffer
+/// when the monitor restarts; those events have the same boot ID and
+/// sequence number as the stored ones.
+pub fn insert_multiple_kernelevents(
+    conn: &mut SqliteConnection,
+    events: &[SchemaKernelEvent],
+) -> Result<usize, Error> {
+    use teus_schema::schema::kernelevent::dsl::*;
+
+    diesel::insert_or_ignore_into(kernelevent)
+        .values(events)
+        .execute(conn)
+}
+
+/// Stores the package state of a day, replacing the one stored by an
+/// earlier check of the same day.
+pub fn replace_packagestatus(
+    conn: &mut SqliteConnection,
+    status: &SchemaPackageStatus,
+    updates: &[SchemaPackageUpdate],
+) -> Result<(), Error> {
+    use teus_schema::schema::{packagestatus, packageupdate};
+
+    conn.transaction(|conn| {
+        diesel::delete(packageupdate::table.filter(packageupdate::day.eq(&status.day)))
+            .execute(conn)?;
+        diesel::delete(packagestatus::table.filter(packagestatus::day.eq(&status.day)))
+            .execute(conn)?;
+        diesel::insert_into(packagestatus::table)
+            .values(status)
+            .execute(conn)?;
+        diesel::insert_into(packageupdate::table)
+            .values(updates)
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// Inserts the clock synchronization state of a single snapshot.
+pub fn insert_timesyncinfo(
+    conn: &mut SqliteConnection,
+    timesync_info: &SchemaTimeSyncInfo,
+) -> Result<usize, Error> {
+    use teus_schema::schema::timesyncinfo::dsl::*;
+
+    diesel::insert_into(timesyncinfo)
+        .values(timesync_info)
+        .execute(conn)
+}
+
+/// Inserts the disk usage of watched directories.
+pub fn insert_multiple_pathinfo(
+    conn: &mut SqliteConnection,
+    path_infos: &[SchemaPathInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::pathinfo::dsl::*;
+
+    diesel::insert_into(pathinfo)
+        .values(path_infos)
+        .execute(conn)
+}
+
+/// Inserts the pattern counts of watched log files.
+pub fn insert_multiple_logmatches(
+    conn: &mut SqliteConnection,
+    matches: &[SchemaLogMatch],
+) -> Result<usize, Error> {
+    use teus_schema::schema::logmatch::dsl::*;
+
+    diesel::insert_into(logmatch).values(matches).execute(conn)
+}
+
+/// Inserts matching lines of watched log files.
+pub fn insert_multiple_logevents(
+    conn: &mut SqliteConnection,
+    events: &[SchemaLogEvent],
+) -> Result<usize, Error> {
+    use teus_schema::schema::logevent::dsl::*;
+
+    diesel::insert_into(logevent).values(events).execute(conn)
+}
+
+/// Inserts the process rows of a single snapshot.
+pub fn insert_multiple_processinfo(
+    conn: &mut SqliteConnection,
+    process_infos: &[SchemaProcessInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::processinfo::dsl::*;
+
+    diesel::insert_into(processinfo)
+        .values(process_infos)
+        .execute(conn)
+}
+
+/// Inserts the hardware sensor rows of a single snapshot.
+pub fn insert_multiple_sensorinfo(
+    conn: &mut SqliteConnection,
+    sensor_infos: &[SchemaSensorInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::sensorinfo::dsl::*;
+
+    diesel::insert_into(sensorinfo)
+        .values(sensor_infos)
+        .execute(conn)
+}
+
+/// Inserts the pressure stall rows of a single snapshot.
+pub fn insert_multiple_psiinfo(
+    conn: &mut SqliteConnection,
+    psi_infos: &[SchemaPsiInfo],
+) -> Result<usize, Error> {
+    use teus_schema::schema::psiinfo::dsl::*;
+
+    diesel::insert_into(psiinfo).values(psi_infos).execute(conn)
+}
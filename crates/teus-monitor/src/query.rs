@@ -1,8 +1,21 @@
-use crate::schema::{DiskInfo, SysInfo};
+use crate::schema::{
+    CgroupInfo, CpuInfo, DiskInfo, DiskIo, KernelEvent, LogEvent, LogMatch, NetInfo, PackageStatus,
+    PackageUpdate, PathInfo, ProcessInfo, PsiInfo, ScriptMetric, SecurityEvent, SensorInfo,
+    SocketInfo, SysInfo, TimeSyncInfo,
+};
 use diesel::prelude::*;
 use diesel::result::Error;
+use teus_schema::schema::{
+    cgroupinfo, diskio, kernelevent, logevent, logmatch, netinfo, packagestatus, packageupdate,
+    pathinfo, processinfo, psiinfo, scriptmetric, securityevent, sensorinfo, socketinfo, sysinfo,
+    timesyncinfo,
+};
 
-/// Fetches the latest SysInfo record along with its associated DiskInfo records.
+/// Fetches the latest SysInfo record along with the DiskInfo records of the
+/// latest snapshot that has any.
+///
+/// Disks may be collected less often than the snapshots are taken, so the
+/// latest snapshot doesn't necessarily have disk records of its own.
 pub fn get_latest_sysinfo_with_disks(
     conn: &mut SqliteConnection,
 ) -> Result<Option<(SysInfo, Vec<DiskInfo>)>, Error> {
@@ -19,10 +32,20 @@ pub fn get_latest_sysinfo_with_disks(
             /* import here because the ID is ambiguos */
             use teus_schema::schema::diskinfo::dsl::*;
 
-            let disks = diskinfo
-                .filter(sysinfo_id.eq(latest_sysinfo.id.unwrap()))
-                .select(DiskInfo::as_select())
-                .load::<DiskInfo>(conn)?; // Load all associated disks
+            /* rows are inserted in snapshot order, the last one belongs to the latest snapshot */
+            let disks_snapshot_id = diskinfo
+                .order(id.desc())
+                .select(sysinfo_id)
+                .first::<i32>(conn)
+                .optional()?;
+
+            let disks = match disks_snapshot_id {
+                Some(snapshot_id) => diskinfo
+                    .filter(sysinfo_id.eq(snapshot_id))
+                    .select(DiskInfo::as_select())
+                    .load::<DiskInfo>(conn)?, // Load all associated disks
+                None => Vec::new(),
+            };
 
             Ok(Some((latest_sysinfo, disks)))
         }
@@ -32,3 +55,700 @@ pub fn get_latest_sysinfo_with_disks(
         }
     }
 }
+
+/// Fetches the per-core CPU records of a SysInfo snapshot, ordered by core.
+pub fn get_cpuinfo_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<CpuInfo>, Error> {
+    use teus_schema::schema::cpuinfo::dsl::*;
+
+    cpuinfo
+        .filter(sysinfo_id.eq(snapshot_id))
+        .order(core_index.asc())
+        .select(CpuInfo::as_select())
+        .load::<CpuInfo>(conn)
+}
+
+/// Fetches the network interface records of the latest snapshot that has any,
+/// together with that snapshot's timestamp.
+///
+/// Used by the monitor as the baseline to compute throughput rates.
+pub fn get_latest_netinfo(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(String, Vec<NetInfo>)>, Error> {
+    let latest = netinfo::table
+        .inner_join(sysinfo::table)
+        .order(netinfo::sysinfo_id.desc())
+        .select((netinfo::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+
+    match latest {
+        Some((snapshot_id, snapshot_timestamp)) => {
+            let interfaces = get_netinfo_by_sysinfo_id(conn, snapshot_id)?;
+            Ok(Some((snapshot_timestamp, interfaces)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetches the network interface records of a SysInfo snapshot, ordered by name.
+pub fn get_netinfo_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<NetInfo>, Error> {
+    netinfo::table
+        .filter(netinfo::sysinfo_id.eq(snapshot_id))
+        .order(netinfo::interface.asc())
+        .select(NetInfo::as_select())
+        .load::<NetInfo>(conn)
+}
+
+/// Fetches the last `limit` records of one interface with their snapshot
+/// timestamps, oldest first.
+pub fn get_netinfo_history(
+    conn: &mut SqliteConnection,
+    interface_name: &str,
+    limit: i64,
+) -> Result<Vec<(String, NetInfo)>, Error> {
+    let mut history = netinfo::table
+        .inner_join(sysinfo::table)
+        .filter(netinfo::interface.eq(interface_name))
+        .order(netinfo::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, NetInfo::as_select()))
+        .load::<(String, NetInfo)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the block device I/O records of the latest snapshot that has any,
+/// together with that snapshot's timestamp.
+///
+/// Used by the monitor as the baseline to compute I/O rates.
+pub fn get_latest_diskio(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(String, Vec<DiskIo>)>, Error> {
+    let latest = diskio::table
+        .inner_join(sysinfo::table)
+        .order(diskio::sysinfo_id.desc())
+        .select((diskio::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+
+    match latest {
+        Some((snapshot_id, snapshot_timestamp)) => {
+            let devices = get_diskio_by_sysinfo_id(conn, snapshot_id)?;
+            Ok(Some((snapshot_timestamp, devices)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetches the block device I/O records of a SysInfo snapshot, ordered by device.
+pub fn get_diskio_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<DiskIo>, Error> {
+    diskio::table
+        .filter(diskio::sysinfo_id.eq(snapshot_id))
+        .order(diskio::device.asc())
+        .select(DiskIo::as_select())
+        .load::<DiskIo>(conn)
+}
+
+/// Fetches the last `limit` I/O records of one block device with their
+/// snapshot timestamps, oldest first.
+pub fn get_diskio_history(
+    conn: &mut SqliteConnection,
+    device_name: &str,
+    limit: i64,
+) -> Result<Vec<(String, DiskIo)>, Error> {
+    let mut history = diskio::table
+        .inner_join(sysinfo::table)
+        .filter(diskio::device.eq(device_name))
+        .order(diskio::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, DiskIo::as_select()))
+        .load::<(String, DiskIo)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the cgroup records of the latest snapshot that has any,
+/// together with that snapshot's timestamp.
+///
+/// Used by the monitor as the baseline to compute CPU and I/O rates.
+pub fn get_latest_cgroupinfo(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(String, Vec<CgroupInfo>)>, Error> {
+    let latest = cgroupinfo::table
+        .inner_join(sysinfo::table)
+        .order(cgroupinfo::sysinfo_id.desc())
+        .select((cgroupinfo::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+
+    match latest {
+        Some((snapshot_id, snapshot_timestamp)) => {
+            let cgroups = get_cgroupinfo_by_sysinfo_id(conn, snapshot_id)?;
+            Ok(Some((snapshot_timestamp, cgroups)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetches the cgroup records of a SysInfo snapshot, ordered by path.
+pub fn get_cgroupinfo_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<CgroupInfo>, Error> {
+    cgroupinfo::table
+        .filter(cgroupinfo::sysinfo_id.eq(snapshot_id))
+        .order(cgroupinfo::path.asc())
+        .select(CgroupInfo::as_select())
+        .load::<CgroupInfo>(conn)
+}
+
+/// Fetches the socket state counts of the last `limit` snapshots that have
+/// any, grouped by snapshot with its timestamp, oldest first.
+///
+/// The counts of each snapshot are ordered by protocol and state.
+pub fn get_socketinfo_history(
+    conn: &mut SqliteConnection,
+    limit: i64,
+) -> Result<Vec<(String, Vec<SocketInfo>)>, Error> {
+    let snapshot_ids = socketinfo::table
+        .select(socketinfo::sysinfo_id)
+        .distinct()
+        .order(socketinfo::sysinfo_id.desc())
+        .limit(limit)
+        .load::<i32>(conn)?;
+
+    let rows = socketinfo::table
+        .inner_join(sysinfo::table)
+        .filter(socketinfo::sysinfo_id.eq_any(&snapshot_ids))
+        .order((
+            socketinfo::sysinfo_id.asc(),
+            socketinfo::protocol.asc(),
+            socketinfo::state.asc(),
+        ))
+        .select((sysinfo::timestamp, SocketInfo::as_select()))
+        .load::<(String, SocketInfo)>(conn)?;
+
+    let mut history: Vec<(String, Vec<SocketInfo>)> = Vec::new();
+    for (timestamp, row) in rows {
+        match history.last_mut() {
+            Some((_, counts)) if counts[0].sysinfo_id == row.sysinfo_id => counts.push(row),
+            _ => history.push((timestamp, vec![row])),
+        }
+    }
+    Ok(history)
+}
+
+/// Fetches the values of the latest run of every script, with the
+/// timestamp of the sample they were stored with.
+pub fn get_latest_scriptmetrics(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<(String, ScriptMetric)>, Error> {
+    let scripts = scriptmetric::table
+        .select(scriptmetric::script)
+        .distinct()
+        .order(scriptmetric::script.asc())
+        .load::<String>(conn)?;
+
+    let mut latest = Vec::new();
+    for script in scripts {
+        let Some(snapshot_id) = scriptmetric::table
+            .filter(scriptmetric::script.eq(&script))
+            .order(scriptmetric::sysinfo_id.desc())
+            .select(scriptmetric::sysinfo_id)
+            .first::<i32>(conn)
+            .optional()?
+        else {
+            continue;
+        };
+
+        latest.extend(
+            scriptmetric::table
+                .inner_join(sysinfo::table)
+                .filter(scriptmetric::script.eq(&script))
+                .filter(scriptmetric::sysinfo_id.eq(snapshot_id))
+                .order((scriptmetric::name.asc(), scriptmetric::labels.asc()))
+                .select((sysinfo::timestamp, ScriptMetric::as_select()))
+                .load::<(String, ScriptMetric)>(conn)?,
+        );
+    }
+    Ok(latest)
+}
+
+/// Fetches the values of metric `name` printed by `script` over the latest
+/// `limit` runs, grouped per sample and oldest first.
+pub fn get_scriptmetric_history(
+    conn: &mut SqliteConnection,
+    script: &str,
+    name: &str,
+    limit: i64,
+) -> Result<Vec<(String, Vec<ScriptMetric>)>, Error> {
+    let snapshot_ids = scriptmetric::table
+        .filter(scriptmetric::script.eq(script))
+        .filter(scriptmetric::name.eq(name))
+        .select(scriptmetric::sysinfo_id)
+        .distinct()
+        .order(scriptmetric::sysinfo_id.desc())
+        .limit(limit)
+        .load::<i32>(conn)?;
+
+    let rows = scriptmetric::table
+        .inner_join(sysinfo::table)
+        .filter(scriptmetric::script.eq(script))
+        .filter(scriptmetric::name.eq(name))
+        .filter(scriptmetric::sysinfo_id.eq_any(&snapshot_ids))
+        .order((scriptmetric::sysinfo_id.asc(), scriptmetric::labels.asc()))
+        .select((sysinfo::timestamp, ScriptMetric::as_select()))
+        .load::<(String, ScriptMetric)>(conn)?;
+
+    let mut history: Vec<(String, Vec<ScriptMetric>)> = Vec::new();
+    for (timestamp, row) in rows {
+        match history.last_mut() {
+            Some((_, values)) if values[0].sysinfo_id == row.sysinfo_id => values.push(row),
+            _ => history.push((timestamp, vec![row])),
+        }
+    }
+    Ok(history)
+}
+
+/// Fetches the log pattern counts of the latest run of the `logs`
+/// collector, sorted by log and pattern, with the timestamp of its sample.
+pub fn get_latest_logmatches(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(String, Vec<LogMatch>)>, Error> {
+    let Some((snapshot_id, timestamp)) = logmatch::table
+        .inner_join(sysinfo::table)
+        .order(logmatch::sysinfo_id.desc())
+        .select((logmatch::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let matches = logmatch::table
+        .filter(logmatch::sysinfo_id.eq(snapshot_id))
+        .order((logmatch::log.asc(), logmatch::pattern.asc()))
+        .select(LogMatch::as_select())
+        .load::<LogMatch>(conn)?;
+    Ok(Some((timestamp, matches)))
+}
+
+/// Fetches the latest `limit` counts of `pattern` in the log `log`,
+/// oldest first.
+pub fn get_logmatch_history(
+    conn: &mut SqliteConnection,
+    log: &str,
+    pattern: &str,
+    limit: i64,
+) -> Result<Vec<(String, LogMatch)>, Error> {
+    let mut history = logmatch::table
+        .inner_join(sysinfo::table)
+        .filter(logmatch::log.eq(log))
+        .filter(logmatch::pattern.eq(pattern))
+        .order(logmatch::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, LogMatch::as_select()))
+        .load::<(String, LogMatch)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the latest `limit` stored log lines, newest first, with the
+/// timestamp of the sample they were read in. Lines can be restricted to
+/// one `log` and one `pattern`.
+pub fn get_logevents(
+    conn: &mut SqliteConnection,
+    log: Option<&str>,
+    pattern: Option<&str>,
+    limit: i64,
+) -> Result<Vec<(String, LogEvent)>, Error> {
+    let mut query = logevent::table.inner_join(sysinfo::table).into_boxed();
+    if let Some(log) = log {
+        query = query.filter(logevent::log.eq(log));
+    }
+    if let Some(pattern) = pattern {
+        query = query.filter(logevent::pattern.eq(pattern));
+    }
+
+    query
+        .order(logevent::id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, LogEvent::as_select()))
+        .load::<(String, LogEvent)>(conn)
+}
+
+/// Fetches the latest measurement of every watched directory, with the
+/// timestamp of the sample it was stored with, sorted by path.
+pub fn get_latest_pathinfo(conn: &mut SqliteConnection) -> Result<Vec<(String, PathInfo)>, Error> {
+    let paths = pathinfo::table
+        .select(pathinfo::path)
+        .distinct()
+        .order(pathinfo::path.asc())
+        .load::<String>(conn)?;
+
+    let mut latest = Vec::new();
+    for path in paths {
+        latest.extend(
+            pathinfo::table
+                .inner_join(sysinfo::table)
+                .filter(pathinfo::path.eq(&path))
+                .order(pathinfo::sysinfo_id.desc())
+                .select((sysinfo::timestamp, PathInfo::as_select()))
+                .first::<(String, PathInfo)>(conn)
+                .optional()?,
+        );
+    }
+    Ok(latest)
+}
+
+/// Fetches the oldest measurement of `path` stored at or after `since`
+/// (RFC3339, UTC), with the timestamp of its sample.
+pub fn get_first_pathinfo_since(
+    conn: &mut SqliteConnection,
+    path: &str,
+    since: &str,
+) -> Result<Option<(String, PathInfo)>, Error> {
+    pathinfo::table
+        .inner_join(sysinfo::table)
+        .filter(pathinfo::path.eq(path))
+        .filter(sysinfo::timestamp.ge(since))
+        .order(pathinfo::sysinfo_id.asc())
+        .select((sysinfo::timestamp, PathInfo::as_select()))
+        .first::<(String, PathInfo)>(conn)
+        .optional()
+}
+
+/// Fetches the latest `limit` measurements of `path`, oldest first.
+pub fn get_pathinfo_history(
+    conn: &mut SqliteConnection,
+    path: &str,
+    limit: i64,
+) -> Result<Vec<(String, PathInfo)>, Error> {
+    let mut history = pathinfo::table
+        .inner_join(sysinfo::table)
+        .filter(pathinfo::path.eq(path))
+        .order(pathinfo::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, PathInfo::as_select()))
+        .load::<(String, PathInfo)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the process records of the latest snapshot taken at or before
+/// `timestamp` (RFC3339, UTC), together with that snapshot's timestamp.
+///
+/// Snapshots without stored processes are skipped, so the closest earlier
+/// sample is returned even if process collection was briefly disabled.
+pub fn get_processinfo_at(
+    conn: &mut SqliteConnection,
+    timestamp: &str,
+) -> Result<Option<(String, Vec<ProcessInfo>)>, Error> {
+    let snapshot = processinfo::table
+        .inner_join(sysinfo::table)
+        .filter(sysinfo::timestamp.le(timestamp))
+        .order(processinfo::sysinfo_id.desc())
+        .select((processinfo::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+
+    match snapshot {
+        Some((snapshot_id, snapshot_timestamp)) => {
+            let processes = get_processinfo_by_sysinfo_id(conn, snapshot_id)?;
+            Ok(Some((snapshot_timestamp, processes)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetches the process records of a SysInfo snapshot, highest CPU usage first.
+pub fn get_processinfo_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<ProcessInfo>, Error> {
+    processinfo::table
+        .filter(processinfo::sysinfo_id.eq(snapshot_id))
+        .order(processinfo::cpu_usage.desc())
+        .select(ProcessInfo::as_select())
+        .load::<ProcessInfo>(conn)
+}
+
+/// Fetches the hardware sensor records of the latest snapshot that has any,
+/// together with that snapshot's timestamp.
+pub fn get_latest_sensorinfo(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(String, Vec<SensorInfo>)>, Error> {
+    let latest = sensorinfo::table
+        .inner_join(sysinfo::table)
+        .order(sensorinfo::sysinfo_id.desc())
+        .select((sensorinfo::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+
+    match latest {
+        Some((snapshot_id, snapshot_timestamp)) => {
+            let sensors = get_sensorinfo_by_sysinfo_id(conn, snapshot_id)?;
+            Ok(Some((snapshot_timestamp, sensors)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetches the hardware sensor records of a SysInfo snapshot, ordered by chip and label.
+pub fn get_sensorinfo_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<SensorInfo>, Error> {
+    sensorinfo::table
+        .filter(sensorinfo::sysinfo_id.eq(snapshot_id))
+        .order((sensorinfo::chip.asc(), sensorinfo::label.asc()))
+        .select(SensorInfo::as_select())
+        .load::<SensorInfo>(conn)
+}
+
+/// Fetches the last `limit` readings of one sensor with their snapshot
+/// timestamps, oldest first.
+pub fn get_sensorinfo_history(
+    conn: &mut SqliteConnection,
+    chip_name: &str,
+    sensor_label: &str,
+    limit: i64,
+) -> Result<Vec<(String, SensorInfo)>, Error> {
+    let mut history = sensorinfo::table
+        .inner_join(sysinfo::table)
+        .filter(sensorinfo::chip.eq(chip_name))
+        .filter(sensorinfo::label.eq(sensor_label))
+        .order(sensorinfo::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, SensorInfo::as_select()))
+        .load::<(String, SensorInfo)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the pressure stall records of the latest snapshot that has any,
+/// together with that snapshot's timestamp.
+pub fn get_latest_psiinfo(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(String, Vec<PsiInfo>)>, Error> {
+    let latest = psiinfo::table
+        .inner_join(sysinfo::table)
+        .order(psiinfo::sysinfo_id.desc())
+        .select((psiinfo::sysinfo_id, sysinfo::timestamp))
+        .first::<(i32, String)>(conn)
+        .optional()?;
+
+    match latest {
+        Some((snapshot_id, snapshot_timestamp)) => {
+            let pressure = get_psiinfo_by_sysinfo_id(conn, snapshot_id)?;
+            Ok(Some((snapshot_timestamp, pressure)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetches the pressure stall records of a SysInfo snapshot.
+pub fn get_psiinfo_by_sysinfo_id(
+    conn: &mut SqliteConnection,
+    snapshot_id: i32,
+) -> Result<Vec<PsiInfo>, Error> {
+    psiinfo::table
+        .filter(psiinfo::sysinfo_id.eq(snapshot_id))
+        .order(psiinfo::id.asc())
+        .select(PsiInfo::as_select())
+        .load::<PsiInfo>(conn)
+}
+
+/// Fetches the last `limit` pressure records of one resource with their
+/// snapshot timestamps, oldest first.
+pub fn get_psiinfo_history(
+    conn: &mut SqliteConnection,
+    resource_name: &str,
+    limit: i64,
+) -> Result<Vec<(String, PsiInfo)>, Error> {
+    let mut history = psiinfo::table
+        .inner_join(sysinfo::table)
+        .filter(psiinfo::resource.eq(resource_name))
+        .order(psiinfo::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, PsiInfo::as_select()))
+        .load::<(String, PsiInfo)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the clock synchronization state of the latest `limit`
+/// snapshots, oldest first, with the timestamp of each snapshot.
+pub fn get_timesyncinfo_history(
+    conn: &mut SqliteConnection,
+    limit: i64,
+) -> Result<Vec<(String, TimeSyncInfo)>, Error> {
+    let mut history = timesyncinfo::table
+        .inner_join(sysinfo::table)
+        .order(timesyncinfo::sysinfo_id.desc())
+        .limit(limit)
+        .select((sysinfo::timestamp, TimeSyncInfo::as_select()))
+        .load::<(String, TimeSyncInfo)>(conn)?;
+
+    history.reverse();
+    Ok(history)
+}
+
+/// Fetches the security events read from `source` at the time of the
+/// latest one.
+///
+/// Used by the monitor on startup to skip the events it already stored
+/// when reading the logs again.
+pub fn get_latest_securityevents(
+    conn: &mut SqliteConnection,
+    event_source: &str,
+) -> Result<Vec<SecurityEvent>, Error> {
+    let latest = securityevent::table
+        .filter(securityevent::source.eq(event_source))
+        .select(diesel::dsl::max(securityevent::timestamp))
+        .first::<Option<String>>(conn)?;
+    let Some(latest) = latest else {
+        return Ok(Vec::new());
+    };
+
+    securityevent::table
+        .filter(securityevent::source.eq(event_source))
+        .filter(securityevent::timestamp.eq(latest))
+        .order(securityevent::id.asc())
+        .select(SecurityEvent::as_select())
+        .load::<SecurityEvent>(conn)
+}
+
+/// Filters of `get_securityevents`; `None` matches every event.
+#[derive(Debug, Default)]
+pub struct SecurityEventFilter<'a> {
+    pub kind: Option<&'a str>,
+    pub source_ip: Option<&'a str>,
+    pub username: Option<&'a str>,
+    /// RFC3339 time (UTC) of the oldest event returned.
+    pub since: Option<&'a str>,
+}
+
+/// Fetches the latest `limit` security events matching `filter`, newest first.
+pub fn get_securityevents(
+    conn: &mut SqliteConnection,
+    filter: &SecurityEventFilter,
+    limit: i64,
+) -> Result<Vec<SecurityEvent>, Error> {
+    let mut query = securityevent::table.into_boxed();
+    if let Some(kind) = filter.kind {
+        query = query.filter(securityevent::kind.eq(kind));
+    }
+    if let Some(source_ip) = filter.source_ip {
+        query = query.filter(securityevent::source_ip.eq(source_ip));
+    }
+    if let Some(username) = filter.username {
+        query = query.filter(securityevent::username.eq(username));
+    }
+    if let Some(since) = filter.since {
+        query = query.filter(securityevent::timestamp.ge(since));
+    }
+
+    query
+        .order((securityevent::timestamp.desc(), securityevent::id.desc()))
+        .limit(limit)
+        .select(SecurityEvent::as_select())
+        .load::<SecurityEvent>(conn)
+}
+
+/// Fetches the source address, kind and time of every SSH attempt since
+/// `since` (RFC3339, UTC), oldest first.
+pub fn get_auth_attempts_since(
+    conn: &mut SqliteConnection,
+    since: &str,
+) -> Result<Vec<(String, String, String)>, Error> {
+    let attempts = securityevent::table
+        .filter(securityevent::timestamp.ge(since))
+        .filter(securityevent::kind.ne("login"))
+        .order(securityevent::timestamp.asc())
+        .select((
+            securityevent::source_ip,
+            securityevent::kind,
+            securityevent::timestamp,
+        ))
+        .load::<(Option<String>, String, String)>(conn)?;
+
+    Ok(attempts
+        .into_iter()
+        .filter_map(|(ip, kind, timestamp)| Some((ip?, kind, timestamp)))
+        .collect())
+}
+
+/// Fetches the latest `limit` kernel events, newest first, optionally only
+/// those of `category` and since `since` (RFC3339, UTC).
+pub fn get_kernelevents(
+    conn: &mut SqliteConnection,
+    category: Option<&str>,
+    since: Option<&str>,
+    limit: i64,
+) -> Result<Vec<KernelEvent>, Error> {
+    let mut query = kernelevent::table.into_boxed();
+    if let Some(category) = category {
+        query = query.filter(kernelevent::category.eq(category));
+    }
+    if let Some(since) = since {
+        query = query.filter(kernelevent::timestamp.ge(since));
+    }
+
+    query
+        .order((kernelevent::timestamp.desc(), kernelevent::id.desc()))
+        .limit(limit)
+        .select(KernelEvent::as_select())
+        .load::<KernelEvent>(conn)
+}
+
+/// Fetches the package state of the latest check, with its pending
+/// updates sorted by name.
+pub fn get_latest_packagestatus(
+    conn: &mut SqliteConnection,
+) -> Result<Option<(PackageStatus, Vec<PackageUpdate>)>, Error> {
+    let Some(status) = packagestatus::table
+        .order(packagestatus::day.desc())
+        .select(PackageStatus::as_select())
+        .first::<PackageStatus>(conn)
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let updates = packageupdate::table
+        .filter(packageupdate::day.eq(&status.day))
+        .order(packageupdate::name.asc())
+        .select(PackageUpdate::as_select())
+        .load::<PackageUpdate>(conn)?;
+    Ok(Some((status, updates)))
+}
+
+/// Fetches the package state of the latest `limit` days, oldest first.
+pub fn get_packagestatus_history(
+    conn: &mut SqliteConnection,
+    limit: i64,
+) -> Result<Vec<PackageStatus>, Error> {
+    let mut history = packagestatus::table
+        .order(packagestatus::day.desc())
+        .limit(limit)
+        .select(PackageStatus::as_select())
+        .load::<PackageStatus>(conn)?;
+    history.reverse();
+    Ok(history)
+}
@@ -0,0 +1,547 @@
+//! Retention of the metric history, from `[database.retention]`.
+//!
+//! Samples (`sysinfo` rows) expire by age or beyond a maximum count, and
+//! are deleted together with every row stored with them. Deletes run in
+//! batches, each in its own transaction and under its own lock of the
+//! connection, so the monitor and the API get the database between two
+//! batches.
+//!
+//! With rollups, the job first rolls the samples up (see `rollup`), and
+//! only deletes samples and rollups once they are rolled up into the next
+//! resolution.
+
+use crate::rollup::{self, Resolution};
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::Integer;
+use std::time::Duration;
+use teus_database::storage::Storage;
+use teus_schema::schema::{kernelevent, metricrollup, securityevent, sysinfo};
+use teus_types::config::RetentionConfig;
+use tokio::sync::watch;
+use tokio::task;
+use tokio::time::{self, MissedTickBehavior};
+
+/// Rows deleted by a pruning.
+#[derive(Debug, Default, PartialEq)]
+pub struct PruneStats {
+    /// Samples, not counting the rows stored with them.
+    pub samples: usize,
+    /// Security and kernel events.
+    pub events: usize,
+    /// Minute and hour rollups.
+    pub rollups: usize,
+}
+
+/// Returns the ID of the newest expired sample: taken before `cutoff`
+/// (RFC3339, UTC) or older than the latest `max_rows` samples. `None` when
+/// no sample expired.
+pub fn expired_sysinfo_bound(
+    conn: &mut SqliteConnection,
+    cutoff: Option<&str>,
+    max_rows: Option<u64>,
+) -> Result<Option<i32>, Error> {
+    let mut bound = None;
+    if let Some(cutoff) = cutoff {
+        bound = sysinfo::table
+            .filter(sysinfo::timestamp.lt(cutoff))
+            .select(diesel::dsl::max(sysinfo::id))
+            .first::<Option<i32>>(conn)?;
+    }
+    if let Some(max_rows) = max_rows {
+        let beyond = sysinfo::table
+            .order(sysinfo::id.desc())
+            .select(sysinfo::id)
+            .offset(max_rows as i64)
+            .first::<Option<i32>>(conn)
+            .optional()?
+            .flatten();
+        bound = bound.max(beyond);
+    }
+    Ok(bound)
+}
+
+/// Deletes the oldest samples up to ID `bound`, at most `batch_size` of
+/// them, with the rows stored with them. Returns the number of samples
+/// deleted.
+pub fn prune_sysinfo_batch(
+    conn: &mut SqliteConnection,
+    bound: i32,
+    batch_size: i64,
+) -> Result<usize, Error> {
+    conn.transaction(|conn| {
+        let ids = sysinfo::table
+            .filter(sysinfo::id.le(bound))
+            .order(sysinfo::id.asc())
+            .limit(batch_size)
+            .select(sysinfo::id)
+            .load::<Option<i32>>(conn)?;
+        let Some(batch_end) = ids.last().copied().flatten() else {
+            return Ok(0);
+        };
+
+        // The rows of the samples go with them, through ON DELETE CASCADE
+        diesel::delete(sysinfo::table.filter(sysinfo::id.le(batch_end))).execute(conn)
+    })
+}
+
+/// Deletes at most `batch_size` security events and as many kernel events
+/// logged before `cutoff` (RFC3339, UTC). Returns the number of events
+/// deleted.
+pub fn prune_events_batch(
+    conn: &mut SqliteConnection,
+    cutoff: &str,
+    batch_size: i64,
+) -> Result<usize, Error> {
+    conn.transaction(|conn| {
+        let security_ids = securityevent::table
+            .filter(securityevent::timestamp.lt(cutoff))
+            .limit(batch_size)
+            .select(securityevent::id)
+            .load::<Option<i32>>(conn)?;
+        let kernel_ids = kernelevent::table
+            .filter(kernelevent::timestamp.lt(cutoff))
+            .limit(batch_size)
+            .select(kernelevent::id)
+            .load::<Option<i32>>(conn)?;
+
+        Ok(
+            diesel::delete(securityevent::table.filter(securityevent::id.eq_any(security_ids)))
+                .execute(conn)?
+                + diesel::delete(kernelevent::table.filter(kernelevent::id.eq_any(kernel_ids)))
+                    .execute(conn)?,
+        )
+    })
+}
+
+/// Deletes at most `batch_size` rollups of `resolution` whose bucket
+/// starts before `cutoff` (RFC3339, UTC). Returns the number of rollups
+/// deleted.
+pub fn prune_rollups_batch(
+    conn: &mut SqliteConnection,
+    resolution: Resolution,
+    cutoff: &str,
+    batch_size: i64,
+) -> Result<usize, Error> {
+    let ids = metricrollup::table
+        .filter(metricrollup::resolution_secs.eq(resolution.secs() as i32))
+        .filter(metricrollup::bucket.lt(cutoff))
+        .limit(batch_size)
+        .select(metricrollup::id)
+        .load::<Option<i32>>(conn)?;
+    diesel::delete(metricrollup::table.filter(metricrollup::id.eq_any(ids))).execute(conn)
+}
+
+#[derive(QueryableByName)]
+struct AutoVacuum {
+    #[diesel(sql_type = Integer)]
+    auto_vacuum: i32,
+}
+
+/// Whether the database is in incremental auto-vacuum mode.
+pub fn is_incremental_vacuum(conn: &mut SqliteConnection) -> Result<bool, Error> {
+    // 2 is INCREMENTAL
+    Ok(diesel::sql_query("PRAGMA auto_vacuum")
+        .get_result::<AutoVacuum>(conn)?
+        .auto_vacuum
+        == 2)
+}
+
+/// Puts the database in incremental auto-vacuum mode, with a `VACUUM` that
+/// rewrites the whole file once. Returns false when it already was.
+///
+/// The rewrite takes as long as copying the database and blocks every
+/// other connection meanwhile, so it runs before the monitor and the API
+/// start, never from the retention job.
+pub fn enable_incremental_vacuum(conn: &mut SqliteConnection) -> Result<bool, Error> {
+    if is_incremental_vacuum(conn)? {
+        return Ok(false);
+    }
+    conn.batch_execute("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+    Ok(true)
+}
+
+/// Gives the free pages of the database back to the filesystem. Does
+/// nothing until the database is converted by `enable_incremental_vacuum`.
+pub fn incremental_vacuum(conn: &mut SqliteConnection) -> Result<(), Error> {
+    if !is_incremental_vacuum(conn)? {
+        return Ok(());
+    }
+    conn.batch_execute("PRAGMA incremental_vacuum;")
+}
+
+/// Deletes the history expired at `now` under `retention`, locking the
+/// connection once per batch. Stops between two batches when `shutdown`
+/// is set.
+pub fn prune(
+    storage: &Storage,
+    retention: &RetentionConfig,
+    now: DateTime<Utc>,
+    shutdown: &watch::Receiver<bool>,
+) -> Result<PruneStats, Box<dyn std::error::Error>> {
+    let lock = || {
+        storage
+            .write()
+            .map_err(|_| "Mutex poisoned while pruning the history")
+    };
+    let cutoff = retention
+        .max_age_days
+        .map(|days| (now - TimeDelta::days(days as i64)).to_rfc3339());
+    let batch_size = retention.batch_size.max(1) as i64;
+    let mut stats = PruneStats::default();
+
+    let mut bound = expired_sysinfo_bound(&mut *lock()?, cutoff.as_deref(), retention.max_rows)?;
+    if retention.rollups && bound.is_some() {
+        // Samples not rolled up yet are kept
+        let rolled_up = rollup::rolled_up_until(&mut *lock()?, Resolution::Minute)?;
+        bound = match rolled_up {
+            Some(until) => bound.min(expired_sysinfo_bound(
+                &mut *lock()?,
+                Some(&until.to_rfc3339()),
+                None,
+            )?),
+            None => None,
+        };
+    }
+    if let Some(bound) = bound {
+        while !*shutdown.borrow() {
+            let deleted = prune_sysinfo_batch(&mut *lock()?, bound, batch_size)?;
+            stats.samples += deleted;
+            if deleted < batch_size as usize {
+                break;
+            }
+        }
+    }
+    if let Some(cutoff) = &cutoff {
+        while !*shutdown.borrow() {
+            let deleted = prune_events_batch(&mut *lock()?, cutoff, batch_size)?;
+            stats.events += deleted;
+            if deleted == 0 {
+                break;
+            }
+        }
+    }
+
+    for (resolution, days, next) in [
+        (
+            Resolution::Minute,
+            retention.minute_rollup_days,
+            Resolution::Hour,
+        ),
+        (
+            Resolution::Hour,
+            retention.hour_rollup_days,
+            Resolution::Day,
+        ),
+    ] {
+        let mut rollup_cutoff = now - TimeDelta::days(days as i64);
+        if retention.rollups {
+            // Rollups not rolled up into the next resolution yet are kept
+            match rollup::rolled_up_until(&mut *lock()?, next)? {
+                Some(until) => rollup_cutoff = rollup_cutoff.min(until),
+                None => continue,
+            }
+        }
+        let rollup_cutoff = rollup_cutoff.to_rfc3339();
+        while !*shutdown.borrow() {
+            let deleted =
+                prune_rollups_batch(&mut *lock()?, resolution, &rollup_cutoff, batch_size)?;
+            stats.rollups += deleted;
+            if deleted < batch_size as usize {
+                break;
+            }
+        }
+    }
+
+    if retention.incremental_vacuum && !*shutdown.borrow() {
+        incremental_vacuum(&mut *lock()?)?;
+    }
+    Ok(stats)
+}
+
+/// Background job rolling up the samples and deleting the expired
+/// history, every `interval_secs` of `[database.retention]`.
+pub struct Pruner {
+    retention: RetentionConfig,
+}
+
+impl Pruner {
+    pub fn new(retention: RetentionConfig) -> Self {
+        Self { retention }
+    }
+
+    /// Compacts and prunes the history right away, then every interval
+    /// until `shutdown` is set or dropped.
+    pub async fn run(self, storage: Storage, mut shutdown: watch::Receiver<bool>) {
+        let mut ticks = time::interval(Duration::from_secs(self.retention.interval_secs.max(1)));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+
+            /* deletes are blocking, keep them off the async workers */
+            let storage = storage.clone();
+            let retention = self.retention.clone();
+            let stop = shutdown.clone();
+            let pruning = task::spawn_blocking(move || {
+                let now = Utc::now();
+                if retention.rollups
+                    && let Err(e) = rollup::compact(&storage, now, &stop)
+                {
+                    eprintln!("Failed to roll up the history: {}", e); // TODO: Use log::error!
+                }
+                match prune(&storage, &retention, now, &stop) {
+                    Ok(stats) if stats != PruneStats::default() => println!(
+                        "Pruned {} samples, {} events and {} rollups from the history",
+                        stats.samples, stats.events, stats.rollups
+                    ),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to prune the history: {}", e), // TODO: Use log::error!
+                }
+            });
+            if let Err(e) = pruning.await {
+                eprintln!("Pruning failed, stopping the retention job: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::SysInfo;
+    use crate::test_utils::migrated_storage;
+
+    #[derive(QueryableByName)]
+    struct Name {
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        name: String,
+    }
+
+    #[derive(QueryableByName)]
+    struct Count {
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        count: i64,
+    }
+
+    fn count(conn: &mut SqliteConnection, table: &str) -> i64 {
+        diesel::sql_query(format!("SELECT COUNT(*) AS count FROM {}", table))
+            .get_result::<Count>(conn)
+            .unwrap()
+            .count
+    }
+
+    /// Stores a sample taken `minute` minutes after 10:00, with a disk and
+    /// a log line.
+    fn store_sample(conn: &mut SqliteConnection, minute: u32) -> i32 {
+        let info = SysInfo {
+            timestamp: format!("2024-01-15T10:{:02}:00+00:00", minute),
+            ..SysInfo::default()
+        };
+        let id = info.store(conn).unwrap().id;
+        conn.batch_execute(&format!(
+            "INSERT INTO diskinfo (sysinfo_id, filesystem, size, used, available, used_percentage, mounted_path) \
+             VALUES ({id}, 'ext4', 100, 50, 50, 50, '/');
+             INSERT INTO logevent (sysinfo_id, log, pattern, line) VALUES ({id}, 'app', 'error', 'ERROR');"
+        ))
+        .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_child_tables_cascade() {
+        let (_dir, storage) = migrated_storage();
+        let mut conn = storage.write().unwrap();
+
+        // Every table referencing a sample must be pruned with it
+        let not_cascading: Vec<String> = diesel::sql_query(
+            "SELECT m.name AS name FROM sqlite_master m, pragma_table_info(m.name) c \
+             WHERE m.type = 'table' AND c.name = 'sysinfo_id' AND NOT EXISTS ( \
+                 SELECT 1 FROM pragma_foreign_key_list(m.name) f \
+                 WHERE f.\"from\" = 'sysinfo_id' AND f.\"table\" = 'sysinfo' \
+                 AND f.on_delete = 'CASCADE')",
+        )
+        .load::<Name>(&mut *conn)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+        assert!(not_cascading.is_empty(), "{:?}", not_cascading);
+    }
+
+    #[test]
+    fn test_expired_sysinfo_bound() {
+        let (_dir, storage) = migrated_storage();
+        let mut conn = storage.write().unwrap();
+        assert_eq!(
+            expired_sysinfo_bound(&mut conn, Some("2024-01-15T10:03:00+00:00"), Some(2)).unwrap(),
+            None
+        );
+
+        let ids: Vec<i32> = (0..5)
+            .map(|minute| store_sample(&mut conn, minute))
+            .collect();
+        let bound = |conn: &mut SqliteConnection, cutoff, max_rows| {
+            expired_sysinfo_bound(conn, cutoff, max_rows).unwrap()
+        };
+        assert_eq!(bound(&mut conn, None, None), None);
+        assert_eq!(
+            bound(&mut conn, Some("2024-01-15T10:02:00+00:00"), None),
+            Some(ids[1])
+        );
+        assert_eq!(bound(&mut conn, None, Some(2)), Some(ids[2]));
+        assert_eq!(bound(&mut conn, None, Some(5)), None);
+        // The stricter limit wins
+        assert_eq!(
+            bound(&mut conn, Some("2024-01-15T10:02:00+00:00"), Some(4)),
+            Some(ids[1])
+        );
+    }
+
+    #[test]
+    fn test_prune_deletes_samples_with_their_rows_in_batches() {
+        let (_dir, storage) = migrated_storage();
+        let ids: Vec<i32> = {
+            let mut conn = storage.write().unwrap();
+            // Pruning doesn't convert the database by itself
+            incremental_vacuum(&mut conn).unwrap();
+            assert!(!is_incremental_vacuum(&mut conn).unwrap());
+            assert!(enable_incremental_vacuum(&mut conn).unwrap());
+            assert!(!enable_incremental_vacuum(&mut conn).unwrap());
+            (0..10)
+                .map(|minute| store_sample(&mut conn, minute))
+                .collect()
+        };
+
+        let retention = RetentionConfig {
+            max_rows: Some(3),
+            batch_size: 2,
+            incremental_vacuum: true,
+            rollups: false,
+            ..Default::default()
+        };
+        let (_shutdown_tx, shutdown) = watch::channel(false);
+        let stats = prune(&storage, &retention, Utc::now(), &shutdown).unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                samples: 7,
+                events: 0,
+                rollups: 0
+            }
+        );
+
+        let mut conn = storage.write().unwrap();
+        let kept = sysinfo::table
+            .order(sysinfo::id.asc())
+            .select(sysinfo::id)
+            .load::<Option<i32>>(&mut *conn)
+            .unwrap();
+        assert_eq!(kept, vec![Some(ids[7]), Some(ids[8]), Some(ids[9])]);
+        assert_eq!(count(&mut conn, "diskinfo"), 3);
+        assert_eq!(count(&mut conn, "logevent"), 3);
+    }
+
+    #[test]
+    fn test_prune_by_age_deletes_old_events() {
+        let (_dir, storage) = migrated_storage();
+        {
+            let mut conn = storage.write().unwrap();
+            for minute in 0..4 {
+                store_sample(&mut conn, minute);
+            }
+            conn.batch_execute(
+                "INSERT INTO kernelevent (boot_id, seq, timestamp, level, category, message) \
+                 VALUES ('boot', 1, '2024-01-14T10:00:00+00:00', 3, 'oom_kill', 'old'), \
+                        ('boot', 2, '2024-01-15T10:03:00+00:00', 3, 'oom_kill', 'new');",
+            )
+            .unwrap();
+        }
+
+        let retention = RetentionConfig {
+            max_age_days: Some(1),
+            rollups: false,
+            ..Default::default()
+        };
+        let now = DateTime::parse_from_rfc3339("2024-01-16T10:02:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (_shutdown_tx, shutdown) = watch::channel(false);
+        let stats = prune(&storage, &retention, now, &shutdown).unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                samples: 2,
+                events: 1,
+                rollups: 0
+            }
+        );
+
+        let mut conn = storage.write().unwrap();
+        assert_eq!(count(&mut conn, "sysinfo"), 2);
+        assert_eq!(count(&mut conn, "kernelevent"), 1);
+    }
+
+    #[test]
+    fn test_prune_keeps_what_is_not_rolled_up() {
+        let (_dir, storage) = migrated_storage();
+        {
+            let mut conn = storage.write().unwrap();
+            for minute in 0..10 {
+                store_sample(&mut conn, minute);
+            }
+        }
+        let at = |timestamp: &str| {
+            DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let retention = RetentionConfig {
+            max_rows: Some(3),
+            minute_rollup_days: 0,
+            ..Default::default()
+        };
+        let (_shutdown_tx, shutdown) = watch::channel(false);
+
+        // Only the minutes before 10:05 are rolled up, and the hour isn't
+        let now = at("2024-01-15T10:05:30+00:00");
+        rollup::compact(&storage, now, &shutdown).unwrap();
+        let stats = prune(&storage, &retention, now, &shutdown).unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                samples: 5,
+                events: 0,
+                rollups: 0
+            }
+        );
+
+        // Minutes are deleted once rolled up into the hour
+        let now = at("2024-01-15T11:00:30+00:00");
+        rollup::compact(&storage, now, &shutdown).unwrap();
+        let stats = prune(&storage, &retention, now, &shutdown).unwrap();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(
+            stats.rollups,
+            10 * (rollup::SYSINFO_METRICS.len() + rollup::DISK_METRICS.len())
+        );
+
+        let mut conn = storage.write().unwrap();
+        assert_eq!(count(&mut conn, "sysinfo"), 3);
+        let hours = metricrollup::table
+            .filter(metricrollup::resolution_secs.eq(3600))
+            .count()
+            .get_result::<i64>(&mut *conn)
+            .unwrap();
+        assert_eq!(
+            hours as usize,
+            rollup::SYSINFO_METRICS.len() + rollup::DISK_METRICS.len()
+        );
+    }
+}
@@ -0,0 +1,1175 @@
+//! Downsampled history: 1-minute, 1-hour and 1-day rollups.
+//!
+//! The host metrics of every sample (CPU, memory, load) and the usage of
+//! every mounted disk are summarized per bucket as their average, minimum,
+//! maximum and last value. Minutes are rolled up from the raw samples,
+//! hours from the minutes and days from the hours, by the retention job
+//! before it deletes anything, so the rollups outlive the raw samples.
+//!
+//! Series are read back with `plan_segments` and `read_segment`: every
+//! part of a range comes from the coarsest source whose buckets still fit
+//! in the requested step, and the finer sources fill what the compactor
+//! hasn't rolled up yet. `StepAggregator` then merges the points into
+//! steps.
+
+use crate::mutation;
+use crate::schema::{DiskInfo, MetricRollup, SchemaMetricRollup, SysInfo};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use diesel::prelude::*;
+use diesel::result::Error;
+use std::collections::{BTreeMap, HashMap};
+use teus_database::storage::Storage;
+use teus_schema::schema::{diskinfo, metricrollup, sysinfo};
+use tokio::sync::watch;
+
+/// Host metrics, named after their `sysinfo` column.
+pub const SYSINFO_METRICS: [&str; 13] = [
+    "cpu_usage",
+    "ram_usage",
+    "free_ram",
+    "used_swap",
+    "load_avg_one",
+    "load_avg_five",
+    "load_avg_fifteen",
+    "available_ram",
+    "cached_ram",
+    "buffers_ram",
+    "shared_ram",
+    "free_swap",
+    "dirty_ram",
+];
+
+/// Disk metrics, with one series per mount point.
+pub const DISK_METRICS: [&str; 4] = [
+    "disk_used",
+    "disk_available",
+    "disk_used_percentage",
+    "disk_inodes_used_percentage",
+];
+
+/// Values of the host metrics of a sample, in the order of
+/// `SYSINFO_METRICS`.
+pub fn sysinfo_metric_values(s: &SysInfo) -> [(&'static str, f64); 13] {
+    [
+        ("cpu_usage", s.cpu_usage as f64),
+        ("ram_usage", s.ram_usage as f64),
+        ("free_ram", s.free_ram as f64),
+        ("used_swap", s.used_swap as f64),
+        ("load_avg_one", s.load_avg_one as f64),
+        ("load_avg_five", s.load_avg_five as f64),
+        ("load_avg_fifteen", s.load_avg_fifteen as f64),
+        ("available_ram", s.available_ram as f64),
+        ("cached_ram", s.cached_ram as f64),
+        ("buffers_ram", s.buffers_ram as f64),
+        ("shared_ram", s.shared_ram as f64),
+        ("free_swap", s.free_swap as f64),
+        ("dirty_ram", s.dirty_ram as f64),
+    ]
+}
+
+/// Values of the disk metrics of a mount point, in the order of
+/// `DISK_METRICS`.
+pub fn disk_metric_values(d: &DiskInfo) -> [(&'static str, f64); 4] {
+    [
+        ("disk_used", d.used as f64),
+        ("disk_available", d.available as f64),
+        ("disk_used_percentage", d.used_percentage as f64),
+        (
+            "disk_inodes_used_percentage",
+            d.inodes_used_percentage as f64,
+        ),
+    ]
+}
+
+/// Length of the buckets of a rollup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Resolution {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Resolution {
+    pub fn secs(self) -> i64 {
+        match self {
+            Resolution::Minute => 60,
+            Resolution::Hour => 3600,
+            Resolution::Day => 86400,
+        }
+    }
+
+    /// Start of the bucket containing `time`. Days start at midnight UTC.
+    pub fn truncate(self, time: DateTime<Utc>) -> DateTime<Utc> {
+        time.duration_trunc(TimeDelta::seconds(self.secs()))
+            .unwrap_or(time)
+    }
+
+    /// Buckets rolled up per transaction, as the span of time they cover.
+    fn window(self) -> TimeDelta {
+        match self {
+            Resolution::Minute => TimeDelta::hours(1),
+            Resolution::Hour => TimeDelta::days(1),
+            Resolution::Day => TimeDelta::days(30),
+        }
+    }
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Average, minimum, maximum and last value of consecutive values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aggregate {
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+    pub last: f64,
+    /// Raw samples summarized.
+    pub count: i64,
+}
+
+impl Aggregate {
+    pub fn new(value: f64) -> Self {
+        Self {
+            avg: value,
+            min: value,
+            max: value,
+            last: value,
+            count: 1,
+        }
+    }
+
+    /// Adds the values summarized by `later`, which come after these.
+    pub fn merge(&mut self, later: &Aggregate) {
+        let count = self.count + later.count;
+        if count > 0 {
+            self.avg =
+                (self.avg * self.count as f64 + later.avg * later.count as f64) / count as f64;
+        }
+        self.min = self.min.min(later.min);
+        self.max = self.max.max(later.max);
+        self.last = later.last;
+        self.count = count;
+    }
+}
+
+impl From<&MetricRollup> for Aggregate {
+    fn from(r: &MetricRollup) -> Self {
+        Self {
+            avg: r.avg_value,
+            min: r.min_value,
+            max: r.max_value,
+            last: r.last_value,
+            count: r.sample_count,
+        }
+    }
+}
+
+/* aggregates of a window, keyed by bucket, metric and series */
+type Buckets = BTreeMap<(DateTime<Utc>, String, String), Aggregate>;
+
+fn add_to_bucket(buckets: &mut Buckets, key: (DateTime<Utc>, String, String), value: Aggregate) {
+    buckets
+        .entry(key)
+        .and_modify(|a| a.merge(&value))
+        .or_insert(value);
+}
+
+fn write_buckets(
+    conn: &mut SqliteConnection,
+    resolution: Resolution,
+    buckets: Buckets,
+) -> Result<usize, Error> {
+    let rows: Vec<SchemaMetricRollup> = buckets
+        .into_iter()
+        .map(|((bucket, metric, series), a)| SchemaMetricRollup {
+            resolution_secs: resolution.secs() as i32,
+            bucket: bucket.to_rfc3339(),
+            metric,
+            series,
+            avg_value: a.avg,
+            min_value: a.min,
+            max_value: a.max,
+            last_value: a.last,
+            sample_count: a.count,
+        })
+        .collect();
+
+    let mut written = 0;
+    for chunk in rows.chunks(mutation::INSERT_CHUNK) {
+        written += diesel::replace_into(metricrollup::table)
+            .values(chunk)
+            .execute(conn)?;
+    }
+    Ok(written)
+}
+
+/// Start of the first bucket of `resolution` not rolled up yet, `None` when
+/// there is no rollup of that resolution.
+pub fn rolled_up_until(
+    conn: &mut SqliteConnection,
+    resolution: Resolution,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    let latest = metricrollup::table
+        .filter(metricrollup::resolution_secs.eq(resolution.secs() as i32))
+        .select(diesel::dsl::max(metricrollup::bucket))
+        .first::<Option<String>>(conn)?;
+    Ok(latest
+        .as_deref()
+        .and_then(parse_timestamp)
+        .map(|bucket| bucket + TimeDelta::seconds(resolution.secs())))
+}
+
+/// Rolls up the next hour of raw samples taken before the minute of
+/// `until` into 1-minute buckets, starting after the latest minute rolled
+/// up. Returns the number of rollups written, 0 once up to date.
+pub fn compact_samples_batch(
+    conn: &mut SqliteConnection,
+    until: DateTime<Utc>,
+) -> Result<usize, Error> {
+    conn.transaction(|conn| {
+        let until = Resolution::Minute.truncate(until);
+        let start = rolled_up_until(conn, Resolution::Minute)?
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        let first = sysinfo::table
+            .filter(sysinfo::timestamp.ge(&start))
+            .filter(sysinfo::timestamp.lt(until.to_rfc3339()))
+            .select(diesel::dsl::min(sysinfo::timestamp))
+            .first::<Option<String>>(conn)?;
+        let Some(first) = first.as_deref().and_then(parse_timestamp) else {
+            return Ok(0);
+        };
+        let window_start = Resolution::Minute.truncate(first);
+        let window_end = (window_start + Resolution::Minute.window()).min(until);
+
+        let samples = sysinfo::table
+            .filter(sysinfo::timestamp.ge(window_start.to_rfc3339()))
+            .filter(sysinfo::timestamp.lt(window_end.to_rfc3339()))
+            .order(sysinfo::timestamp.asc())
+            .select(SysInfo::as_select())
+            .load::<SysInfo>(conn)?;
+
+        let mut buckets = Buckets::new();
+        let mut sample_buckets = HashMap::new();
+        for sample in &samples {
+            let Some(time) = parse_timestamp(&sample.timestamp) else {
+                continue;
+            };
+            let bucket = Resolution::Minute.truncate(time);
+            for (metric, value) in sysinfo_metric_values(sample) {
+                add_to_bucket(
+                    &mut buckets,
+                    (bucket, metric.to_string(), String::new()),
+                    Aggregate::new(value),
+                );
+            }
+            if let Some(id) = sample.id {
+                sample_buckets.insert(id, bucket);
+            }
+        }
+
+        // Disks are collected on their own schedule, not with every sample
+        let ids: Vec<i32> = sample_buckets.keys().copied().collect();
+        let disks = diskinfo::table
+            .filter(diskinfo::sysinfo_id.eq_any(&ids))
+            .order(diskinfo::sysinfo_id.asc())
+            .select(DiskInfo::as_select())
+            .load::<DiskInfo>(conn)?;
+        for disk in &disks {
+            let bucket = sample_buckets[&disk.sysinfo_id];
+            for (metric, value) in disk_metric_values(disk) {
+                add_to_bucket(
+                    &mut buckets,
+                    (bucket, metric.to_string(), disk.mounted_path.clone()),
+                    Aggregate::new(value),
+                );
+            }
+        }
+
+        write_buckets(conn, Resolution::Minute, buckets)
+    })
+}
+
+/// Rolls up the next window of `from` rollups ending before the bucket of
+/// `until` into `to` buckets, starting after the latest `to` bucket.
+/// Returns the number of rollups written, 0 once up to date.
+pub fn compact_rollups_batch(
+    conn: &mut SqliteConnection,
+    from: Resolution,
+    to: Resolution,
+    until: DateTime<Utc>,
+) -> Result<usize, Error> {
+    conn.transaction(|conn| {
+        let until = to.truncate(until);
+        let start = rolled_up_until(conn, to)?
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        let first = metricrollup::table
+            .filter(metricrollup::resolution_secs.eq(from.secs() as i32))
+            .filter(metricrollup::bucket.ge(&start))
+            .filter(metricrollup::bucket.lt(until.to_rfc3339()))
+            .select(diesel::dsl::min(metricrollup::bucket))
+            .first::<Option<String>>(conn)?;
+        let Some(first) = first.as_deref().and_then(parse_timestamp) else {
+            return Ok(0);
+        };
+        let window_start = to.truncate(first);
+        let window_end = (window_start + to.window()).min(until);
+
+        let rollups = metricrollup::table
+            .filter(metricrollup::resolution_secs.eq(from.secs() as i32))
+            .filter(metricrollup::bucket.ge(window_start.to_rfc3339()))
+            .filter(metricrollup::bucket.lt(window_end.to_rfc3339()))
+            .order(metricrollup::bucket.asc())
+            .select(MetricRollup::as_select())
+            .load::<MetricRollup>(conn)?;
+
+        let mut buckets = Buckets::new();
+        for rollup in &rollups {
+            let Some(time) = parse_timestamp(&rollup.bucket) else {
+                continue;
+            };
+            add_to_bucket(
+                &mut buckets,
+                (
+                    to.truncate(time),
+                    rollup.metric.clone(),
+                    rollup.series.clone(),
+                ),
+                Aggregate::from(rollup),
+            );
+        }
+
+        write_buckets(conn, to, buckets)
+    })
+}
+
+/// Rolls up every bucket complete at `now`: minutes, then hours, then
+/// days. Locks the connection once per batch and stops between two
+/// batches when `shutdown` is set. Returns the number of rollups written.
+pub fn compact(
+    storage: &Storage,
+    now: DateTime<Utc>,
+    shutdown: &watch::Receiver<bool>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let lock = || {
+        storage
+            .write()
+            .map_err(|_| "Mutex poisoned while compacting the history")
+    };
+    let mut written = 0;
+
+    while !*shutdown.borrow() {
+        let batch = compact_samples_batch(&mut *lock()?, now)?;
+        written += batch;
+        if batch == 0 {
+            break;
+        }
+    }
+    for (from, to) in [
+        (Resolution::Minute, Resolution::Hour),
+        (Resolution::Hour, Resolution::Day),
+    ] {
+        while !*shutdown.borrow() {
+            let batch = compact_rollups_batch(&mut *lock()?, from, to, now)?;
+            written += batch;
+            if batch == 0 {
+                break;
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Where the points of a series are read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Raw,
+    Rollup(Resolution),
+}
+
+impl Source {
+    /* finest first */
+    const ALL: [Source; 4] = [
+        Source::Raw,
+        Source::Rollup(Resolution::Minute),
+        Source::Rollup(Resolution::Hour),
+        Source::Rollup(Resolution::Day),
+    ];
+
+    /// Length of the buckets of the source, 0 for raw samples.
+    pub fn secs(self) -> i64 {
+        match self {
+            Source::Raw => 0,
+            Source::Rollup(resolution) => resolution.secs(),
+        }
+    }
+}
+
+/// Part of a range read from a single source, from `from` included to `to`
+/// excluded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub source: Source,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/* start included and end excluded */
+type Span = (DateTime<Utc>, DateTime<Utc>);
+
+/// Time span holding data in `source`, `None` when it's empty. Raw samples
+/// cover everything after the oldest one.
+fn coverage(conn: &mut SqliteConnection, source: Source) -> Result<Option<Span>, Error> {
+    match source {
+        Source::Raw => {
+            let oldest = sysinfo::table
+                .select(diesel::dsl::min(sysinfo::timestamp))
+                .first::<Option<String>>(conn)?;
+            Ok(oldest
+                .as_deref()
+                .and_then(parse_timestamp)
+                .map(|oldest| (oldest, DateTime::<Utc>::MAX_UTC)))
+        }
+        Source::Rollup(resolution) => {
+            let (oldest, latest) = metricrollup::table
+                .filter(metricrollup::resolution_secs.eq(resolution.secs() as i32))
+                .select((
+                    diesel::dsl::min(metricrollup::bucket),
+                    diesel::dsl::max(metricrollup::bucket),
+                ))
+                .first::<(Option<String>, Option<String>)>(conn)?;
+            Ok(oldest
+                .as_deref()
+                .and_then(parse_timestamp)
+                .zip(latest.as_deref().and_then(parse_timestamp))
+                .map(|(oldest, latest)| (oldest, latest + TimeDelta::seconds(resolution.secs()))))
+        }
+    }
+}
+
+/// Splits `from..to` into the segments to read for points every
+/// `step_secs`.
+///
+/// Every instant is read from the coarsest source whose buckets fit in a
+/// step and that holds data there: the rollups for the past, the finer
+/// rollups and the raw samples for what isn't compacted yet. Where none of
+/// them holds data, because it was pruned, the finest coarser source is
+/// used instead. Spans without data in any source are left out.
+pub fn plan_segments(
+    conn: &mut SqliteConnection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step_secs: i64,
+) -> Result<Vec<Segment>, Error> {
+    let preferred = Source::ALL
+        .iter()
+        .rev()
+        .filter(|s| s.secs() <= step_secs)
+        .chain(Source::ALL.iter().filter(|s| s.secs() > step_secs));
+    let mut sources = Vec::new();
+    for &source in preferred {
+        if let Some((start, end)) = coverage(conn, source)? {
+            sources.push((source, start, end));
+        }
+    }
+
+    let mut segments = Vec::new();
+    let mut cursor = from;
+    while cursor < to {
+        let Some(index) = sources
+            .iter()
+            .position(|(_, start, end)| *start <= cursor && cursor < *end)
+        else {
+            // Nothing at the cursor, skip to the next source starting later
+            match sources
+                .iter()
+                .map(|(_, start, _)| *start)
+                .filter(|start| *start > cursor)
+                .min()
+            {
+                Some(next) => {
+                    cursor = next;
+                    continue;
+                }
+                None => break,
+            }
+        };
+        let (source, _, end) = sources[index];
+        // Until a preferred source starts
+        let segment_end = sources[..index]
+            .iter()
+            .map(|(_, start, _)| *start)
+            .filter(|start| *start > cursor)
+            .fold(end.min(to), |a, b| a.min(b));
+        segments.push(Segment {
+            source,
+            from: cursor,
+            to: segment_end,
+        });
+        cursor = segment_end;
+    }
+    Ok(segments)
+}
+
+/// A point read from a source: a raw value, or the aggregate of a rollup
+/// bucket.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourcePoint {
+    /// Time of the sample or start of the bucket, as stored.
+    pub timestamp: String,
+    pub aggregate: Aggregate,
+}
+
+/// Reads at most `limit` points of `metric` for `series` (a mount point
+/// for disk metrics, empty otherwise) in `segment`, oldest first.
+///
+/// Long segments are read page by page: `after` is the timestamp of the
+/// last point of the previous page, `None` for the first one.
+pub fn read_segment(
+    conn: &mut SqliteConnection,
+    metric: &str,
+    series: &str,
+    segment: &Segment,
+    after: Option<&str>,
+    limit: i64,
+) -> Result<Vec<SourcePoint>, Error> {
+    let from = segment.from.to_rfc3339();
+    let to = segment.to.to_rfc3339();
+    let after = after.unwrap_or_default();
+
+    match segment.source {
+        Source::Rollup(resolution) => Ok(metricrollup::table
+            .filter(metricrollup::resolution_secs.eq(resolution.secs() as i32))
+            .filter(metricrollup::metric.eq(metric))
+            .filter(metricrollup::series.eq(series))
+            .filter(metricrollup::bucket.ge(&from))
+            .filter(metricrollup::bucket.gt(after))
+            .filter(metricrollup::bucket.lt(&to))
+            .order(metricrollup::bucket.asc())
+            .limit(limit)
+            .select(MetricRollup::as_select())
+            .load::<MetricRollup>(conn)?
+            .into_iter()
+            .map(|rollup| SourcePoint {
+                aggregate: Aggregate::from(&rollup),
+                timestamp: rollup.bucket,
+            })
+            .collect()),
+        Source::Raw if DISK_METRICS.contains(&metric) => Ok(diskinfo::table
+            .inner_join(sysinfo::table)
+            .filter(diskinfo::mounted_path.eq(series))
+            .filter(sysinfo::timestamp.ge(&from))
+            .filter(sysinfo::timestamp.gt(after))
+            .filter(sysinfo::timestamp.lt(&to))
+            .order(sysinfo::timestamp.asc())
+            .limit(limit)
+            .select((sysinfo::timestamp, DiskInfo::as_select()))
+            .load::<(String, DiskInfo)>(conn)?
+            .into_iter()
+            .filter_map(|(timestamp, disk)| {
+                let (_, value) = disk_metric_values(&disk)
+                    .into_iter()
+                    .find(|(name, _)| *name == metric)?;
+                Some(SourcePoint {
+                    timestamp,
+                    aggregate: Aggregate::new(value),
+                })
+            })
+            .collect()),
+        Source::Raw => {
+            if !SYSINFO_METRICS.contains(&metric) {
+                return Ok(Vec::new());
+            }
+            Ok(sysinfo::table
+                .filter(sysinfo::timestamp.ge(&from))
+                .filter(sysinfo::timestamp.gt(after))
+                .filter(sysinfo::timestamp.lt(&to))
+                .order(sysinfo::timestamp.asc())
+                .limit(limit)
+                .select(SysInfo::as_select())
+                .load::<SysInfo>(conn)?
+                .into_iter()
+                .filter_map(|sample| {
+                    let (_, value) = sysinfo_metric_values(&sample)
+                        .into_iter()
+                        .find(|(name, _)| *name == metric)?;
+                    Some(SourcePoint {
+                        timestamp: sample.timestamp,
+                        aggregate: Aggregate::new(value),
+                    })
+                })
+                .collect())
+        }
+    }
+}
+
+/// Mount points with disk metrics between `from` and `to`, sorted.
+pub fn disk_series(
+    conn: &mut SqliteConnection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<String>, Error> {
+    let mut mounts: Vec<String> = metricrollup::table
+        .filter(metricrollup::metric.eq_any(DISK_METRICS))
+        .filter(metricrollup::bucket.ge(Resolution::Day.truncate(from).to_rfc3339()))
+        .filter(metricrollup::bucket.lt(to.to_rfc3339()))
+        .select(metricrollup::series)
+        .distinct()
+        .load(conn)?;
+    mounts.extend(
+        diskinfo::table
+            .inner_join(sysinfo::table)
+            .filter(sysinfo::timestamp.ge(from.to_rfc3339()))
+            .filter(sysinfo::timestamp.lt(to.to_rfc3339()))
+            .select(diskinfo::mounted_path)
+            .distinct()
+            .load::<String>(conn)?,
+    );
+    mounts.sort();
+    mounts.dedup();
+    Ok(mounts)
+}
+
+/// Values of a series over one step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesPoint {
+    /// Start of the step.
+    pub timestamp: DateTime<Utc>,
+    pub aggregate: Aggregate,
+    /// Values of the step when kept for percentiles: the raw samples, or
+    /// the averages of the rollup buckets.
+    pub values: Vec<f64>,
+}
+
+/// Start of the step of `step_secs` containing `time`, steps being aligned
+/// on the Unix epoch. A step too long to be represented starts at the
+/// epoch.
+pub fn step_start(time: DateTime<Utc>, step_secs: i64) -> DateTime<Utc> {
+    TimeDelta::try_seconds(step_secs.max(1))
+        .and_then(|step| time.duration_trunc(step).ok())
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+/// Merges consecutive points of a series into steps of `step_secs`.
+///
+/// Only the step being filled is held, so a series of any length is
+/// aggregated in constant memory.
+pub struct StepAggregator {
+    step_secs: i64,
+    keep_values: bool,
+    current: Option<SeriesPoint>,
+}
+
+impl StepAggregator {
+    /// With `keep_values`, the values of each step are kept for
+    /// percentiles.
+    pub fn new(step_secs: i64, keep_values: bool) -> Self {
+        Self {
+            step_secs,
+            keep_values,
+            current: None,
+        }
+    }
+
+    /// Adds a point, later than the previous ones. Returns the previous
+    /// step once this point starts a new one.
+    pub fn push(&mut self, point: &SourcePoint) -> Option<SeriesPoint> {
+        let time = parse_timestamp(&point.timestamp)?;
+        let timestamp = step_start(time, self.step_secs);
+        let values = if self.keep_values {
+            vec![point.aggregate.avg]
+        } else {
+            Vec::new()
+        };
+
+        match &mut self.current {
+            Some(current) if current.timestamp == timestamp => {
+                current.aggregate.merge(&point.aggregate);
+                current.values.extend(values);
+                None
+            }
+            _ => self.current.replace(SeriesPoint {
+                timestamp,
+                aggregate: point.aggregate,
+                values,
+            }),
+        }
+    }
+
+    /// Returns the last step.
+    pub fn finish(self) -> Option<SeriesPoint> {
+        self.current
+    }
+}
+
+/// `p`th percentile of `values` (0 to 100), interpolated between the two
+/// closest ranks. `None` without values.
+pub fn percentile(values: &mut [f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (values.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(values[low] + (values[high] - values[low]) * (rank - low as f64))
+}
+
+/// How the values of a step are summarized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aggregation {
+    Avg,
+    Min,
+    Max,
+    Last,
+    /// Percentile from 0 to 100.
+    Percentile(f64),
+}
+
+impl Aggregation {
+    /// Parses "avg", "min", "max", "last" or a percentile like "p95" or
+    /// "p99.9".
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "avg" => Some(Aggregation::Avg),
+            "min" => Some(Aggregation::Min),
+            "max" => Some(Aggregation::Max),
+            "last" => Some(Aggregation::Last),
+            _ => {
+                let p = name.strip_prefix('p')?.parse::<f64>().ok()?;
+                (0.0..=100.0)
+                    .contains(&p)
+                    .then_some(Aggregation::Percentile(p))
+            }
+        }
+    }
+
+    /// Value of `point`, whose values must have been kept for
+    /// percentiles.
+    pub fn value(self, point: &mut SeriesPoint) -> Option<f64> {
+        match self {
+            Aggregation::Avg => Some(point.aggregate.avg),
+            Aggregation::Min => Some(point.aggregate.min),
+            Aggregation::Max => Some(point.aggregate.max),
+            Aggregation::Last => Some(point.aggregate.last),
+            Aggregation::Percentile(p) => percentile(&mut point.values, p),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::SysInfo as Sample;
+    use crate::test_utils::migrated_storage;
+    use diesel::connection::SimpleConnection;
+
+    fn time(timestamp: &str) -> DateTime<Utc> {
+        parse_timestamp(timestamp).unwrap()
+    }
+
+    /// Stores a sample at `timestamp` with a CPU usage of `cpu` and the root
+    /// disk `used` MB full.
+    fn store_sample(conn: &mut SqliteConnection, timestamp: &str, cpu: f64, used: i32) {
+        let info = Sample {
+            timestamp: timestamp.to_string(),
+            cpu_usage: cpu,
+            ..Sample::default()
+        };
+        let id = info.store(conn).unwrap().id;
+        conn.batch_execute(&format!(
+            "INSERT INTO diskinfo (sysinfo_id, filesystem, size, used, available, used_percentage, mounted_path) \
+             VALUES ({id}, 'ext4', 1000, {used}, {}, {}, '/');",
+            1000 - used,
+            used / 10
+        ))
+        .unwrap();
+    }
+
+    fn rollups(
+        conn: &mut SqliteConnection,
+        resolution: Resolution,
+        metric: &str,
+    ) -> Vec<MetricRollup> {
+        metricrollup::table
+            .filter(metricrollup::resolution_secs.eq(resolution.secs() as i32))
+            .filter(metricrollup::metric.eq(metric))
+            .order(metricrollup::bucket.asc())
+            .select(MetricRollup::as_select())
+            .load(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_metric_names() {
+        let (_dir, storage) = migrated_storage();
+        let mut conn = storage.write().unwrap();
+        store_sample(&mut conn, "2024-01-15T10:00:00+00:00", 10.0, 100);
+        let sample = sysinfo::table
+            .select(SysInfo::as_select())
+            .first::<SysInfo>(&mut *conn)
+            .unwrap();
+        let disk = diskinfo::table
+            .select(DiskInfo::as_select())
+            .first::<DiskInfo>(&mut *conn)
+            .unwrap();
+
+        let names: Vec<&str> = sysinfo_metric_values(&sample)
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        assert_eq!(names, SYSINFO_METRICS);
+        let names: Vec<&str> = disk_metric_values(&disk)
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        assert_eq!(names, DISK_METRICS);
+    }
+
+    #[test]
+    fn test_resolution_truncate() {
+        let t = time("2024-01-15T10:31:45.250+00:00");
+        assert_eq!(
+            Resolution::Minute.truncate(t),
+            time("2024-01-15T10:31:00+00:00")
+        );
+        assert_eq!(
+            Resolution::Hour.truncate(t),
+            time("2024-01-15T10:00:00+00:00")
+        );
+        assert_eq!(
+            Resolution::Day.truncate(t),
+            time("2024-01-15T00:00:00+00:00")
+        );
+        assert_eq!(step_start(t, 300), time("2024-01-15T10:30:00+00:00"));
+        assert_eq!(step_start(t, i64::MAX), DateTime::UNIX_EPOCH);
+    }
+
+    #[test]
+    fn test_aggregate_merge() {
+        let mut a = Aggregate::new(10.0);
+        a.merge(&Aggregate::new(30.0));
+        a.merge(&Aggregate {
+            avg: 5.0,
+            min: 2.0,
+            max: 8.0,
+            last: 8.0,
+            count: 2,
+        });
+        // Averages are weighted by their samples
+        assert_eq!(
+            a,
+            Aggregate {
+                avg: 12.5,
+                min: 2.0,
+                max: 30.0,
+                last: 8.0,
+                count: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_compact_rolls_up_minutes_hours_and_days() {
+        let (_dir, storage) = migrated_storage();
+        {
+            let mut conn = storage.write().unwrap();
+            store_sample(&mut conn, "2024-01-15T10:00:10+00:00", 10.0, 100);
+            store_sample(&mut conn, "2024-01-15T10:00:40+00:00", 30.0, 200);
+            store_sample(&mut conn, "2024-01-15T10:01:10+00:00", 50.0, 300);
+            store_sample(&mut conn, "2024-01-15T11:30:00+00:00", 90.0, 400);
+            // Not complete yet
+            store_sample(&mut conn, "2024-01-16T00:00:30+00:00", 70.0, 500);
+        }
+
+        let (_shutdown_tx, shutdown) = watch::channel(false);
+        let now = time("2024-01-16T00:00:45+00:00");
+        assert!(compact(&storage, now, &shutdown).unwrap() > 0);
+
+        let mut conn = storage.write().unwrap();
+        let minutes = rollups(&mut conn, Resolution::Minute, "cpu_usage");
+        assert_eq!(minutes.len(), 3);
+        assert_eq!(minutes[0].bucket, "2024-01-15T10:00:00+00:00");
+        assert_eq!(minutes[0].series, "");
+        assert_eq!(
+            Aggregate::from(&minutes[0]),
+            Aggregate {
+                avg: 20.0,
+                min: 10.0,
+                max: 30.0,
+                last: 30.0,
+                count: 2,
+            }
+        );
+
+        let hours = rollups(&mut conn, Resolution::Hour, "cpu_usage");
+        assert_eq!(hours.len(), 2);
+        assert_eq!(hours[0].bucket, "2024-01-15T10:00:00+00:00");
+        assert_eq!(hours[0].avg_value, 30.0);
+        assert_eq!(hours[0].last_value, 50.0);
+        assert_eq!(hours[0].sample_count, 3);
+
+        let days = rollups(&mut conn, Resolution::Day, "cpu_usage");
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].bucket, "2024-01-15T00:00:00+00:00");
+        assert_eq!(days[0].avg_value, 45.0);
+        assert_eq!(days[0].max_value, 90.0);
+        assert_eq!(days[0].sample_count, 4);
+
+        // Disks have a series per mount point
+        let disk_days = rollups(&mut conn, Resolution::Day, "disk_used");
+        assert_eq!(disk_days.len(), 1);
+        assert_eq!(disk_days[0].series, "/");
+        assert_eq!(disk_days[0].avg_value, 250.0);
+        assert_eq!(disk_days[0].last_value, 400.0);
+        drop(conn);
+
+        // Up to date, a later run carries on where this one stopped
+        assert_eq!(compact(&storage, now, &shutdown).unwrap(), 0);
+        compact(&storage, time("2024-01-17T00:00:00+00:00"), &shutdown).unwrap();
+        let mut conn = storage.write().unwrap();
+        assert_eq!(rollups(&mut conn, Resolution::Minute, "cpu_usage").len(), 4);
+        assert_eq!(rollups(&mut conn, Resolution::Day, "cpu_usage").len(), 2);
+    }
+
+    #[test]
+    fn test_plan_segments() {
+        let (_dir, storage) = migrated_storage();
+        {
+            let mut conn = storage.write().unwrap();
+            for hour in 0..24 {
+                store_sample(
+                    &mut conn,
+                    &format!("2024-01-15T{:02}:00:00+00:00", hour),
+                    hour as f64,
+                    100,
+                );
+            }
+            store_sample(&mut conn, "2024-01-16T00:00:00+00:00", 24.0, 100);
+            store_sample(&mut conn, "2024-01-16T01:30:00+00:00", 25.0, 100);
+        }
+        let (_shutdown_tx, shutdown) = watch::channel(false);
+        compact(&storage, time("2024-01-16T01:10:00+00:00"), &shutdown).unwrap();
+
+        let mut conn = storage.write().unwrap();
+        let segment = |source, from: &str, to: &str| Segment {
+            source,
+            from: time(from),
+            to: time(to),
+        };
+
+        // Days as far as they are rolled up, then the hours, the minutes and
+        // the raw samples
+        let from = time("2024-01-15T00:00:00+00:00");
+        let to = time("2024-01-16T02:00:00+00:00");
+        assert_eq!(
+            plan_segments(&mut conn, from, to, 86400).unwrap(),
+            vec![
+                segment(
+                    Source::Rollup(Resolution::Day),
+                    "2024-01-15T00:00:00+00:00",
+                    "2024-01-16T00:00:00+00:00"
+                ),
+                segment(
+                    Source::Rollup(Resolution::Hour),
+                    "2024-01-16T00:00:00+00:00",
+                    "2024-01-16T01:00:00+00:00"
+                ),
+                segment(
+                    Source::Raw,
+                    "2024-01-16T01:00:00+00:00",
+                    "2024-01-16T02:00:00+00:00"
+                ),
+            ]
+        );
+        // Small steps read the minutes
+        assert_eq!(
+            plan_segments(&mut conn, from, to, 60).unwrap()[0].source,
+            Source::Rollup(Resolution::Minute)
+        );
+
+        // Once the raw samples and minutes are pruned, the hours stand in
+        conn.batch_execute(
+            "DELETE FROM sysinfo WHERE timestamp < '2024-01-16T00:00:00+00:00';
+             DELETE FROM metricrollup WHERE resolution_secs = 60;",
+        )
+        .unwrap();
+        assert_eq!(
+            plan_segments(&mut conn, from, to, 10).unwrap()[0],
+            segment(
+                Source::Rollup(Resolution::Hour),
+                "2024-01-15T00:00:00+00:00",
+                "2024-01-16T00:00:00+00:00"
+            )
+        );
+    }
+
+    #[test]
+    fn test_read_segment() {
+        let (_dir, storage) = migrated_storage();
+        let mut conn = storage.write().unwrap();
+        store_sample(&mut conn, "2024-01-15T10:00:00+00:00", 10.0, 100);
+        store_sample(&mut conn, "2024-01-15T10:00:30+00:00", 20.0, 200);
+        store_sample(&mut conn, "2024-01-15T10:01:00+00:00", 30.0, 300);
+
+        let segment = Segment {
+            source: Source::Raw,
+            from: time("2024-01-15T00:00:00+00:00"),
+            to: time("2024-01-16T00:00:00+00:00"),
+        };
+        let read = |conn: &mut SqliteConnection, metric, series, after| {
+            read_segment(conn, metric, series, &segment, after, 2)
+                .unwrap()
+                .into_iter()
+                .map(|p| (p.timestamp, p.aggregate.avg))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            read(&mut conn, "cpu_usage", "", None),
+            vec![
+                ("2024-01-15T10:00:00+00:00".to_string(), 10.0),
+                ("2024-01-15T10:00:30+00:00".to_string(), 20.0)
+            ]
+        );
+        // The next page starts after the last point
+        assert_eq!(
+            read(
+                &mut conn,
+                "cpu_usage",
+                "",
+                Some("2024-01-15T10:00:30+00:00")
+            ),
+            vec![("2024-01-15T10:01:00+00:00".to_string(), 30.0)]
+        );
+        assert_eq!(read(&mut conn, "disk_available", "/", None)[1].1, 800.0);
+        assert!(read(&mut conn, "disk_used", "/home", None).is_empty());
+        assert!(read(&mut conn, "unknown", "", None).is_empty());
+
+        let from = time("2024-01-15T00:00:00+00:00");
+        assert_eq!(disk_series(&mut conn, from, segment.to).unwrap(), vec!["/"]);
+        assert!(
+            disk_series(&mut conn, segment.to, time("2024-01-17T00:00:00+00:00"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_step_aggregator() {
+        let point = |timestamp: &str, value| SourcePoint {
+            timestamp: timestamp.to_string(),
+            aggregate: Aggregate::new(value),
+        };
+        let mut steps = StepAggregator::new(60, true);
+        assert_eq!(steps.push(&point("2024-01-15T10:00:05+00:00", 1.0)), None);
+        assert_eq!(steps.push(&point("2024-01-15T10:00:35+00:00", 3.0)), None);
+
+        let first = steps
+            .push(&point("2024-01-15T10:02:10+00:00", 8.0))
+            .unwrap();
+        assert_eq!(first.timestamp, time("2024-01-15T10:00:00+00:00"));
+        assert_eq!(first.aggregate.avg, 2.0);
+        assert_eq!(first.values, vec![1.0, 3.0]);
+
+        let mut last = steps.finish().unwrap();
+        assert_eq!(last.timestamp, time("2024-01-15T10:02:00+00:00"));
+        assert_eq!(Aggregation::Percentile(50.0).value(&mut last), Some(8.0));
+    }
+
+    #[test]
+    fn test_percentile() {
+        let mut values = vec![40.0, 10.0, 30.0, 20.0, 50.0];
+        assert_eq!(percentile(&mut values, 0.0), Some(10.0));
+        assert_eq!(percentile(&mut values, 50.0), Some(30.0));
+        assert_eq!(percentile(&mut values, 90.0), Some(46.0));
+        assert_eq!(percentile(&mut values, 100.0), Some(50.0));
+        assert_eq!(percentile(&mut [], 50.0), None);
+    }
+
+    #[test]
+    fn test_aggregation_parse() {
+        assert_eq!(Aggregation::parse("avg"), Some(Aggregation::Avg));
+        assert_eq!(Aggregation::parse("last"), Some(Aggregation::Last));
+        assert_eq!(
+            Aggregation::parse("p99.9"),
+            Some(Aggregation::Percentile(99.9))
+        );
+        assert_eq!(Aggregation::parse("p101"), None);
+        assert_eq!(Aggregation::parse("median"), None);
+        assert_eq!(Aggregation::parse("p"), None);
+    }
+
+    /// Reads `metric` of `series` between `from` and `to` as the history
+    /// API does, collecting the steps.
+    fn metric_series(
+        conn: &mut SqliteConnection,
+        metric: &str,
+        series: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        step_secs: i64,
+    ) -> Vec<SeriesPoint> {
+        const PAGE_ROWS: i64 = 10_000;
+
+        let mut points = Vec::new();
+        let mut steps = StepAggregator::new(step_secs, false);
+        for segment in plan_segments(conn, from, to, step_secs).unwrap() {
+            let mut after: Option<String> = None;
+            loop {
+                let page =
+                    read_segment(conn, metric, series, &segment, after.as_deref(), PAGE_ROWS)
+                        .unwrap();
+                points.extend(page.iter().filter_map(|point| steps.push(point)));
+                if page.len() < PAGE_ROWS as usize {
+                    break;
+                }
+                after = page.last().map(|point| point.timestamp.clone());
+            }
+        }
+        points.extend(steps.finish());
+        points
+    }
+
+    #[test]
+    fn test_metric_series_stitches_sources() {
+        let (_dir, storage) = migrated_storage();
+        {
+            let mut conn = storage.write().unwrap();
+            store_sample(&mut conn, "2024-01-15T10:10:00+00:00", 10.0, 100);
+            store_sample(&mut conn, "2024-01-15T10:50:00+00:00", 30.0, 100);
+            store_sample(&mut conn, "2024-01-15T11:10:00+00:00", 50.0, 100);
+            store_sample(&mut conn, "2024-01-15T11:20:00+00:00", 70.0, 100);
+        }
+        // 11:10 is rolled up into a minute, 11:20 is still raw
+        let (_shutdown_tx, shutdown) = watch::channel(false);
+        compact(&storage, time("2024-01-15T11:15:00+00:00"), &shutdown).unwrap();
+
+        let mut conn = storage.write().unwrap();
+        let points = metric_series(
+            &mut conn,
+            "cpu_usage",
+            "",
+            time("2024-01-15T00:00:00+00:00"),
+            time("2024-01-16T00:00:00+00:00"),
+            7200,
+        );
+        assert_eq!(
+            points,
+            vec![SeriesPoint {
+                timestamp: time("2024-01-15T10:00:00+00:00"),
+                aggregate: Aggregate {
+                    avg: 40.0,
+                    min: 10.0,
+                    max: 70.0,
+                    last: 70.0,
+                    count: 4,
+                },
+                values: Vec::new(),
+            }]
+        );
+    }
+}
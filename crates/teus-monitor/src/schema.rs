@@ -7,7 +7,11 @@
 
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
-use teus_schema::schema::{diskinfo, sysinfo};
+use teus_schema::schema::{
+    cgroupinfo, cpuinfo, diskinfo, diskio, kernelevent, logevent, logmatch, metricrollup, netinfo,
+    packagestatus, packageupdate, pathinfo, processinfo, psiinfo, scriptmetric, securityevent,
+    sensorinfo, socketinfo, sysinfo, timesyncinfo,
+};
 
 /// Structure for inserting system information records into the database.
 ///
@@ -25,7 +29,7 @@ use teus_schema::schema::{diskinfo, sysinfo};
 /// # Examples
 ///
 /// ```rust
-/// use teus::monitor::schema::SchemaSysInfo;
+/// use teus_monitor::schema::SchemaSysInfo;
 /// use chrono::Utc;
 ///
 /// let sys_info = SchemaSysInfo {
@@ -35,6 +39,11 @@ use teus_schema::schema::{diskinfo, sysinfo};
 ///     total_ram: 16384.0,
 ///     free_ram: 8192.0,
 ///     used_swap: 512.0,
+///     load_avg_one: 0.42,
+///     load_avg_five: 0.35,
+///     load_avg_fifteen: 0.30,
+///     available_ram: 11_811_160_064,
+///     ..Default::default()
 /// };
 /// ```
 #[derive(Insertable, Debug, Serialize, Deserialize)]
@@ -74,6 +83,64 @@ pub struct SchemaSysInfo {
     /// High swap usage may indicate memory pressure and can
     /// significantly impact system performance.
     pub used_swap: f32,
+
+    /// System load average over the last minute.
+    ///
+    /// Average number of runnable (or uninterruptible) tasks. Values
+    /// above the number of cores mean work is queuing for CPU time.
+    #[serde(default)]
+    pub load_avg_one: f32,
+
+    /// System load average over the last five minutes.
+    #[serde(default)]
+    pub load_avg_five: f32,
+
+    /// System load average over the last fifteen minutes.
+    #[serde(default)]
+    pub load_avg_fifteen: f32,
+
+    /// Memory available for new workloads without swapping, in bytes.
+    ///
+    /// The kernel's `MemAvailable` estimate, which counts reclaimable
+    /// cache. This is the figure to alert on, not `free_ram`.
+    #[serde(default)]
+    pub available_ram: i64,
+
+    /// Page cache, in bytes.
+    #[serde(default)]
+    pub cached_ram: i64,
+
+    /// Block device buffers, in bytes.
+    #[serde(default)]
+    pub buffers_ram: i64,
+
+    /// Shared memory (including tmpfs), in bytes.
+    #[serde(default)]
+    pub shared_ram: i64,
+
+    /// Total swap space, in bytes.
+    #[serde(default)]
+    pub total_swap: i64,
+
+    /// Unused swap space, in bytes.
+    #[serde(default)]
+    pub free_swap: i64,
+
+    /// Memory waiting to be written back to disk, in bytes.
+    #[serde(default)]
+    pub dirty_ram: i64,
+
+    /// Number of huge pages in the pool.
+    #[serde(default)]
+    pub hugepages_total: i64,
+
+    /// Number of huge pages not allocated.
+    #[serde(default)]
+    pub hugepages_free: i64,
+
+    /// Size of a huge page, in bytes.
+    #[serde(default)]
+    pub hugepage_size: i64,
 }
 
 /// Structure for inserting disk information records into the database.
@@ -95,7 +162,7 @@ pub struct SchemaSysInfo {
 /// # Examples
 ///
 /// ```rust
-/// use teus::monitor::schema::SchemaDiskInfo;
+/// use teus_monitor::schema::SchemaDiskInfo;
 ///
 /// let disk_info = SchemaDiskInfo {
 ///     sysinfo_id: 1,
@@ -105,6 +172,10 @@ pub struct SchemaSysInfo {
 ///     available: 250000,  // 250GB in MB
 ///     used_percentage: 75,
 ///     mounted_path: "/".to_string(),
+///     inodes_total: 6553600,
+///     inodes_used: 655360,
+///     inodes_free: 5898240,
+///     inodes_used_percentage: 10,
 /// };
 /// ```
 #[derive(Insertable, Debug, Serialize, Deserialize)]
@@ -152,6 +223,25 @@ pub struct SchemaDiskInfo {
     /// This helps identify which part of the system's storage hierarchy
     /// this disk information represents.
     pub mounted_path: String,
+    /// Total number of inodes of the filesystem.
+    ///
+    /// Zero on filesystems that allocate inodes dynamically (e.g. btrfs).
+    #[serde(default)]
+    pub inodes_total: i64,
+
+    /// Number of inodes in use.
+    #[serde(default)]
+    pub inodes_used: i64,
+
+    /// Number of free inodes.
+    #[serde(default)]
+    pub inodes_free: i64,
+
+    /// Percentage of inodes in use (0 to 100).
+    ///
+    /// A filesystem at 100% can't create new files even with free space left.
+    #[serde(default)]
+    pub inodes_used_percentage: i32,
 }
 
 /// Structure for querying system information records from the database.
@@ -171,7 +261,7 @@ pub struct SchemaDiskInfo {
 /// # Examples
 ///
 /// ```rust
-/// use teus::monitor::schema::SysInfo;
+/// use teus_monitor::schema::SysInfo;
 /// use diesel::prelude::*;
 ///
 /// // Query recent system information (pseudo-code)
@@ -221,6 +311,45 @@ pub struct SysInfo {
     ///
     /// High values may indicate memory pressure and performance issues.
     pub used_swap: f32,
+
+    /// System load average over the last minute.
+    pub load_avg_one: f32,
+
+    /// System load average over the last five minutes.
+    pub load_avg_five: f32,
+
+    /// System load average over the last fifteen minutes.
+    pub load_avg_fifteen: f32,
+
+    /// Memory available for new workloads without swapping, in bytes.
+    pub available_ram: i64,
+
+    /// Page cache, in bytes.
+    pub cached_ram: i64,
+
+    /// Block device buffers, in bytes.
+    pub buffers_ram: i64,
+
+    /// Shared memory (including tmpfs), in bytes.
+    pub shared_ram: i64,
+
+    /// Total swap space, in bytes.
+    pub total_swap: i64,
+
+    /// Unused swap space, in bytes.
+    pub free_swap: i64,
+
+    /// Memory waiting to be written back to disk, in bytes.
+    pub dirty_ram: i64,
+
+    /// Number of huge pages in the pool.
+    pub hugepages_total: i64,
+
+    /// Number of huge pages not allocated.
+    pub hugepages_free: i64,
+
+    /// Size of a huge page, in bytes.
+    pub hugepage_size: i64,
 }
 
 /// Structure for querying disk information records from the database.
@@ -246,7 +375,7 @@ pub struct SysInfo {
 /// # Examples
 ///
 /// ```rust
-/// use teus::monitor::schema::DiskInfo;
+/// use teus_monitor::schema::DiskInfo;
 /// use diesel::prelude::*;
 ///
 /// // Query disk info for high usage (pseudo-code)
@@ -301,210 +430,1567 @@ pub struct DiskInfo {
     /// The path in the system's directory hierarchy where this
     /// storage device can be accessed (e.g., "/", "/home", "C:").
     pub mounted_path: String,
-}
+    /// Total number of inodes of the filesystem.
+    pub inodes_total: i64,
 
-impl Default for SchemaSysInfo {
-    fn default() -> Self {
-        Self {
-            timestamp: "".to_string(),
-            cpu_usage: 0.0,
-            ram_usage: 0.0,
-            total_ram: 0.0,
-            free_ram: 0.0,
-            used_swap: 0.0,
-            // user_id: 0,
-        }
-    }
+    /// Number of inodes in use.
+    pub inodes_used: i64,
+
+    /// Number of free inodes.
+    pub inodes_free: i64,
+
+    /// Percentage of inodes in use (0 to 100).
+    pub inodes_used_percentage: i32,
 }
 
-impl Default for SchemaDiskInfo {
-    fn default() -> Self {
-        Self {
-            sysinfo_id: 0,
-            filesystem: "".to_string(),
-            size: 0,
-            used: 0,
-            available: 0,
-            used_percentage: 0,
-            mounted_path: "".to_string(),
-        }
-    }
+/// Structure for inserting per-core CPU usage records into the database.
+///
+/// The overall `cpu_usage` stored on `SchemaSysInfo` is an average across
+/// every core, which hides a single core pinned at 100%. One `SchemaCpuInfo`
+/// row is stored per logical core for each monitoring snapshot.
+///
+/// # Database Relationships
+///
+/// - `sysinfo_id`: Foreign key referencing the `sysinfo` table
+/// - Each `SchemaSysInfo` record has one `SchemaCpuInfo` record per core
+///
+/// # Examples
+///
+/// ```rust
+/// use teus_monitor::schema::SchemaCpuInfo;
+///
+/// let core = SchemaCpuInfo {
+///     sysinfo_id: 1,
+///     core_index: 0,
+///     name: "cpu0".to_string(),
+///     cpu_usage: 97.5,
+///     frequency: 2400,
+/// };
+/// ```
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = cpuinfo)]
+pub struct SchemaCpuInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Position of the core in the list reported by the operating system.
+    ///
+    /// Starts at 0 and is stable for a given machine, which makes it
+    /// suitable for plotting the same core across snapshots.
+    pub core_index: i32,
+
+    /// Name of the core as reported by the operating system (e.g. "cpu0").
+    pub name: String,
+
+    /// Usage percentage of this core at the time of collection.
+    ///
+    /// Range: 0.0 to 100.0.
+    pub cpu_usage: f32,
+
+    /// Current frequency of the core, in MHz.
+    pub frequency: i32,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::Utc;
+/// Structure for querying per-core CPU usage records from the database.
+///
+/// Mirrors `SchemaCpuInfo` with the database-generated ID. Records are
+/// retrieved through their parent `SysInfo` snapshot.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = cpuinfo)]
+pub struct CpuInfo {
+    /// Database-generated unique identifier for this core record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
 
-    #[test]
-    fn test_schema_sys_info_default() {
-        let sys_info = SchemaSysInfo::default();
-        assert_eq!(sys_info.timestamp, "");
-        assert_eq!(sys_info.cpu_usage, 0.0);
-        assert_eq!(sys_info.ram_usage, 0.0);
-        assert_eq!(sys_info.total_ram, 0.0);
-        assert_eq!(sys_info.free_ram, 0.0);
-        assert_eq!(sys_info.used_swap, 0.0);
-    }
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
 
-    #[test]
-    fn test_schema_disk_info_default() {
-        let disk_info = SchemaDiskInfo::default();
-        assert_eq!(disk_info.sysinfo_id, 0);
-        assert_eq!(disk_info.filesystem, "");
-        assert_eq!(disk_info.size, 0);
-        assert_eq!(disk_info.used, 0);
-        assert_eq!(disk_info.available, 0);
-        assert_eq!(disk_info.used_percentage, 0);
-        assert_eq!(disk_info.mounted_path, "");
-    }
+    /// Position of the core in the list reported by the operating system.
+    pub core_index: i32,
 
-    #[test]
-    fn test_schema_sys_info_creation() {
-        let sys_info = SchemaSysInfo {
-            timestamp: Utc::now().to_rfc3339(),
-            cpu_usage: 25.5,
-            ram_usage: 1024.0,
-            total_ram: 8192.0,
-            free_ram: 4096.0,
-            used_swap: 512.0,
-        };
+    /// Name of the core as reported by the operating system (e.g. "cpu0").
+    pub name: String,
 
-        assert!(!sys_info.timestamp.is_empty());
-        assert_eq!(sys_info.cpu_usage, 25.5);
-        assert_eq!(sys_info.ram_usage, 1024.0);
-        assert_eq!(sys_info.total_ram, 8192.0);
-        assert_eq!(sys_info.free_ram, 4096.0);
-        assert_eq!(sys_info.used_swap, 512.0);
-    }
+    /// Usage percentage of this core at the time of collection.
+    pub cpu_usage: f32,
 
-    #[test]
-    fn test_schema_disk_info_creation() {
-        let disk_info = SchemaDiskInfo {
-            sysinfo_id: 1,
-            filesystem: "ext4".to_string(),
-            size: 1000,
-            used: 500,
-            available: 500,
-            used_percentage: 50,
-            mounted_path: "/".to_string(),
-        };
+    /// Frequency of the core at the time of collection, in MHz.
+    pub frequency: i32,
+}
 
-        assert_eq!(disk_info.sysinfo_id, 1);
-        assert_eq!(disk_info.filesystem, "ext4");
-        assert_eq!(disk_info.size, 1000);
-        assert_eq!(disk_info.used, 500);
-        assert_eq!(disk_info.available, 500);
-        assert_eq!(disk_info.used_percentage, 50);
-        assert_eq!(disk_info.mounted_path, "/");
-    }
+/// Structure for inserting network interface records into the database.
+///
+/// One row is stored per interface (physical NICs, bridges, docker veths,
+/// loopback) for each monitoring snapshot. The raw counters are cumulative
+/// since boot; the per-second rates are computed from the difference with
+/// the previous snapshot.
+///
+/// # Rates
+///
+/// `rx_bytes_per_sec` and `tx_bytes_per_sec` are `None` for the first
+/// snapshot of an interface and whenever its counters went backwards
+/// (interface recreated or counter wrap), since no meaningful delta exists.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = netinfo)]
+pub struct SchemaNetInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
 
-    #[test]
-    fn test_sys_info_serialization() {
-        let sys_info = SysInfo {
-            id: Some(1),
-            timestamp: Utc::now().to_rfc3339(),
-            cpu_usage: 25.5,
-            ram_usage: 1024.0,
-            total_ram: 8192.0,
-            free_ram: 4096.0,
-            used_swap: 512.0,
-        };
+    /// Name of the network interface (e.g. "eth0", "docker0").
+    pub interface: String,
 
-        let serialized = serde_json::to_string(&sys_info).unwrap();
-        assert!(serialized.contains("\"id\":1"));
-        assert!(serialized.contains("\"cpu_usage\":25.5"));
-        assert!(serialized.contains("\"ram_usage\":1024"));
-    }
+    /// Total bytes received since boot.
+    pub rx_bytes: i64,
 
-    #[test]
-    fn test_disk_info_serialization() {
-        let disk_info = DiskInfo {
-            id: Some(1),
-            sysinfo_id: 1,
-            filesystem: "ext4".to_string(),
-            size: 1000,
-            used: 500,
-            available: 500,
-            used_percentage: 50,
-            mounted_path: "/".to_string(),
-        };
+    /// Total bytes transmitted since boot.
+    pub tx_bytes: i64,
 
-        let serialized = serde_json::to_string(&disk_info).unwrap();
-        assert!(serialized.contains("\"id\":1"));
-        assert!(serialized.contains("\"sysinfo_id\":1"));
-        assert!(serialized.contains("\"filesystem\":\"ext4\""));
-        assert!(serialized.contains("\"mounted_path\":\"/\""));
-    }
+    /// Total packets received since boot.
+    pub rx_packets: i64,
 
-    #[test]
-    fn test_sys_info_debug_format() {
-        let sys_info = SysInfo {
-            id: Some(1),
-            timestamp: "2024-01-01T00:00:00Z".to_string(),
-            cpu_usage: 25.5,
-            ram_usage: 1024.0,
-            total_ram: 8192.0,
-            free_ram: 4096.0,
-            used_swap: 512.0,
-        };
+    /// Total packets transmitted since boot.
+    pub tx_packets: i64,
 
-        let debug_str = format!("{:?}", sys_info);
-        assert!(debug_str.contains("SysInfo"));
-        assert!(debug_str.contains("25.5"));
-        assert!(debug_str.contains("1024"));
-    }
+    /// Total receive errors since boot.
+    pub rx_errors: i64,
 
-    #[test]
-    fn test_edge_values() {
-        // Test with extreme values
-        let sys_info = SchemaSysInfo {
-            timestamp: "2024-01-01T00:00:00Z".to_string(),
-            cpu_usage: 100.0,
-            ram_usage: 0.0,
-            total_ram: f32::MAX,
-            free_ram: f32::MAX,
-            used_swap: 0.0,
-        };
+    /// Total transmit errors since boot.
+    pub tx_errors: i64,
 
-        assert_eq!(sys_info.cpu_usage, 100.0);
-        assert_eq!(sys_info.ram_usage, 0.0);
-        assert_eq!(sys_info.total_ram, f32::MAX);
-        assert_eq!(sys_info.free_ram, f32::MAX);
-        assert_eq!(sys_info.used_swap, 0.0);
+    /// Total received packets dropped since boot.
+    pub rx_dropped: i64,
 
-        // Test disk info with edge values
-        let disk_info = SchemaDiskInfo {
-            sysinfo_id: i32::MAX,
-            filesystem: "test".to_string(),
-            size: i32::MAX,
-            used: 0,
-            available: i32::MAX,
-            used_percentage: 100,
-            mounted_path: "/test".to_string(),
-        };
+    /// Total transmitted packets dropped since boot.
+    pub tx_dropped: i64,
 
-        assert_eq!(disk_info.sysinfo_id, i32::MAX);
-        assert_eq!(disk_info.size, i32::MAX);
-        assert_eq!(disk_info.used, 0);
-        assert_eq!(disk_info.available, i32::MAX);
-        assert_eq!(disk_info.used_percentage, 100);
-    }
+    /// Receive throughput since the previous snapshot, in bytes per second.
+    pub rx_bytes_per_sec: Option<f32>,
 
-    #[test]
-    fn test_deserialization() {
-        let json_str = r#"{
-            "timestamp": "2024-01-01T00:00:00Z",
-            "cpu_usage": 25.5,
-            "ram_usage": 1024.0,
-            "total_ram": 8192.0,
-            "free_ram": 4096.0,
-            "used_swap": 512.0
-        }"#;
+    /// Transmit throughput since the previous snapshot, in bytes per second.
+    pub tx_bytes_per_sec: Option<f32>,
+}
 
-        let sys_info: SchemaSysInfo = serde_json::from_str(json_str).unwrap();
-        assert_eq!(sys_info.timestamp, "2024-01-01T00:00:00Z");
-        assert_eq!(sys_info.cpu_usage, 25.5);
-        assert_eq!(sys_info.ram_usage, 1024.0);
-    }
+/// Structure for querying network interface records from the database.
+///
+/// Mirrors `SchemaNetInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = netinfo)]
+pub struct NetInfo {
+    /// Database-generated unique identifier for this interface record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the network interface (e.g. "eth0", "docker0").
+    pub interface: String,
+
+    /// Total bytes received since boot.
+    pub rx_bytes: i64,
+
+    /// Total bytes transmitted since boot.
+    pub tx_bytes: i64,
+
+    /// Total packets received since boot.
+    pub rx_packets: i64,
+
+    /// Total packets transmitted since boot.
+    pub tx_packets: i64,
+
+    /// Total receive errors since boot.
+    pub rx_errors: i64,
+
+    /// Total transmit errors since boot.
+    pub tx_errors: i64,
+
+    /// Total received packets dropped since boot.
+    pub rx_dropped: i64,
+
+    /// Total transmitted packets dropped since boot.
+    pub tx_dropped: i64,
+
+    /// Receive throughput since the previous snapshot, in bytes per second.
+    pub rx_bytes_per_sec: Option<f32>,
+
+    /// Transmit throughput since the previous snapshot, in bytes per second.
+    pub tx_bytes_per_sec: Option<f32>,
+}
+
+/// Structure for inserting block device I/O records into the database.
+///
+/// One row is stored per block device for each monitoring snapshot. The
+/// raw counters come from `/proc/diskstats` and are cumulative since boot;
+/// throughput, IOPS, latency and utilization are derived from the
+/// difference with the previous snapshot of the same device.
+///
+/// # Derived Values
+///
+/// All derived fields are `None` for the first snapshot of a device and
+/// whenever its counters went backwards (device removed and re-added).
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = diskio)]
+pub struct SchemaDiskIo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Kernel name of the block device (e.g. "sda", "nvme0n1", "dm-0").
+    pub device: String,
+
+    /// Total read requests completed since boot.
+    pub reads_completed: i64,
+
+    /// Total 512-byte sectors read since boot.
+    pub sectors_read: i64,
+
+    /// Total time spent on read requests since boot, in milliseconds.
+    pub read_time_ms: i64,
+
+    /// Total write requests completed since boot.
+    pub writes_completed: i64,
+
+    /// Total 512-byte sectors written since boot.
+    pub sectors_written: i64,
+
+    /// Total time spent on write requests since boot, in milliseconds.
+    pub write_time_ms: i64,
+
+    /// Total time the device had requests in flight since boot, in milliseconds.
+    pub io_time_ms: i64,
+
+    /// Read throughput since the previous snapshot, in bytes per second.
+    pub read_bytes_per_sec: Option<f32>,
+
+    /// Write throughput since the previous snapshot, in bytes per second.
+    pub write_bytes_per_sec: Option<f32>,
+
+    /// Read requests completed per second since the previous snapshot.
+    pub read_iops: Option<f32>,
+
+    /// Write requests completed per second since the previous snapshot.
+    pub write_iops: Option<f32>,
+
+    /// Average duration of a read request since the previous snapshot, in milliseconds.
+    pub avg_read_latency_ms: Option<f32>,
+
+    /// Average duration of a write request since the previous snapshot, in milliseconds.
+    pub avg_write_latency_ms: Option<f32>,
+
+    /// Share of the time since the previous snapshot the device was busy (0-100).
+    ///
+    /// Values close to 100 mean the device is saturated.
+    pub busy_percentage: Option<f32>,
+}
+
+/// Structure for querying block device I/O records from the database.
+///
+/// Mirrors `SchemaDiskIo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = diskio)]
+pub struct DiskIo {
+    /// Database-generated unique identifier for this device record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Kernel name of the block device (e.g. "sda", "nvme0n1", "dm-0").
+    pub device: String,
+
+    /// Total read requests completed since boot.
+    pub reads_completed: i64,
+
+    /// Total 512-byte sectors read since boot.
+    pub sectors_read: i64,
+
+    /// Total time spent on read requests since boot, in milliseconds.
+    pub read_time_ms: i64,
+
+    /// Total write requests completed since boot.
+    pub writes_completed: i64,
+
+    /// Total 512-byte sectors written since boot.
+    pub sectors_written: i64,
+
+    /// Total time spent on write requests since boot, in milliseconds.
+    pub write_time_ms: i64,
+
+    /// Total time the device had requests in flight since boot, in milliseconds.
+    pub io_time_ms: i64,
+
+    /// Read throughput since the previous snapshot, in bytes per second.
+    pub read_bytes_per_sec: Option<f32>,
+
+    /// Write throughput since the previous snapshot, in bytes per second.
+    pub write_bytes_per_sec: Option<f32>,
+
+    /// Read requests completed per second since the previous snapshot.
+    pub read_iops: Option<f32>,
+
+    /// Write requests completed per second since the previous snapshot.
+    pub write_iops: Option<f32>,
+
+    /// Average duration of a read request since the previous snapshot, in milliseconds.
+    pub avg_read_latency_ms: Option<f32>,
+
+    /// Average duration of a write request since the previous snapshot, in milliseconds.
+    pub avg_write_latency_ms: Option<f32>,
+
+    /// Share of the time since the previous snapshot the device was busy (0-100).
+    ///
+    /// Values close to 100 mean the device is saturated.
+    pub busy_percentage: Option<f32>,
+}
+
+/// Structure for inserting process records into the database.
+///
+/// Only the top processes by CPU usage and by memory are stored with each
+/// monitoring snapshot (see `monitor.top_processes`), so the table answers
+/// questions like "what was using the RAM at 3am" without recording the
+/// whole process table every cycle.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = processinfo)]
+pub struct SchemaProcessInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Process ID at the time of the snapshot.
+    pub pid: i32,
+
+    /// Process name (usually the executable name).
+    pub name: String,
+
+    /// Full command line, arguments separated by spaces.
+    pub cmd: String,
+
+    /// Name of the user owning the process, if it could be resolved.
+    pub username: Option<String>,
+
+    /// CPU usage of the process as a percentage of one core.
+    ///
+    /// Multi-threaded processes can exceed 100.
+    pub cpu_usage: f32,
+
+    /// Resident set size of the process, in bytes.
+    pub memory: i64,
+
+    /// Start time of the process, in seconds since the Unix epoch.
+    pub start_time: i64,
+}
+
+/// Structure for querying process records from the database.
+///
+/// Mirrors `SchemaProcessInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = processinfo)]
+pub struct ProcessInfo {
+    /// Database-generated unique identifier for this process record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Process ID at the time of the snapshot.
+    pub pid: i32,
+
+    /// Process name (usually the executable name).
+    pub name: String,
+
+    /// Full command line, arguments separated by spaces.
+    pub cmd: String,
+
+    /// Name of the user owning the process, if it could be resolved.
+    pub username: Option<String>,
+
+    /// CPU usage of the process as a percentage of one core.
+    pub cpu_usage: f32,
+
+    /// Resident set size of the process, in bytes.
+    pub memory: i64,
+
+    /// Start time of the process, in seconds since the Unix epoch.
+    pub start_time: i64,
+}
+
+/// Structure for inserting hardware sensor records into the database.
+///
+/// One row is stored per hwmon channel for each monitoring snapshot. A
+/// sensor is identified by its `chip` and `label`. Temperature channels
+/// fill `temperature` and, when the driver exposes them, the `high` and
+/// `critical` thresholds; fan channels fill `fan_rpm`.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = sensorinfo)]
+pub struct SchemaSensorInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the hwmon chip (e.g. "coretemp", "nvme", "acpitz").
+    pub chip: String,
+
+    /// Channel label (e.g. "Package id 0", "CPU Fan").
+    pub label: String,
+
+    /// Current temperature, in degrees Celsius.
+    pub temperature: Option<f32>,
+
+    /// High temperature threshold, in degrees Celsius.
+    pub high: Option<f32>,
+
+    /// Critical temperature threshold, in degrees Celsius.
+    pub critical: Option<f32>,
+
+    /// Current fan speed, in RPM.
+    pub fan_rpm: Option<f32>,
+}
+
+/// Structure for querying hardware sensor records from the database.
+///
+/// Mirrors `SchemaSensorInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = sensorinfo)]
+pub struct SensorInfo {
+    /// Database-generated unique identifier for this sensor record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the hwmon chip (e.g. "coretemp", "nvme", "acpitz").
+    pub chip: String,
+
+    /// Channel label (e.g. "Package id 0", "CPU Fan").
+    pub label: String,
+
+    /// Current temperature, in degrees Celsius.
+    pub temperature: Option<f32>,
+
+    /// High temperature threshold, in degrees Celsius.
+    pub high: Option<f32>,
+
+    /// Critical temperature threshold, in degrees Celsius.
+    pub critical: Option<f32>,
+
+    /// Current fan speed, in RPM.
+    pub fan_rpm: Option<f32>,
+}
+
+/// Structure for inserting pressure stall records into the database.
+///
+/// One row is stored per resource (`cpu`, `memory`, `io`) for each
+/// monitoring snapshot on kernels with PSI. Values are the percentage of
+/// time tasks were stalled on the resource, averaged over 10, 60 and 300
+/// seconds. `some` means at least one task was stalled, `full` that all
+/// non-idle tasks were stalled at once.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = psiinfo)]
+pub struct SchemaPsiInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Resource under pressure: "cpu", "memory" or "io".
+    pub resource: String,
+
+    /// Share of time some tasks were stalled, over the last 10 seconds.
+    pub some_avg10: f32,
+
+    /// Share of time some tasks were stalled, over the last 60 seconds.
+    pub some_avg60: f32,
+
+    /// Share of time some tasks were stalled, over the last 300 seconds.
+    pub some_avg300: f32,
+
+    /// Share of time all tasks were stalled, over the last 10 seconds.
+    ///
+    /// `None` for CPU on kernels before 5.13.
+    pub full_avg10: Option<f32>,
+
+    /// Share of time all tasks were stalled, over the last 60 seconds.
+    pub full_avg60: Option<f32>,
+
+    /// Share of time all tasks were stalled, over the last 300 seconds.
+    pub full_avg300: Option<f32>,
+}
+
+/// Structure for querying pressure stall records from the database.
+///
+/// Mirrors `SchemaPsiInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = psiinfo)]
+pub struct PsiInfo {
+    /// Database-generated unique identifier for this pressure record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Resource under pressure: "cpu", "memory" or "io".
+    pub resource: String,
+
+    /// Share of time some tasks were stalled, over the last 10 seconds.
+    pub some_avg10: f32,
+
+    /// Share of time some tasks were stalled, over the last 60 seconds.
+    pub some_avg60: f32,
+
+    /// Share of time some tasks were stalled, over the last 300 seconds.
+    pub some_avg300: f32,
+
+    /// Share of time all tasks were stalled, over the last 10 seconds.
+    pub full_avg10: Option<f32>,
+
+    /// Share of time all tasks were stalled, over the last 60 seconds.
+    pub full_avg60: Option<f32>,
+
+    /// Share of time all tasks were stalled, over the last 300 seconds.
+    pub full_avg300: Option<f32>,
+}
+
+/// Structure for inserting cgroup accounting records into the database.
+///
+/// One row is stored per workload cgroup (systemd slice, service or scope,
+/// docker container) for each snapshot the cgroups collector runs in. The
+/// counters are cumulative; CPU usage and I/O throughput are derived from
+/// the previous snapshot of the same cgroup.
+///
+/// Memory, I/O and task values are `None` when the matching controller is
+/// not enabled for the cgroup.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = cgroupinfo)]
+pub struct SchemaCgroupInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Path of the cgroup relative to the hierarchy root
+    /// (e.g. "system.slice/nginx.service").
+    pub path: String,
+
+    /// Total CPU time consumed since the cgroup was created, in microseconds.
+    pub cpu_usage_usec: i64,
+
+    /// CPU time spent in user mode, in microseconds.
+    pub cpu_user_usec: i64,
+
+    /// CPU time spent in kernel mode, in microseconds.
+    pub cpu_system_usec: i64,
+
+    /// Memory charged to the cgroup, in bytes.
+    pub memory_current: Option<i64>,
+
+    /// Memory limit of the cgroup in bytes, `None` when unlimited.
+    pub memory_max: Option<i64>,
+
+    /// Total bytes read from block devices.
+    pub io_read_bytes: Option<i64>,
+
+    /// Total bytes written to block devices.
+    pub io_write_bytes: Option<i64>,
+
+    /// Number of tasks in the cgroup.
+    pub pids_current: Option<i64>,
+
+    /// CPU usage since the previous snapshot, as a percentage of one CPU.
+    pub cpu_usage_percentage: Option<f32>,
+
+    /// Read throughput since the previous snapshot, in bytes per second.
+    pub io_read_bytes_per_sec: Option<f32>,
+
+    /// Write throughput since the previous snapshot, in bytes per second.
+    pub io_write_bytes_per_sec: Option<f32>,
+}
+
+/// Structure for querying cgroup accounting records from the database.
+///
+/// Mirrors `SchemaCgroupInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = cgroupinfo)]
+pub struct CgroupInfo {
+    /// Database-generated unique identifier for this cgroup record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Path of the cgroup relative to the hierarchy root
+    /// (e.g. "system.slice/nginx.service").
+    pub path: String,
+
+    /// Total CPU time consumed since the cgroup was created, in microseconds.
+    pub cpu_usage_usec: i64,
+
+    /// CPU time spent in user mode, in microseconds.
+    pub cpu_user_usec: i64,
+
+    /// CPU time spent in kernel mode, in microseconds.
+    pub cpu_system_usec: i64,
+
+    /// Memory charged to the cgroup, in bytes.
+    pub memory_current: Option<i64>,
+
+    /// Memory limit of the cgroup in bytes, `None` when unlimited.
+    pub memory_max: Option<i64>,
+
+    /// Total bytes read from block devices.
+    pub io_read_bytes: Option<i64>,
+
+    /// Total bytes written to block devices.
+    pub io_write_bytes: Option<i64>,
+
+    /// Number of tasks in the cgroup.
+    pub pids_current: Option<i64>,
+
+    /// CPU usage since the previous snapshot, as a percentage of one CPU.
+    pub cpu_usage_percentage: Option<f32>,
+
+    /// Read throughput since the previous snapshot, in bytes per second.
+    pub io_read_bytes_per_sec: Option<f32>,
+
+    /// Write throughput since the previous snapshot, in bytes per second.
+    pub io_write_bytes_per_sec: Option<f32>,
+}
+
+/// Structure for inserting socket state counts into the database.
+///
+/// One row is stored per protocol and state for each snapshot the sockets
+/// collector runs in, including states without any socket, so every
+/// snapshot of the history has the same rows.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = socketinfo)]
+pub struct SchemaSocketInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Transport protocol, "tcp" or "udp" (both address families).
+    pub protocol: String,
+
+    /// Lowercase socket state (e.g. "established", "time_wait").
+    pub state: String,
+
+    /// Number of sockets in this state.
+    pub socket_count: i64,
+}
+
+/// Structure for querying socket state counts from the database.
+///
+/// Mirrors `SchemaSocketInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = socketinfo)]
+pub struct SocketInfo {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Transport protocol, "tcp" or "udp" (both address families).
+    pub protocol: String,
+
+    /// Lowercase socket state (e.g. "established", "time_wait").
+    pub state: String,
+
+    /// Number of sockets in this state.
+    pub socket_count: i64,
+}
+
+/// Structure for inserting the values printed by user-defined scripts
+/// into the database.
+///
+/// A script's values are stored with the first sample after it exits.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = scriptmetric)]
+pub struct SchemaScriptMetric {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the script, from `[[monitor.scripts]]`.
+    pub script: String,
+
+    /// Metric name as printed by the script.
+    pub name: String,
+
+    /// Labels of the value as a JSON object, `{}` without labels.
+    pub labels: String,
+
+    pub value: f64,
+}
+
+/// Structure for querying script values from the database.
+///
+/// Mirrors `SchemaScriptMetric` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = scriptmetric)]
+pub struct ScriptMetric {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the script, from `[[monitor.scripts]]`.
+    pub script: String,
+
+    /// Metric name as printed by the script.
+    pub name: String,
+
+    /// Labels of the value as a JSON object, `{}` without labels.
+    pub labels: String,
+
+    pub value: f64,
+}
+
+/// Structure for inserting the clock synchronization state into the
+/// database.
+///
+/// One row per sample, read with `adjtimex`.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = timesyncinfo)]
+pub struct SchemaTimeSyncInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Whether the kernel considers the clock synchronized.
+    pub synchronized: bool,
+
+    /// Offset still being corrected, in microseconds.
+    pub offset_us: f64,
+
+    /// Maximum error of the clock, in microseconds.
+    pub max_error_us: i64,
+
+    /// Estimated error of the clock, in microseconds.
+    pub est_error_us: i64,
+
+    /// NTP client running on the host ("systemd-timesyncd", "chrony" or
+    /// "ntpd"), `None` when there is none.
+    pub ntp_client: Option<String>,
+}
+
+/// Structure for querying the clock synchronization state from the
+/// database.
+///
+/// Mirrors `SchemaTimeSyncInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = timesyncinfo)]
+pub struct TimeSyncInfo {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Whether the kernel considers the clock synchronized.
+    pub synchronized: bool,
+
+    /// Offset still being corrected, in microseconds.
+    pub offset_us: f64,
+
+    /// Maximum error of the clock, in microseconds.
+    pub max_error_us: i64,
+
+    /// Estimated error of the clock, in microseconds.
+    pub est_error_us: i64,
+
+    /// NTP client running on the host ("systemd-timesyncd", "chrony" or
+    /// "ntpd"), `None` when there is none.
+    pub ntp_client: Option<String>,
+}
+
+/// Structure for inserting the disk usage of watched directories into the
+/// database.
+///
+/// A directory's usage is stored with the first sample after its walk
+/// finishes.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = pathinfo)]
+pub struct SchemaPathInfo {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Directory, as configured in `[[monitor.paths]]`.
+    pub path: String,
+
+    /// Bytes allocated on disk by the tree.
+    pub total_bytes: i64,
+
+    pub file_count: i64,
+
+    pub dir_count: i64,
+
+    /// False when the walk stopped at its entry or time limit.
+    pub complete: bool,
+
+    /// Largest files as a JSON array of `{"bytes", "path"}` objects.
+    pub largest_files: String,
+}
+
+/// Structure for querying the disk usage of watched directories from the
+/// database.
+///
+/// Mirrors `SchemaPathInfo` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = pathinfo)]
+pub struct PathInfo {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Directory, as configured in `[[monitor.paths]]`.
+    pub path: String,
+
+    /// Bytes allocated on disk by the tree.
+    pub total_bytes: i64,
+
+    pub file_count: i64,
+
+    pub dir_count: i64,
+
+    /// False when the walk stopped at its entry or time limit.
+    pub complete: bool,
+
+    /// Largest files as a JSON array of `{"bytes", "path"}` objects.
+    pub largest_files: String,
+}
+
+/// Structure for inserting login and SSH authentication events into the
+/// database.
+///
+/// Events are not tied to a snapshot: each one keeps the time it happened
+/// at, as read from `wtmp` or the authentication log.
+#[derive(Insertable, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[diesel(table_name = securityevent)]
+pub struct SchemaSecurityEvent {
+    /// RFC3339 time of the event, in UTC.
+    pub timestamp: String,
+
+    /// Where the event was read from: "wtmp", "auth_log" or "journal".
+    pub source: String,
+
+    /// "login" for sessions from `wtmp`; "accepted", "failed" or
+    /// "invalid_user" for SSH attempts.
+    pub kind: String,
+
+    /// User that logged in or that the attempt was made for.
+    pub username: String,
+
+    /// Address the login or attempt came from, `None` for local logins.
+    pub source_ip: Option<String>,
+
+    /// SSH authentication method (e.g. "password", "publickey").
+    pub auth_method: Option<String>,
+
+    /// Terminal of a login session (e.g. "pts/0").
+    pub tty: Option<String>,
+}
+
+/// Structure for querying login and SSH authentication events from the
+/// database.
+///
+/// Mirrors `SchemaSecurityEvent` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = securityevent)]
+pub struct SecurityEvent {
+    /// Database-generated unique identifier for this event.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// RFC3339 time of the event, in UTC.
+    pub timestamp: String,
+
+    /// Where the event was read from: "wtmp", "auth_log" or "journal".
+    pub source: String,
+
+    /// "login" for sessions from `wtmp`; "accepted", "failed" or
+    /// "invalid_user" for SSH attempts.
+    pub kind: String,
+
+    /// User that logged in or that the attempt was made for.
+    pub username: String,
+
+    /// Address the login or attempt came from, `None` for local logins.
+    pub source_ip: Option<String>,
+
+    /// SSH authentication method (e.g. "password", "publickey").
+    pub auth_method: Option<String>,
+
+    /// Terminal of a login session (e.g. "pts/0").
+    pub tty: Option<String>,
+}
+
+/// Structure for inserting kernel events into the database.
+///
+/// One row per classified message of the kernel log.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = kernelevent)]
+pub struct SchemaKernelEvent {
+    /// Boot the event happened in, from `/proc/sys/kernel/random/boot_id`.
+    pub boot_id: String,
+
+    /// Sequence number of the kernel log record, unique within a boot.
+    pub seq: i64,
+
+    /// RFC3339 time of the event, in UTC.
+    pub timestamp: String,
+
+    /// Kernel log level, from 0 (emergency) to 7 (debug).
+    pub level: i32,
+
+    /// "oom_kill", "io_error", "segfault", "fs_readonly" or
+    /// "hardware_error".
+    pub category: String,
+
+    /// The kernel message.
+    pub message: String,
+
+    /// Process killed by the OOM killer or that crashed.
+    pub pid: Option<i32>,
+
+    /// Name of that process.
+    pub process: Option<String>,
+
+    /// Device of an I/O error or of a filesystem remounted read-only.
+    pub device: Option<String>,
+
+    /// Memory cgroup of an OOM kill victim.
+    pub cgroup: Option<String>,
+}
+
+/// Structure for querying kernel events from the database.
+///
+/// Mirrors `SchemaKernelEvent` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = kernelevent)]
+pub struct KernelEvent {
+    /// Database-generated unique identifier for this event.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Boot the event happened in, from `/proc/sys/kernel/random/boot_id`.
+    pub boot_id: String,
+
+    /// Sequence number of the kernel log record, unique within a boot.
+    pub seq: i64,
+
+    /// RFC3339 time of the event, in UTC.
+    pub timestamp: String,
+
+    /// Kernel log level, from 0 (emergency) to 7 (debug).
+    pub level: i32,
+
+    /// "oom_kill", "io_error", "segfault", "fs_readonly" or
+    /// "hardware_error".
+    pub category: String,
+
+    /// The kernel message.
+    pub message: String,
+
+    /// Process killed by the OOM killer or that crashed.
+    pub pid: Option<i32>,
+
+    /// Name of that process.
+    pub process: Option<String>,
+
+    /// Device of an I/O error or of a filesystem remounted read-only.
+    pub device: Option<String>,
+
+    /// Memory cgroup of an OOM kill victim.
+    pub cgroup: Option<String>,
+}
+
+/// Structure for inserting the package state of a day into the database.
+///
+/// One row per day, replaced by every check of that day, so the table
+/// keeps the daily history of pending updates.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = packagestatus)]
+pub struct SchemaPackageStatus {
+    /// Day of the check, `YYYY-MM-DD` in UTC.
+    pub day: String,
+
+    /// RFC3339 time of the latest check of the day, in UTC.
+    pub timestamp: String,
+
+    /// Package manager the updates were read from, "apt" or "apk".
+    pub manager: String,
+
+    /// Number of installed packages with a newer version available.
+    pub pending_updates: i32,
+
+    /// How many of them are security updates, `None` when the package
+    /// manager doesn't tell them apart.
+    pub security_updates: Option<i32>,
+
+    /// Whether `/var/run/reboot-required` exists.
+    pub reboot_required: bool,
+
+    /// Packages that asked for the reboot, as a JSON array.
+    pub reboot_packages: String,
+
+    /// RFC3339 time the package lists were last fetched, `None` when they
+    /// never were.
+    pub lists_updated_at: Option<String>,
+}
+
+/// Structure for querying the daily package state from the database.
+///
+/// Mirrors `SchemaPackageStatus` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = packagestatus)]
+pub struct PackageStatus {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Day of the check, `YYYY-MM-DD` in UTC.
+    pub day: String,
+
+    /// RFC3339 time of the latest check of the day, in UTC.
+    pub timestamp: String,
+
+    /// Package manager the updates were read from, "apt" or "apk".
+    pub manager: String,
+
+    /// Number of installed packages with a newer version available.
+    pub pending_updates: i32,
+
+    /// How many of them are security updates, `None` when the package
+    /// manager doesn't tell them apart.
+    pub security_updates: Option<i32>,
+
+    /// Whether `/var/run/reboot-required` exists.
+    pub reboot_required: bool,
+
+    /// Packages that asked for the reboot, as a JSON array.
+    pub reboot_packages: String,
+
+    /// RFC3339 time the package lists were last fetched, `None` when they
+    /// never were.
+    pub lists_updated_at: Option<String>,
+}
+
+/// Structure for inserting pending package updates into the database.
+///
+/// Stored with the day of the check, alongside its `packagestatus` row.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = packageupdate)]
+pub struct SchemaPackageUpdate {
+    /// Day of the check, `YYYY-MM-DD` in UTC.
+    pub day: String,
+
+    /// Name of the installed package.
+    pub name: String,
+
+    pub installed_version: String,
+
+    /// Newest version in the package lists.
+    pub available_version: String,
+
+    /// Whether the new version comes from a security repository.
+    pub security: bool,
+}
+
+/// Structure for querying pending package updates from the database.
+///
+/// Mirrors `SchemaPackageUpdate` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = packageupdate)]
+pub struct PackageUpdate {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Day of the check, `YYYY-MM-DD` in UTC.
+    pub day: String,
+
+    /// Name of the installed package.
+    pub name: String,
+
+    pub installed_version: String,
+
+    /// Newest version in the package lists.
+    pub available_version: String,
+
+    /// Whether the new version comes from a security repository.
+    pub security: bool,
+}
+
+impl Default for SchemaSysInfo {
+    fn default() -> Self {
+        Self {
+            timestamp: "".to_string(),
+            cpu_usage: 0.0,
+            ram_usage: 0.0,
+            total_ram: 0.0,
+            free_ram: 0.0,
+            used_swap: 0.0,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            available_ram: 0,
+            cached_ram: 0,
+            buffers_ram: 0,
+            shared_ram: 0,
+            total_swap: 0,
+            free_swap: 0,
+            dirty_ram: 0,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 0,
+            // user_id: 0,
+        }
+    }
+}
+
+impl Default for SchemaDiskInfo {
+    fn default() -> Self {
+        Self {
+            sysinfo_id: 0,
+            filesystem: "".to_string(),
+            size: 0,
+            used: 0,
+            available: 0,
+            used_percentage: 0,
+            mounted_path: "".to_string(),
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_schema_sys_info_default() {
+        let sys_info = SchemaSysInfo::default();
+        assert_eq!(sys_info.timestamp, "");
+        assert_eq!(sys_info.cpu_usage, 0.0);
+        assert_eq!(sys_info.ram_usage, 0.0);
+        assert_eq!(sys_info.total_ram, 0.0);
+        assert_eq!(sys_info.free_ram, 0.0);
+        assert_eq!(sys_info.used_swap, 0.0);
+        assert_eq!(sys_info.load_avg_one, 0.0);
+        assert_eq!(sys_info.load_avg_five, 0.0);
+        assert_eq!(sys_info.load_avg_fifteen, 0.0);
+    }
+
+    #[test]
+    fn test_schema_disk_info_default() {
+        let disk_info = SchemaDiskInfo::default();
+        assert_eq!(disk_info.sysinfo_id, 0);
+        assert_eq!(disk_info.filesystem, "");
+        assert_eq!(disk_info.size, 0);
+        assert_eq!(disk_info.used, 0);
+        assert_eq!(disk_info.available, 0);
+        assert_eq!(disk_info.used_percentage, 0);
+        assert_eq!(disk_info.mounted_path, "");
+    }
+
+    #[test]
+    fn test_schema_sys_info_creation() {
+        let sys_info = SchemaSysInfo {
+            timestamp: Utc::now().to_rfc3339(),
+            cpu_usage: 25.5,
+            ram_usage: 1024.0,
+            total_ram: 8192.0,
+            free_ram: 4096.0,
+            used_swap: 512.0,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            available_ram: 0,
+            cached_ram: 0,
+            buffers_ram: 0,
+            shared_ram: 0,
+            total_swap: 0,
+            free_swap: 0,
+            dirty_ram: 0,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 0,
+        };
+
+        assert!(!sys_info.timestamp.is_empty());
+        assert_eq!(sys_info.cpu_usage, 25.5);
+        assert_eq!(sys_info.ram_usage, 1024.0);
+        assert_eq!(sys_info.total_ram, 8192.0);
+        assert_eq!(sys_info.free_ram, 4096.0);
+        assert_eq!(sys_info.used_swap, 512.0);
+    }
+
+    #[test]
+    fn test_schema_disk_info_creation() {
+        let disk_info = SchemaDiskInfo {
+            sysinfo_id: 1,
+            filesystem: "ext4".to_string(),
+            size: 1000,
+            used: 500,
+            available: 500,
+            used_percentage: 50,
+            mounted_path: "/".to_string(),
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
+        };
+
+        assert_eq!(disk_info.sysinfo_id, 1);
+        assert_eq!(disk_info.filesystem, "ext4");
+        assert_eq!(disk_info.size, 1000);
+        assert_eq!(disk_info.used, 500);
+        assert_eq!(disk_info.available, 500);
+        assert_eq!(disk_info.used_percentage, 50);
+        assert_eq!(disk_info.mounted_path, "/");
+    }
+
+    #[test]
+    fn test_sys_info_serialization() {
+        let sys_info = SysInfo {
+            id: Some(1),
+            timestamp: Utc::now().to_rfc3339(),
+            cpu_usage: 25.5,
+            ram_usage: 1024.0,
+            total_ram: 8192.0,
+            free_ram: 4096.0,
+            used_swap: 512.0,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            available_ram: 0,
+            cached_ram: 0,
+            buffers_ram: 0,
+            shared_ram: 0,
+            total_swap: 0,
+            free_swap: 0,
+            dirty_ram: 0,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 0,
+        };
+
+        let serialized = serde_json::to_string(&sys_info).unwrap();
+        assert!(serialized.contains("\"id\":1"));
+        assert!(serialized.contains("\"cpu_usage\":25.5"));
+        assert!(serialized.contains("\"ram_usage\":1024"));
+    }
+
+    #[test]
+    fn test_disk_info_serialization() {
+        let disk_info = DiskInfo {
+            id: Some(1),
+            sysinfo_id: 1,
+            filesystem: "ext4".to_string(),
+            size: 1000,
+            used: 500,
+            available: 500,
+            used_percentage: 50,
+            mounted_path: "/".to_string(),
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
+        };
+
+        let serialized = serde_json::to_string(&disk_info).unwrap();
+        assert!(serialized.contains("\"id\":1"));
+        assert!(serialized.contains("\"sysinfo_id\":1"));
+        assert!(serialized.contains("\"filesystem\":\"ext4\""));
+        assert!(serialized.contains("\"mounted_path\":\"/\""));
+    }
+
+    #[test]
+    fn test_sys_info_debug_format() {
+        let sys_info = SysInfo {
+            id: Some(1),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            cpu_usage: 25.5,
+            ram_usage: 1024.0,
+            total_ram: 8192.0,
+            free_ram: 4096.0,
+            used_swap: 512.0,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            available_ram: 0,
+            cached_ram: 0,
+            buffers_ram: 0,
+            shared_ram: 0,
+            total_swap: 0,
+            free_swap: 0,
+            dirty_ram: 0,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 0,
+        };
+
+        let debug_str = format!("{:?}", sys_info);
+        assert!(debug_str.contains("SysInfo"));
+        assert!(debug_str.contains("25.5"));
+        assert!(debug_str.contains("1024"));
+    }
+
+    #[test]
+    fn test_edge_values() {
+        // Test with extreme values
+        let sys_info = SchemaSysInfo {
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            cpu_usage: 100.0,
+            ram_usage: 0.0,
+            total_ram: f32::MAX,
+            free_ram: f32::MAX,
+            used_swap: 0.0,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            available_ram: 0,
+            cached_ram: 0,
+            buffers_ram: 0,
+            shared_ram: 0,
+            total_swap: 0,
+            free_swap: 0,
+            dirty_ram: 0,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 0,
+        };
+
+        assert_eq!(sys_info.cpu_usage, 100.0);
+        assert_eq!(sys_info.ram_usage, 0.0);
+        assert_eq!(sys_info.total_ram, f32::MAX);
+        assert_eq!(sys_info.free_ram, f32::MAX);
+        assert_eq!(sys_info.used_swap, 0.0);
+
+        // Test disk info with edge values
+        let disk_info = SchemaDiskInfo {
+            sysinfo_id: i32::MAX,
+            filesystem: "test".to_string(),
+            size: i32::MAX,
+            used: 0,
+            available: i32::MAX,
+            used_percentage: 100,
+            mounted_path: "/test".to_string(),
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
+        };
+
+        assert_eq!(disk_info.sysinfo_id, i32::MAX);
+        assert_eq!(disk_info.size, i32::MAX);
+        assert_eq!(disk_info.used, 0);
+        assert_eq!(disk_info.available, i32::MAX);
+        assert_eq!(disk_info.used_percentage, 100);
+    }
+
+    #[test]
+    fn test_deserialization() {
+        let json_str = r#"{
+            "timestamp": "2024-01-01T00:00:00Z",
+            "cpu_usage": 25.5,
+            "ram_usage": 1024.0,
+            "total_ram": 8192.0,
+            "free_ram": 4096.0,
+            "used_swap": 512.0
+        }"#;
+
+        let sys_info: SchemaSysInfo = serde_json::from_str(json_str).unwrap();
+        assert_eq!(sys_info.timestamp, "2024-01-01T00:00:00Z");
+        assert_eq!(sys_info.cpu_usage, 25.5);
+        assert_eq!(sys_info.ram_usage, 1024.0);
+        // Load averages are optional in older payloads
+        assert_eq!(sys_info.load_avg_one, 0.0);
+    }
+
+    #[test]
+    fn test_cpu_info_serialization() {
+        let cpu_info = CpuInfo {
+            id: Some(3),
+            sysinfo_id: 1,
+            core_index: 2,
+            name: "cpu2".to_string(),
+            cpu_usage: 100.0,
+            frequency: 3200,
+        };
+
+        let serialized = serde_json::to_string(&cpu_info).unwrap();
+        assert!(serialized.contains("\"sysinfo_id\":1"));
+        assert!(serialized.contains("\"core_index\":2"));
+        assert!(serialized.contains("\"name\":\"cpu2\""));
+        assert!(serialized.contains("\"cpu_usage\":100"));
+    }
+}
+
+/// Structure for inserting the number of lines of a watched log file
+/// matching a pattern into the database.
+///
+/// One row per pattern of `[[monitor.logs]]` and run of the `logs`
+/// collector, 0 when no line matched.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = logmatch)]
+pub struct SchemaLogMatch {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the log, from `[[monitor.logs]]`.
+    pub log: String,
+
+    /// Name of the pattern.
+    pub pattern: String,
+
+    /// Lines appended since the previous run that matched the pattern.
+    pub count: i64,
+
+    /// `count` over the time since the previous run, per minute.
+    pub per_minute: f64,
+}
+
+/// Structure for querying log pattern counts from the database.
+///
+/// Mirrors `SchemaLogMatch` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = logmatch)]
+pub struct LogMatch {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the log, from `[[monitor.logs]]`.
+    pub log: String,
+
+    /// Name of the pattern.
+    pub pattern: String,
+
+    /// Lines appended since the previous run that matched the pattern.
+    pub count: i64,
+
+    /// `count` over the time since the previous run, per minute.
+    pub per_minute: f64,
+}
+
+/// Structure for inserting a line of a watched log file that matched a
+/// pattern into the database.
+///
+/// Only the latest `max_lines` matching lines of each run are stored.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = logevent)]
+pub struct SchemaLogEvent {
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the log, from `[[monitor.logs]]`.
+    pub log: String,
+
+    /// Name of the pattern the line matched.
+    pub pattern: String,
+
+    /// The line, cut to 1024 bytes.
+    pub line: String,
+}
+
+/// Structure for querying matching log lines from the database.
+///
+/// Mirrors `SchemaLogEvent` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = logevent)]
+pub struct LogEvent {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Foreign key reference to the associated system information record.
+    pub sysinfo_id: i32,
+
+    /// Name of the log, from `[[monitor.logs]]`.
+    pub log: String,
+
+    /// Name of the pattern the line matched.
+    pub pattern: String,
+
+    /// The line, cut to 1024 bytes.
+    pub line: String,
+}
+
+/// Structure for inserting a rollup of a metric into the database.
+///
+/// One row per metric, series and bucket of 1 minute, 1 hour or 1 day,
+/// written by the compactor of the retention job.
+#[derive(Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = metricrollup)]
+pub struct SchemaMetricRollup {
+    /// Length of the bucket: 60, 3600 or 86400 seconds.
+    pub resolution_secs: i32,
+
+    /// RFC3339 start of the bucket.
+    pub bucket: String,
+
+    /// Metric name, a `sysinfo` column (e.g. "cpu_usage") or a disk metric
+    /// (e.g. "disk_used").
+    pub metric: String,
+
+    /// Mount point of disk metrics, empty for host metrics.
+    pub series: String,
+
+    pub avg_value: f64,
+
+    pub min_value: f64,
+
+    pub max_value: f64,
+
+    /// Latest value of the bucket.
+    pub last_value: f64,
+
+    /// Raw samples summarized by the bucket.
+    pub sample_count: i64,
+}
+
+/// Structure for querying metric rollups from the database.
+///
+/// Mirrors `SchemaMetricRollup` with the database-generated ID.
+#[derive(Queryable, Selectable, Identifiable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = metricrollup)]
+pub struct MetricRollup {
+    /// Database-generated unique identifier for this record.
+    #[diesel(column_name = id)]
+    pub id: Option<i32>,
+
+    /// Length of the bucket: 60, 3600 or 86400 seconds.
+    pub resolution_secs: i32,
+
+    /// RFC3339 start of the bucket.
+    pub bucket: String,
+
+    /// Metric name, a `sysinfo` column (e.g. "cpu_usage") or a disk metric
+    /// (e.g. "disk_used").
+    pub metric: String,
+
+    /// Mount point of disk metrics, empty for host metrics.
+    pub series: String,
+
+    pub avg_value: f64,
+
+    pub min_value: f64,
+
+    pub max_value: f64,
+
+    /// Latest value of the bucket.
+    pub last_value: f64,
+
+    /// Raw samples summarized by the bucket.
+    pub sample_count: i64,
 }
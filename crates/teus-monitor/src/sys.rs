@@ -1,12 +1,26 @@
+use super::collector::{CollectorRegistry, Sample};
+use super::collectors::{
+    self, cgroups::CgroupsCollector, dirsize::PathsCollector, diskio::DiskIoCollector,
+    disks::DisksCollector, kmsg::KmsgCollector, logwatch::LogsCollector, memory::MemInfo,
+    network::NetworkCollector, packages::PackagesCollector, pressure::PressureCollector,
+    processes::ProcessesCollector, scripts::ScriptsCollector, security::SecurityCollector,
+    sensors::SensorsCollector, sockets::SocketsCollector, timesync::TimeSyncCollector,
+};
 use super::mutation;
-use super::schema::{SchemaDiskInfo, SchemaSysInfo}; // Import the Diesel insertable structs
+use super::schema::{SchemaCpuInfo, SchemaSysInfo}; // Import the Diesel insertable structs
 use chrono::Utc;
 use teus_database::storage::Storage;
 use teus_database::storage::TeuSQLiteConnection;
-use teus_types::config::Config;
+use teus_types::config::MonitorConfig;
 // use diesel::SqliteConnection; // Import SqliteConnection
-use std::{thread, time::Duration}; // Import Mutex
-use sysinfo::{Disks, MemoryRefreshKind, System};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+}; // Import Mutex
+use sysinfo::{MINIMUM_CPU_UPDATE_INTERVAL, MemoryRefreshKind, System};
+use tokio::sync::watch;
+use tokio::task;
+use tokio::time::{self, Instant, MissedTickBehavior};
 
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
@@ -20,6 +34,14 @@ pub struct DiskInfo {
     pub used_percentage: usize,
 }
 
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct CpuCoreInfo {
+    pub name: String,
+    pub cpu_usage: f64,
+    pub frequency: u64,
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct SysInfo {
@@ -31,6 +53,11 @@ pub struct SysInfo {
     pub total_ram: f64,
     pub free_ram: f64,
     pub used_swap: f64,
+    pub load_avg_one: f64,
+    pub load_avg_five: f64,
+    pub load_avg_fifteen: f64,
+    pub memory: MemInfo,
+    pub cpus: Vec<CpuCoreInfo>,
     pub disks: Vec<DiskInfo>,
 }
 
@@ -52,136 +79,309 @@ impl SysInfo {
             total_ram,
             free_ram,
             used_swap,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            memory: MemInfo::default(),
+            cpus: Vec::new(),
             disks,
         }
     }
 
-    // Default constructor
-    pub fn default() -> Self {
-        Self {
-            id: 0,
-            timestamp: Utc::now().to_rfc3339(),
-            cpu_usage: 0.0,
-            ram_usage: 0.0,
-            total_ram: 0.0,
-            free_ram: 0.0,
-            used_swap: 0.0,
-            disks: vec![DiskInfo {
-                filesystem: String::new(),
-                size: 0,
-                used: 0,
-                available: 0,
-                used_percentage: 0,
-                mounted_path: String::new(),
-            }],
-        }
-    }
-
-    pub fn run_monitor(mut self, config: &Config) {
-        let storage = match Storage::new(&config.database.path) {
-            Ok(storage) => storage,
+    /// Reads the figures of a sample from `sys`, which must have had its
+    /// CPU and memory refreshed, and the memory breakdown from procfs.
+    pub fn from_system(sys: &System, procfs_root: &Path) -> Self {
+        let mut info = Self::default();
+
+        info.total_ram = sys.total_memory() as f64;
+        info.free_ram = sys.free_memory() as f64;
+        info.used_swap = sys.used_swap() as f64;
+        info.ram_usage = sys.used_memory() as f64; // Use used_memory for ram_usage
+
+        info.cpus = sys
+            .cpus()
+            .iter()
+            .map(|cpu| CpuCoreInfo {
+                name: cpu.name().to_string(),
+                cpu_usage: cpu.cpu_usage() as f64,
+                frequency: cpu.frequency(),
+            })
+            .collect();
+        info.cpu_usage = average_cpu_usage(&info.cpus);
+
+        let load_avg = System::load_average();
+        info.load_avg_one = load_avg.one;
+        info.load_avg_five = load_avg.five;
+        info.load_avg_fifteen = load_avg.fifteen;
+
+        info.memory = match collectors::memory::read_meminfo(procfs_root) {
+            Ok(memory) => memory,
             Err(e) => {
-                eprintln!("Failed to create storage: {}", e);
-                return;
+                eprintln!("Failed to read memory breakdown: {}", e);
+                // Keep what sysinfo knows when procfs is unavailable
+                MemInfo {
+                    total: sys.total_memory(),
+                    free: sys.free_memory(),
+                    available: sys.available_memory(),
+                    swap_total: sys.total_swap(),
+                    swap_free: sys.free_swap(),
+                    ..Default::default()
+                }
             }
         };
 
-        // Get a mutable connection from the Arc<Mutex<>>
-        let mut conn_guard = match storage.diesel_conn.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                eprintln!("Failed to acquire lock on DB connection: {}", poisoned);
-                // Handle the poisoned mutex appropriately, maybe panic or return
-                return;
-            }
-        };
-        // Dereference the guard to get the &mut SqliteConnection
-        let conn: &mut TeuSQLiteConnection = &mut *conn_guard;
-
-        let mut sys = System::new_all();
-        let disks_sysinfo = Disks::new_with_refreshed_list(); // Renamed to avoid conflict
-
-        sys.refresh_all();
-        sys.refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
-
-        self.total_ram = sys.total_memory() as f64;
-        self.free_ram = sys.free_memory() as f64;
-        self.used_swap = sys.used_swap() as f64;
-        self.ram_usage = sys.used_memory() as f64; // Use used_memory for ram_usage
-
-        thread::sleep(Duration::from_millis(250));
-        sys.refresh_cpu_all();
-
-        let cpu_count = sys.cpus().len();
-        let total_cpu_usage: f64 = sys.cpus().iter().map(|cpu| cpu.cpu_usage() as f64).sum();
-        self.cpu_usage = if cpu_count > 0 {
-            total_cpu_usage / cpu_count as f64
-        } else {
-            0.0
-        };
-
-        self.timestamp = Utc::now().to_rfc3339(); // Ensure timestamp is current
+        info.timestamp = Utc::now().to_rfc3339(); // Ensure timestamp is current
+        info
+    }
 
+    /// Stores the sample with its per-core CPU records.
+    pub fn store(&self, conn: &mut TeuSQLiteConnection) -> Result<Sample, diesel::result::Error> {
         // Create the SchemaSysInfo struct for insertion
         let new_sys_info_to_insert = SchemaSysInfo {
-            timestamp: self.timestamp,
+            timestamp: self.timestamp.clone(),
             cpu_usage: self.cpu_usage as f32, // Cast f64 to f32
             ram_usage: self.ram_usage as f32, // Cast f64 to f32
             total_ram: self.total_ram as f32, // Cast f64 to f32
             free_ram: self.free_ram as f32,   // Cast f64 to f32
             used_swap: self.used_swap as f32, // Cast f64 to f32
+            load_avg_one: self.load_avg_one as f32,
+            load_avg_five: self.load_avg_five as f32,
+            load_avg_fifteen: self.load_avg_fifteen as f32,
+            available_ram: self.memory.available as i64,
+            cached_ram: self.memory.cached as i64,
+            buffers_ram: self.memory.buffers as i64,
+            shared_ram: self.memory.shared as i64,
+            total_swap: self.memory.swap_total as i64,
+            free_swap: self.memory.swap_free as i64,
+            dirty_ram: self.memory.dirty as i64,
+            hugepages_total: self.memory.hugepages_total as i64,
+            hugepages_free: self.memory.hugepages_free as i64,
+            hugepage_size: self.memory.hugepage_size as i64,
         };
 
         // Insert system info using the SchemaSysInfo struct
-        let sysinfo_id = match mutation::insert_sysinfo(conn, &new_sys_info_to_insert) {
-            Ok(id) => id,
+        let sysinfo_id = mutation::insert_sysinfo(conn, &new_sys_info_to_insert)?;
+
+        let cpu_infos_to_insert: Vec<SchemaCpuInfo> = self
+            .cpus
+            .iter()
+            .enumerate()
+            .map(|(index, cpu)| SchemaCpuInfo {
+                sysinfo_id,
+                core_index: index as i32,
+                name: cpu.name.clone(),
+                cpu_usage: cpu.cpu_usage as f32,
+                frequency: cpu.frequency as i32,
+            })
+            .collect();
+
+        if !cpu_infos_to_insert.is_empty()
+            && let Err(e) = mutation::insert_multiple_cpuinfo(conn, &cpu_infos_to_insert)
+        {
+            eprintln!("Failed to insert cpu info batch: {}", e);
+        }
+
+        Ok(Sample {
+            id: sysinfo_id,
+            timestamp: self.timestamp.clone(),
+        })
+    }
+}
+
+/// The system monitor: takes a sample every `interval_secs` and runs the
+/// collectors due in that cycle.
+///
+/// One `System` is kept for the lifetime of the monitor, so the CPU usage
+/// of a sample is measured since the previous one instead of over a short
+/// blocking sleep.
+pub struct Monitor {
+    interval: Duration,
+    procfs_root: PathBuf,
+    sys: System,
+    collectors: CollectorRegistry,
+}
+
+impl Monitor {
+    pub fn new(config: &MonitorConfig, collectors: CollectorRegistry) -> Self {
+        let mut sys = System::new();
+        // Baseline for the CPU usage of the first sample
+        sys.refresh_cpu_all();
+
+        Self {
+            interval: Duration::from_secs(config.interval_secs.max(1)),
+            procfs_root: PathBuf::from(&config.procfs_root),
+            sys,
+            collectors,
+        }
+    }
+
+    /// Takes a sample and runs the collectors due this cycle, then stores
+    /// them all. The database is only locked for the inserts, reading the
+    /// sources doesn't hold up the API.
+    pub fn record(&mut self, storage: &Storage) -> Option<Sample> {
+        self.sys.refresh_cpu_all();
+        self.sys
+            .refresh_memory_specifics(MemoryRefreshKind::everything());
+        let info = SysInfo::from_system(&self.sys, &self.procfs_root);
+        self.collectors.collect_due();
+
+        let mut conn = match storage.write() {
+            Ok(conn) => conn,
+            Err(poisoned) => {
+                eprintln!("Failed to acquire lock on DB connection: {}", poisoned);
+                return None;
+            }
+        };
+        let sample = match info.store(&mut conn) {
+            Ok(sample) => sample,
             Err(e) => {
                 eprintln!("Failed to insert system info: {}", e);
-                // Drop the lock before returning
-                drop(conn_guard);
-                return;
+                return None;
             }
         };
+        self.collectors.store_collected(&mut conn, &sample);
+        Some(sample)
+    }
 
-        // Prepare disk info data for batch insertion
-        let mut disk_infos_to_insert: Vec<SchemaDiskInfo> = Vec::new();
-        for disk in disks_sysinfo.list() {
-            let space_used = disk.total_space() - disk.available_space();
-            // Calculate usage percentage correctly
-            let usage_percentage = if disk.total_space() > 0 {
-                (space_used as f64 / disk.total_space() as f64 * 100.0) as i32
-            } else {
-                0
-            };
+    /// Records a sample every interval until `shutdown` is set or dropped.
+    ///
+    /// Ticks are scheduled from the start time, not from the end of the
+    /// previous cycle, so sampling doesn't drift. A cycle that overruns the
+    /// interval skips the missed ticks instead of catching up in a burst.
+    pub async fn run(mut self, storage: Storage, mut shutdown: watch::Receiver<bool>) {
+        // sysinfo needs a minimum delay between two CPU refreshes
+        let start = Instant::now() + MINIMUM_CPU_UPDATE_INTERVAL;
+        let mut ticks = time::interval_at(start, self.interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = shutdown.changed() => break,
+            }
 
-            let fs_name = disk.name().to_string_lossy().to_string();
-            let mount_point = disk.mount_point().to_string_lossy().to_string();
-
-            disk_infos_to_insert.push(SchemaDiskInfo {
-                sysinfo_id, // Use the ID from the inserted sysinfo
-                filesystem: fs_name,
-                size: (disk.total_space() / 1024 / 1024) as i32, // Convert bytes to MB (adjust if needed) and cast usize to i32
-                used: (space_used / 1024 / 1024) as i32, // Convert bytes to MB and cast usize to i32
-                available: (disk.available_space() / 1024 / 1024) as i32, // Convert bytes to MB and cast usize to i32
-                used_percentage: usage_percentage, // Use calculated percentage
-                mounted_path: mount_point,
+            /* collection and SQLite writes are blocking, keep them off the async workers */
+            let storage = storage.clone();
+            let cycle = task::spawn_blocking(move || {
+                self.record(&storage);
+                self
             });
+            self = match cycle.await {
+                Ok(monitor) => monitor,
+                Err(e) => {
+                    eprintln!("Monitor cycle failed, stopping the monitor: {}", e);
+                    return;
+                }
+            };
         }
+    }
+}
 
-        // Insert disk info using the SchemaDiskInfo structs
-        if !disk_infos_to_insert.is_empty() {
-            if let Err(e) = mutation::insert_multiple_diskinfo(conn, &disk_infos_to_insert) {
-                eprintln!("Failed to insert disk info batch: {}", e);
-            }
+/// Builds the registry of every built-in collector, scheduled as set in
+/// `[monitor.collectors.*]`.
+pub fn builtin_collectors(config: &MonitorConfig) -> CollectorRegistry {
+    let procfs_root = PathBuf::from(&config.procfs_root);
+
+    let mut registry = CollectorRegistry::new(config);
+    registry.register(NetworkCollector::new(procfs_root.clone()));
+    registry.register(DiskIoCollector::new(procfs_root.clone()));
+    registry.register(DisksCollector::new(config.disks.clone()));
+    registry.register(PressureCollector::new(procfs_root.clone()));
+    registry.register(TimeSyncCollector::new(procfs_root.clone()));
+    registry.register(SensorsCollector::new(PathBuf::from(&config.sysfs_root)));
+    if config.top_processes > 0 {
+        registry.register(ProcessesCollector::new(config.top_processes));
+    }
+    registry.register(CgroupsCollector::new(PathBuf::from(&config.cgroup_root)));
+    registry.register(SocketsCollector::new(procfs_root.clone()));
+    registry.register(SecurityCollector::new(config.security.clone()));
+    registry.register(KmsgCollector::new(
+        PathBuf::from(&config.kmsg_path),
+        procfs_root,
+    ));
+    registry.register(PackagesCollector::new(PathBuf::from(&config.host_root)));
+    if !config.scripts.is_empty() {
+        registry.register(ScriptsCollector::new(&config.scripts, config.interval_secs));
+    }
+    if !config.paths.is_empty() {
+        registry.register(PathsCollector::new(&config.paths));
+    }
+    if !config.logs.is_empty() {
+        registry.register(LogsCollector::new(&config.logs));
+    }
+
+    for (index, script) in config.scripts.iter().enumerate() {
+        if config.scripts[..index]
+            .iter()
+            .any(|s| s.name == script.name)
+        {
+            eprintln!(
+                "Script {} is defined more than once in [[monitor.scripts]], their values are stored together",
+                script.name
+            );
         }
-        // Lock is automatically dropped here when conn_guard goes out of scope
     }
+    for (index, log) in config.logs.iter().enumerate() {
+        if config.logs[..index].iter().any(|l| l.name == log.name) {
+            eprintln!(
+                "Log {} is defined more than once in [[monitor.logs]], their counts are stored together",
+                log.name
+            );
+        }
+    }
+    for name in registry.unknown_schedules() {
+        eprintln!(
+            "Unknown collector in [monitor.collectors.{}], ignoring it",
+            name
+        );
+    }
+    registry
+}
+
+impl Default for SysInfo {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            timestamp: Utc::now().to_rfc3339(),
+            cpu_usage: 0.0,
+            ram_usage: 0.0,
+            total_ram: 0.0,
+            free_ram: 0.0,
+            used_swap: 0.0,
+            load_avg_one: 0.0,
+            load_avg_five: 0.0,
+            load_avg_fifteen: 0.0,
+            memory: MemInfo::default(),
+            cpus: Vec::new(),
+            disks: vec![DiskInfo {
+                filesystem: String::new(),
+                size: 0,
+                used: 0,
+                available: 0,
+                used_percentage: 0,
+                mounted_path: String::new(),
+            }],
+        }
+    }
+}
+
+/// Averages the usage of every core into the overall CPU usage.
+fn average_cpu_usage(cpus: &[CpuCoreInfo]) -> f64 {
+    if cpus.is_empty() {
+        return 0.0;
+    }
+    cpus.iter().map(|cpu| cpu.cpu_usage).sum::<f64>() / cpus.len() as f64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use teus_types::config::{Config, DatabaseConfig, Environment, MonitorConfig, ServerConfig};
+    use crate::query;
+    use crate::test_utils::migrated_storage;
+    use teus_types::config::{
+        Config, DatabaseConfig, DiskFilterConfig, Environment, MonitorConfig, SecurityConfig,
+        ServerConfig,
+    };
 
     #[allow(dead_code)]
     fn create_test_config() -> Config {
@@ -194,8 +394,23 @@ mod tests {
             },
             database: DatabaseConfig {
                 path: ":memory:".to_string(),
+                retention: Default::default(),
+            },
+            monitor: MonitorConfig {
+                interval_secs: 60,
+                procfs_root: "/proc".to_string(),
+                sysfs_root: "/sys".to_string(),
+                cgroup_root: "/sys/fs/cgroup".to_string(),
+                kmsg_path: "/dev/kmsg".to_string(),
+                host_root: "/".to_string(),
+                top_processes: 10,
+                disks: DiskFilterConfig::default(),
+                security: SecurityConfig::default(),
+                scripts: Vec::new(),
+                paths: Vec::new(),
+                logs: Vec::new(),
+                collectors: Default::default(),
             },
-            monitor: MonitorConfig { interval_secs: 60 },
         }
     }
 
@@ -273,6 +488,8 @@ mod tests {
         assert_eq!(sysinfo.total_ram, 0.0);
         assert_eq!(sysinfo.free_ram, 0.0);
         assert_eq!(sysinfo.used_swap, 0.0);
+        assert_eq!(sysinfo.load_avg_one, 0.0);
+        assert!(sysinfo.cpus.is_empty());
         assert_eq!(sysinfo.disks.len(), 1);
         assert_eq!(sysinfo.disks[0].filesystem, "");
         assert_eq!(sysinfo.disks[0].mounted_path, "");
@@ -407,4 +624,69 @@ mod tests {
         assert_eq!(sysinfo.disks[0].mounted_path, "/");
         assert_eq!(sysinfo.disks[1].mounted_path, "/home");
     }
+
+    #[test]
+    fn test_average_cpu_usage() {
+        assert_eq!(average_cpu_usage(&[]), 0.0);
+
+        let cpus = vec![
+            CpuCoreInfo {
+                name: "cpu0".to_string(),
+                cpu_usage: 100.0,
+                frequency: 2400,
+            },
+            CpuCoreInfo {
+                name: "cpu1".to_string(),
+                cpu_usage: 0.0,
+                frequency: 2400,
+            },
+        ];
+        assert_eq!(average_cpu_usage(&cpus), 50.0);
+    }
+
+    #[test]
+    fn test_monitor_record_stores_samples() {
+        let (_dir, storage) = migrated_storage();
+        let config = create_test_config().monitor;
+        let mut monitor = Monitor::new(&config, CollectorRegistry::new(&config));
+
+        std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+        let first = monitor.record(&storage).expect("first sample not stored");
+        let second = monitor.record(&storage).expect("second sample not stored");
+        assert!(second.id > first.id);
+
+        let mut conn = storage.write().unwrap();
+        let (latest, _) = query::get_latest_sysinfo_with_disks(&mut conn)
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.id, Some(second.id));
+        assert_eq!(latest.timestamp, second.timestamp);
+        let cpus = query::get_cpuinfo_by_sysinfo_id(&mut conn, second.id).unwrap();
+        assert!(!cpus.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_monitor_run_stops_on_shutdown() {
+        let (_dir, storage) = migrated_storage();
+        let config = create_test_config().monitor;
+        let monitor = Monitor::new(&config, CollectorRegistry::new(&config));
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn(monitor.run(storage.clone(), shutdown_rx));
+
+        // The first sample is taken right after the CPU baseline delay
+        time::sleep(MINIMUM_CPU_UPDATE_INTERVAL * 3).await;
+        shutdown_tx.send(true).unwrap();
+        time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("monitor did not stop")
+            .unwrap();
+
+        let mut conn = storage.write().unwrap();
+        assert!(
+            query::get_latest_sysinfo_with_disks(&mut conn)
+                .unwrap()
+                .is_some()
+        );
+    }
 }
@@ -0,0 +1,29 @@
+//! Helpers shared by the unit tests of the crate.
+
+use crate::collector::{Collector, CollectorError, Sample};
+use teus_database::migrations;
+use teus_database::storage::{Storage, TeuSQLiteConnection};
+
+/// Storage in a temporary directory with every migration of the repository applied.
+pub fn migrated_storage() -> (tempfile::TempDir, Storage) {
+    let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+    let path = dir.path().join("teus.db");
+    let storage = Storage::new(path.to_str().unwrap()).unwrap();
+    {
+        let mut conn = storage.write().unwrap();
+        migrations::apply_pending(&mut conn, path.to_str().unwrap()).unwrap();
+    }
+    (dir, storage)
+}
+
+/// Runs `collector` once against `sample`, as a monitoring cycle does:
+/// collects, then stores even if collecting failed.
+pub fn collect_and_store(
+    collector: &mut impl Collector,
+    conn: &mut TeuSQLiteConnection,
+    sample: &Sample,
+) -> Result<(), CollectorError> {
+    let collected = collector.collect();
+    collector.store(conn, sample)?;
+    collected
+}
@@ -1,5 +1,24 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    cgroupinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        path -> Text,
+        cpu_usage_usec -> BigInt,
+        cpu_user_usec -> BigInt,
+        cpu_system_usec -> BigInt,
+        memory_current -> Nullable<BigInt>,
+        memory_max -> Nullable<BigInt>,
+        io_read_bytes -> Nullable<BigInt>,
+        io_write_bytes -> Nullable<BigInt>,
+        pids_current -> Nullable<BigInt>,
+        cpu_usage_percentage -> Nullable<Float>,
+        io_read_bytes_per_sec -> Nullable<Float>,
+        io_write_bytes_per_sec -> Nullable<Float>,
+    }
+}
+
 diesel::table! {
     config (id) {
         id -> Nullable<Integer>,
@@ -7,6 +26,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    cpuinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        core_index -> Integer,
+        name -> Text,
+        cpu_usage -> Float,
+        frequency -> Integer,
+    }
+}
+
 diesel::table! {
     diskinfo (id) {
         id -> Nullable<Integer>,
@@ -17,6 +47,205 @@ diesel::table! {
         available -> Integer,
         used_percentage -> Integer,
         mounted_path -> Text,
+        inodes_total -> BigInt,
+        inodes_used -> BigInt,
+        inodes_free -> BigInt,
+        inodes_used_percentage -> Integer,
+    }
+}
+
+diesel::table! {
+    diskio (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        device -> Text,
+        reads_completed -> BigInt,
+        sectors_read -> BigInt,
+        read_time_ms -> BigInt,
+        writes_completed -> BigInt,
+        sectors_written -> BigInt,
+        write_time_ms -> BigInt,
+        io_time_ms -> BigInt,
+        read_bytes_per_sec -> Nullable<Float>,
+        write_bytes_per_sec -> Nullable<Float>,
+        read_iops -> Nullable<Float>,
+        write_iops -> Nullable<Float>,
+        avg_read_latency_ms -> Nullable<Float>,
+        avg_write_latency_ms -> Nullable<Float>,
+        busy_percentage -> Nullable<Float>,
+    }
+}
+
+diesel::table! {
+    kernelevent (id) {
+        id -> Nullable<Integer>,
+        boot_id -> Text,
+        seq -> BigInt,
+        timestamp -> Text,
+        level -> Integer,
+        category -> Text,
+        message -> Text,
+        pid -> Nullable<Integer>,
+        process -> Nullable<Text>,
+        device -> Nullable<Text>,
+        cgroup -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    logevent (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        log -> Text,
+        pattern -> Text,
+        line -> Text,
+    }
+}
+
+diesel::table! {
+    logmatch (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        log -> Text,
+        pattern -> Text,
+        count -> BigInt,
+        per_minute -> Double,
+    }
+}
+
+diesel::table! {
+    metricrollup (id) {
+        id -> Nullable<Integer>,
+        resolution_secs -> Integer,
+        bucket -> Text,
+        metric -> Text,
+        series -> Text,
+        avg_value -> Double,
+        min_value -> Double,
+        max_value -> Double,
+        last_value -> Double,
+        sample_count -> BigInt,
+    }
+}
+
+diesel::table! {
+    netinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        interface -> Text,
+        rx_bytes -> BigInt,
+        tx_bytes -> BigInt,
+        rx_packets -> BigInt,
+        tx_packets -> BigInt,
+        rx_errors -> BigInt,
+        tx_errors -> BigInt,
+        rx_dropped -> BigInt,
+        tx_dropped -> BigInt,
+        rx_bytes_per_sec -> Nullable<Float>,
+        tx_bytes_per_sec -> Nullable<Float>,
+    }
+}
+
+diesel::table! {
+    packagestatus (id) {
+        id -> Nullable<Integer>,
+        day -> Text,
+        timestamp -> Text,
+        manager -> Text,
+        pending_updates -> Integer,
+        security_updates -> Nullable<Integer>,
+        reboot_required -> Bool,
+        reboot_packages -> Text,
+        lists_updated_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    packageupdate (id) {
+        id -> Nullable<Integer>,
+        day -> Text,
+        name -> Text,
+        installed_version -> Text,
+        available_version -> Text,
+        security -> Bool,
+    }
+}
+
+diesel::table! {
+    pathinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        path -> Text,
+        total_bytes -> BigInt,
+        file_count -> BigInt,
+        dir_count -> BigInt,
+        complete -> Bool,
+        largest_files -> Text,
+    }
+}
+
+diesel::table! {
+    processinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        pid -> Integer,
+        name -> Text,
+        cmd -> Text,
+        username -> Nullable<Text>,
+        cpu_usage -> Float,
+        memory -> BigInt,
+        start_time -> BigInt,
+    }
+}
+
+diesel::table! {
+    psiinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        resource -> Text,
+        some_avg10 -> Float,
+        some_avg60 -> Float,
+        some_avg300 -> Float,
+        full_avg10 -> Nullable<Float>,
+        full_avg60 -> Nullable<Float>,
+        full_avg300 -> Nullable<Float>,
+    }
+}
+
+diesel::table! {
+    scriptmetric (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        script -> Text,
+        name -> Text,
+        labels -> Text,
+        value -> Double,
+    }
+}
+
+diesel::table! {
+    securityevent (id) {
+        id -> Nullable<Integer>,
+        timestamp -> Text,
+        source -> Text,
+        kind -> Text,
+        username -> Text,
+        source_ip -> Nullable<Text>,
+        auth_method -> Nullable<Text>,
+        tty -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    sensorinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        chip -> Text,
+        label -> Text,
+        temperature -> Nullable<Float>,
+        high -> Nullable<Float>,
+        critical -> Nullable<Float>,
+        fan_rpm -> Nullable<Float>,
     }
 }
 
@@ -30,6 +259,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    socketinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        protocol -> Text,
+        state -> Text,
+        socket_count -> BigInt,
+    }
+}
+
 diesel::table! {
     sysinfo (id) {
         id -> Nullable<Integer>,
@@ -39,6 +278,31 @@ diesel::table! {
         total_ram -> Float,
         free_ram -> Float,
         used_swap -> Float,
+        load_avg_one -> Float,
+        load_avg_five -> Float,
+        load_avg_fifteen -> Float,
+        available_ram -> BigInt,
+        cached_ram -> BigInt,
+        buffers_ram -> BigInt,
+        shared_ram -> BigInt,
+        total_swap -> BigInt,
+        free_swap -> BigInt,
+        dirty_ram -> BigInt,
+        hugepages_total -> BigInt,
+        hugepages_free -> BigInt,
+        hugepage_size -> BigInt,
+    }
+}
+
+diesel::table! {
+    timesyncinfo (id) {
+        id -> Nullable<Integer>,
+        sysinfo_id -> Integer,
+        synchronized -> Bool,
+        offset_us -> Double,
+        max_error_us -> BigInt,
+        est_error_us -> BigInt,
+        ntp_client -> Nullable<Text>,
     }
 }
 
@@ -51,7 +315,44 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(cgroupinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(cpuinfo -> sysinfo (sysinfo_id));
 diesel::joinable!(diskinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(diskio -> sysinfo (sysinfo_id));
+diesel::joinable!(logevent -> sysinfo (sysinfo_id));
+diesel::joinable!(logmatch -> sysinfo (sysinfo_id));
+diesel::joinable!(netinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(pathinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(processinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(psiinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(scriptmetric -> sysinfo (sysinfo_id));
+diesel::joinable!(sensorinfo -> sysinfo (sysinfo_id));
 diesel::joinable!(services -> user (user_id));
+diesel::joinable!(socketinfo -> sysinfo (sysinfo_id));
+diesel::joinable!(timesyncinfo -> sysinfo (sysinfo_id));
 
-diesel::allow_tables_to_appear_in_same_query!(config, diskinfo, services, sysinfo, user,);
+diesel::allow_tables_to_appear_in_same_query!(
+    cgroupinfo,
+    config,
+    cpuinfo,
+    diskinfo,
+    diskio,
+    kernelevent,
+    logevent,
+    logmatch,
+    metricrollup,
+    netinfo,
+    packagestatus,
+    packageupdate,
+    pathinfo,
+    processinfo,
+    psiinfo,
+    scriptmetric,
+    securityevent,
+    sensorinfo,
+    services,
+    socketinfo,
+    sysinfo,
+    timesyncinfo,
+    user,
+);
@@ -1,8 +1,9 @@
 use crate::bookmarks::schema::{NewService, Service, ServicePatchPayload, ServicePayload};
+use actix_web::http::StatusCode;
 use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, patch, post, web};
+use serde_json::Value;
 use teus_auth::middleware::Claims;
-use teus_database::storage::Storage;
-use teus_types::config::Config;
+use teus_database::storage::{PoolError, Storage, TeuSQLiteConnection};
 
 #[allow(dead_code)]
 /// Helper function to extract claims from request
@@ -14,12 +15,39 @@ fn extract_claims_from_request(req: &HttpRequest) -> Result<Claims, HttpResponse
     })
 }
 
+/// Runs `write` with the write connection on a blocking thread, since the
+/// background jobs can hold it for a while, and answers with the status
+/// and JSON body it returns.
+async fn with_writer<F>(storage: web::Data<Storage>, write: F) -> HttpResponse
+where
+    F: FnOnce(&mut TeuSQLiteConnection) -> (StatusCode, Value) + Send + 'static,
+{
+    let result = web::block(move || {
+        let mut conn = storage.write()?;
+        Ok::<_, PoolError>(write(&mut conn))
+    })
+    .await;
+    match result {
+        Ok(Ok((StatusCode::NO_CONTENT, _))) => HttpResponse::NoContent().finish(),
+        Ok(Ok((status, body))) => HttpResponse::build(status).json(body),
+        Ok(Err(e)) => {
+            eprintln!("Failed to acquire the database for bookmarks: {}", e); // TODO: Use log::error!
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to acquire database lock"
+            }))
+        }
+        Err(e) => {
+            eprintln!("Bookmarks write failed: {}", e); // TODO: Use log::error!
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to write bookmarks"
+            }))
+        }
+    }
+}
+
 #[get("/bookmarks")]
 /// Get all services for the authenticated user
-pub async fn get_user_services(
-    req: HttpRequest,
-    config: actix_web::web::Data<Config>,
-) -> impl Responder {
+pub async fn get_user_services(req: HttpRequest, storage: web::Data<Storage>) -> impl Responder {
     // Clone the claims to own them
     let claims = match req.extensions().get::<Claims>().cloned() {
         Some(claims) => claims,
@@ -31,8 +59,20 @@ pub async fn get_user_services(
     };
 
     let user_id = claims.id;
-    let storage = Storage::new(&config.database.path).unwrap();
-    let mut conn = storage.diesel_conn.lock().unwrap();
+    let mut conn = match storage.read() {
+        Ok(conn) => conn,
+        Err(e) if e.is_busy() => {
+            return HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "error": "All database connections are busy, try again"
+            }));
+        }
+        Err(e) => {
+            eprintln!("Failed to acquire the database for bookmarks: {}", e); // TODO: Use log::error!
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to acquire database lock"
+            }));
+        }
+    };
     let services =
         Service::get_services_by_user_id(&mut conn, user_id).expect("Error getting services");
 
@@ -44,7 +84,7 @@ pub async fn get_user_services(
 pub async fn add_service(
     req: HttpRequest,
     service_data: web::Json<ServicePayload>,
-    config: actix_web::web::Data<Config>,
+    storage: web::Data<Storage>,
 ) -> impl Responder {
     // if !service_data.values() {}
     let claims = extract_claims_from_request(&req).expect("Cannot extract claims from request");
@@ -55,64 +95,75 @@ pub async fn add_service(
         user_id: claims.id,
     };
 
-    let storage = Storage::new(&config.database.path).unwrap();
-    let mut conn = storage.diesel_conn.lock().unwrap();
-
-    let service_added = match Service::add_service(&mut conn, new_service) {
-        Ok(service) => service,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "message": "Error creating a new Service",
-            }));
+    with_writer(storage, move |conn| {
+        match Service::add_service(conn, new_service) {
+            Ok(service_added) => (StatusCode::CREATED, serde_json::json!(service_added)),
+            Err(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({
+                    "message": "Error creating a new Service",
+                }),
+            ),
         }
-    };
-
-    HttpResponse::Created().json(service_added)
+    })
+    .await
 }
 
 #[delete("/bookmarks/{id}")]
 pub async fn delete_service_by_id(
     id: web::Path<i32>,
     req: HttpRequest,
-    config: actix_web::web::Data<Config>,
+    storage: web::Data<Storage>,
 ) -> impl Responder {
     let claims = extract_claims_from_request(&req).expect("Cannot extract claims from request");
     let user_id = claims.id;
-    let bookmark_id = id.clone();
-
-    let storage = Storage::new(&config.database.path).unwrap();
-    let mut conn = storage.diesel_conn.lock().unwrap();
-
-    match Service::_get_service_by_id(&mut conn, bookmark_id) {
-        Ok(service) => {
-            if service.user_id != user_id {
-                return HttpResponse::Unauthorized().json(serde_json::json!({
-                    "message": "You are not authorized to delete this service"
-                }));
-            }
+    let bookmark_id = *id;
+
+    with_writer(storage, move |conn| {
+        match Service::_get_service_by_id(conn, bookmark_id) {
+            Ok(service) => {
+                if service.user_id != user_id {
+                    return (
+                        StatusCode::UNAUTHORIZED,
+                        serde_json::json!({
+                            "message": "You are not authorized to delete this service"
+                        }),
+                    );
+                }
 
-            match Service::delete_service(&mut conn, bookmark_id, user_id) {
-                Ok(rows_affected) => {
-                    if rows_affected > 0 {
-                        HttpResponse::NoContent().finish()
-                    } else {
-                        HttpResponse::InternalServerError().json(serde_json::json!({
-                            "message": "Unexpected error during deletion"
-                        }))
+                match Service::delete_service(conn, bookmark_id, user_id) {
+                    Ok(rows_affected) => {
+                        if rows_affected > 0 {
+                            (StatusCode::NO_CONTENT, Value::Null)
+                        } else {
+                            (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                serde_json::json!({
+                                    "message": "Unexpected error during deletion"
+                                }),
+                            )
+                        }
                     }
+                    Err(_) => (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        serde_json::json!({
+                            "message": "Error deleting bookmark"
+                        }),
+                    ),
                 }
-                Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
-                    "message": "Error deleting bookmark"
-                })),
+            }
+            Err(_) => {
+                // Service doesn't exist
+                (
+                    StatusCode::NOT_FOUND,
+                    serde_json::json!({
+                        "message": "Service not found"
+                    }),
+                )
             }
         }
-        Err(_) => {
-            // Service doesn't exist
-            HttpResponse::NotFound().json(serde_json::json!({
-                "message": "Service not found"
-            }))
-        }
-    }
+    })
+    .await
 }
 
 #[patch("/bookmarks/{id}")]
@@ -120,33 +171,42 @@ pub async fn update_service_by_id(
     id: web::Path<i32>,
     service_data: web::Json<ServicePatchPayload>,
     req: HttpRequest,
-    config: actix_web::web::Data<Config>,
+    storage: web::Data<Storage>,
 ) -> impl Responder {
     let claims = extract_claims_from_request(&req).expect("Cannot extract claims from request");
     let user_id = claims.id;
-    let bookmark_id = id.clone();
-
-    let storage = Storage::new(&config.database.path).unwrap();
-    let mut conn = storage.diesel_conn.lock().unwrap();
-
-    match Service::_get_service_by_id(&mut conn, bookmark_id) {
-        Ok(service) => {
-            if service.user_id != user_id {
-                return HttpResponse::Unauthorized().json(serde_json::json!({
-                    "message": "You are not authorized to update this service"
-                }));
-            }
+    let bookmark_id = *id;
+
+    let service_data = service_data.into_inner();
+    with_writer(storage, move |conn| {
+        match Service::_get_service_by_id(conn, bookmark_id) {
+            Ok(service) => {
+                if service.user_id != user_id {
+                    return (
+                        StatusCode::UNAUTHORIZED,
+                        serde_json::json!({
+                            "message": "You are not authorized to update this service"
+                        }),
+                    );
+                }
 
-            match Service::patch_service(&mut conn, bookmark_id, user_id, service_data.into_inner())
-            {
-                Ok(service) => HttpResponse::Ok().json(service),
-                Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
-                    "message": "Error updating bookmark"
-                })),
+                match Service::patch_service(conn, bookmark_id, user_id, service_data) {
+                    Ok(service) => (StatusCode::OK, serde_json::json!(service)),
+                    Err(_) => (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        serde_json::json!({
+                            "message": "Error updating bookmark"
+                        }),
+                    ),
+                }
             }
+            Err(_) => (
+                StatusCode::NOT_FOUND,
+                serde_json::json!({
+                    "message": "Service not found"
+                }),
+            ),
         }
-        Err(_) => HttpResponse::NotFound().json(serde_json::json!({
-            "message": "Service not found"
-        })),
-    }
+    })
+    .await
 }
@@ -5,6 +5,7 @@
 //! system information, network details, and resource utilization data to clients.
 
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 /// Network interface IP address information for API responses.
 ///
@@ -156,6 +157,7 @@ pub struct MACInfo {
 ///     ipv4: "192.168.1.100".to_string(),
 ///     networks: vec![/* network interfaces */],
 ///     mac_addresses: vec![/* MAC addresses */],
+///     clock: None,
 /// };
 /// ```
 ///
@@ -169,7 +171,14 @@ pub struct MACInfo {
 ///   "kernel_version": "5.15.0-91-generic",
 ///   "ipv4": "192.168.1.100",
 ///   "networks": [...],
-///   "mac_addresses": [...]
+///   "mac_addresses": [...],
+///   "clock": {
+///     "synchronized": true,
+///     "offset_us": -12.5,
+///     "max_error_us": 16500,
+///     "est_error_us": 250,
+///     "ntp_client": "chrony"
+///   }
 /// }
 /// ```
 #[derive(serde::Serialize, Debug)]
@@ -223,128 +232,1452 @@ pub struct GenericSysInfoResponse {
     /// Provides hardware identifiers for network interfaces,
     /// useful for asset tracking and network security.
     pub mac_addresses: Vec<MACInfo>,
+
+    /// Synchronization state of the system clock, `None` when the kernel
+    /// state can't be read.
+    ///
+    /// Token expiry and every stored timestamp rely on the clock being
+    /// right.
+    pub clock: Option<ClockSyncResponse>,
+}
+
+impl GenericSysInfoResponse {}
+impl Default for GenericSysInfoResponse {
+    fn default() -> Self {
+        Self {
+            hostname: "No Info".to_string(),
+            os: "No Info".to_string(),
+            uptime: "No Info".to_string(),
+            kernel_version: "No Info".to_string(),
+            ipv4: "No Info".to_string(),
+            networks: vec![],
+            mac_addresses: vec![],
+            clock: None,
+        }
+    }
+}
+
+/// Synchronization state of the system clock, from `adjtimex`.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "synchronized": true,
+///   "offset_us": -12.5,
+///   "max_error_us": 16500,
+///   "est_error_us": 250,
+///   "ntp_client": "chrony"
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ClockSyncResponse {
+    /// Whether the kernel considers the clock synchronized to a time
+    /// source.
+    pub synchronized: bool,
+
+    /// Offset from the time source still being corrected, in
+    /// microseconds.
+    pub offset_us: f64,
+
+    /// Maximum error of the clock, in microseconds.
+    pub max_error_us: i64,
+
+    /// Estimated error of the clock, in microseconds.
+    pub est_error_us: i64,
+
+    /// "systemd-timesyncd", "chrony" or "ntpd", `null` when no NTP client
+    /// is running.
+    pub ntp_client: Option<String>,
+}
+
+/// A single point of the clock synchronization history.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "synchronized": true,
+///   "offset_us": -12.5,
+///   ...
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ClockSyncSampleResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Clock state at that snapshot.
+    #[serde(flatten)]
+    pub sample: ClockSyncResponse,
+}
+
+/// Real-time system performance and resource utilization response.
+///
+/// This structure represents a complete snapshot of system resource
+/// usage at a specific point in time. It's the primary response format
+/// for monitoring API endpoints that provide current system performance data.
+///
+/// # Monitoring Data
+///
+/// Includes comprehensive resource utilization metrics:
+/// - CPU usage percentage
+/// - Memory usage and availability
+/// - Swap space utilization
+/// - Storage usage across all mounted filesystems
+///
+/// # Time Series Data
+///
+/// The timestamp field enables this structure to be used for time-series
+/// monitoring, allowing clients to track performance trends over time.
+///
+/// # API Endpoints
+///
+/// Typically returned by:
+/// - `/api/system/current` - Current system status
+/// - `/api/monitoring/snapshot` - Performance snapshot
+/// - Real-time monitoring WebSocket endpoints
+///
+/// # Examples
+///
+/// ```rust
+/// use teus_types::api_models::{LoadAverageResponse, SysInfoResponse};
+/// use chrono::Utc;
+///
+/// let response = SysInfoResponse {
+///     timestamp: Utc::now().to_rfc3339(),
+///     cpu_usage: 35.2,
+///     ram_usage: 8192.0,
+///     total_ram: 16384.0,
+///     free_ram: 8192.0,
+///     used_swap: 0.0,
+///     available_ram: 11_811_160_064,
+///     cached_ram: 10_366_418_944,
+///     buffers_ram: 411_762_688,
+///     shared_ram: 535_556_096,
+///     total_swap: 2_147_479_552,
+///     free_swap: 2_147_479_552,
+///     dirty_ram: 139_264,
+///     hugepages_total: 0,
+///     hugepages_free: 0,
+///     hugepage_size: 2_097_152,
+///     load_average: LoadAverageResponse { one: 0.5, five: 0.4, fifteen: 0.3 },
+///     cpus: vec![/* per-core usage */],
+///     interfaces: vec![/* network interfaces */],
+///     disk_io: vec![/* block device I/O */],
+///     sensors: vec![/* temperatures and fans */],
+///     pressure: vec![/* cpu, memory and io stalls */],
+///     disks: vec![/* disk information */],
+/// };
+/// ```
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "cpu_usage": 35.2,
+///   "ram_usage": 8192.0,
+///   "total_ram": 16384.0,
+///   "free_ram": 8192.0,
+///   "used_swap": 0.0,
+///   "available_ram": 11811160064,
+///   "cached_ram": 10366418944,
+///   "buffers_ram": 411762688,
+///   "shared_ram": 535556096,
+///   "total_swap": 2147479552,
+///   "free_swap": 2147479552,
+///   "dirty_ram": 139264,
+///   "hugepages_total": 0,
+///   "hugepages_free": 0,
+///   "hugepage_size": 2097152,
+///   "load_average": { "one": 0.5, "five": 0.4, "fifteen": 0.3 },
+///   "cpus": [...],
+///   "interfaces": [...],
+///   "disk_io": [...],
+///   "sensors": [...],
+///   "pressure": [...],
+///   "disks": [...]
+/// }
+/// ```
+#[derive(Serialize)]
+pub struct SysInfoResponse {
+    /// ISO 8601 timestamp when this data was collected.
+    ///
+    /// Typically in RFC3339 format (e.g., "2024-01-15T10:30:00Z").
+    /// Essential for time-series analysis and determining data freshness.
+    pub timestamp: String,
+
+    /// Current CPU usage as a percentage (0.0 to 100.0).
+    ///
+    /// Represents the overall CPU utilization across all cores
+    /// and threads. Values approaching 100% indicate high
+    /// computational load that may affect system responsiveness.
+    pub cpu_usage: f32,
+
+    /// Current RAM usage in megabytes.
+    ///
+    /// This represents the amount of physical memory currently
+    /// allocated to running processes, excluding cached and
+    /// buffered memory that can be quickly reclaimed.
+    pub ram_usage: f32,
+
+    /// Total available RAM in the system, in megabytes.
+    ///
+    /// This is the total physical memory capacity and should
+    /// remain constant unless hardware changes occur. Used
+    /// to calculate usage percentages and available capacity.
+    pub total_ram: f32,
+
+    /// Amount of RAM currently free and immediately available, in megabytes.
+    ///
+    /// This represents memory that can be immediately allocated
+    /// to new processes without requiring swap operations or
+    /// cache eviction. Critical for assessing memory pressure.
+    pub free_ram: f32,
+
+    /// Current swap space usage in megabytes.
+    ///
+    /// High swap usage may indicate memory pressure and can
+    /// significantly impact system performance. Values should
+    /// typically remain low in well-configured systems.
+    pub used_swap: f32,
+
+    /// Memory available for new workloads without swapping, in bytes.
+    ///
+    /// This is the kernel's `MemAvailable` estimate and includes page cache
+    /// that can be reclaimed. On Linux `free_ram` is usually low because
+    /// idle memory is used as cache; use this field to judge memory pressure.
+    pub available_ram: i64,
+
+    /// Memory used by the page cache, in bytes.
+    pub cached_ram: i64,
+
+    /// Memory used by block device buffers, in bytes.
+    pub buffers_ram: i64,
+
+    /// Shared memory, including tmpfs, in bytes.
+    pub shared_ram: i64,
+
+    /// Total swap space, in bytes.
+    ///
+    /// Zero when the system has no swap configured.
+    pub total_swap: i64,
+
+    /// Unused swap space, in bytes.
+    pub free_swap: i64,
+
+    /// Memory waiting to be written back to disk, in bytes.
+    pub dirty_ram: i64,
+
+    /// Number of huge pages in the pool.
+    pub hugepages_total: i64,
+
+    /// Number of huge pages not allocated.
+    pub hugepages_free: i64,
+
+    /// Size of one huge page, in bytes.
+    pub hugepage_size: i64,
+
+    /// System load averages over the last 1, 5 and 15 minutes.
+    ///
+    /// Compared with the number of cores, these show whether work is
+    /// queuing for CPU time even when the instantaneous usage looks fine.
+    pub load_average: LoadAverageResponse,
+
+    /// Usage of every logical core at the time of collection.
+    ///
+    /// The overall `cpu_usage` is the average of these values; a single
+    /// core stuck at 100% is only visible here.
+    pub cpus: Vec<CpuCoreResponse>,
+
+    /// Traffic counters and throughput of every network interface.
+    ///
+    /// Includes physical NICs as well as bridges, docker veths and
+    /// the loopback device.
+    pub interfaces: Vec<NetworkInterfaceResponse>,
+
+    /// Throughput, IOPS, latency and utilization of every block device.
+    ///
+    /// Loop and RAM devices are not reported.
+    pub disk_io: Vec<DiskIoResponse>,
+
+    /// Temperature and fan sensors of the machine.
+    ///
+    /// Empty on systems without hwmon sensors, such as most virtual machines.
+    pub sensors: Vec<SensorResponse>,
+
+    /// Pressure stall information for CPU, memory and I/O.
+    ///
+    /// Shows whether tasks are actually waiting on a resource, which usage
+    /// percentages alone don't. Empty on kernels without PSI support.
+    pub pressure: Vec<PressureResponse>,
+
+    /// Storage utilization information for all mounted filesystems.
+    ///
+    /// Provides detailed disk usage data for each storage device
+    /// or partition mounted on the system. Critical for storage
+    /// capacity planning and preventing disk space exhaustion.
+    pub disks: Vec<DiskInfoResponse>,
+}
+
+/// System load averages for API responses.
+///
+/// Load average is the average number of tasks that are runnable or
+/// waiting on uninterruptible I/O. It is reported over three windows so
+/// clients can tell a short spike from sustained pressure.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "one": 1.25,
+///   "five": 0.80,
+///   "fifteen": 0.42
+/// }
+/// ```
+#[derive(Serialize, Debug, Default)]
+pub struct LoadAverageResponse {
+    /// Load average over the last minute.
+    pub one: f32,
+
+    /// Load average over the last five minutes.
+    pub five: f32,
+
+    /// Load average over the last fifteen minutes.
+    pub fifteen: f32,
+}
+
+/// Per-core CPU utilization information for API responses.
+///
+/// One entry is returned for every logical core, in the order reported
+/// by the operating system.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "name": "cpu0",
+///   "cpu_usage": 97.5,
+///   "frequency": 2400
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct CpuCoreResponse {
+    /// Name of the core as reported by the operating system (e.g. "cpu0").
+    pub name: String,
+
+    /// Usage of this core as a percentage (0.0 to 100.0).
+    pub cpu_usage: f32,
+
+    /// Frequency of the core at the time of collection, in MHz.
+    pub frequency: i32,
+}
+
+/// Network interface traffic information for API responses.
+///
+/// Counters are cumulative since boot, as reported by the kernel. The
+/// throughput fields are computed from the difference with the previous
+/// monitoring snapshot and are `null` when no baseline exists yet (first
+/// sample of an interface, or counters reset because the interface was
+/// recreated).
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "interface": "eth0",
+///   "rx_bytes": 1073741824,
+///   "tx_bytes": 536870912,
+///   "rx_packets": 812345,
+///   "tx_packets": 612345,
+///   "rx_errors": 0,
+///   "tx_errors": 0,
+///   "rx_dropped": 17,
+///   "tx_dropped": 0,
+///   "rx_bytes_per_sec": 125000.0,
+///   "tx_bytes_per_sec": 48000.0
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct NetworkInterfaceResponse {
+    /// Name of the network interface (e.g. "eth0", "docker0").
+    pub interface: String,
+
+    /// Total bytes received since boot.
+    pub rx_bytes: i64,
+
+    /// Total bytes transmitted since boot.
+    pub tx_bytes: i64,
+
+    /// Total packets received since boot.
+    pub rx_packets: i64,
+
+    /// Total packets transmitted since boot.
+    pub tx_packets: i64,
+
+    /// Total receive errors since boot.
+    pub rx_errors: i64,
+
+    /// Total transmit errors since boot.
+    pub tx_errors: i64,
+
+    /// Total received packets dropped since boot.
+    pub rx_dropped: i64,
+
+    /// Total transmitted packets dropped since boot.
+    pub tx_dropped: i64,
+
+    /// Receive throughput in bytes per second, if a baseline exists.
+    pub rx_bytes_per_sec: Option<f32>,
+
+    /// Transmit throughput in bytes per second, if a baseline exists.
+    pub tx_bytes_per_sec: Option<f32>,
+}
+
+/// A single point of a network interface history.
+///
+/// Returned as a chronologically ordered list by the interface history
+/// endpoint, one entry per monitoring snapshot.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "interface": "eth0",
+///   "rx_bytes": 1073741824,
+///   ...
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct NetworkInterfaceSampleResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Interface counters and throughput at that snapshot.
+    #[serde(flatten)]
+    pub sample: NetworkInterfaceResponse,
+}
+
+/// Block device I/O activity for API responses.
+///
+/// Counters are cumulative since boot, as read from `/proc/diskstats`.
+/// The derived fields describe the interval since the previous monitoring
+/// snapshot and are `null` when no baseline exists yet (first sample of a
+/// device, or counters reset because the device was re-added).
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "device": "nvme0n1",
+///   "reads_completed": 120000,
+///   "sectors_read": 9600000,
+///   "writes_completed": 80000,
+///   "sectors_written": 6400000,
+///   "io_time_ms": 90000,
+///   "read_bytes_per_sec": 209715.2,
+///   "write_bytes_per_sec": 51200.0,
+///   "read_iops": 20.0,
+///   "write_iops": 5.0,
+///   "avg_read_latency_ms": 0.8,
+///   "avg_write_latency_ms": 2.5,
+///   "busy_percentage": 12.5
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct DiskIoResponse {
+    /// Kernel name of the block device (e.g. "sda", "nvme0n1", "dm-0").
+    pub device: String,
+
+    /// Total reads completed since boot.
+    pub reads_completed: i64,
+
+    /// Total 512-byte sectors read since boot.
+    pub sectors_read: i64,
+
+    /// Total writes completed since boot.
+    pub writes_completed: i64,
+
+    /// Total 512-byte sectors written since boot.
+    pub sectors_written: i64,
+
+    /// Total time the device had I/O in flight since boot, in milliseconds.
+    pub io_time_ms: i64,
+
+    /// Read throughput in bytes per second, if a baseline exists.
+    pub read_bytes_per_sec: Option<f32>,
+
+    /// Write throughput in bytes per second, if a baseline exists.
+    pub write_bytes_per_sec: Option<f32>,
+
+    /// Reads completed per second, if a baseline exists.
+    pub read_iops: Option<f32>,
+
+    /// Writes completed per second, if a baseline exists.
+    pub write_iops: Option<f32>,
+
+    /// Average time a read took over the interval, in milliseconds.
+    pub avg_read_latency_ms: Option<f32>,
+
+    /// Average time a write took over the interval, in milliseconds.
+    pub avg_write_latency_ms: Option<f32>,
+
+    /// Share of the interval the device was busy (0.0 to 100.0).
+    ///
+    /// A device close to 100% is saturated: requests queue up and the
+    /// latencies above grow.
+    pub busy_percentage: Option<f32>,
+}
+
+/// A single point of a block device I/O history.
+///
+/// Returned as a chronologically ordered list by the disk I/O history
+/// endpoint, one entry per monitoring snapshot.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "device": "nvme0n1",
+///   "reads_completed": 120000,
+///   ...
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct DiskIoSampleResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Device counters and rates at that snapshot.
+    #[serde(flatten)]
+    pub sample: DiskIoResponse,
+}
+
+/// Pressure stall information of one resource for API responses.
+///
+/// Values are the percentage of wall time (0.0 to 100.0) in which tasks
+/// were stalled waiting for the resource, averaged over the last 10, 60
+/// and 300 seconds. `some` means at least one task was stalled; `full`
+/// means all non-idle tasks were stalled at once, so no useful work was
+/// done. `full` values are `null` for CPU on kernels before 5.13.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "resource": "memory",
+///   "some_avg10": 1.53,
+///   "some_avg60": 0.87,
+///   "some_avg300": 0.25,
+///   "full_avg10": 0.5,
+///   "full_avg60": 0.2,
+///   "full_avg300": 0.05
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct PressureResponse {
+    /// Resource under pressure: "cpu", "memory" or "io".
+    pub resource: String,
+
+    /// Share of time some tasks were stalled, over the last 10 seconds.
+    pub some_avg10: f32,
+
+    /// Share of time some tasks were stalled, over the last 60 seconds.
+    pub some_avg60: f32,
+
+    /// Share of time some tasks were stalled, over the last 300 seconds.
+    pub some_avg300: f32,
+
+    /// Share of time all tasks were stalled, over the last 10 seconds.
+    pub full_avg10: Option<f32>,
+
+    /// Share of time all tasks were stalled, over the last 60 seconds.
+    pub full_avg60: Option<f32>,
+
+    /// Share of time all tasks were stalled, over the last 300 seconds.
+    pub full_avg300: Option<f32>,
+}
+
+/// A single point of a pressure stall history.
+///
+/// Returned as a chronologically ordered list by the pressure history
+/// endpoint, one entry per monitoring snapshot.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "resource": "memory",
+///   "some_avg10": 1.53,
+///   ...
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct PressureSampleResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Pressure averages at that snapshot.
+    #[serde(flatten)]
+    pub sample: PressureResponse,
+}
+
+/// Hardware sensor reading for API responses.
+///
+/// A sensor is identified by its `chip` and `label`. Temperature sensors
+/// report `temperature` and, when the driver exposes them, `high` and
+/// `critical` thresholds; fan sensors report `fan_rpm`. Fields that don't
+/// apply are `null`.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "chip": "coretemp",
+///   "label": "Package id 0",
+///   "temperature": 54.0,
+///   "high": 80.0,
+///   "critical": 100.0,
+///   "fan_rpm": null
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SensorResponse {
+    /// Name of the hwmon chip (e.g. "coretemp", "nvme", "acpitz").
+    pub chip: String,
+
+    /// Sensor label (e.g. "Package id 0", "CPU Fan").
+    pub label: String,
+
+    /// Current temperature, in degrees Celsius.
+    pub temperature: Option<f32>,
+
+    /// High temperature threshold, in degrees Celsius.
+    ///
+    /// Readings above this value usually mean the hardware is throttling.
+    pub high: Option<f32>,
+
+    /// Critical temperature threshold, in degrees Celsius.
+    pub critical: Option<f32>,
+
+    /// Current fan speed, in RPM.
+    pub fan_rpm: Option<f32>,
+}
+
+/// A single point of a sensor history.
+///
+/// Returned as a chronologically ordered list by the sensor history
+/// endpoint, one entry per monitoring snapshot.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "chip": "coretemp",
+///   "label": "Package id 0",
+///   "temperature": 54.0,
+///   ...
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SensorSampleResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Sensor reading at that snapshot.
+    #[serde(flatten)]
+    pub sample: SensorResponse,
+}
+
+/// A single process for API responses.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "pid": 1234,
+///   "name": "postgres",
+///   "cmd": "/usr/lib/postgresql/16/bin/postgres -D /var/lib/postgresql/16/main",
+///   "user": "postgres",
+///   "cpu_usage": 12.5,
+///   "memory": 268435456,
+///   "start_time": 1705312200
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ProcessResponse {
+    /// Process ID.
+    pub pid: i32,
+
+    /// Process name (usually the executable name).
+    pub name: String,
+
+    /// Full command line, arguments separated by spaces.
+    pub cmd: String,
+
+    /// Name of the user owning the process, `null` if it can't be resolved.
+    pub user: Option<String>,
+
+    /// CPU usage as a percentage of one core.
+    ///
+    /// Multi-threaded processes can exceed 100.
+    pub cpu_usage: f32,
+
+    /// Resident set size, in bytes.
+    pub memory: i64,
+
+    /// Start time of the process, in seconds since the Unix epoch.
+    pub start_time: i64,
+}
+
+/// A list of processes at a point in time.
+///
+/// Live views contain every running process at the time of the request.
+/// Historical views contain the top processes by CPU and by memory stored
+/// with the closest monitoring snapshot at or before the requested time.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T03:00:02Z",
+///   "live": false,
+///   "processes": [...]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ProcessListResponse {
+    /// RFC3339 timestamp of the process table.
+    pub timestamp: String,
+
+    /// `true` if the processes were read at request time, `false` if they
+    /// come from a stored snapshot.
+    pub live: bool,
+
+    /// Processes after filtering, sorting and limiting.
+    pub processes: Vec<ProcessResponse>,
+}
+
+/// Resource accounting of one cgroup, with the cgroups nested below it.
+///
+/// Cgroups are systemd slices, services and scopes, including docker
+/// containers. Counters are cumulative; `cpu_usage_percentage` and the
+/// throughput fields are measured since the previous collection and are
+/// `null` the first time a cgroup is seen. Memory, I/O and task values are
+/// `null` when the matching controller is not enabled for the cgroup.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "name": "nginx.service",
+///   "path": "system.slice/nginx.service",
+///   "cpu_usage_usec": 5500000,
+///   "cpu_user_usec": 4000000,
+///   "cpu_system_usec": 1500000,
+///   "cpu_usage_percentage": 12.5,
+///   "memory_current": 52428800,
+///   "memory_max": null,
+///   "io_read_bytes": 1049600,
+///   "io_write_bytes": 4096,
+///   "io_read_bytes_per_sec": 0.0,
+///   "io_write_bytes_per_sec": 409.6,
+///   "pids_current": 5,
+///   "children": []
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct CgroupResponse {
+    /// Last component of the path (e.g. "nginx.service").
+    pub name: String,
+
+    /// Path relative to the cgroup root (e.g. "system.slice/nginx.service").
+    pub path: String,
+
+    /// Total CPU time consumed, in microseconds.
+    pub cpu_usage_usec: i64,
+
+    /// CPU time spent in user mode, in microseconds.
+    pub cpu_user_usec: i64,
+
+    /// CPU time spent in kernel mode, in microseconds.
+    pub cpu_system_usec: i64,
+
+    /// Recent CPU usage as a percentage of one CPU.
+    ///
+    /// Workloads running on several CPUs can exceed 100.
+    pub cpu_usage_percentage: Option<f32>,
+
+    /// Memory charged to the cgroup, in bytes.
+    pub memory_current: Option<i64>,
+
+    /// Memory limit in bytes, `null` when unlimited.
+    pub memory_max: Option<i64>,
+
+    /// Total bytes read from block devices.
+    pub io_read_bytes: Option<i64>,
+
+    /// Total bytes written to block devices.
+    pub io_write_bytes: Option<i64>,
+
+    /// Recent read throughput, in bytes per second.
+    pub io_read_bytes_per_sec: Option<f32>,
+
+    /// Recent write throughput, in bytes per second.
+    pub io_write_bytes_per_sec: Option<f32>,
+
+    /// Number of tasks (processes and threads) in the cgroup.
+    pub pids_current: Option<i64>,
+
+    /// Recorded cgroups nested below this one, ordered by path.
+    pub children: Vec<CgroupResponse>,
+}
+
+/// The cgroup tree of the latest collection.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "cgroups": [
+///     { "name": "system.slice", "path": "system.slice", ..., "children": [...] },
+///     { "name": "user.slice", "path": "user.slice", ..., "children": [...] }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct CgroupTreeResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Top-level cgroups, each with its nested cgroups.
+    pub cgroups: Vec<CgroupResponse>,
+}
+
+/// A TCP or UDP socket with the process owning it.
+///
+/// Listening sockets have the unspecified remote address and port 0. The
+/// owner is `null` when the process can't be inspected (sockets of other
+/// users when Teus doesn't run as root) or the socket is no longer held
+/// by a process, like connections in `time_wait`.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "protocol": "tcp",
+///   "local_address": "0.0.0.0",
+///   "local_port": 8080,
+///   "remote_address": "0.0.0.0",
+///   "remote_port": 0,
+///   "state": "listen",
+///   "uid": 1000,
+///   "inode": 41234,
+///   "pid": 812,
+///   "process": "nginx"
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SocketResponse {
+    /// "tcp" or "udp", for both IPv4 and IPv6.
+    pub protocol: String,
+
+    /// Local address; IPv4-mapped IPv6 addresses are shown as IPv4.
+    pub local_address: String,
+
+    pub local_port: u16,
+
+    pub remote_address: String,
+
+    pub remote_port: u16,
+
+    /// Lowercase TCP state (e.g. "listen", "established", "time_wait").
+    ///
+    /// UDP sockets are "listen" when bound without a peer and
+    /// "established" when connected.
+    pub state: String,
+
+    /// UID of the socket owner.
+    pub uid: u32,
+
+    /// Inode of the socket, 0 once its process closed it.
+    pub inode: u64,
+
+    /// PID of the process holding the socket.
+    pub pid: Option<u32>,
+
+    /// Name of the process holding the socket.
+    pub process: Option<String>,
+}
+
+/// Number of sockets of a protocol in a given state.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "protocol": "tcp",
+///   "state": "established",
+///   "count": 42
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SocketStateCountResponse {
+    /// "tcp" or "udp".
+    pub protocol: String,
+
+    /// Lowercase socket state.
+    pub state: String,
+
+    /// Number of sockets in this state.
+    pub count: i64,
+}
+
+/// The sockets of the host, read at request time.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "counts": [
+///     { "protocol": "tcp", "state": "close_wait", "count": 0 },
+///     { "protocol": "tcp", "state": "established", "count": 42 },
+///     ...
+///   ],
+///   "sockets": [
+///     { "protocol": "tcp", "local_port": 8080, "state": "listen", "pid": 812, ... }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SocketListResponse {
+    /// RFC3339 time at which the sockets were read.
+    pub timestamp: String,
+
+    /// Sockets per protocol and state, over all sockets regardless of the
+    /// filters of the request.
+    pub counts: Vec<SocketStateCountResponse>,
+
+    /// Sockets matching the filters of the request, listening sockets first.
+    pub sockets: Vec<SocketResponse>,
+}
+
+/// Socket counts per protocol and state at one monitoring snapshot.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "counts": [
+///     { "protocol": "tcp", "state": "established", "count": 42 },
+///     ...
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SocketStatesSampleResponse {
+    /// RFC3339 timestamp of the monitoring snapshot.
+    pub timestamp: String,
+
+    /// Counts of every protocol and state, including empty states.
+    pub counts: Vec<SocketStateCountResponse>,
+}
+
+/// A login session currently open on the host, from `utmp`.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "username": "alice",
+///   "tty": "pts/0",
+///   "host": "192.168.1.10",
+///   "source_ip": "192.168.1.10",
+///   "pid": 4242,
+///   "login_time": "2024-01-15T10:30:00+00:00"
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct LoginSessionResponse {
+    pub username: String,
+
+    /// Terminal of the session (e.g. "pts/0", "tty1").
+    pub tty: String,
+
+    /// Remote host as recorded by the login program, empty for local logins.
+    pub host: String,
+
+    /// Address of the remote host, `null` for local logins.
+    pub source_ip: Option<String>,
+
+    /// PID of the session leader (the shell or sshd process).
+    pub pid: i32,
+
+    /// RFC3339 time the session was opened.
+    pub login_time: String,
+}
+
+/// A stored login or SSH authentication attempt.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:31:20+00:00",
+///   "source": "auth_log",
+///   "kind": "failed",
+///   "username": "root",
+///   "source_ip": "203.0.113.7",
+///   "auth_method": "password",
+///   "tty": null
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct SecurityEventResponse {
+    /// RFC3339 time of the event.
+    pub timestamp: String,
+
+    /// Where the event was read from: "wtmp", "auth_log" or "journal".
+    pub source: String,
+
+    /// "login" for sessions from `wtmp`; "accepted", "failed" or
+    /// "invalid_user" for SSH attempts.
+    pub kind: String,
+
+    pub username: String,
+
+    /// Address the event came from, `null` for local logins.
+    pub source_ip: Option<String>,
+
+    /// SSH authentication method (e.g. "password", "publickey").
+    pub auth_method: Option<String>,
+
+    /// Terminal of a login session.
+    pub tty: Option<String>,
+}
+
+/// SSH attempts from a single address.
+///
+/// Usernames unknown to the host are usually logged twice by sshd, once
+/// as `invalid_user` and, when password authentication is enabled, once
+/// as `failed`.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "source_ip": "203.0.113.7",
+///   "failed": 312,
+///   "invalid_user": 57,
+///   "accepted": 0,
+///   "first_seen": "2024-01-15T02:11:09+00:00",
+///   "last_seen": "2024-01-15T10:31:20+00:00"
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct AuthFailuresResponse {
+    pub source_ip: String,
+
+    /// Failed authentications.
+    pub failed: i64,
+
+    /// Attempts for usernames that don't exist on the host.
+    pub invalid_user: i64,
+
+    /// Successful authentications.
+    pub accepted: i64,
+
+    /// RFC3339 time of the first attempt in the period.
+    pub first_seen: String,
+
+    /// RFC3339 time of the last attempt in the period.
+    pub last_seen: String,
+}
+
+/// SSH attempts per source address over a period.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "since": "2024-01-14T10:30:00+00:00",
+///   "sources": [
+///     { "source_ip": "203.0.113.7", "failed": 312, "invalid_user": 57, ... }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct AuthFailureListResponse {
+    /// RFC3339 start of the period.
+    pub since: String,
+
+    /// Addresses with at least one failed attempt, most failures first.
+    pub sources: Vec<AuthFailuresResponse>,
+}
+
+/// The latest value of a metric printed by a user-defined script.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:31:20+00:00",
+///   "script": "queue",
+///   "name": "queue_depth",
+///   "labels": { "queue": "emails" },
+///   "value": 42.0
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ScriptMetricResponse {
+    /// RFC3339 time of the sample the value was stored with.
+    pub timestamp: String,
+
+    /// Name of the script in `[[monitor.scripts]]`.
+    pub script: String,
+
+    /// Metric name as printed by the script.
+    pub name: String,
+
+    /// Labels of the value, empty for `key_value` scripts.
+    pub labels: BTreeMap<String, String>,
+
+    pub value: f64,
+}
+
+/// A value of a script metric, identified by its labels.
+#[derive(Serialize, Debug)]
+pub struct ScriptMetricValueResponse {
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+/// The values of a script metric at a single sample.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:31:20+00:00",
+///   "values": [
+///     { "labels": { "queue": "emails" }, "value": 42.0 },
+///     { "labels": { "queue": "webhooks" }, "value": 3.0 }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ScriptMetricSampleResponse {
+    /// RFC3339 time of the sample.
+    pub timestamp: String,
+
+    /// One value per label set printed by the script.
+    pub values: Vec<ScriptMetricValueResponse>,
+}
+
+/// An event of the kernel log.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:31:20.512043+00:00",
+///   "boot_id": "0f3c5e1a-7d2b-4c1e-9a8f-2b6d4e8c1a3f",
+///   "seq": 1023,
+///   "level": 3,
+///   "category": "oom_kill",
+///   "message": "Memory cgroup out of memory: Killed process 4321 (java) total-vm:4508124kB, ...",
+///   "pid": 4321,
+///   "process": "java",
+///   "device": null,
+///   "cgroup": "/system.slice/docker-ab12.scope"
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct KernelEventResponse {
+    /// RFC3339 time of the message.
+    pub timestamp: String,
+
+    /// Boot the message was logged in.
+    pub boot_id: String,
+
+    /// Sequence number of the message within its boot.
+    pub seq: i64,
+
+    /// Kernel log level, from 0 (emergency) to 7 (debug).
+    pub level: i32,
+
+    /// "oom_kill", "io_error", "segfault", "fs_readonly" or
+    /// "hardware_error".
+    pub category: String,
+
+    /// The kernel message.
+    pub message: String,
+
+    /// Process killed by the OOM killer or that crashed.
+    pub pid: Option<i32>,
+
+    /// Name of that process.
+    pub process: Option<String>,
+
+    /// Device of an I/O error or of a filesystem remounted read-only.
+    pub device: Option<String>,
+
+    /// Memory cgroup of an OOM kill victim, which identifies the container
+    /// or service it belonged to.
+    pub cgroup: Option<String>,
+}
+
+/// An installed package with a newer version available.
+#[derive(Serialize, Debug)]
+pub struct PackageUpdateResponse {
+    pub name: String,
+    pub installed_version: String,
+    pub available_version: String,
+
+    /// Whether the new version comes from a security repository.
+    pub security: bool,
 }
 
-impl GenericSysInfoResponse {}
-impl Default for GenericSysInfoResponse {
-    fn default() -> Self {
-        Self {
-            hostname: "No Info".to_string(),
-            os: "No Info".to_string(),
-            uptime: "No Info".to_string(),
-            kernel_version: "No Info".to_string(),
-            ipv4: "No Info".to_string(),
-            networks: vec![],
-            mac_addresses: vec![],
-        }
-    }
+/// Pending package updates of the host, from the latest check.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "day": "2024-01-15",
+///   "timestamp": "2024-01-15T10:31:20+00:00",
+///   "manager": "apt",
+///   "pending_updates": 2,
+///   "security_updates": 1,
+///   "reboot_required": true,
+///   "reboot_packages": ["linux-image-6.1.0-17-amd64"],
+///   "lists_updated_at": "2024-01-15T06:12:03+00:00",
+///   "updates": [
+///     {
+///       "name": "openssl",
+///       "installed_version": "3.0.11-1~deb12u2",
+///       "available_version": "3.0.13-1~deb12u1",
+///       "security": true
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct PackageStatusResponse {
+    /// Day of the check, `YYYY-MM-DD` in UTC.
+    pub day: String,
+
+    /// RFC3339 time of the check.
+    pub timestamp: String,
+
+    /// "apt" or "apk".
+    pub manager: String,
+
+    pub pending_updates: i32,
+
+    /// Pending security updates, `null` on Alpine where they can't be
+    /// told apart.
+    pub security_updates: Option<i32>,
+
+    /// Whether `/var/run/reboot-required` exists.
+    pub reboot_required: bool,
+
+    /// Packages that asked for the reboot.
+    pub reboot_packages: Vec<String>,
+
+    /// RFC3339 time the package lists were last fetched. Updates published
+    /// since then are not counted.
+    pub lists_updated_at: Option<String>,
+
+    pub updates: Vec<PackageUpdateResponse>,
 }
 
-/// Real-time system performance and resource utilization response.
-///
-/// This structure represents a complete snapshot of system resource
-/// usage at a specific point in time. It's the primary response format
-/// for monitoring API endpoints that provide current system performance data.
-///
-/// # Monitoring Data
-///
-/// Includes comprehensive resource utilization metrics:
-/// - CPU usage percentage
-/// - Memory usage and availability
-/// - Swap space utilization
-/// - Storage usage across all mounted filesystems
-///
-/// # Time Series Data
+/// Pending package update counts of a single day.
 ///
-/// The timestamp field enables this structure to be used for time-series
-/// monitoring, allowing clients to track performance trends over time.
+/// # JSON Response Format
 ///
-/// # API Endpoints
+/// ```json
+/// {
+///   "day": "2024-01-15",
+///   "pending_updates": 2,
+///   "security_updates": 1,
+///   "reboot_required": true
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct PackageDayResponse {
+    /// Day, `YYYY-MM-DD` in UTC.
+    pub day: String,
+
+    pub pending_updates: i32,
+
+    /// Pending security updates, `null` when unknown.
+    pub security_updates: Option<i32>,
+
+    pub reboot_required: bool,
+}
+
+/// A file of a watched directory, with its size on disk.
+#[derive(Serialize, Debug)]
+pub struct LargeFileResponse {
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Disk usage of a directory of `[[monitor.paths]]`.
+#[derive(Serialize, Debug)]
+pub struct PathUsageResponse {
+    /// Directory, as configured.
+    pub path: String,
+
+    /// Bytes allocated on disk by the tree.
+    pub total_bytes: i64,
+
+    pub file_count: i64,
+
+    /// Subdirectories, the directory itself excluded.
+    pub dir_count: i64,
+
+    /// False when the walk stopped at its entry or time limit, in which
+    /// case the figures are lower bounds.
+    pub complete: bool,
+
+    /// Largest files of the tree, biggest first.
+    pub largest_files: Vec<LargeFileResponse>,
+}
+
+/// Latest measurement of a watched directory, with its growth rate.
 ///
-/// Typically returned by:
-/// - `/api/system/current` - Current system status
-/// - `/api/monitoring/snapshot` - Performance snapshot
-/// - Real-time monitoring WebSocket endpoints
+/// # JSON Response Format
 ///
-/// # Examples
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "path": "/var/log",
+///   "total_bytes": 1073741824,
+///   "file_count": 412,
+///   "dir_count": 37,
+///   "complete": true,
+///   "largest_files": [
+///     { "path": "/var/log/journal/system.journal", "bytes": 134217728 }
+///   ],
+///   "growth_bytes_per_hour": 5242880.0
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct PathStatusResponse {
+    /// RFC3339 timestamp of the measurement.
+    pub timestamp: String,
+
+    #[serde(flatten)]
+    pub usage: PathUsageResponse,
+
+    /// Average growth since the first measurement of the requested window,
+    /// negative when the directory shrank. `null` with a single
+    /// measurement.
+    pub growth_bytes_per_hour: Option<f64>,
+}
+
+/// A single point of the history of a watched directory.
+#[derive(Serialize, Debug)]
+pub struct PathUsageSampleResponse {
+    /// RFC3339 timestamp of the measurement.
+    pub timestamp: String,
+
+    #[serde(flatten)]
+    pub usage: PathUsageResponse,
+}
+
+/// Lines of a watched log file that matched a pattern in one run.
+#[derive(Serialize, Debug)]
+pub struct LogMatchResponse {
+    /// Name of the log, from `[[monitor.logs]]`.
+    pub log: String,
+
+    /// Name of the pattern.
+    pub pattern: String,
+
+    /// Lines appended since the previous run that matched.
+    pub count: i64,
+
+    /// `count` over the time since the previous run, per minute.
+    pub per_minute: f64,
+}
+
+/// Pattern counts of every watched log file, from the latest run.
 ///
-/// ```rust
-/// use teus::webserver::models::sysmodels::{SysInfoResponse, DiskInfoResponse};
-/// use chrono::Utc;
+/// # JSON Response Format
 ///
-/// let response = SysInfoResponse {
-///     timestamp: Utc::now().to_rfc3339(),
-///     cpu_usage: 35.2,
-///     ram_usage: 8192.0,
-///     total_ram: 16384.0,
-///     free_ram: 8192.0,
-///     used_swap: 0.0,
-///     disks: vec![/* disk information */],
-/// };
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "matches": [
+///     { "log": "nginx", "pattern": "error", "count": 12, "per_minute": 24.0 },
+///     { "log": "nginx", "pattern": "upstream_timeout", "count": 0, "per_minute": 0.0 }
+///   ]
+/// }
 /// ```
+#[derive(Serialize, Debug)]
+pub struct LogMatchesResponse {
+    /// RFC3339 timestamp of the sample the counts were stored with.
+    pub timestamp: String,
+
+    pub matches: Vec<LogMatchResponse>,
+}
+
+/// A single point of the history of a log pattern.
 ///
 /// # JSON Response Format
 ///
 /// ```json
 /// {
 ///   "timestamp": "2024-01-15T10:30:00Z",
-///   "cpu_usage": 35.2,
-///   "ram_usage": 8192.0,
-///   "total_ram": 16384.0,
-///   "free_ram": 8192.0,
-///   "used_swap": 0.0,
-///   "disks": [...]
+///   "count": 12,
+///   "per_minute": 24.0
 /// }
 /// ```
-#[derive(Serialize)]
-pub struct SysInfoResponse {
-    /// ISO 8601 timestamp when this data was collected.
-    ///
-    /// Typically in RFC3339 format (e.g., "2024-01-15T10:30:00Z").
-    /// Essential for time-series analysis and determining data freshness.
+#[derive(Serialize, Debug)]
+pub struct LogMatchSampleResponse {
+    /// RFC3339 timestamp of the sample.
     pub timestamp: String,
 
-    /// Current CPU usage as a percentage (0.0 to 100.0).
-    ///
-    /// Represents the overall CPU utilization across all cores
-    /// and threads. Values approaching 100% indicate high
-    /// computational load that may affect system responsiveness.
-    pub cpu_usage: f32,
+    pub count: i64,
 
-    /// Current RAM usage in megabytes.
-    ///
-    /// This represents the amount of physical memory currently
-    /// allocated to running processes, excluding cached and
-    /// buffered memory that can be quickly reclaimed.
-    pub ram_usage: f32,
+    pub per_minute: f64,
+}
 
-    /// Total available RAM in the system, in megabytes.
-    ///
-    /// This is the total physical memory capacity and should
-    /// remain constant unless hardware changes occur. Used
-    /// to calculate usage percentages and available capacity.
-    pub total_ram: f32,
+/// A line of a watched log file that matched a pattern.
+///
+/// # JSON Response Format
+///
+/// ```json
+/// {
+///   "timestamp": "2024-01-15T10:30:00Z",
+///   "log": "nginx",
+///   "pattern": "error",
+///   "line": "2024/01/15 10:29:58 [error] 812#812: *7 upstream timed out"
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct LogEventResponse {
+    /// RFC3339 timestamp of the sample the line was read in.
+    pub timestamp: String,
 
-    /// Amount of RAM currently free and immediately available, in megabytes.
-    ///
-    /// This represents memory that can be immediately allocated
-    /// to new processes without requiring swap operations or
-    /// cache eviction. Critical for assessing memory pressure.
-    pub free_ram: f32,
+    pub log: String,
 
-    /// Current swap space usage in megabytes.
-    ///
-    /// High swap usage may indicate memory pressure and can
-    /// significantly impact system performance. Values should
-    /// typically remain low in well-configured systems.
-    pub used_swap: f32,
+    pub pattern: String,
 
-    /// Storage utilization information for all mounted filesystems.
-    ///
-    /// Provides detailed disk usage data for each storage device
-    /// or partition mounted on the system. Critical for storage
-    /// capacity planning and preventing disk space exhaustion.
-    pub disks: Vec<DiskInfoResponse>,
+    /// The line, cut to 1024 bytes.
+    pub line: String,
 }
 
 /// Storage device utilization information for API responses.
@@ -371,7 +1704,7 @@ pub struct SysInfoResponse {
 /// # Examples
 ///
 /// ```rust
-/// use teus::webserver::models::sysmodels::DiskInfoResponse;
+/// use teus_types::api_models::DiskInfoResponse;
 ///
 /// let root_disk = DiskInfoResponse {
 ///     filesystem: "ext4".to_string(),
@@ -379,6 +1712,10 @@ pub struct SysInfoResponse {
 ///     total_space: 1000000,    // 1TB in MB
 ///     available_space: 750000, // 750GB available
 ///     used_space: 250000,      // 250GB used
+///     inodes_total: 65536000,
+///     inodes_used: 1310720,
+///     inodes_free: 64225280,
+///     inodes_used_percentage: 2,
 /// };
 ///
 /// let data_disk = DiskInfoResponse {
@@ -387,6 +1724,10 @@ pub struct SysInfoResponse {
 ///     total_space: 2000000,    // 2TB in MB
 ///     available_space: 1800000, // 1.8TB available
 ///     used_space: 200000,      // 200GB used
+///     inodes_total: 65536000,
+///     inodes_used: 1310720,
+///     inodes_free: 64225280,
+///     inodes_used_percentage: 2,
 /// };
 /// ```
 ///
@@ -398,7 +1739,11 @@ pub struct SysInfoResponse {
 ///   "mount_point": "/",
 ///   "total_space": 1000000,
 ///   "available_space": 750000,
-///   "used_space": 250000
+///   "used_space": 250000,
+///   "inodes_total": 65536000,
+///   "inodes_used": 1310720,
+///   "inodes_free": 64225280,
+///   "inodes_used_percentage": 2
 /// }
 /// ```
 #[derive(Serialize)]
@@ -440,6 +1785,58 @@ pub struct DiskInfoResponse {
     /// and filesystem metadata. Used to calculate utilization
     /// percentages and remaining capacity.
     pub used_space: i32,
+    /// Total number of inodes of the filesystem.
+    ///
+    /// Zero on filesystems that allocate inodes dynamically, such as
+    /// btrfs, vfat and most network filesystems.
+    pub inodes_total: i64,
+
+    /// Number of inodes in use, i.e. files, directories and links.
+    pub inodes_used: i64,
+
+    /// Number of free inodes.
+    pub inodes_free: i64,
+
+    /// Percentage of inodes in use (0 to 100).
+    ///
+    /// A filesystem out of inodes can't create new files even if
+    /// `available_space` is large, so both values need monitoring.
+    pub inodes_used_percentage: i32,
+}
+
+/// Values of a metric over one step of `GET /sysinfo/history`.
+///
+/// Points are streamed inside their series, whose metric and mount point
+/// (`null` for host metrics) come first:
+///
+/// ```json
+/// {
+///   "from": "2024-01-15T00:00:00+00:00",
+///   "to": "2024-01-16T00:00:00+00:00",
+///   "step": 3600,
+///   "series": [
+///     {
+///       "metric": "disk_used_percentage",
+///       "mount": "/",
+///       "points": [
+///         { "timestamp": "2024-01-15T00:00:00+00:00", "count": 720, "avg": 41.5, "max": 42.0, "p95": 42.0 }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Debug)]
+pub struct MetricPointResponse {
+    /// RFC3339 start of the step.
+    pub timestamp: String,
+
+    /// Raw samples summarized by the step.
+    pub count: i64,
+
+    /// Requested aggregations, keyed by their name ("avg", "min", "max",
+    /// "last", or a percentile like "p95").
+    #[serde(flatten)]
+    pub values: BTreeMap<String, f64>,
 }
 
 #[cfg(test)]
@@ -507,6 +1904,7 @@ mod tests {
         assert_eq!(sys_info.ipv4, "No Info");
         assert!(sys_info.networks.is_empty());
         assert!(sys_info.mac_addresses.is_empty());
+        assert!(sys_info.clock.is_none());
     }
 
     #[test]
@@ -537,6 +1935,7 @@ mod tests {
             ipv4: "192.168.1.10".to_string(),
             networks,
             mac_addresses,
+            clock: None,
         };
 
         assert_eq!(sys_info.hostname, "test-machine");
@@ -558,6 +1957,13 @@ mod tests {
             ipv4: "10.0.0.1".to_string(),
             networks: vec![],
             mac_addresses: vec![],
+            clock: Some(ClockSyncResponse {
+                synchronized: false,
+                offset_us: 0.0,
+                max_error_us: 16_000_000,
+                est_error_us: 16_000_000,
+                ntp_client: None,
+            }),
         };
 
         let serialized = serde_json::to_string(&sys_info).unwrap();
@@ -566,6 +1972,8 @@ mod tests {
         assert!(serialized.contains("\"uptime\":\"10 hours\""));
         assert!(serialized.contains("\"kernel_version\":\"5.4.0-42\""));
         assert!(serialized.contains("\"ipv4\":\"10.0.0.1\""));
+        assert!(serialized.contains("\"synchronized\":false"));
+        assert!(serialized.contains("\"ntp_client\":null"));
     }
 
     #[test]
@@ -576,6 +1984,10 @@ mod tests {
             total_space: 1000000,
             available_space: 500000,
             used_space: 500000,
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
         };
 
         assert_eq!(disk_info.filesystem, "ext4");
@@ -593,6 +2005,10 @@ mod tests {
             total_space: 2000000,
             available_space: 1000000,
             used_space: 1000000,
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
         };
 
         let serialized = serde_json::to_string(&disk_info).unwrap();
@@ -601,6 +2017,7 @@ mod tests {
         assert!(serialized.contains("\"total_space\":2000000"));
         assert!(serialized.contains("\"available_space\":1000000"));
         assert!(serialized.contains("\"used_space\":1000000"));
+        assert!(serialized.contains("\"inodes_used_percentage\":0"));
     }
 
     #[test]
@@ -612,6 +2029,10 @@ mod tests {
                 total_space: 1000000,
                 available_space: 600000,
                 used_space: 400000,
+                inodes_total: 0,
+                inodes_used: 0,
+                inodes_free: 0,
+                inodes_used_percentage: 0,
             },
             DiskInfoResponse {
                 filesystem: "ext4".to_string(),
@@ -619,6 +2040,10 @@ mod tests {
                 total_space: 2000000,
                 available_space: 1500000,
                 used_space: 500000,
+                inodes_total: 0,
+                inodes_used: 0,
+                inodes_free: 0,
+                inodes_used_percentage: 0,
             },
         ];
 
@@ -629,6 +2054,30 @@ mod tests {
             total_ram: 8192.0,
             free_ram: 4096.0,
             used_swap: 512.0,
+            available_ram: 6_442_450_944,
+            cached_ram: 2_147_483_648,
+            buffers_ram: 268_435_456,
+            shared_ram: 134_217_728,
+            total_swap: 2_147_483_648,
+            free_swap: 1_610_612_736,
+            dirty_ram: 4096,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 2_097_152,
+            load_average: LoadAverageResponse {
+                one: 1.5,
+                five: 1.0,
+                fifteen: 0.5,
+            },
+            cpus: vec![CpuCoreResponse {
+                name: "cpu0".to_string(),
+                cpu_usage: 25.5,
+                frequency: 2400,
+            }],
+            interfaces: vec![],
+            disk_io: vec![],
+            sensors: vec![],
+            pressure: vec![],
             disks,
         };
 
@@ -638,6 +2087,10 @@ mod tests {
         assert_eq!(sys_info.total_ram, 8192.0);
         assert_eq!(sys_info.free_ram, 4096.0);
         assert_eq!(sys_info.used_swap, 512.0);
+        assert_eq!(sys_info.available_ram, 6_442_450_944);
+        assert_eq!(sys_info.total_swap - sys_info.free_swap, 536_870_912);
+        assert_eq!(sys_info.load_average.one, 1.5);
+        assert_eq!(sys_info.cpus.len(), 1);
         assert_eq!(sys_info.disks.len(), 2);
         assert_eq!(sys_info.disks[0].mount_point, "/");
         assert_eq!(sys_info.disks[1].mount_point, "/home");
@@ -652,6 +2105,22 @@ mod tests {
             total_ram: 4096.0,
             free_ram: 2048.0,
             used_swap: 0.0,
+            available_ram: 3_221_225_472,
+            cached_ram: 0,
+            buffers_ram: 0,
+            shared_ram: 0,
+            total_swap: 0,
+            free_swap: 0,
+            dirty_ram: 0,
+            hugepages_total: 0,
+            hugepages_free: 0,
+            hugepage_size: 0,
+            load_average: LoadAverageResponse::default(),
+            cpus: vec![],
+            interfaces: vec![],
+            disk_io: vec![],
+            sensors: vec![],
+            pressure: vec![],
             disks: vec![],
         };
 
@@ -662,9 +2131,330 @@ mod tests {
         assert!(serialized.contains("\"total_ram\":4096"));
         assert!(serialized.contains("\"free_ram\":2048"));
         assert!(serialized.contains("\"used_swap\":0"));
+        assert!(serialized.contains("\"available_ram\":3221225472"));
+        assert!(serialized.contains("\"total_swap\":0"));
+        assert!(serialized.contains("\"load_average\":{\"one\":0.0,\"five\":0.0,\"fifteen\":0.0}"));
+        assert!(serialized.contains("\"cpus\":[]"));
+        assert!(serialized.contains("\"disk_io\":[]"));
+        assert!(serialized.contains("\"sensors\":[]"));
+        assert!(serialized.contains("\"pressure\":[]"));
         assert!(serialized.contains("\"disks\":[]"));
     }
 
+    #[test]
+    fn test_network_interface_sample_serialization() {
+        let sample = NetworkInterfaceSampleResponse {
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            sample: NetworkInterfaceResponse {
+                interface: "eth0".to_string(),
+                rx_bytes: 6000,
+                tx_bytes: 3000,
+                rx_packets: 60,
+                tx_packets: 30,
+                rx_errors: 0,
+                tx_errors: 0,
+                rx_dropped: 2,
+                tx_dropped: 0,
+                rx_bytes_per_sec: Some(1000.0),
+                tx_bytes_per_sec: None,
+            },
+        };
+
+        let serialized = serde_json::to_string(&sample).unwrap();
+        // Interface fields are flattened next to the timestamp
+        assert!(serialized.contains("\"timestamp\":\"2024-01-01T12:00:00Z\""));
+        assert!(serialized.contains("\"interface\":\"eth0\""));
+        assert!(serialized.contains("\"rx_dropped\":2"));
+        assert!(serialized.contains("\"rx_bytes_per_sec\":1000.0"));
+        assert!(serialized.contains("\"tx_bytes_per_sec\":null"));
+    }
+
+    #[test]
+    fn test_disk_io_sample_serialization() {
+        let sample = DiskIoSampleResponse {
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            sample: DiskIoResponse {
+                device: "sda".to_string(),
+                reads_completed: 1100,
+                sectors_read: 10048,
+                writes_completed: 500,
+                sectors_written: 4000,
+                io_time_ms: 12500,
+                read_bytes_per_sec: Some(209715.2),
+                write_bytes_per_sec: Some(0.0),
+                read_iops: Some(20.0),
+                write_iops: Some(0.0),
+                avg_read_latency_ms: Some(5.0),
+                avg_write_latency_ms: Some(0.0),
+                busy_percentage: None,
+            },
+        };
+
+        let serialized = serde_json::to_string(&sample).unwrap();
+        assert!(serialized.contains("\"timestamp\":\"2024-01-01T12:00:00Z\""));
+        assert!(serialized.contains("\"device\":\"sda\""));
+        assert!(serialized.contains("\"read_iops\":20.0"));
+        assert!(serialized.contains("\"avg_read_latency_ms\":5.0"));
+        assert!(serialized.contains("\"busy_percentage\":null"));
+    }
+
+    #[test]
+    fn test_cgroup_tree_serialization() {
+        let cgroup = |name: &str, path: &str, children| CgroupResponse {
+            name: name.to_string(),
+            path: path.to_string(),
+            cpu_usage_usec: 5500000,
+            cpu_user_usec: 4000000,
+            cpu_system_usec: 1500000,
+            cpu_usage_percentage: Some(12.5),
+            memory_current: Some(52428800),
+            memory_max: None,
+            io_read_bytes: None,
+            io_write_bytes: None,
+            io_read_bytes_per_sec: None,
+            io_write_bytes_per_sec: None,
+            pids_current: Some(5),
+            children,
+        };
+        let tree = CgroupTreeResponse {
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            cgroups: vec![cgroup(
+                "system.slice",
+                "system.slice",
+                vec![cgroup(
+                    "nginx.service",
+                    "system.slice/nginx.service",
+                    vec![],
+                )],
+            )],
+        };
+
+        let serialized = serde_json::to_string(&tree).unwrap();
+        assert!(serialized.contains("\"cgroups\":[{\"name\":\"system.slice\""));
+        assert!(serialized.contains("\"path\":\"system.slice/nginx.service\""));
+        assert!(serialized.contains("\"cpu_usage_percentage\":12.5"));
+        assert!(serialized.contains("\"memory_max\":null"));
+        assert!(serialized.contains("\"children\":[]"));
+    }
+
+    #[test]
+    fn test_socket_list_serialization() {
+        let list = SocketListResponse {
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            counts: vec![SocketStateCountResponse {
+                protocol: "tcp".to_string(),
+                state: "listen".to_string(),
+                count: 1,
+            }],
+            sockets: vec![SocketResponse {
+                protocol: "tcp".to_string(),
+                local_address: "0.0.0.0".to_string(),
+                local_port: 8080,
+                remote_address: "0.0.0.0".to_string(),
+                remote_port: 0,
+                state: "listen".to_string(),
+                uid: 1000,
+                inode: 41234,
+                pid: None,
+                process: None,
+            }],
+        };
+
+        let serialized = serde_json::to_string(&list).unwrap();
+        assert!(
+            serialized
+                .contains("\"counts\":[{\"protocol\":\"tcp\",\"state\":\"listen\",\"count\":1}]")
+        );
+        assert!(serialized.contains("\"local_port\":8080"));
+        assert!(serialized.contains("\"pid\":null"));
+        assert!(serialized.contains("\"process\":null"));
+    }
+
+    #[test]
+    fn test_auth_failure_list_serialization() {
+        let list = AuthFailureListResponse {
+            since: "2024-01-14T12:00:00+00:00".to_string(),
+            sources: vec![AuthFailuresResponse {
+                source_ip: "203.0.113.7".to_string(),
+                failed: 3,
+                invalid_user: 1,
+                accepted: 0,
+                first_seen: "2024-01-15T10:31:12+00:00".to_string(),
+                last_seen: "2024-01-15T10:31:50+00:00".to_string(),
+            }],
+        };
+
+        let serialized = serde_json::to_string(&list).unwrap();
+        assert!(serialized.contains("\"since\":\"2024-01-14T12:00:00+00:00\""));
+        assert!(serialized.contains("\"source_ip\":\"203.0.113.7\""));
+        assert!(serialized.contains("\"failed\":3"));
+        assert!(serialized.contains("\"invalid_user\":1"));
+    }
+
+    #[test]
+    fn test_script_metric_sample_serialization() {
+        let sample = ScriptMetricSampleResponse {
+            timestamp: "2024-01-15T10:31:20+00:00".to_string(),
+            values: vec![ScriptMetricValueResponse {
+                labels: BTreeMap::from([("queue".to_string(), "emails".to_string())]),
+                value: 42.0,
+            }],
+        };
+
+        let serialized = serde_json::to_string(&sample).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"timestamp":"2024-01-15T10:31:20+00:00","values":[{"labels":{"queue":"emails"},"value":42.0}]}"#
+        );
+    }
+
+    #[test]
+    fn test_kernel_event_serialization() {
+        let event = KernelEventResponse {
+            timestamp: "2024-01-15T10:31:20+00:00".to_string(),
+            boot_id: "0f3c5e1a-7d2b-4c1e-9a8f-2b6d4e8c1a3f".to_string(),
+            seq: 1023,
+            level: 3,
+            category: "oom_kill".to_string(),
+            message: "Out of memory: Killed process 4321 (java)".to_string(),
+            pid: Some(4321),
+            process: Some("java".to_string()),
+            device: None,
+            cgroup: Some("/system.slice/docker-ab12.scope".to_string()),
+        };
+
+        let serialized = serde_json::to_string(&event).unwrap();
+        assert!(serialized.contains("\"category\":\"oom_kill\""));
+        assert!(serialized.contains("\"pid\":4321"));
+        assert!(serialized.contains("\"device\":null"));
+        assert!(serialized.contains("\"cgroup\":\"/system.slice/docker-ab12.scope\""));
+    }
+
+    #[test]
+    fn test_package_day_serialization() {
+        let day = PackageDayResponse {
+            day: "2024-01-15".to_string(),
+            pending_updates: 12,
+            security_updates: None,
+            reboot_required: false,
+        };
+
+        let serialized = serde_json::to_string(&day).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"day":"2024-01-15","pending_updates":12,"security_updates":null,"reboot_required":false}"#
+        );
+    }
+
+    #[test]
+    fn test_path_status_serialization() {
+        let status = PathStatusResponse {
+            timestamp: "2024-01-15T10:30:00Z".to_string(),
+            usage: PathUsageResponse {
+                path: "/var/log".to_string(),
+                total_bytes: 8192,
+                file_count: 1,
+                dir_count: 0,
+                complete: true,
+                largest_files: vec![LargeFileResponse {
+                    path: "/var/log/syslog".to_string(),
+                    bytes: 8192,
+                }],
+            },
+            growth_bytes_per_hour: None,
+        };
+
+        let serialized = serde_json::to_string(&status).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"timestamp":"2024-01-15T10:30:00Z","path":"/var/log","total_bytes":8192,"file_count":1,"dir_count":0,"complete":true,"largest_files":[{"path":"/var/log/syslog","bytes":8192}],"growth_bytes_per_hour":null}"#
+        );
+    }
+
+    #[test]
+    fn test_log_matches_serialization() {
+        let matches = LogMatchesResponse {
+            timestamp: "2024-01-15T10:30:00Z".to_string(),
+            matches: vec![LogMatchResponse {
+                log: "nginx".to_string(),
+                pattern: "error".to_string(),
+                count: 12,
+                per_minute: 24.0,
+            }],
+        };
+
+        let serialized = serde_json::to_string(&matches).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"timestamp":"2024-01-15T10:30:00Z","matches":[{"log":"nginx","pattern":"error","count":12,"per_minute":24.0}]}"#
+        );
+    }
+
+    #[test]
+    fn test_pressure_sample_serialization() {
+        let sample = PressureSampleResponse {
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            sample: PressureResponse {
+                resource: "cpu".to_string(),
+                some_avg10: 12.5,
+                some_avg60: 8.0,
+                some_avg300: 3.0,
+                full_avg10: None,
+                full_avg60: None,
+                full_avg300: None,
+            },
+        };
+
+        let serialized = serde_json::to_string(&sample).unwrap();
+        assert!(serialized.contains("\"resource\":\"cpu\""));
+        assert!(serialized.contains("\"some_avg10\":12.5"));
+        assert!(serialized.contains("\"full_avg10\":null"));
+    }
+
+    #[test]
+    fn test_sensor_sample_serialization() {
+        let sample = SensorSampleResponse {
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            sample: SensorResponse {
+                chip: "it8728".to_string(),
+                label: "CPU Fan".to_string(),
+                temperature: None,
+                high: None,
+                critical: None,
+                fan_rpm: Some(1250.0),
+            },
+        };
+
+        let serialized = serde_json::to_string(&sample).unwrap();
+        assert!(serialized.contains("\"timestamp\":\"2024-01-01T12:00:00Z\""));
+        assert!(serialized.contains("\"label\":\"CPU Fan\""));
+        assert!(serialized.contains("\"temperature\":null"));
+        assert!(serialized.contains("\"fan_rpm\":1250.0"));
+    }
+
+    #[test]
+    fn test_process_list_serialization() {
+        let list = ProcessListResponse {
+            timestamp: "2024-01-01T03:00:00Z".to_string(),
+            live: false,
+            processes: vec![ProcessResponse {
+                pid: 1234,
+                name: "postgres".to_string(),
+                cmd: "postgres -D /var/lib/postgresql".to_string(),
+                user: None,
+                cpu_usage: 12.5,
+                memory: 268435456,
+                start_time: 1705312200,
+            }],
+        };
+
+        let serialized = serde_json::to_string(&list).unwrap();
+        assert!(serialized.contains("\"live\":false"));
+        assert!(serialized.contains("\"pid\":1234"));
+        assert!(serialized.contains("\"user\":null"));
+        assert!(serialized.contains("\"memory\":268435456"));
+    }
+
     #[test]
     fn test_debug_formatting() {
         let ip_info = IpInfo {
@@ -717,9 +2507,28 @@ mod tests {
             total_space: 0,
             available_space: 0,
             used_space: 0,
+            inodes_total: 0,
+            inodes_used: 0,
+            inodes_free: 0,
+            inodes_used_percentage: 0,
         };
         assert_eq!(disk_info.total_space, 0);
         assert_eq!(disk_info.available_space, 0);
         assert_eq!(disk_info.used_space, 0);
     }
+
+    #[test]
+    fn test_metric_point_serialization() {
+        let point = MetricPointResponse {
+            timestamp: "2024-01-15T10:00:00+00:00".to_string(),
+            count: 12,
+            values: BTreeMap::from([("avg".to_string(), 41.5), ("p95".to_string(), 48.0)]),
+        };
+
+        let serialized = serde_json::to_string(&point).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"timestamp":"2024-01-15T10:00:00+00:00","count":12,"avg":41.5,"p95":48.0}"#
+        );
+    }
 }
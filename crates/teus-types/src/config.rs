@@ -1,4 +1,7 @@
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 /// Represents the deployment environment for the Teus application.
@@ -184,16 +187,18 @@ pub struct ServerConfig {
 /// # Examples
 ///
 /// ```rust
-/// use teus::config::types::DatabaseConfig;
+/// use teus_types::config::{DatabaseConfig, RetentionConfig};
 ///
 /// // Relative path
 /// let db_config = DatabaseConfig {
 ///     path: "./data/teus.db".to_string(),
+///     retention: RetentionConfig::default(),
 /// };
 ///
 /// // Absolute path
 /// let db_config = DatabaseConfig {
 ///     path: "/var/lib/teus/teus.db".to_string(),
+///     retention: RetentionConfig::default(),
 /// };
 /// ```
 #[derive(Debug, Deserialize, Clone)]
@@ -204,6 +209,124 @@ pub struct DatabaseConfig {
     /// by the Teus process. If the file doesn't exist, it will be
     /// created automatically on first startup.
     pub path: String,
+
+    /// How long the metric history is kept, from the
+    /// `[database.retention]` section.
+    #[serde(default)]
+    pub retention: RetentionConfig,
+}
+
+/// Retention of the metric history.
+///
+/// Without `max_age_days` or `max_rows` nothing is ever deleted. With
+/// either, a background job deletes the expired samples together with
+/// every row stored with them (disks, network, processes, ...), in
+/// batches of `batch_size` samples so the monitor and the API are never
+/// blocked for long. `max_age_days` also applies to the security and
+/// kernel events.
+///
+/// Before pruning, the same job rolls the samples up into 1-minute,
+/// 1-hour and 1-day averages, minimums, maximums and last values (see
+/// `rollups`), kept much longer than the samples: long ranges of history
+/// stay available with only the recent samples stored. Minute rollups are
+/// kept `minute_rollup_days`, hour rollups `hour_rollup_days` and day
+/// rollups forever.
+///
+/// SQLite keeps the freed pages for later inserts, the file doesn't
+/// shrink. With `incremental_vacuum` they are given back to the
+/// filesystem after each pruning. The database is converted once when
+/// Teus starts or by `teus migrate up`, which rewrites the whole file.
+///
+/// # Examples
+///
+/// ```toml
+/// [database.retention]
+/// max_age_days = 30
+/// max_rows = 500000
+/// incremental_vacuum = true
+/// minute_rollup_days = 7
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct RetentionConfig {
+    /// Samples older than this many days are deleted.
+    pub max_age_days: Option<u64>,
+
+    /// Only the latest this many samples are kept.
+    pub max_rows: Option<u64>,
+
+    /// Seconds between two prunings. Defaults to 3600.
+    #[serde(default = "default_retention_interval_secs")]
+    pub interval_secs: u64,
+
+    /// Samples deleted per transaction. Defaults to 1000.
+    #[serde(default = "default_retention_batch_size")]
+    pub batch_size: u64,
+
+    /// Whether the freed space is given back to the filesystem. Defaults
+    /// to false.
+    #[serde(default)]
+    pub incremental_vacuum: bool,
+
+    /// Whether the samples are rolled up before they expire. Defaults to
+    /// true.
+    #[serde(default = "default_rollups")]
+    pub rollups: bool,
+
+    /// Days the 1-minute rollups are kept. Defaults to 30.
+    #[serde(default = "default_minute_rollup_days")]
+    pub minute_rollup_days: u64,
+
+    /// Days the 1-hour rollups are kept. Defaults to 365.
+    #[serde(default = "default_hour_rollup_days")]
+    pub hour_rollup_days: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_rows: None,
+            interval_secs: default_retention_interval_secs(),
+            batch_size: default_retention_batch_size(),
+            incremental_vacuum: false,
+            rollups: default_rollups(),
+            minute_rollup_days: default_minute_rollup_days(),
+            hour_rollup_days: default_hour_rollup_days(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Returns whether any sample is ever deleted.
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_rows.is_some()
+    }
+
+    /// Returns whether the background job has anything to do: rolling up
+    /// or deleting samples.
+    pub fn needs_job(&self) -> bool {
+        self.is_enabled() || self.rollups
+    }
+}
+
+fn default_retention_interval_secs() -> u64 {
+    3600
+}
+
+fn default_retention_batch_size() -> u64 {
+    1000
+}
+
+fn default_rollups() -> bool {
+    true
+}
+
+fn default_minute_rollup_days() -> u64 {
+    30
+}
+
+fn default_hour_rollup_days() -> u64 {
+    365
 }
 
 /// Configuration for the system monitoring component.
@@ -230,16 +353,40 @@ pub struct DatabaseConfig {
 /// # Examples
 ///
 /// ```rust
-/// use teus::config::types::MonitorConfig;
+/// use teus_types::config::{DiskFilterConfig, MonitorConfig, SecurityConfig};
 ///
 /// // High-frequency monitoring
 /// let monitor_config = MonitorConfig {
 ///     interval_secs: 10,
+///     procfs_root: "/proc".to_string(),
+///     sysfs_root: "/sys".to_string(),
+///     cgroup_root: "/sys/fs/cgroup".to_string(),
+///     kmsg_path: "/dev/kmsg".to_string(),
+///     host_root: "/".to_string(),
+///     top_processes: 10,
+///     disks: DiskFilterConfig::default(),
+///     security: SecurityConfig::default(),
+///     scripts: Vec::new(),
+///     paths: Vec::new(),
+///     logs: Vec::new(),
+///     collectors: Default::default(),
 /// };
 ///
 /// // Standard production monitoring
 /// let monitor_config = MonitorConfig {
 ///     interval_secs: 60,
+///     procfs_root: "/proc".to_string(),
+///     sysfs_root: "/sys".to_string(),
+///     cgroup_root: "/sys/fs/cgroup".to_string(),
+///     kmsg_path: "/dev/kmsg".to_string(),
+///     host_root: "/".to_string(),
+///     top_processes: 10,
+///     disks: DiskFilterConfig::default(),
+///     security: SecurityConfig::default(),
+///     scripts: Vec::new(),
+///     paths: Vec::new(),
+///     logs: Vec::new(),
+///     collectors: Default::default(),
 /// };
 /// ```
 #[derive(Debug, Deserialize, Clone)]
@@ -254,6 +401,487 @@ pub struct MonitorConfig {
     /// Must be greater than 0. Values less than 5 seconds are not
     /// recommended for production use due to performance overhead.
     pub interval_secs: u64,
+
+    /// Root of the procfs mount the collectors read from.
+    ///
+    /// Defaults to `/proc`. Useful when Teus runs in a container with the
+    /// host's procfs mounted elsewhere (e.g. `/host/proc`), and in tests
+    /// where collectors are pointed at fixture files.
+    #[serde(default = "default_procfs_root")]
+    pub procfs_root: String,
+
+    /// Root of the sysfs mount the collectors read from.
+    ///
+    /// Defaults to `/sys`. Hardware sensors are read from
+    /// `<sysfs_root>/class/hwmon`.
+    #[serde(default = "default_sysfs_root")]
+    pub sysfs_root: String,
+
+    /// Root of the cgroup v2 hierarchy the workload accounting is read from.
+    ///
+    /// Defaults to `/sys/fs/cgroup`. On hybrid hosts the unified hierarchy
+    /// is usually mounted at `/sys/fs/cgroup/unified`.
+    #[serde(default = "default_cgroup_root")]
+    pub cgroup_root: String,
+
+    /// Kernel log device the OOM kills, I/O errors and other kernel events
+    /// are read from.
+    ///
+    /// Defaults to `/dev/kmsg`. Reading it requires `CAP_SYSLOG` when the
+    /// `kernel.dmesg_restrict` sysctl is set.
+    #[serde(default = "default_kmsg_path")]
+    pub kmsg_path: String,
+
+    /// Root of the host filesystem the package databases and the
+    /// reboot-required flag are read from.
+    ///
+    /// Defaults to `/`. Set it to the mount point of the host's root (e.g.
+    /// `/host`) when Teus runs in a container.
+    #[serde(default = "default_host_root")]
+    pub host_root: String,
+
+    /// Number of processes stored with each sample, per ranking.
+    ///
+    /// The top N processes by CPU usage and the top N by memory are
+    /// saved; a process in both rankings is stored once. Defaults to 10.
+    /// Set to 0 to disable process snapshots.
+    #[serde(default = "default_top_processes")]
+    pub top_processes: usize,
+
+    /// Which mounted filesystems are recorded, from the `[monitor.disks]`
+    /// section.
+    #[serde(default)]
+    pub disks: DiskFilterConfig,
+
+    /// Where login records and SSH authentication logs are read from, from
+    /// the `[monitor.security]` section.
+    #[serde(default)]
+    pub security: SecurityConfig,
+
+    /// External commands whose output is stored as metrics, from the
+    /// `[[monitor.scripts]]` sections.
+    #[serde(default)]
+    pub scripts: Vec<ScriptConfig>,
+
+    /// Directories whose size is measured, from the `[[monitor.paths]]`
+    /// sections.
+    #[serde(default)]
+    pub paths: Vec<PathWatchConfig>,
+
+    /// Log files whose lines are matched against patterns, from the
+    /// `[[monitor.logs]]` sections.
+    #[serde(default)]
+    pub logs: Vec<LogWatchConfig>,
+
+    /// Per-collector schedules, from the `[monitor.collectors.<name>]`
+    /// sections, keyed by collector name.
+    ///
+    /// Collectors without a section run on every cycle. CPU, memory and
+    /// load figures are always recorded every `interval_secs`; every other
+    /// collector stores its rows with the latest of those samples.
+    #[serde(default)]
+    pub collectors: BTreeMap<String, CollectorConfig>,
+}
+
+/// Schedule of a single metric collector.
+///
+/// # Examples
+///
+/// ```toml
+/// [monitor.collectors.processes]
+/// interval_secs = 60
+///
+/// [monitor.collectors.sensors]
+/// enabled = false
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct CollectorConfig {
+    /// Whether the collector runs at all. Defaults to `true`.
+    #[serde(default = "default_collector_enabled")]
+    pub enabled: bool,
+
+    /// Seconds between two runs of the collector, defaulting to the
+    /// monitor `interval_secs`.
+    ///
+    /// Collectors run with a monitoring cycle, so the value is rounded up
+    /// to a multiple of the monitor interval.
+    pub interval_secs: Option<u64>,
+}
+
+fn default_collector_enabled() -> bool {
+    true
+}
+
+fn default_procfs_root() -> String {
+    "/proc".to_string()
+}
+
+fn default_sysfs_root() -> String {
+    "/sys".to_string()
+}
+
+fn default_cgroup_root() -> String {
+    "/sys/fs/cgroup".to_string()
+}
+
+fn default_kmsg_path() -> String {
+    "/dev/kmsg".to_string()
+}
+
+fn default_host_root() -> String {
+    "/".to_string()
+}
+
+fn default_top_processes() -> usize {
+    10
+}
+
+/// Include and exclude rules for the filesystems stored in `diskinfo`.
+///
+/// A mount is recorded when it passes all three rule pairs: filesystem
+/// type, mount path and device. For each pair, an empty include list
+/// accepts everything, and an exclude match always wins over an include
+/// match. Mount and device rules are glob patterns where `*` stays within
+/// a path component and `**` crosses components.
+///
+/// Pseudo filesystems (`tmpfs`, `devtmpfs`, `squashfs`) are excluded by
+/// default; set `exclude_fs_types = []` to record them again. Container
+/// `overlay` mounts are recorded unless excluded as in the example below,
+/// which also hides an overlay running out of inodes.
+///
+/// # Examples
+///
+/// ```toml
+/// [monitor.disks]
+/// exclude_fs_types = ["tmpfs", "squashfs", "overlay", "nfs4"]
+/// exclude_mounts = ["/var/lib/docker/**", "/snap/**"]
+/// include_devices = ["/dev/nvme*", "/dev/sd*"]
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiskFilterConfig {
+    /// Filesystem types to record (e.g. "ext4", "xfs"). Empty means all.
+    #[serde(default)]
+    pub include_fs_types: Vec<String>,
+
+    /// Filesystem types never recorded.
+    #[serde(default = "default_exclude_fs_types")]
+    pub exclude_fs_types: Vec<String>,
+
+    /// Mount path patterns to record (e.g. "/", "/mnt/*"). Empty means all.
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub include_mounts: Vec<Pattern>,
+
+    /// Mount path patterns never recorded (e.g. "/var/lib/docker/**").
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub exclude_mounts: Vec<Pattern>,
+
+    /// Device patterns to record (e.g. "/dev/sd*"). Empty means all.
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub include_devices: Vec<Pattern>,
+
+    /// Device patterns never recorded (e.g. "/dev/loop*").
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub exclude_devices: Vec<Pattern>,
+}
+
+impl Default for DiskFilterConfig {
+    fn default() -> Self {
+        Self {
+            include_fs_types: Vec::new(),
+            exclude_fs_types: default_exclude_fs_types(),
+            include_mounts: Vec::new(),
+            exclude_mounts: Vec::new(),
+            include_devices: Vec::new(),
+            exclude_devices: Vec::new(),
+        }
+    }
+}
+
+impl DiskFilterConfig {
+    /// Returns whether a filesystem of type `fs_type`, mounted at
+    /// `mount_point` from `device`, should be recorded.
+    pub fn matches(&self, fs_type: &str, mount_point: &str, device: &str) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        let any_pattern = |patterns: &[Pattern], value: &str| {
+            patterns.iter().any(|p| p.matches_with(value, options))
+        };
+        let any_fs_type = |types: &[String]| types.iter().any(|t| t == fs_type);
+
+        (self.include_fs_types.is_empty() || any_fs_type(&self.include_fs_types))
+            && !any_fs_type(&self.exclude_fs_types)
+            && (self.include_mounts.is_empty() || any_pattern(&self.include_mounts, mount_point))
+            && !any_pattern(&self.exclude_mounts, mount_point)
+            && (self.include_devices.is_empty() || any_pattern(&self.include_devices, device))
+            && !any_pattern(&self.exclude_devices, device)
+    }
+}
+
+fn default_exclude_fs_types() -> Vec<String> {
+    ["tmpfs", "devtmpfs", "squashfs"]
+        .iter()
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/* invalid patterns are reported when the configuration is loaded, not on every cycle */
+fn deserialize_patterns<'de, D>(deserializer: D) -> Result<Vec<Pattern>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|p| {
+            Pattern::new(p)
+                .map_err(|e| serde::de::Error::custom(format!("invalid pattern `{}`: {}", p, e)))
+        })
+        .collect()
+}
+
+/// Sources of the login and SSH authentication events.
+///
+/// Logins are read from the binary `utmp` (current sessions) and `wtmp`
+/// (past sessions) records. SSH attempts are read from the syslog
+/// authentication log; on hosts without one (journald only) they are
+/// read from the journal through `journalctl`. Missing files are skipped.
+///
+/// # Examples
+///
+/// ```toml
+/// [monitor.security]
+/// auth_log_path = "/var/log/secure"  # RHEL and derivatives
+/// journal = false
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct SecurityConfig {
+    /// Record of the current login sessions. Defaults to `/var/run/utmp`.
+    #[serde(default = "default_utmp_path")]
+    pub utmp_path: String,
+
+    /// Record of past logins. Defaults to `/var/log/wtmp`.
+    #[serde(default = "default_wtmp_path")]
+    pub wtmp_path: String,
+
+    /// Syslog file with the sshd messages. Defaults to `/var/log/auth.log`.
+    #[serde(default = "default_auth_log_path")]
+    pub auth_log_path: String,
+
+    /// Whether to read sshd messages from the journal when `auth_log_path`
+    /// doesn't exist. Defaults to `true`.
+    #[serde(default = "default_journal")]
+    pub journal: bool,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            utmp_path: default_utmp_path(),
+            wtmp_path: default_wtmp_path(),
+            auth_log_path: default_auth_log_path(),
+            journal: default_journal(),
+        }
+    }
+}
+
+fn default_utmp_path() -> String {
+    "/var/run/utmp".to_string()
+}
+
+fn default_wtmp_path() -> String {
+    "/var/log/wtmp".to_string()
+}
+
+fn default_auth_log_path() -> String {
+    "/var/log/auth.log".to_string()
+}
+
+fn default_journal() -> bool {
+    true
+}
+
+/// An external command whose output is stored as metrics.
+///
+/// The command runs with `sh -c` in the background, so a slow script
+/// doesn't delay the monitoring cycle; its values are stored with the
+/// first sample after it exits. A script still running after
+/// `timeout_secs` is killed, together with the processes it started.
+///
+/// # Examples
+///
+/// ```toml
+/// [[monitor.scripts]]
+/// name = "queue"
+/// command = "/usr/local/bin/queue-depth"
+/// interval_secs = 60
+///
+/// [[monitor.scripts]]
+/// name = "app"
+/// command = "curl -sf http://127.0.0.1:9100/metrics"
+/// format = "prometheus"
+/// timeout_secs = 5
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScriptConfig {
+    /// Name the metrics are stored under.
+    pub name: String,
+
+    /// Shell command to run.
+    pub command: String,
+
+    /// How the output is parsed. Defaults to `key_value`.
+    #[serde(default)]
+    pub format: ScriptFormat,
+
+    /// Seconds between two runs of the script, defaulting to the monitor
+    /// `interval_secs`.
+    pub interval_secs: Option<u64>,
+
+    /// Seconds the script may run before it is killed. Defaults to 10.
+    #[serde(default = "default_script_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// Output format of a script.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptFormat {
+    /// One `name=value` line per metric, without labels.
+    #[default]
+    KeyValue,
+    /// The Prometheus text exposition format, with labels.
+    Prometheus,
+}
+
+fn default_script_timeout_secs() -> u64 {
+    10
+}
+
+/// A directory whose total size, file count and largest files are
+/// recorded.
+///
+/// The tree is walked on a background thread without following symbolic
+/// links or crossing into other filesystems, and hard-linked files are
+/// counted once, like `du -x`. A walk is stopped after `max_entries`
+/// entries or `timeout_secs` seconds, so a huge tree can't hang the
+/// monitor; the partial figures are then stored as incomplete.
+///
+/// # Examples
+///
+/// ```toml
+/// [[monitor.paths]]
+/// path = "/var/log"
+///
+/// [[monitor.paths]]
+/// path = "/var/lib/docker"
+/// interval_secs = 3600
+/// max_entries = 5000000
+/// timeout_secs = 120
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct PathWatchConfig {
+    /// Directory to measure.
+    pub path: String,
+
+    /// Seconds between two walks of the directory. Defaults to 900.
+    #[serde(default = "default_path_interval_secs")]
+    pub interval_secs: u64,
+
+    /// Files and directories visited before the walk is stopped. Defaults
+    /// to 1,000,000.
+    #[serde(default = "default_path_max_entries")]
+    pub max_entries: u64,
+
+    /// Seconds the walk may take before it is stopped. Defaults to 60.
+    #[serde(default = "default_path_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Number of largest files stored with each measurement. Defaults to
+    /// 10.
+    #[serde(default = "default_path_largest_files")]
+    pub largest_files: usize,
+}
+
+fn default_path_interval_secs() -> u64 {
+    900
+}
+
+fn default_path_max_entries() -> u64 {
+    1_000_000
+}
+
+fn default_path_timeout_secs() -> u64 {
+    60
+}
+
+fn default_path_largest_files() -> usize {
+    10
+}
+
+/// A log file whose new lines are matched against regular expressions.
+///
+/// The file is tailed: each run of the `logs` collector reads the lines
+/// appended since the previous run, following logrotate renames and
+/// truncation. Lines written before Teus started are not counted. For
+/// every pattern, the number of matching lines (and their rate per
+/// minute) is stored with the sample, along with the latest matching
+/// lines.
+///
+/// # Examples
+///
+/// ```toml
+/// [[monitor.logs]]
+/// name = "nginx"
+/// path = "/var/log/nginx/error.log"
+///
+/// [[monitor.logs.patterns]]
+/// name = "error"
+/// regex = "\\[(error|crit|alert|emerg)\\]"
+///
+/// [[monitor.logs.patterns]]
+/// name = "upstream_timeout"
+/// regex = "upstream timed out"
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct LogWatchConfig {
+    /// Name the counts are stored under.
+    pub name: String,
+
+    /// Log file to tail.
+    pub path: String,
+
+    /// Patterns matched against every new line. A line can match several.
+    pub patterns: Vec<LogPatternConfig>,
+
+    /// Latest matching lines stored per pattern and run. Defaults to 5.
+    #[serde(default = "default_log_max_lines")]
+    pub max_lines: usize,
+}
+
+/// A pattern of a watched log file.
+#[derive(Debug, Deserialize, Clone)]
+pub struct LogPatternConfig {
+    /// Name the count is stored under (e.g. "error").
+    pub name: String,
+
+    /// Regular expression searched in each line.
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub regex: Regex,
+}
+
+fn default_log_max_lines() -> usize {
+    5
+}
+
+/* like patterns, invalid regexes are reported when the configuration is loaded */
+fn deserialize_regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let regex = String::deserialize(deserializer)?;
+    Regex::new(&regex)
+        .map_err(|e| serde::de::Error::custom(format!("invalid regex `{}`: {}", regex, e)))
 }
 
 /// Response structure for the first-visit check API endpoint.
@@ -471,4 +1099,384 @@ mod tests {
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.monitor.interval_secs, 3600);
     }
+
+    #[test]
+    fn test_monitor_config_procfs_root() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.monitor.procfs_root, "/proc");
+        assert_eq!(config.monitor.sysfs_root, "/sys");
+        assert_eq!(config.monitor.cgroup_root, "/sys/fs/cgroup");
+        assert_eq!(config.monitor.kmsg_path, "/dev/kmsg");
+        assert_eq!(config.monitor.host_root, "/");
+
+        let toml_str = toml_str.replace(
+            "interval_secs = 5",
+            "interval_secs = 5\nprocfs_root = \"/host/proc\"",
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(config.monitor.procfs_root, "/host/proc");
+
+        let toml_str = toml_str.replace(
+            "interval_secs = 5",
+            "interval_secs = 5\ncgroup_root = \"/sys/fs/cgroup/unified\"",
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(config.monitor.cgroup_root, "/sys/fs/cgroup/unified");
+    }
+
+    #[test]
+    fn test_monitor_config_top_processes() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.monitor.top_processes, 10);
+
+        let toml_str =
+            toml_str.replace("interval_secs = 5", "interval_secs = 5\ntop_processes = 25");
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(config.monitor.top_processes, 25);
+    }
+
+    #[test]
+    fn test_monitor_config_disk_filter() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+        "#;
+
+        // Without a [monitor.disks] section, only pseudo filesystems are skipped
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let disks = &config.monitor.disks;
+        assert!(disks.matches("ext4", "/", "/dev/sda1"));
+        assert!(!disks.matches("tmpfs", "/run", "tmpfs"));
+        assert!(!disks.matches("squashfs", "/snap/core/1", "/dev/loop0"));
+        assert!(disks.matches("overlay", "/var/lib/docker/overlay2/x/merged", "overlay"));
+
+        let toml_str = format!(
+            "{}{}",
+            toml_str,
+            r#"
+            [monitor.disks]
+            include_fs_types = ["ext4", "xfs", "tmpfs"]
+            exclude_fs_types = []
+            exclude_mounts = ["/var/lib/docker/**", "/boot/*"]
+            exclude_devices = ["/dev/loop*"]
+            "#
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        let disks = &config.monitor.disks;
+        assert!(disks.matches("ext4", "/", "/dev/sda1"));
+        assert!(disks.matches("tmpfs", "/run", "tmpfs"));
+        assert!(!disks.matches("btrfs", "/home", "/dev/sda2"));
+        assert!(!disks.matches("ext4", "/var/lib/docker/volumes/db", "/dev/sda1"));
+        assert!(!disks.matches("ext4", "/boot/efi", "/dev/sda3"));
+        // `*` doesn't cross path components
+        assert!(disks.matches("ext4", "/boot/efi/nested", "/dev/sda3"));
+        assert!(!disks.matches("ext4", "/mnt/image", "/dev/loop3"));
+    }
+
+    #[test]
+    fn test_monitor_config_security() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let security = &config.monitor.security;
+        assert_eq!(security.utmp_path, "/var/run/utmp");
+        assert_eq!(security.wtmp_path, "/var/log/wtmp");
+        assert_eq!(security.auth_log_path, "/var/log/auth.log");
+        assert!(security.journal);
+
+        let toml_str = format!(
+            "{}{}",
+            toml_str,
+            r#"
+            [monitor.security]
+            auth_log_path = "/var/log/secure"
+            journal = false
+            "#
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+        let security = &config.monitor.security;
+        assert_eq!(security.auth_log_path, "/var/log/secure");
+        assert_eq!(security.wtmp_path, "/var/log/wtmp");
+        assert!(!security.journal);
+    }
+
+    #[test]
+    fn test_monitor_config_collectors() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+
+            [monitor.collectors.processes]
+            interval_secs = 60
+
+            [monitor.collectors.sensors]
+            enabled = false
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let collectors = &config.monitor.collectors;
+        assert_eq!(collectors.len(), 2);
+        assert!(collectors["processes"].enabled);
+        assert_eq!(collectors["processes"].interval_secs, Some(60));
+        assert!(!collectors["sensors"].enabled);
+        assert_eq!(collectors["sensors"].interval_secs, None);
+    }
+
+    #[test]
+    fn test_monitor_config_scripts() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+
+            [[monitor.scripts]]
+            name = "queue"
+            command = "/usr/local/bin/queue-depth"
+
+            [[monitor.scripts]]
+            name = "app"
+            command = "curl -sf http://127.0.0.1:9100/metrics"
+            format = "prometheus"
+            interval_secs = 60
+            timeout_secs = 5
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let scripts = &config.monitor.scripts;
+        assert_eq!(scripts.len(), 2);
+        assert_eq!(scripts[0].name, "queue");
+        assert_eq!(scripts[0].format, ScriptFormat::KeyValue);
+        assert_eq!(scripts[0].interval_secs, None);
+        assert_eq!(scripts[0].timeout_secs, 10);
+        assert_eq!(scripts[1].format, ScriptFormat::Prometheus);
+        assert_eq!(scripts[1].interval_secs, Some(60));
+        assert_eq!(scripts[1].timeout_secs, 5);
+
+        let invalid = toml_str.replace("\"prometheus\"", "\"json\"");
+        assert!(toml::from_str::<Config>(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_monitor_config_paths() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+
+            [[monitor.paths]]
+            path = "/var/log"
+
+            [[monitor.paths]]
+            path = "/var/lib/docker"
+            interval_secs = 3600
+            max_entries = 5000000
+            timeout_secs = 120
+            largest_files = 3
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let paths = &config.monitor.paths;
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].path, "/var/log");
+        assert_eq!(paths[0].interval_secs, 900);
+        assert_eq!(paths[0].max_entries, 1_000_000);
+        assert_eq!(paths[0].timeout_secs, 60);
+        assert_eq!(paths[0].largest_files, 10);
+        assert_eq!(paths[1].interval_secs, 3600);
+        assert_eq!(paths[1].max_entries, 5_000_000);
+        assert_eq!(paths[1].timeout_secs, 120);
+        assert_eq!(paths[1].largest_files, 3);
+    }
+
+    #[test]
+    fn test_database_retention_config() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let retention = &config.database.retention;
+        assert!(!retention.is_enabled());
+        assert_eq!(retention.interval_secs, 3600);
+        assert_eq!(retention.batch_size, 1000);
+        assert!(!retention.incremental_vacuum);
+        assert!(retention.rollups);
+        assert!(retention.needs_job());
+        assert_eq!(retention.minute_rollup_days, 30);
+        assert_eq!(retention.hour_rollup_days, 365);
+
+        let with_retention = toml_str.replace(
+            "[monitor]",
+            "[database.retention]\n max_age_days = 30\n incremental_vacuum = true\n minute_rollup_days = 7\n [monitor]",
+        );
+        let config: Config = toml::from_str(&with_retention).unwrap();
+        let retention = &config.database.retention;
+        assert!(retention.is_enabled());
+        assert_eq!(retention.max_age_days, Some(30));
+        assert_eq!(retention.max_rows, None);
+        assert!(retention.incremental_vacuum);
+        assert_eq!(retention.minute_rollup_days, 7);
+
+        let without_rollups = toml_str.replace(
+            "[monitor]",
+            "[database.retention]\n rollups = false\n [monitor]",
+        );
+        let config: Config = toml::from_str(&without_rollups).unwrap();
+        assert!(!config.database.retention.needs_job());
+    }
+
+    #[test]
+    fn test_monitor_config_logs() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+
+            [[monitor.logs]]
+            name = "nginx"
+            path = "/var/log/nginx/error.log"
+            max_lines = 2
+
+            [[monitor.logs.patterns]]
+            name = "error"
+            regex = "\\[(error|crit)\\]"
+
+            [[monitor.logs.patterns]]
+            name = "timeout"
+            regex = "upstream timed out"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let logs = &config.monitor.logs;
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].name, "nginx");
+        assert_eq!(logs[0].max_lines, 2);
+        assert_eq!(logs[0].patterns.len(), 2);
+        assert_eq!(logs[0].patterns[0].name, "error");
+        assert!(
+            logs[0].patterns[0]
+                .regex
+                .is_match("[crit] 42#42: worker exited")
+        );
+        assert!(!logs[0].patterns[0].regex.is_match("[notice] 42#42: signal"));
+
+        let invalid = toml_str.replace("upstream timed out", "upstream (timed");
+        let err = toml::from_str::<Config>(&invalid).unwrap_err();
+        assert!(err.to_string().contains("invalid regex `upstream (timed`"));
+    }
+
+    #[test]
+    fn test_monitor_config_disk_filter_invalid_pattern() {
+        let toml_str = r#"
+            [server]
+            host = "localhost"
+            port = 8080
+            secret = "secret_key"
+            environment = "test"
+
+            [database]
+            path = "./test.db"
+
+            [monitor]
+            interval_secs = 5
+
+            [monitor.disks]
+            exclude_mounts = ["/var/[lib"]
+        "#;
+
+        let err = toml::from_str::<Config>(toml_str).unwrap_err();
+        assert!(err.to_string().contains("invalid pattern `/var/[lib`"));
+    }
 }
@@ -174,31 +174,6 @@ fi
 
 success "Rust version check passed."
 
-# Check for Diesel CLI
-info "Checking Diesel CLI installation..."
-if ! command -v diesel >/dev/null 2>&1; then
-    warning "Diesel CLI is not installed."
-    read -rp "Do you want to install Diesel CLI? [Y/n] " install_diesel
-    install_diesel=${install_diesel:-Y}
-    
-    if [[ $install_diesel =~ ^[Yy]$ ]]; then
-        info "Installing Diesel CLI..."
-        if cargo install diesel_cli --no-default-features --features sqlite; then
-            success "Diesel CLI installed successfully."
-        else
-            # fallback installer to ensure diesel installation
-            if curl --proto '=https' --tlsv1.2 -LsSf https://github.com/diesel-rs/diesel/releases/latest/download/diesel_cli-installer.sh | sh; then
-                success "Diesel CLI installed successfully. "
-            fi
-            error "Failed to install Diesel CLI. Please install it manually with: cargo install diesel_cli --no-default-features --features sqlite"
-        fi
-    else
-        error "Diesel CLI is required for database migrations. Please install it manually with: cargo install diesel_cli --no-default-features --features sqlite"
-    fi
-else
-    success "Diesel CLI is already installed."
-fi
-
 # Name of the generated executable (modify if necessary)
 BINARY_NAME="teus"
 
@@ -279,28 +254,14 @@ sudo mkdir -p /var/lib/teus || error "Failed to create directory /var/lib/teus"
 sudo chown -R teus:teus /var/lib/teus || error "Failed to set initial ownership for /var/lib/teus"
 success "Database directory setup complete for initial ownership."
 
-# Run migrations with improved error handling
+# Migrations are embedded in the binary, run them as the service user
 info "Running database migrations..."
-echo "DATABASE_URL=/var/lib/teus/sysinfo.db" > .env
-
-# Verify diesel is available before running migrations
-if ! command -v diesel >/dev/null 2>&1; then
-    error "Diesel CLI is not available. Cannot run database migrations. Please install Diesel CLI and try again."
-fi
-
-if diesel migration run; then
+if sudo -u teus "${BIN_DEST}/${BINARY_NAME}" migrate up "${CONF_DEST}/${CONF_FILE}"; then
     success "Migrations completed successfully."
-    info "Setting final ownership for /var/lib/teus after migrations..."
-    sudo chown -R teus:teus /var/lib/teus || error "Failed to set final ownership for /var/lib/teus"
-    success "Final ownership set."
 else
-    error "Database migrations failed. Please check that Diesel CLI is properly installed and the database directory is accessible."
+    error "Database migrations failed. Please check that the database directory is accessible."
 fi
 
-info "Cleaning up temporary .env file..."
-rm -f .env
-success ".env file cleaned up."
-
 # Handle the web dashboard installation if user selected yes
 if [[ $install_dashboard =~ ^[Yy]$ ]]; then
     info "Setting up web dashboard..."
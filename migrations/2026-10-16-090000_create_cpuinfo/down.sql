@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS cpuinfo;
+ALTER TABLE sysinfo DROP COLUMN load_avg_fifteen;
+ALTER TABLE sysinfo DROP COLUMN load_avg_five;
+ALTER TABLE sysinfo DROP COLUMN load_avg_one;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Load averages on every sysinfo sample
+ALTER TABLE sysinfo ADD COLUMN load_avg_one REAL NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN load_avg_five REAL NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN load_avg_fifteen REAL NOT NULL DEFAULT 0;
+
+-- Cpuinfo table (one row per logical core per sysinfo sample)
+CREATE TABLE cpuinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    core_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    cpu_usage REAL NOT NULL,
+    frequency INTEGER NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_cpuinfo_sysinfo_id ON cpuinfo(sysinfo_id);
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- Netinfo table (one row per network interface per sysinfo sample)
+CREATE TABLE netinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    interface TEXT NOT NULL,
+    rx_bytes BIGINT NOT NULL,
+    tx_bytes BIGINT NOT NULL,
+    rx_packets BIGINT NOT NULL,
+    tx_packets BIGINT NOT NULL,
+    rx_errors BIGINT NOT NULL,
+    tx_errors BIGINT NOT NULL,
+    rx_dropped BIGINT NOT NULL,
+    tx_dropped BIGINT NOT NULL,
+    rx_bytes_per_sec REAL,
+    tx_bytes_per_sec REAL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_netinfo_sysinfo_id ON netinfo(sysinfo_id);
+CREATE INDEX idx_netinfo_interface ON netinfo(interface, sysinfo_id);
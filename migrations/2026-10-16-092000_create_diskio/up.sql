@@ -0,0 +1,25 @@
+-- Your SQL goes here
+-- Diskio table (one row per block device per sysinfo sample)
+CREATE TABLE diskio (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    device TEXT NOT NULL,
+    reads_completed BIGINT NOT NULL,
+    sectors_read BIGINT NOT NULL,
+    read_time_ms BIGINT NOT NULL,
+    writes_completed BIGINT NOT NULL,
+    sectors_written BIGINT NOT NULL,
+    write_time_ms BIGINT NOT NULL,
+    io_time_ms BIGINT NOT NULL,
+    read_bytes_per_sec REAL,
+    write_bytes_per_sec REAL,
+    read_iops REAL,
+    write_iops REAL,
+    avg_read_latency_ms REAL,
+    avg_write_latency_ms REAL,
+    busy_percentage REAL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_diskio_sysinfo_id ON diskio(sysinfo_id);
+CREATE INDEX idx_diskio_device ON diskio(device, sysinfo_id);
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- Processinfo table (top processes by CPU and by memory per sysinfo sample)
+CREATE TABLE processinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    pid INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    cmd TEXT NOT NULL,
+    username TEXT,
+    cpu_usage REAL NOT NULL,
+    memory BIGINT NOT NULL,
+    start_time BIGINT NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_processinfo_sysinfo_id ON processinfo(sysinfo_id);
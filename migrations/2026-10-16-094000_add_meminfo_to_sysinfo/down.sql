@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE sysinfo DROP COLUMN hugepage_size;
+ALTER TABLE sysinfo DROP COLUMN hugepages_free;
+ALTER TABLE sysinfo DROP COLUMN hugepages_total;
+ALTER TABLE sysinfo DROP COLUMN dirty_ram;
+ALTER TABLE sysinfo DROP COLUMN free_swap;
+ALTER TABLE sysinfo DROP COLUMN total_swap;
+ALTER TABLE sysinfo DROP COLUMN shared_ram;
+ALTER TABLE sysinfo DROP COLUMN buffers_ram;
+ALTER TABLE sysinfo DROP COLUMN cached_ram;
+ALTER TABLE sysinfo DROP COLUMN available_ram;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- Full /proc/meminfo breakdown on every sysinfo sample (sizes in bytes)
+ALTER TABLE sysinfo ADD COLUMN available_ram BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN cached_ram BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN buffers_ram BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN shared_ram BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN total_swap BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN free_swap BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN dirty_ram BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN hugepages_total BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN hugepages_free BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE sysinfo ADD COLUMN hugepage_size BIGINT NOT NULL DEFAULT 0;
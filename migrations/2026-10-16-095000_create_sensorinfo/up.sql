@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- Sensorinfo table (one row per hwmon temperature or fan channel per sysinfo sample)
+CREATE TABLE sensorinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    chip TEXT NOT NULL,
+    label TEXT NOT NULL,
+    temperature REAL,
+    high REAL,
+    critical REAL,
+    fan_rpm REAL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_sensorinfo_sysinfo_id ON sensorinfo(sysinfo_id);
+CREATE INDEX idx_sensorinfo_sensor ON sensorinfo(chip, label, sysinfo_id);
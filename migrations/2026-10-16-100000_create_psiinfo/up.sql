@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- Psiinfo table (pressure stall averages per resource per sysinfo sample)
+CREATE TABLE psiinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    resource TEXT NOT NULL,
+    some_avg10 REAL NOT NULL,
+    some_avg60 REAL NOT NULL,
+    some_avg300 REAL NOT NULL,
+    full_avg10 REAL,
+    full_avg60 REAL,
+    full_avg300 REAL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_psiinfo_sysinfo_id ON psiinfo(sysinfo_id);
+CREATE INDEX idx_psiinfo_resource ON psiinfo(resource, sysinfo_id);
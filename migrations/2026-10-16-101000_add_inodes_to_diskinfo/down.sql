@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE diskinfo DROP COLUMN inodes_used_percentage;
+ALTER TABLE diskinfo DROP COLUMN inodes_free;
+ALTER TABLE diskinfo DROP COLUMN inodes_used;
+ALTER TABLE diskinfo DROP COLUMN inodes_total;
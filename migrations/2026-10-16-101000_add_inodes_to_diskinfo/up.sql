@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Inode counters of every filesystem (statvfs f_files / f_ffree)
+ALTER TABLE diskinfo ADD COLUMN inodes_total BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE diskinfo ADD COLUMN inodes_used BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE diskinfo ADD COLUMN inodes_free BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE diskinfo ADD COLUMN inodes_used_percentage INTEGER NOT NULL DEFAULT 0;
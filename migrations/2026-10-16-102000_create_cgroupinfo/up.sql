@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- Cgroupinfo table (cgroup v2 accounting per workload per sysinfo sample)
+CREATE TABLE cgroupinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    cpu_usage_usec BIGINT NOT NULL,
+    cpu_user_usec BIGINT NOT NULL,
+    cpu_system_usec BIGINT NOT NULL,
+    memory_current BIGINT,
+    memory_max BIGINT,
+    io_read_bytes BIGINT,
+    io_write_bytes BIGINT,
+    pids_current BIGINT,
+    cpu_usage_percentage REAL,
+    io_read_bytes_per_sec REAL,
+    io_write_bytes_per_sec REAL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_cgroupinfo_sysinfo_id ON cgroupinfo(sysinfo_id);
+CREATE INDEX idx_cgroupinfo_path ON cgroupinfo(path, sysinfo_id);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- Socketinfo table (TCP and UDP socket counts per state per sysinfo sample)
+CREATE TABLE socketinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    protocol TEXT NOT NULL,
+    state TEXT NOT NULL,
+    socket_count BIGINT NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_socketinfo_sysinfo_id ON socketinfo(sysinfo_id);
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- Securityevent table (logins and SSH authentication attempts)
+CREATE TABLE securityevent (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,
+    source TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    username TEXT NOT NULL,
+    source_ip TEXT,
+    auth_method TEXT,
+    tty TEXT
+);
+
+CREATE INDEX idx_securityevent_timestamp ON securityevent(timestamp);
+CREATE INDEX idx_securityevent_source_ip ON securityevent(source_ip, kind);
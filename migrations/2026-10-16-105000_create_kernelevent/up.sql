@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- Kernelevent table (OOM kills, I/O errors and other events from the kernel log)
+CREATE TABLE kernelevent (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    boot_id TEXT NOT NULL,
+    seq BIGINT NOT NULL,
+    timestamp TEXT NOT NULL,
+    level INTEGER NOT NULL,
+    category TEXT NOT NULL,
+    message TEXT NOT NULL,
+    pid INTEGER,
+    process TEXT,
+    device TEXT,
+    cgroup TEXT
+);
+
+-- The kernel log is read again from the start of its ring buffer after a restart
+CREATE UNIQUE INDEX idx_kernelevent_boot_seq ON kernelevent(boot_id, seq);
+CREATE INDEX idx_kernelevent_timestamp ON kernelevent(timestamp);
+CREATE INDEX idx_kernelevent_category ON kernelevent(category, timestamp);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- Scriptmetric table (values printed by the user-defined scripts, per sysinfo sample)
+CREATE TABLE scriptmetric (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    script TEXT NOT NULL,
+    name TEXT NOT NULL,
+    labels TEXT NOT NULL,
+    value REAL NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_scriptmetric_sysinfo_id ON scriptmetric(sysinfo_id);
+CREATE INDEX idx_scriptmetric_script_name ON scriptmetric(script, name, sysinfo_id);
@@ -0,0 +1,27 @@
+-- Your SQL goes here
+-- Packagestatus table (pending package updates and reboot-required flag, one row per day)
+CREATE TABLE packagestatus (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    day TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    manager TEXT NOT NULL,
+    pending_updates INTEGER NOT NULL,
+    security_updates INTEGER,
+    reboot_required BOOLEAN NOT NULL,
+    reboot_packages TEXT NOT NULL,
+    lists_updated_at TEXT
+);
+
+-- Packageupdate table (the pending updates behind each day's counts)
+CREATE TABLE packageupdate (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    day TEXT NOT NULL,
+    name TEXT NOT NULL,
+    installed_version TEXT NOT NULL,
+    available_version TEXT NOT NULL,
+    security BOOLEAN NOT NULL
+);
+
+-- The row of the current day is replaced on every check
+CREATE UNIQUE INDEX idx_packagestatus_day ON packagestatus(day);
+CREATE UNIQUE INDEX idx_packageupdate_day_name ON packageupdate(day, name);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- Timesyncinfo table (clock synchronization state from adjtimex, per sysinfo sample)
+CREATE TABLE timesyncinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    synchronized BOOLEAN NOT NULL,
+    offset_us REAL NOT NULL,
+    max_error_us BIGINT NOT NULL,
+    est_error_us BIGINT NOT NULL,
+    ntp_client TEXT,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_timesyncinfo_sysinfo_id ON timesyncinfo(sysinfo_id);
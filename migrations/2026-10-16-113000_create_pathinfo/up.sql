@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- Pathinfo table (disk usage of the [[monitor.paths]] directories, per sysinfo sample)
+CREATE TABLE pathinfo (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    total_bytes BIGINT NOT NULL,
+    file_count BIGINT NOT NULL,
+    dir_count BIGINT NOT NULL,
+    complete BOOLEAN NOT NULL,
+    largest_files TEXT NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_pathinfo_sysinfo_id ON pathinfo(sysinfo_id);
+CREATE INDEX idx_pathinfo_path ON pathinfo(path, sysinfo_id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS logevent;
+DROP TABLE IF EXISTS logmatch;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- Logmatch table (lines of a watched log file matching a pattern, per sysinfo sample)
+CREATE TABLE logmatch (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    log TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    count BIGINT NOT NULL,
+    per_minute REAL NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_logmatch_sysinfo_id ON logmatch(sysinfo_id);
+CREATE INDEX idx_logmatch_log_pattern ON logmatch(log, pattern);
+
+-- Logevent table (latest matching lines of each pattern, per sysinfo sample)
+CREATE TABLE logevent (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sysinfo_id INTEGER NOT NULL,
+    log TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    line TEXT NOT NULL,
+    FOREIGN KEY (sysinfo_id) REFERENCES sysinfo(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_logevent_sysinfo_id ON logevent(sysinfo_id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_diskinfo_sysinfo_id;
+DROP INDEX IF EXISTS idx_sysinfo_timestamp;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Indexes used to prune the metric history: expired samples are found by
+-- timestamp, and their disk usage rows by sample
+CREATE INDEX idx_sysinfo_timestamp ON sysinfo(timestamp);
+CREATE INDEX idx_diskinfo_sysinfo_id ON diskinfo(sysinfo_id);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Metricrollup table (average, minimum, maximum and last value of a metric per
+-- 1-minute, 1-hour or 1-day bucket, filled from the raw samples as they age)
+CREATE TABLE metricrollup (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    resolution_secs INTEGER NOT NULL,
+    bucket TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    series TEXT NOT NULL,
+    avg_value REAL NOT NULL,
+    min_value REAL NOT NULL,
+    max_value REAL NOT NULL,
+    last_value REAL NOT NULL,
+    sample_count BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_metricrollup_series ON metricrollup(resolution_secs, metric, series, bucket);
+CREATE INDEX idx_metricrollup_bucket ON metricrollup(resolution_secs, bucket);
@@ -1,41 +1,128 @@
-use std::{
-    env,
-    path::Path,
-    process,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
-    thread,
-};
+use std::{env, path::Path, process, thread};
 use teus_api::routes;
 use teus_config::config;
-use teus_database::storage;
-use teus_monitor::sys::SysInfo;
+use teus_database::{migrations, storage};
+use teus_monitor::retention::{self, Pruner};
+use teus_monitor::sys::{builtin_collectors, Monitor};
+use teus_types::config::Config;
+use tokio::sync::watch;
 
-fn main() {
-    println!("Starting Teus service...");
-    let args: Vec<String> = env::args().collect();
-    let config_path = if args.len() > 1 {
-        let path = &args[1];
-        // Check if the provided file exists.
-        if !Path::new(path).exists() {
-            eprintln!("Configuration file '{}' does not exist.", path);
-            process::exit(1);
+/// Returns the configuration file given on the command line, or the default
+/// one, and exits if it doesn't exist.
+fn config_path(arg: Option<&String>) -> String {
+    match arg {
+        Some(path) => {
+            // Check if the provided file exists.
+            if !Path::new(path).exists() {
+                eprintln!("Configuration file '{}' does not exist.", path);
+                process::exit(1);
+            }
+            path.clone()
         }
-        path.clone()
-    } else {
         // Use default config path if none is provided.
-        "./teus-dev.toml".to_string()
-    };
+        None => "./teus-dev.toml".to_string(),
+    }
+}
 
-    let config = match config::parser::load_config(config_path) {
+fn load_config(arg: Option<&String>) -> Config {
+    match config::parser::load_config(config_path(arg)) {
         Ok(cfg) => cfg,
         Err(e) => {
             eprintln!("Error loading configuration: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Converts the database for `incremental_vacuum` of `[database.retention]`
+/// when it isn't yet. The conversion rewrites the whole file while holding
+/// the database, so it runs before anything else uses it.
+fn enable_incremental_vacuum(conn: &mut storage::TeuSQLiteConnection, config: &Config) {
+    if !config.database.retention.incremental_vacuum {
+        return;
+    }
+    match retention::is_incremental_vacuum(conn) {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("Failed to read the auto-vacuum mode: {}", e);
             return;
         }
+    }
+
+    println!(
+        "Converting {} for incremental vacuum. The whole file is rewritten once, this can take a while on a large database...",
+        config.database.path
+    );
+    match retention::enable_incremental_vacuum(conn) {
+        Ok(_) => println!("Database converted for incremental vacuum"),
+        Err(e) => eprintln!(
+            "Failed to convert the database, it won't shrink after pruning: {}",
+            e
+        ),
+    }
+}
+
+/// `teus migrate <status|up|rollback> [config]`: shows the migrations of
+/// the database, applies the pending ones or reverts the latest one.
+fn migrate(args: &[String]) {
+    let action = args.first().map(String::as_str).unwrap_or("status");
+    let config = load_config(args.get(1));
+    let db_path = &config.database.path;
+    let storage = match storage::Storage::new(db_path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize storage: {}", e);
+            process::exit(1);
+        }
+    };
+    let mut conn = storage
+        .write()
+        .expect("Storage connection poisoned before use");
+
+    let result = match action {
+        "status" => migrations::status(&mut conn).map(|status| {
+            for (name, applied) in &status.known {
+                let state = if *applied { "applied" } else { "pending" };
+                println!("{:<8} {}", state, name);
+            }
+            for version in &status.unknown {
+                println!("{:<8} {}", "unknown", version);
+            }
+        }),
+        "up" => migrations::apply_pending(&mut conn, db_path).map(|applied| {
+            if applied.is_empty() {
+                println!("The database is up to date");
+            }
+            for name in applied {
+                println!("Applied {}", name);
+            }
+            enable_incremental_vacuum(&mut conn, &config);
+        }),
+        "rollback" => {
+            migrations::revert_last(&mut conn, db_path).map(|name| println!("Reverted {}", name))
+        }
+        _ => {
+            eprintln!("Usage: teus migrate <status|up|rollback> [config]");
+            process::exit(2);
+        }
     };
+    if let Err(e) = result {
+        eprintln!("Migration failed: {}", e);
+        process::exit(1);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("migrate") {
+        migrate(&args[2..]);
+        return;
+    }
+
+    println!("Starting Teus service...");
+    let config = load_config(args.get(1));
 
     // Initialize Storage once
     let storage = match storage::Storage::new(&config.database.path) {
@@ -46,13 +133,32 @@ fn main() {
         }
     };
 
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
+    // Bring the schema up to date. A database written by a newer Teus is
+    // refused rather than read with the wrong schema. Both this and the
+    // vacuum conversion run before the webserver and the monitor start.
+    {
+        let mut conn = storage
+            .write()
+            .expect("Storage connection poisoned before use");
+        match migrations::apply_pending(&mut conn, &config.database.path) {
+            Ok(applied) if !applied.is_empty() => {
+                println!("Applied migrations: {}", applied.join(", "));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to migrate the database: {}", e);
+                process::exit(1);
+            }
+        }
+        enable_incremental_vacuum(&mut conn, &config);
+    }
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
     // Handle Ctrl+C (SIGINT)
     ctrlc::set_handler(move || {
         println!("Signal received, stopping...");
-        r.store(false, Ordering::SeqCst);
+        let _ = shutdown_tx.send(true);
     })
     .expect("Failed to set Ctrl+C handler");
 
@@ -65,17 +171,28 @@ fn main() {
     });
 
     // Give the webserver a moment to start
-    std::thread::sleep(std::time::Duration::from_millis(500));
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
     println!("Teus service started");
 
-    // Run the system monitor in the main thread
-    // The monitor will create its own Storage instance as needed per run_monitor cycle for now.
-    // This could be further refactored if SysInfo instance became long-lived.
-    while running.load(Ordering::SeqCst) {
-        let sysinfo = SysInfo::default();
-        sysinfo.run_monitor(&config); // run_monitor internally creates Storage for its DB ops
+    // History is rolled up and expired samples are deleted in the
+    // background, one batch at a time
+    let pruner = config.database.retention.needs_job().then(|| {
+        let pruner = Pruner::new(config.database.retention.clone());
+        tokio::spawn(pruner.run(storage.clone(), shutdown_rx.clone()))
+    });
 
-        thread::sleep(std::time::Duration::from_secs(config.monitor.interval_secs));
+    // The monitor lives for the whole run: it keeps its sysinfo state and
+    // collectors between cycles and shares the webserver's storage.
+    let collectors = builtin_collectors(&config.monitor);
+    println!("Enabled collectors: {}", collectors.enabled().join(", "));
+    let monitor = Monitor::new(&config.monitor, collectors);
+    if let Err(e) = tokio::spawn(monitor.run(storage, shutdown_rx)).await {
+        eprintln!("Monitor task failed: {}", e);
+    }
+    if let Some(pruner) = pruner {
+        if let Err(e) = pruner.await {
+            eprintln!("Retention task failed: {}", e);
+        }
     }
 
     // Wait for the webserver to finish and ensure a clean shutdown